@@ -0,0 +1,84 @@
+//! A contract embedding the escrow logic, as with the library feature: it calls the
+//! handlers and checks itself instead of going through this crate's entry points.
+//! Built with the rest of the workspace, so the API in escrow_logic keeps compiling.
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{coins, from_slice, Addr, Decimal};
+use cw20::Balance;
+
+use ellib_escrow::contract::{execute, instantiate};
+use ellib_escrow::escrow_logic::{
+    accept_blockers, assert_can_accept, build_transfers, payout_messages, validate_create,
+    ContractError, CONFIG, ESCROWS,
+};
+use ellib_escrow::msg::{CreateMsg, ExecuteMsg, InstantiateMsg};
+use ellib_escrow::state::TrustMetrics;
+
+fn main() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+
+    // the host takes the escrow's config as part of its own, unset fields are defaults
+    let msg: InstantiateMsg =
+        from_slice(br#"{"admin":"host_admin","initial_arbiters":[],"seed_metrics":[]}"#).unwrap();
+    instantiate(deps.as_mut(), env.clone(), mock_info("host", &[]), msg).unwrap();
+    assert_eq!(
+        CONFIG.load(&deps.storage).unwrap().admin,
+        Addr::unchecked("host_admin")
+    );
+
+    // vet a listing before committing to it, then create it as the host's user would
+    let creator = Addr::unchecked("creator");
+    let funds = coins(100, "uusd");
+    let create = CreateMsg {
+        id: "listing".to_string(),
+        arbiter: None,
+        arbitration_enabled: false,
+        end_height: None,
+        end_time: None,
+        exchange_rate: Decimal::percent(3650),
+        fiat_currency: "VES".to_string(),
+        cw20_whitelist: None,
+        required_trust_metrics: TrustMetrics::default().into(),
+        auto_complete_trust: None,
+        allowed_fulfillers: None,
+        description: None,
+        dispute_window_seconds: None,
+        require_creator_ack: false,
+        auto_extend_on_relist: None,
+        payout_memo: false,
+        tags: None,
+        start_time: None,
+        creator_locale: None,
+        creator_utc_offset_minutes: None,
+    };
+    let balance = Balance::from(funds.clone());
+    let valid = validate_create(deps.as_ref(), &env, &create, &balance, Some(&creator));
+    assert_eq!(
+        valid.ok().map(|valid| valid.fiat_currency),
+        Some("VES".into())
+    );
+    let info = mock_info(creator.as_str(), &funds);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::CCreate(create),
+    )
+    .unwrap();
+
+    // who may take it, and why not
+    let escrow = ESCROWS.load(&deps.storage, "listing").unwrap();
+    let fulfiller = Addr::unchecked("fulfiller");
+    assert_can_accept(&deps.storage, &env, "listing", &escrow, &fulfiller).unwrap();
+    let blockers = accept_blockers(&deps.storage, &env, "listing", &escrow, &creator).unwrap();
+    assert_eq!(blockers[0], ("creator", ContractError::Unauthorized {}));
+
+    // the messages refunding it, under the stored config or as the host wants them
+    let refund = escrow.refund_payouts(&escrow.balance);
+    let messages = payout_messages(&deps.storage, &refund).unwrap();
+    assert_eq!(
+        messages,
+        build_transfers(&creator, &escrow.balance, false).unwrap()
+    );
+}
@@ -3,9 +3,17 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use cw20_escrow::msg::{
-    DetailsResponse, ExecuteMsg, InstantiateMsg, ListResponse, QueryMsg, ReceiveMsg,
+use ellib_escrow::kyc::{IsVerifiedResponse, KycQueryMsg};
+use ellib_escrow::msg::{
+    AdminLogResponse, AllArbiterStatsResponse, ArbiterStatsResponse, ArbitersResponse,
+    AsCreateMsgResponse, BatchCreateResponse, BlacklistResponse, CapabilitiesResponse,
+    ChangedSinceResponse, DeadlinesResponse, DetailsResponse, DetailsV2Response,
+    EligibleListingsResponse, ExecuteMsg, FindByPartiesResponse, HistoryResponse, InstantiateMsg,
+    InvariantsResponse, ListResponse, ListingsResponse, OpenInterestResponse,
+    PayoutDetailsResponse, PayoutHookMsg, QueryMsg, ReceiveMsg, SimulateReleaseResponse,
+    StatsResponse, TotalLockedResponse, TrustPresetsResponse, ValidateCreateResponse,
 };
+use ellib_escrow::state::{CreatorMetrics, Receipt, TrustMetrics};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -17,6 +25,35 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ReceiveMsg), &out_dir);
+    export_schema(&schema_for!(PayoutHookMsg), &out_dir);
     export_schema(&schema_for!(DetailsResponse), &out_dir);
+    export_schema(&schema_for!(DetailsV2Response), &out_dir);
+    export_schema(&schema_for!(AsCreateMsgResponse), &out_dir);
+    export_schema(&schema_for!(Receipt), &out_dir);
     export_schema(&schema_for!(ListResponse), &out_dir);
+    export_schema(&schema_for!(SimulateReleaseResponse), &out_dir);
+    export_schema(&schema_for!(BlacklistResponse), &out_dir);
+    export_schema(&schema_for!(DeadlinesResponse), &out_dir);
+    export_schema(&schema_for!(ArbitersResponse), &out_dir);
+    export_schema(&schema_for!(ListingsResponse), &out_dir);
+    export_schema(&schema_for!(EligibleListingsResponse), &out_dir);
+    export_schema(&schema_for!(ChangedSinceResponse), &out_dir);
+    export_schema(&schema_for!(TrustMetrics), &out_dir);
+    export_schema(&schema_for!(CreatorMetrics), &out_dir);
+    export_schema(&schema_for!(ArbiterStatsResponse), &out_dir);
+    export_schema(&schema_for!(AllArbiterStatsResponse), &out_dir);
+    export_schema(&schema_for!(AdminLogResponse), &out_dir);
+    export_schema(&schema_for!(CapabilitiesResponse), &out_dir);
+    export_schema(&schema_for!(ValidateCreateResponse), &out_dir);
+    export_schema(&schema_for!(OpenInterestResponse), &out_dir);
+    export_schema(&schema_for!(TotalLockedResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(InvariantsResponse), &out_dir);
+    export_schema(&schema_for!(TrustPresetsResponse), &out_dir);
+    export_schema(&schema_for!(HistoryResponse), &out_dir);
+    export_schema(&schema_for!(FindByPartiesResponse), &out_dir);
+    export_schema(&schema_for!(BatchCreateResponse), &out_dir);
+    export_schema(&schema_for!(PayoutDetailsResponse), &out_dir);
+    export_schema(&schema_for!(KycQueryMsg), &out_dir);
+    export_schema(&schema_for!(IsVerifiedResponse), &out_dir);
 }
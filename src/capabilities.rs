@@ -0,0 +1,408 @@
+/// A feature frontends can check for before offering it, along with the
+/// ExecuteMsg and QueryMsg variants (by their JSON names) that make it up
+pub struct Capability {
+    pub name: &'static str,
+    pub execute: &'static [&'static str],
+    pub query: &'static [&'static str],
+}
+
+/// Every feature of this contract. Each message variant belongs to exactly one,
+/// features that only add fields to existing messages have none.
+pub const CAPABILITIES: &[Capability] = &[
+    Capability {
+        name: "escrow",
+        execute: &[
+            "c_create",
+            "receive",
+            "f_accept",
+            "c_cancel",
+            "f_complete",
+            "c_complete",
+        ],
+        query: &["list", "details"],
+    },
+    Capability {
+        name: "as_create_msg",
+        execute: &[],
+        query: &["as_create_msg"],
+    },
+    Capability {
+        name: "receipts",
+        execute: &[],
+        query: &["receipt"],
+    },
+    Capability {
+        name: "batch_create",
+        execute: &["c_create_batch"],
+        query: &[],
+    },
+    Capability {
+        name: "topup",
+        execute: &["top_up"],
+        query: &[],
+    },
+    Capability {
+        name: "third_party_topup",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "cancel_cooloff",
+        execute: &["undo_cancel", "finalize_cancel"],
+        query: &[],
+    },
+    Capability {
+        name: "cancel_all",
+        execute: &["cancel_all"],
+        query: &[],
+    },
+    Capability {
+        name: "unaccept",
+        execute: &["f_unaccept"],
+        query: &[],
+    },
+    Capability {
+        name: "reject",
+        execute: &["c_reject"],
+        query: &[],
+    },
+    Capability {
+        name: "creator_ack",
+        execute: &["c_ack_fulfiller"],
+        query: &[],
+    },
+    Capability {
+        name: "listing_edit",
+        execute: &["c_change"],
+        query: &[],
+    },
+    Capability {
+        name: "listings",
+        execute: &[],
+        query: &["listings"],
+    },
+    Capability {
+        name: "eligible_listings",
+        execute: &[],
+        query: &["eligible_listings"],
+    },
+    Capability {
+        name: "include_expired",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "listing_grace_period",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "listing_bump",
+        execute: &["bump"],
+        query: &[],
+    },
+    Capability {
+        name: "open_to_all",
+        execute: &["open_to_all"],
+        query: &[],
+    },
+    Capability {
+        name: "clone",
+        execute: &["clone_escrow"],
+        query: &[],
+    },
+    Capability {
+        name: "claim_timed_out",
+        execute: &["claim_timed_out"],
+        query: &[],
+    },
+    Capability {
+        name: "mid_lifecycle_expiry",
+        execute: &["refund_expired"],
+        query: &[],
+    },
+    Capability {
+        name: "dispute_window",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "partial_release",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "payout_memo",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "tags",
+        execute: &[],
+        query: &["list_by_tag"],
+    },
+    Capability {
+        name: "risk_score",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "scheduled_start",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "mediation",
+        execute: &["propose_settlement", "accept_settlement"],
+        query: &[],
+    },
+    Capability {
+        name: "sunset",
+        execute: &["begin_sunset"],
+        query: &[],
+    },
+    Capability {
+        name: "arbiter_load",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "accept_time_guard",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "metrics_import",
+        execute: &["import_metrics"],
+        query: &[],
+    },
+    Capability {
+        name: "locale_hints",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "details_v2",
+        execute: &[],
+        query: &["details_v2"],
+    },
+    Capability {
+        name: "action_throttle",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "payout_details",
+        execute: &["set_payout_details"],
+        query: &["payout_details"],
+    },
+    Capability {
+        name: "fulfiller_allowlist",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "arbitration",
+        execute: &[
+            "el_arbitrate",
+            "c_req_arbitration",
+            "resolve_expired_arbitration",
+        ],
+        query: &[],
+    },
+    Capability {
+        name: "arbitration_split",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "optional_arbitration",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "arbiter_ack",
+        execute: &["ack_arbiter_role"],
+        query: &[],
+    },
+    Capability {
+        name: "arbiter_request_info",
+        execute: &["arbiter_request_info"],
+        query: &[],
+    },
+    Capability {
+        name: "arbiter_recuse",
+        execute: &["arbiter_recuse"],
+        query: &[],
+    },
+    Capability {
+        name: "arbiter_registry",
+        execute: &["add_arbiter", "remove_arbiter", "reassign_arbiter"],
+        query: &["arbiters"],
+    },
+    Capability {
+        name: "arbiter_stats",
+        execute: &[],
+        query: &["arbiter_stats", "all_arbiter_stats"],
+    },
+    Capability {
+        name: "trust_presets",
+        execute: &["set_trust_preset", "remove_trust_preset"],
+        query: &["trust_presets"],
+    },
+    Capability {
+        name: "feedback",
+        execute: &["c_feedback", "f_feedback"],
+        query: &[],
+    },
+    Capability {
+        name: "daily_limits",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "kyc",
+        execute: &[],
+        query: &[],
+    },
+    Capability {
+        name: "blacklist",
+        execute: &["add_to_blacklist", "remove_from_blacklist"],
+        query: &["blacklist"],
+    },
+    Capability {
+        name: "trust_metrics",
+        execute: &["seed_trust_metrics", "close_seeding"],
+        query: &["trust_metrics"],
+    },
+    Capability {
+        name: "creator_metrics",
+        execute: &[],
+        query: &["creator_metrics"],
+    },
+    Capability {
+        name: "update_config",
+        execute: &["update_config"],
+        query: &[],
+    },
+    Capability {
+        name: "admin_log",
+        execute: &[],
+        query: &["admin_log"],
+    },
+    Capability {
+        name: "open_interest",
+        execute: &[],
+        query: &["open_interest"],
+    },
+    Capability {
+        name: "history",
+        execute: &[],
+        query: &["history"],
+    },
+    Capability {
+        name: "total_locked",
+        execute: &["reconcile_locked"],
+        query: &["total_locked"],
+    },
+    Capability {
+        name: "recover_stray",
+        execute: &["recover_stray"],
+        query: &[],
+    },
+    Capability {
+        name: "capacity",
+        execute: &[],
+        query: &["stats"],
+    },
+    Capability {
+        name: "simulate_release",
+        execute: &[],
+        query: &["simulate_release"],
+    },
+    Capability {
+        name: "find_by_parties",
+        execute: &[],
+        query: &["find_by_parties"],
+    },
+    Capability {
+        name: "fingerprints",
+        execute: &[],
+        query: &["by_fingerprint"],
+    },
+    Capability {
+        name: "deadlines",
+        execute: &[],
+        query: &["deadlines"],
+    },
+    Capability {
+        name: "changed_since",
+        execute: &[],
+        query: &["changed_since"],
+    },
+    Capability {
+        name: "validate_create",
+        execute: &[],
+        query: &["validate_create"],
+    },
+    Capability {
+        name: "check_invariants",
+        execute: &[],
+        query: &["check_invariants"],
+    },
+    Capability {
+        name: "capabilities",
+        execute: &[],
+        query: &["capabilities"],
+    },
+];
+
+pub fn capability_names() -> Vec<String> {
+    CAPABILITIES.iter().map(|c| c.name.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use schemars::schema::{RootSchema, Schema};
+    use schemars::schema_for;
+
+    use crate::msg::{ExecuteMsg, QueryMsg};
+
+    /// The JSON names of the enum's variants, read off its schema
+    fn variants(root: RootSchema) -> Vec<String> {
+        let one_of = root.schema.subschemas.unwrap().one_of.unwrap();
+        one_of
+            .into_iter()
+            .flat_map(|schema| match schema {
+                Schema::Object(object) => object.object.unwrap().required.into_iter(),
+                Schema::Bool(_) => panic!("unexpected schema"),
+            })
+            .collect()
+    }
+
+    fn declared(pick: fn(&Capability) -> &'static [&'static str]) -> Vec<String> {
+        let mut declared: Vec<String> = CAPABILITIES
+            .iter()
+            .flat_map(|c| pick(c).iter().map(|v| v.to_string()))
+            .collect();
+        declared.sort();
+        declared
+    }
+
+    #[test]
+    fn every_message_belongs_to_one_capability() {
+        let mut execute = variants(schema_for!(ExecuteMsg));
+        execute.sort();
+        assert_eq!(declared(|c| c.execute), execute);
+        let mut query = variants(schema_for!(QueryMsg));
+        query.sort();
+        assert_eq!(declared(|c| c.query), query);
+
+        let mut names = capability_names();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), CAPABILITIES.len());
+    }
+}
@@ -0,0 +1,602 @@
+//! Builders for the messages bots and frontends send, checking offline what the contract
+//! would reject so a malformed message fails before it's broadcast.
+//!
+//! Checks needing chain state are left to the contract: whether addresses are valid,
+//! trust presets exist, ids are free, and end_height and end_time agree.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cosmwasm_std::{Addr, Decimal};
+use thiserror::Error;
+
+use crate::contract::{
+    assert_start_before_end, assert_utc_offset, clean_currency, clean_description, clean_locale,
+    clean_tags, normalize_whitelist,
+};
+use crate::msg::{
+    is_valid_name, ArbitrateMsg, CreateMsg, ExecuteMsg, FeedbackMsg, TrustRequirement,
+};
+use crate::payments::MAX_BPS;
+use crate::state::{
+    CreatorRequirement, TrustMetrics, DEFAULT_FIAT_CURRENCY, DEFAULT_MAX_WHITELIST_LEN,
+    MAX_ALLOWED_FULFILLERS,
+};
+use crate::ContractError;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ClientError {
+    /// What the contract would fail the message with
+    #[error("{0}")]
+    Contract(#[from] ContractError),
+
+    #[error("Escrow ids are 3 to 20 bytes long")]
+    InvalidId {},
+
+    #[error("The exchange rate can't be zero")]
+    ZeroExchangeRate {},
+
+    #[error("The local clock is before the unix epoch")]
+    Clock {},
+}
+
+fn assert_id(id: &str) -> Result<(), ClientError> {
+    match is_valid_name(id) {
+        true => Ok(()),
+        false => Err(ClientError::InvalidId {}),
+    }
+}
+
+fn assert_rate(rate: Decimal) -> Result<(), ClientError> {
+    match rate.is_zero() {
+        true => Err(ClientError::ZeroExchangeRate {}),
+        false => Ok(()),
+    }
+}
+
+/// Builds a CreateMsg, for CCreate or a cw20 Receive. Without an arbiter the escrow has
+/// arbitration disabled.
+#[derive(Clone, Debug)]
+pub struct CreateMsgBuilder {
+    msg: CreateMsg,
+    end_in_seconds: Option<u64>,
+    max_whitelist_len: u32,
+}
+
+impl CreateMsgBuilder {
+    pub fn new(id: impl Into<String>) -> Self {
+        CreateMsgBuilder {
+            msg: CreateMsg {
+                id: id.into(),
+                arbiter: None,
+                arbitration_enabled: false,
+                end_height: None,
+                end_time: None,
+                exchange_rate: Decimal::zero(),
+                fiat_currency: DEFAULT_FIAT_CURRENCY.to_string(),
+                cw20_whitelist: None,
+                required_trust_metrics: TrustMetrics::default().into(),
+                auto_complete_trust: None,
+                allowed_fulfillers: None,
+                description: None,
+                dispute_window_seconds: None,
+                require_creator_ack: false,
+                auto_extend_on_relist: None,
+                payout_memo: false,
+                tags: None,
+                start_time: None,
+                creator_locale: None,
+                creator_utc_offset_minutes: None,
+            },
+            end_in_seconds: None,
+            max_whitelist_len: DEFAULT_MAX_WHITELIST_LEN,
+        }
+    }
+
+    pub fn exchange_rate(mut self, rate: Decimal) -> Self {
+        self.msg.exchange_rate = rate;
+        self
+    }
+
+    pub fn fiat_currency(mut self, currency: impl Into<String>) -> Self {
+        self.msg.fiat_currency = currency.into();
+        self
+    }
+
+    pub fn arbiter(mut self, arbiter: impl Into<String>) -> Self {
+        self.msg.arbiter = Some(arbiter.into());
+        self.msg.arbitration_enabled = true;
+        self
+    }
+
+    pub fn end_height(mut self, height: u64) -> Self {
+        self.msg.end_height = Some(height);
+        self
+    }
+
+    pub fn end_time(mut self, time: u64) -> Self {
+        self.msg.end_time = Some(time);
+        self.end_in_seconds = None;
+        self
+    }
+
+    /// Expires this long after build, going by the local clock
+    pub fn end_in_seconds(mut self, seconds: u64) -> Self {
+        self.end_in_seconds = Some(seconds);
+        self
+    }
+
+    pub fn cw20_whitelist(mut self, tokens: Vec<String>) -> Self {
+        self.msg.cw20_whitelist = Some(tokens);
+        self
+    }
+
+    /// The contract's max_whitelist_len, when configured other than the default
+    pub fn max_whitelist_len(mut self, max: u32) -> Self {
+        self.max_whitelist_len = max;
+        self
+    }
+
+    pub fn required_trust_metrics(mut self, metrics: TrustMetrics) -> Self {
+        self.msg.required_trust_metrics = metrics.into();
+        self
+    }
+
+    pub fn trust_preset(mut self, name: impl Into<String>) -> Self {
+        self.msg.required_trust_metrics = TrustRequirement::Preset(name.into());
+        self
+    }
+
+    pub fn auto_complete_trust(mut self, metrics: TrustMetrics) -> Self {
+        self.msg.auto_complete_trust = Some(metrics);
+        self
+    }
+
+    pub fn allowed_fulfillers(mut self, fulfillers: Vec<String>) -> Self {
+        self.msg.allowed_fulfillers = Some(fulfillers);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.msg.description = Some(description.into());
+        self
+    }
+
+    pub fn dispute_window_seconds(mut self, seconds: u64) -> Self {
+        self.msg.dispute_window_seconds = Some(seconds);
+        self
+    }
+
+    pub fn require_creator_ack(mut self) -> Self {
+        self.msg.require_creator_ack = true;
+        self
+    }
+
+    pub fn auto_extend_on_relist(mut self, seconds: u64) -> Self {
+        self.msg.auto_extend_on_relist = Some(seconds);
+        self
+    }
+
+    /// For creators that are contracts handling PayoutHookMsg
+    pub fn payout_memo(mut self) -> Self {
+        self.msg.payout_memo = true;
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.msg.tags.get_or_insert_with(Vec::new).push(tag.into());
+        self
+    }
+
+    /// Keeps the listing out of sight and unacceptable until this time
+    pub fn start_time(mut self, time: u64) -> Self {
+        self.msg.start_time = Some(time);
+        self
+    }
+
+    /// Shown to fulfillers as the creator's locale, e.g. "es-VE"
+    pub fn creator_locale(mut self, locale: impl Into<String>) -> Self {
+        self.msg.creator_locale = Some(locale.into());
+        self
+    }
+
+    /// Shown to fulfillers as the creator's offset from UTC, e.g. -240 in Caracas
+    pub fn creator_utc_offset_minutes(mut self, minutes: i16) -> Self {
+        self.msg.creator_utc_offset_minutes = Some(minutes);
+        self
+    }
+
+    /// The message as the contract would store it: currency upper-cased, description
+    /// trimmed, whitelist and tags sorted and deduped
+    pub fn build(self) -> Result<CreateMsg, ClientError> {
+        let mut msg = self.msg;
+        assert_id(&msg.id)?;
+        assert_rate(msg.exchange_rate)?;
+        msg.fiat_currency = clean_currency(&msg.fiat_currency)?;
+        msg.description = clean_description(msg.description)?;
+        if msg.tags.is_some() {
+            msg.tags = Some(clean_tags(msg.tags)?);
+        }
+        if msg.dispute_window_seconds == Some(0) {
+            return Err(ContractError::InvalidDisputeWindow {}.into());
+        }
+        msg.creator_locale = clean_locale(msg.creator_locale)?;
+        assert_utc_offset(msg.creator_utc_offset_minutes)?;
+        if let Some(tokens) = msg.cw20_whitelist {
+            let tokens = tokens.into_iter().map(Addr::unchecked).collect();
+            let tokens = normalize_whitelist(tokens, self.max_whitelist_len)?;
+            msg.cw20_whitelist = Some(tokens.into_iter().map(String::from).collect());
+        }
+        if let Some(mut fulfillers) = msg.allowed_fulfillers {
+            fulfillers.sort();
+            fulfillers.dedup();
+            if fulfillers.is_empty() {
+                return Err(ContractError::EmptyFulfillerAllowlist {}.into());
+            }
+            if fulfillers.len() > MAX_ALLOWED_FULFILLERS {
+                return Err(ContractError::FulfillerAllowlistTooLarge {
+                    max: MAX_ALLOWED_FULFILLERS,
+                }
+                .into());
+            }
+            msg.allowed_fulfillers = Some(fulfillers);
+        }
+        if let Some(seconds) = self.end_in_seconds {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| ClientError::Clock {})?;
+            msg.end_time = Some(now.as_secs() + seconds);
+        }
+        assert_start_before_end(msg.start_time, msg.end_time)?;
+        Ok(msg)
+    }
+
+    pub fn build_execute(self) -> Result<ExecuteMsg, ClientError> {
+        self.build().map(ExecuteMsg::CCreate)
+    }
+}
+
+/// Builds an FAccept
+#[derive(Clone, Debug)]
+pub struct AcceptMsgBuilder {
+    id: String,
+    expected_exchange_rate: Option<Decimal>,
+    required_creator_metrics: Option<CreatorRequirement>,
+    payout_memo: bool,
+}
+
+impl AcceptMsgBuilder {
+    pub fn new(id: impl Into<String>) -> Self {
+        AcceptMsgBuilder {
+            id: id.into(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        }
+    }
+
+    /// The rate shown to the fulfiller, the accept fails if the creator changed it since
+    pub fn expected_exchange_rate(mut self, rate: Decimal) -> Self {
+        self.expected_exchange_rate = Some(rate);
+        self
+    }
+
+    /// Fails the accept unless the escrow's creator has this record
+    pub fn required_creator_metrics(mut self, required: CreatorRequirement) -> Self {
+        self.required_creator_metrics = Some(required);
+        self
+    }
+
+    /// For fulfillers that are contracts handling PayoutHookMsg
+    pub fn payout_memo(mut self) -> Self {
+        self.payout_memo = true;
+        self
+    }
+
+    pub fn build(self) -> Result<ExecuteMsg, ClientError> {
+        assert_id(&self.id)?;
+        if let Some(rate) = self.expected_exchange_rate {
+            assert_rate(rate)?;
+        }
+        Ok(ExecuteMsg::FAccept {
+            id: self.id,
+            expected_exchange_rate: self.expected_exchange_rate,
+            required_creator_metrics: self.required_creator_metrics,
+            payout_memo: self.payout_memo,
+        })
+    }
+}
+
+/// Builds an ElArbitrate ruling for the receiver, or a split with creator_bps
+#[derive(Clone, Debug)]
+pub struct ArbitrateMsgBuilder {
+    id: String,
+    msg: ArbitrateMsg,
+}
+
+impl ArbitrateMsgBuilder {
+    pub fn new(id: impl Into<String>, receiver: impl Into<String>) -> Self {
+        ArbitrateMsgBuilder {
+            id: id.into(),
+            msg: ArbitrateMsg {
+                receiver: receiver.into(),
+                creator_bps: None,
+            },
+        }
+    }
+
+    /// Refunds this share of the balance to the creator, the rest goes to the fulfiller
+    pub fn creator_bps(mut self, bps: u64) -> Self {
+        self.msg.creator_bps = Some(bps);
+        self
+    }
+
+    pub fn build(self) -> Result<ExecuteMsg, ClientError> {
+        assert_id(&self.id)?;
+        if self.msg.creator_bps.is_some_and(|bps| bps > MAX_BPS) {
+            return Err(ContractError::InvalidBps {}.into());
+        }
+        Ok(ExecuteMsg::ElArbitrate {
+            id: self.id,
+            msg: self.msg,
+        })
+    }
+}
+
+/// Builds a CFeedback or FFeedback, depending on which party leaves it
+#[derive(Clone, Debug)]
+pub struct FeedbackMsgBuilder {
+    id: String,
+    from_creator: bool,
+    msg: FeedbackMsg,
+}
+
+impl FeedbackMsgBuilder {
+    fn new(id: impl Into<String>, from_creator: bool) -> Self {
+        FeedbackMsgBuilder {
+            id: id.into(),
+            from_creator,
+            msg: FeedbackMsg {
+                comment: String::new(),
+                satisfied: true,
+            },
+        }
+    }
+
+    pub fn creator(id: impl Into<String>) -> Self {
+        Self::new(id, true)
+    }
+
+    pub fn fulfiller(id: impl Into<String>) -> Self {
+        Self::new(id, false)
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.msg.comment = comment.into();
+        self
+    }
+
+    pub fn satisfied(mut self, satisfied: bool) -> Self {
+        self.msg.satisfied = satisfied;
+        self
+    }
+
+    pub fn build(self) -> Result<ExecuteMsg, ClientError> {
+        assert_id(&self.id)?;
+        let (id, msg) = (self.id, self.msg);
+        Ok(match self.from_creator {
+            true => ExecuteMsg::CFeedback { id, msg },
+            false => ExecuteMsg::FFeedback { id, msg },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{coins, from_slice, Deps, OwnedDeps};
+    use cw20::Balance;
+
+    use crate::contract::{execute, instantiate, validate_create};
+    use crate::msg::InstantiateMsg;
+    use crate::state::UST_DENOM;
+
+    type Configure = fn(CreateMsgBuilder) -> CreateMsgBuilder;
+
+    fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(&[]);
+        let msg: InstantiateMsg =
+            from_slice(br#"{"admin":"admin","initial_arbiters":[],"seed_metrics":[]}"#).unwrap();
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        deps
+    }
+
+    fn builder() -> CreateMsgBuilder {
+        CreateMsgBuilder::new("foobar").exchange_rate(Decimal::percent(3650))
+    }
+
+    /// What the contract says about the message, with the builder's normalization undone
+    fn on_chain(deps: Deps, msg: CreateMsg) -> Vec<ContractError> {
+        let balance = Balance::from(coins(100, UST_DENOM));
+        validate_create(deps, &mock_env(), &msg, &balance, None)
+            .err()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn create_builder_rejects_what_the_contract_does() {
+        let deps = setup();
+        let too_many_tokens = |b: CreateMsgBuilder| {
+            let tokens = (0..=DEFAULT_MAX_WHITELIST_LEN).map(|n| format!("token{}", n));
+            b.cw20_whitelist(tokens.collect())
+        };
+        let too_many_fulfillers = |b: CreateMsgBuilder| {
+            let fulfillers = (0..=MAX_ALLOWED_FULFILLERS).map(|n| format!("fulfiller{}", n));
+            b.allowed_fulfillers(fulfillers.collect())
+        };
+        let cases: Vec<(Configure, ContractError)> = vec![
+            (
+                |b| b.fiat_currency("dollars"),
+                ContractError::InvalidCurrency {},
+            ),
+            (
+                |b| b.description("x".repeat(1025)),
+                ContractError::DescriptionTooLong { max: 1024 },
+            ),
+            (
+                |b| b.dispute_window_seconds(0),
+                ContractError::InvalidDisputeWindow {},
+            ),
+            (
+                |b| b.creator_locale("es_VE"),
+                ContractError::InvalidLocaleHint {
+                    field: "creator_locale".to_string(),
+                },
+            ),
+            (
+                |b| b.creator_utc_offset_minutes(-841),
+                ContractError::InvalidLocaleHint {
+                    field: "creator_utc_offset_minutes".to_string(),
+                },
+            ),
+            (
+                |b| b.allowed_fulfillers(vec![]),
+                ContractError::EmptyFulfillerAllowlist {},
+            ),
+            (
+                too_many_fulfillers,
+                ContractError::FulfillerAllowlistTooLarge {
+                    max: MAX_ALLOWED_FULFILLERS,
+                },
+            ),
+            (
+                too_many_tokens,
+                ContractError::WhitelistTooLarge {
+                    max: DEFAULT_MAX_WHITELIST_LEN,
+                },
+            ),
+        ];
+        for (configure, expected) in cases {
+            let err = configure(builder()).build().unwrap_err();
+            let unchecked = configure(builder()).msg;
+            assert!(on_chain(deps.as_ref(), unchecked).contains(&expected));
+            assert_eq!(err, ClientError::Contract(expected));
+        }
+
+        // duplicates don't count against the limit
+        let fulfillers = (0..MAX_ALLOWED_FULFILLERS).map(|n| format!("fulfiller{}", n));
+        let twice = fulfillers.clone().chain(fulfillers).collect();
+        let msg = builder().allowed_fulfillers(twice).build().unwrap();
+        let allowed = msg.allowed_fulfillers.unwrap();
+        assert_eq!(allowed.len(), MAX_ALLOWED_FULFILLERS);
+    }
+
+    #[test]
+    fn create_builder_checks_id_and_rate() {
+        let err = CreateMsgBuilder::new("ab")
+            .exchange_rate(Decimal::one())
+            .build();
+        assert_eq!(err.unwrap_err(), ClientError::InvalidId {});
+        let err = CreateMsgBuilder::new("x".repeat(21))
+            .exchange_rate(Decimal::one())
+            .build();
+        assert_eq!(err.unwrap_err(), ClientError::InvalidId {});
+        let err = CreateMsgBuilder::new("foobar").build();
+        assert_eq!(err.unwrap_err(), ClientError::ZeroExchangeRate {});
+    }
+
+    #[test]
+    fn create_builder_normalizes_as_stored() {
+        let msg = builder()
+            .fiat_currency(" cop ")
+            .description("  terms  ")
+            .cw20_whitelist(vec!["b".into(), "a".into(), "b".into()])
+            .arbiter("arbitrate")
+            .end_in_seconds(3600)
+            .build()
+            .unwrap();
+        assert_eq!(msg.fiat_currency, "COP");
+        assert_eq!(msg.description, Some("terms".to_string()));
+        assert_eq!(msg.cw20_whitelist, Some(vec!["a".into(), "b".into()]));
+        assert!(msg.arbitration_enabled);
+        assert!(msg.end_time.is_some());
+
+        // what the builder lets through, the contract takes
+        let mut deps = setup();
+        let msg = builder().description("terms").build_execute().unwrap();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn accept_arbitrate_and_feedback_builders() {
+        let msg = AcceptMsgBuilder::new("foobar")
+            .expected_exchange_rate(Decimal::percent(3650))
+            .build()
+            .unwrap();
+        assert_eq!(
+            msg,
+            ExecuteMsg::FAccept {
+                id: "foobar".into(),
+                expected_exchange_rate: Some(Decimal::percent(3650)),
+                required_creator_metrics: None,
+                payout_memo: false,
+            }
+        );
+        let err = AcceptMsgBuilder::new("foobar")
+            .expected_exchange_rate(Decimal::zero())
+            .build();
+        assert_eq!(err.unwrap_err(), ClientError::ZeroExchangeRate {});
+        assert_eq!(
+            AcceptMsgBuilder::new("no").build().unwrap_err(),
+            ClientError::InvalidId {}
+        );
+
+        let msg = ArbitrateMsgBuilder::new("foobar", "creator")
+            .creator_bps(MAX_BPS)
+            .build()
+            .unwrap();
+        assert_eq!(
+            msg,
+            ExecuteMsg::ElArbitrate {
+                id: "foobar".into(),
+                msg: ArbitrateMsg {
+                    receiver: "creator".into(),
+                    creator_bps: Some(MAX_BPS),
+                },
+            }
+        );
+        let err = ArbitrateMsgBuilder::new("foobar", "creator")
+            .creator_bps(MAX_BPS + 1)
+            .build();
+        assert_eq!(err.unwrap_err(), ContractError::InvalidBps {}.into());
+
+        let msg = FeedbackMsgBuilder::fulfiller("foobar")
+            .comment("paid on time")
+            .build()
+            .unwrap();
+        let feedback = FeedbackMsg {
+            comment: "paid on time".into(),
+            satisfied: true,
+        };
+        assert_eq!(
+            msg,
+            ExecuteMsg::FFeedback {
+                id: "foobar".into(),
+                msg: feedback.clone(),
+            }
+        );
+        let msg = FeedbackMsgBuilder::creator("foobar")
+            .comment("paid on time")
+            .satisfied(false)
+            .build()
+            .unwrap();
+        let msg = match msg {
+            ExecuteMsg::CFeedback { msg, .. } => msg,
+            _ => panic!("not creator feedback"),
+        };
+        assert!(!msg.satisfied);
+    }
+}
@@ -1,18 +1,58 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, SubMsg, WasmMsg,
+    from_binary, to_binary, Addr, Api, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg, Uint128,
 };
+use cw_storage_plus::{PrimaryKey, U64Key};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 
-use cw2::set_contract_version;
-use cw20::{Balance, Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{
+    Balance, BalanceResponse, Cw20Coin, Cw20CoinVerified, Cw20QueryMsg, Cw20ReceiveMsg, Denom,
+};
 
+use crate::capabilities::capability_names;
 use crate::error::ContractError;
+use crate::events::{ContractEvent, EscrowEvent};
+use crate::kyc;
 use crate::msg::{
-    CreateMsg, DetailsResponse, ExecuteMsg, InstantiateMsg, ListResponse, QueryMsg, ReceiveMsg, FeedbackMsg, ArbitrateMsg
+    ActionCount, AdminLogResponse, AllArbiterStatsResponse, ArbiterEntry, ArbiterSort,
+    ArbiterStatsResponse, ArbitersResponse, ArbitrateMsg, AsCreateMsgResponse, BatchCreateItem,
+    BatchCreateResponse, BlacklistInfo, BlacklistResponse, CapabilitiesResponse,
+    ChangedSinceResponse, CreateMsg, CurrencyOpenInterest, DeadlinesResponse, DetailsResponse,
+    DetailsV2Response, EligibleListingsResponse, EscrowChange, EscrowFees, EscrowTimestamps,
+    EscrowTrust, ExecuteMsg, FeedbackMsg, FindByPartiesResponse, HistoryResponse, InstantiateMsg,
+    InvariantViolation, InvariantsResponse, ListOrder, ListResponse, ListingSummary,
+    ListingsResponse, NearMiss, OpenInterestResponse, PartyEscrow, PayoutDetailsResponse,
+    PayoutHookMsg, QueryMsg, ReceiveMsg, ReleaseOutcome, SimulateReleaseResponse, StatsResponse,
+    TotalLockedResponse, Transfer, TrustPreset, TrustPresetsResponse, TrustRequirement,
+    UpdateConfigMsg, ValidateCreateResponse,
+};
+use crate::pagination::{page_bounds, page_limit, page_map, page_raw};
+use crate::payments;
+use crate::state::{
+    assert_daily_volume, height_to_time, index_parties, load_active_or_explain, log_admin_action,
+    record_daily_volume, record_history, record_history_note, remove_escrow, reserve_fingerprint,
+    revision_from_key, risk_score, save_escrow, unindex_parties, AdminAction, ArbiterStats,
+    BlacklistEntry, Config, CreatorMetrics, CreatorRequirement, DisplayDenom, Dispute,
+    DisputeReason, Escrow, FundingSource, GenericBalance, OpenInterest, Receipt, ReleasedAmount,
+    SettlementProposal, TrustMetrics, ACCEPTED_VOLUME, ACCEPT_TIMEOUT_SECONDS, ACTIVE_ESCROWS,
+    ADMIN_LOG, ARBITERS, ARBITER_STATS, ARCHIVED_ESCROWS, BLACKLIST, CONFIG, CREATED_ESCROWS,
+    CREATED_VOLUME, CREATOR_ESCROWS, CREATOR_METRICS, DEFAULT_ARBITER_ACK_WINDOW_SECONDS,
+    DEFAULT_ARBITRATION_EXTENSION_SECONDS, DEFAULT_BUMP_COOLDOWN_SECONDS,
+    DEFAULT_MAX_ACTIONS_PER_ADDRESS, DEFAULT_MAX_ARBITRATION_EXTENSIONS, DEFAULT_MAX_ESCROW_BYTES,
+    DEFAULT_MAX_OPEN_TO_ALL_SECONDS, DEFAULT_MAX_WHITELIST_LEN,
+    DEFAULT_MIN_REMAINING_ON_ACCEPT_SECONDS, DEFAULT_SECONDS_PER_BLOCK, ESCROWS, FINGERPRINTS,
+    FULFILL_TIMEOUT_SECONDS, HISTORY, IMPORTED_METRICS, LATEST_REVISION, LISTINGS,
+    MAX_ALLOWED_FULFILLERS, MAX_BATCH_ITEMS, MAX_CONTRIBUTORS, MAX_CW20_BALANCES,
+    MAX_DESCRIPTION_BYTES, MAX_ESCROW_LIFETIME_SECONDS, MAX_EXPIRY_DRIFT_SECONDS, MAX_LOCALE_LEN,
+    MAX_PAYOUT_DETAILS_BYTES, MAX_RECONCILE_ESCROWS, MAX_REJECTIONS, MAX_SEED_ENTRIES, MAX_TAGS,
+    MAX_TAG_LEN, MAX_UTC_OFFSET_MINUTES, MIN_TAG_LEN, OPEN_INTEREST, PARTY_ESCROWS, PAYOUT_DETAILS,
+    RECEIPTS, REVISIONS, TAGGED_LISTINGS, TOTAL_LOCKED, TRUST_METRICS, TRUST_PRESETS, UUSD_PER_UST,
 };
-use crate::state::{all_escrow_ids, Escrow, GenericBalance, ESCROWS, TrustMetrics};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw20-escrow";
@@ -22,11 +62,94 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    // No escrow would track them, and TotalLocked could never be reconciled again
+    if !info.funds.is_empty() {
+        return Err(ContractError::InstantiateWithFunds {});
+    }
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    // no setup
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
+    let fee_collector = match msg.fee_collector {
+        Some(fee_collector) => deps.api.addr_validate(&fee_collector)?,
+        None => admin.clone(),
+    };
+    let kyc_contract = msg
+        .kyc_contract
+        .map(|kyc_contract| deps.api.addr_validate(&kyc_contract))
+        .transpose()?;
+    let config = Config {
+        admin,
+        seconds_per_block: msg.seconds_per_block.unwrap_or(DEFAULT_SECONDS_PER_BLOCK),
+        max_whitelist_len: msg.max_whitelist_len.unwrap_or(DEFAULT_MAX_WHITELIST_LEN),
+        seeding_closed: false,
+        sunset: false,
+        bump_cooldown_seconds: msg
+            .bump_cooldown_seconds
+            .unwrap_or(DEFAULT_BUMP_COOLDOWN_SECONDS),
+        allow_mixed_funding: msg.allow_mixed_funding.unwrap_or(false),
+        post_accept_cancel_window_seconds: msg.post_accept_cancel_window_seconds.unwrap_or(0),
+        cancel_penalty_bps: msg.cancel_penalty_bps.unwrap_or(0),
+        split_native_payouts: msg.split_native_payouts.unwrap_or(false),
+        listing_deposit: msg.listing_deposit.unwrap_or_default(),
+        fee_collector,
+        require_arbiter_ack: msg.require_arbiter_ack.unwrap_or(false),
+        arbiter_ack_window_seconds: msg
+            .arbiter_ack_window_seconds
+            .unwrap_or(DEFAULT_ARBITER_ACK_WINDOW_SECONDS),
+        display_denoms: msg
+            .display_denoms
+            .unwrap_or_else(|| vec![DisplayDenom::ust()]),
+        listing_grace_period_seconds: msg.listing_grace_period_seconds.unwrap_or(0),
+        arbitration_extension_seconds: msg
+            .arbitration_extension_seconds
+            .unwrap_or(DEFAULT_ARBITRATION_EXTENSION_SECONDS),
+        max_arbitration_extensions: msg
+            .max_arbitration_extensions
+            .unwrap_or(DEFAULT_MAX_ARBITRATION_EXTENSIONS),
+        kyc_contract,
+        allow_third_party_topup: msg.allow_third_party_topup.unwrap_or(false),
+        max_active_escrows: msg.max_active_escrows.filter(|max| *max > 0),
+        daily_volume_limit_ust: msg.daily_volume_limit_ust.filter(|limit| !limit.is_zero()),
+        daily_accept_limit_ust: msg.daily_accept_limit_ust.filter(|limit| !limit.is_zero()),
+        dispute_window_seconds: msg
+            .dispute_window_seconds
+            .unwrap_or(FULFILL_TIMEOUT_SECONDS),
+        creator_ack_timeout_seconds: msg
+            .creator_ack_timeout_seconds
+            .unwrap_or(ACCEPT_TIMEOUT_SECONDS),
+        max_open_to_all_seconds: msg
+            .max_open_to_all_seconds
+            .unwrap_or(DEFAULT_MAX_OPEN_TO_ALL_SECONDS),
+        cancel_cooloff_seconds: msg.cancel_cooloff_seconds.unwrap_or(0),
+        max_escrow_bytes: msg.max_escrow_bytes.unwrap_or(DEFAULT_MAX_ESCROW_BYTES),
+        mid_lifecycle_expiry: msg.mid_lifecycle_expiry.unwrap_or_default(),
+        risk_weights: msg.risk_weights.unwrap_or_default(),
+        mediation_window_seconds: msg.mediation_window_seconds.unwrap_or(0),
+        min_remaining_on_accept_seconds: msg
+            .min_remaining_on_accept_seconds
+            .unwrap_or(DEFAULT_MIN_REMAINING_ON_ACCEPT_SECONDS),
+        peer_contracts: validate_peers(deps.api, msg.peer_contracts.unwrap_or_default())?,
+        max_actions_per_address: msg
+            .max_actions_per_address
+            .unwrap_or(DEFAULT_MAX_ACTIONS_PER_ADDRESS),
+    };
+    config.validate()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    if msg.initial_arbiters.len() > MAX_SEED_ENTRIES {
+        return Err(ContractError::TooManySeedEntries {
+            max: MAX_SEED_ENTRIES,
+        });
+    }
+    for arbiter in msg.initial_arbiters {
+        ARBITERS.save(deps.storage, &deps.api.addr_validate(&arbiter)?, &Empty {})?;
+    }
+    seed_trust_metrics(deps, msg.seed_metrics)?;
     Ok(Response::default())
 }
 
@@ -37,786 +160,13030 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Nothing the contract sends is meant for itself. Handlers settle their escrows before
+    // attaching messages, so a cw20 token calling back during a payout finds them settled.
+    assert_not_self(&env, &info.sender)?;
     match msg {
-        ExecuteMsg::ElArbitrate(id, msg) => el_arbitrate(deps, env, info, msg, id),
-        ExecuteMsg::CCreate(msg) => c_create(deps, msg, Balance::from(info.funds), &info.sender),
-        ExecuteMsg::FAccept { id } => f_accept(deps, env, info, id),
+        ExecuteMsg::ElArbitrate { id, msg } => el_arbitrate(deps, env, info, msg, id),
+        ExecuteMsg::CCreate(msg) => {
+            c_create(deps, env, msg, Balance::from(info.funds), &info.sender)
+        }
+        ExecuteMsg::CCreateBatch { items } => c_create_batch(deps, env, info, items),
+        ExecuteMsg::TopUp { id } => top_up(deps, env, id, Balance::from(info.funds), &info.sender),
+        ExecuteMsg::Receive(msg) => receive(deps, env, info, msg),
+        ExecuteMsg::FAccept {
+            id,
+            expected_exchange_rate,
+            required_creator_metrics,
+            payout_memo,
+        } => f_accept(
+            deps,
+            env,
+            info,
+            id,
+            expected_exchange_rate,
+            required_creator_metrics,
+            payout_memo,
+        ),
         ExecuteMsg::CCancel { id } => c_cancel(deps, env, info, id),
+        ExecuteMsg::UndoCancel { id } => c_undo_cancel(deps, env, info, id),
+        ExecuteMsg::FinalizeCancel { id } => finalize_cancel(deps, env, info, id),
         ExecuteMsg::FUnaccept { id } => f_unaccept(deps, env, info, id),
+        ExecuteMsg::CReject { id, reason } => c_reject(deps, env, info, id, reason),
+        ExecuteMsg::CAckFulfiller { id } => c_ack_fulfiller(deps, env, info, id),
         ExecuteMsg::CChange(msg) => c_change(deps, env, info, msg),
-        ExecuteMsg::FComplete { id } => f_complete(deps, env, info, id),
-        ExecuteMsg::CReqArbitration { id } => c_request_arbitration(deps, env, info, id),
-        ExecuteMsg::CComplete { id } => c_complete(deps, env, info, id),
-        ExecuteMsg::CFeedback(id, msg) => c_feedback(deps, env, info, msg, id),
-        ExecuteMsg::FFeedback(id, msg) => f_feedback(deps, env, info, msg, id),
+        ExecuteMsg::FComplete {
+            id,
+            delivered_fiat_amount,
+        } => f_complete(deps, env, info, id, delivered_fiat_amount),
+        ExecuteMsg::CancelAll {
+            start_after,
+            limit,
+            only_expired,
+        } => c_cancel_all(deps, env, info, start_after, limit, only_expired),
+        ExecuteMsg::ClaimTimedOut { id } => f_claim_timed_out(deps, env, info, id),
+        ExecuteMsg::CloneEscrow { source_id } => c_clone(deps, env, info, source_id),
+        ExecuteMsg::Bump { id } => c_bump(deps, env, info, id),
+        ExecuteMsg::OpenToAll {
+            id,
+            duration_seconds,
+        } => c_open_to_all(deps, env, info, id, duration_seconds),
+        ExecuteMsg::RefundExpired { id } => refund_expired(deps, env, info, id),
+        ExecuteMsg::ResolveExpiredArbitration { id } => {
+            resolve_expired_arbitration(deps, env, info, id)
+        }
+        ExecuteMsg::CReqArbitration {
+            id,
+            reason_code,
+            details,
+        } => c_request_arbitration(deps, env, info, id, reason_code, details),
+        ExecuteMsg::ProposeSettlement { id, creator_bps } => {
+            propose_settlement(deps, env, info, id, creator_bps)
+        }
+        ExecuteMsg::AcceptSettlement {
+            id,
+            expected_creator_bps,
+        } => accept_settlement(deps, env, info, id, expected_creator_bps),
+        ExecuteMsg::CComplete {
+            id,
+            accept_rate_deviation,
+            release_bps,
+        } => c_complete(deps, env, info, id, accept_rate_deviation, release_bps),
+        ExecuteMsg::CFeedback { id, msg } => c_feedback(deps, env, info, msg, id),
+        ExecuteMsg::FFeedback { id, msg } => f_feedback(deps, env, info, msg, id),
+        ExecuteMsg::AddToBlacklist { address, reason } => {
+            add_to_blacklist(deps, env, info, address, reason)
+        }
+        ExecuteMsg::RemoveFromBlacklist { address } => {
+            remove_from_blacklist(deps, env, info, address)
+        }
+        ExecuteMsg::SeedTrustMetrics { metrics } => {
+            admin_seed_trust_metrics(deps, env, info, metrics)
+        }
+        ExecuteMsg::CloseSeeding {} => close_seeding(deps, env, info),
+        ExecuteMsg::ImportMetrics { from_contract } => {
+            import_metrics(deps, env, info, from_contract)
+        }
+        ExecuteMsg::BeginSunset {} => begin_sunset(deps, env, info),
+        ExecuteMsg::UpdateConfig(msg) => update_config(deps, env, info, msg),
+        ExecuteMsg::ReconcileLocked {} => reconcile_locked(deps, env, info),
+        ExecuteMsg::RecoverStray {
+            token,
+            denom,
+            to,
+            amount,
+        } => recover_stray(deps, env, info, token, denom, to, amount),
+        ExecuteMsg::AddArbiter { address } => add_arbiter(deps, env, info, address),
+        ExecuteMsg::RemoveArbiter { address } => remove_arbiter(deps, env, info, address),
+        ExecuteMsg::SetTrustPreset { name, metrics } => {
+            set_trust_preset(deps, env, info, name, metrics)
+        }
+        ExecuteMsg::RemoveTrustPreset { name } => remove_trust_preset(deps, env, info, name),
+        ExecuteMsg::ReassignArbiter { id, new_arbiter } => {
+            reassign_arbiter(deps, env, info, id, new_arbiter)
+        }
+        ExecuteMsg::AckArbiterRole { id } => ack_arbiter_role(deps, env, info, id),
+        ExecuteMsg::ArbiterRequestInfo { id, request } => {
+            el_request_info(deps, env, info, id, request)
+        }
+        ExecuteMsg::ArbiterRecuse { id, reason } => el_recuse(deps, env, info, id, reason),
+        ExecuteMsg::SetPayoutDetails { id, ciphertext } => {
+            c_set_payout_details(deps, env, info, id, ciphertext)
+        }
     }
 }
 
-pub fn el_arbitrate(
+/// Counts a non-terminal action of the sender on the escrow their handler then saves,
+/// against max_actions_per_address. Settling, refunding and disputing never count, so the
+/// throttle can't lock funds in, nor do the arbiter's and the admin's messages.
+fn count_action(
+    storage: &dyn Storage,
+    escrow: &mut Escrow,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let max = CONFIG.load(storage)?.max_actions_per_address;
+    escrow.count_action(sender, max)
+}
+
+pub fn receive(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: ArbitrateMsg,
-    id: String,
+    wrapper: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    // ArbitrateMsg contains the wallet of whom to send the funds to
-    return Err(ContractError::Unauthorized {});
+    // the token contract is the sender, and may be buggy or hostile
+    let token = info.sender;
+    if wrapper.amount.is_zero() {
+        return Err(ContractError::ZeroAmount {
+            denom: token.into(),
+        });
+    }
+    let invalid = |reason: String| ContractError::InvalidReceiveMsg {
+        token: token.to_string(),
+        reason,
+    };
+    if wrapper.msg.is_empty() {
+        return Err(invalid("empty message".to_string()));
+    }
+    let msg: ReceiveMsg = from_binary(&wrapper.msg).map_err(|err| match err {
+        StdError::ParseErr { msg, .. } => invalid(msg),
+        err => invalid(err.to_string()),
+    })?;
+    let balance = Balance::Cw20(Cw20CoinVerified {
+        address: token,
+        amount: wrapper.amount,
+    });
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    assert_not_self(&env, &sender)?;
+    match msg {
+        ReceiveMsg::CCreate(msg) => c_create(deps, env, msg, balance, &sender),
+        ReceiveMsg::TopUp { id } => top_up(deps, env, id, balance, &sender),
+    }
 }
 
-pub fn c_create(
+pub fn add_to_blacklist(
     deps: DepsMut,
-    msg: CreateMsg,
-    balance: Balance,
-    sender: &Addr,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    reason: String,
 ) -> Result<Response, ContractError> {
-    if balance.is_empty() {
-        return Err(ContractError::EmptyBalance {});
-    }
+    assert_admin(deps.storage, &info.sender)?;
+    let address = deps.api.addr_validate(&address)?;
+    let entry = BlacklistEntry {
+        reason,
+        since: env.block.time.seconds(),
+    };
+    BLACKLIST.save(deps.storage, &address, &entry)?;
+    let action = AdminAction::AddToBlacklist {
+        address: address.clone(),
+        reason: entry.reason,
+    };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
 
-    let mut cw20_whitelist = msg.addr_whitelist(deps.api)?;
+    Ok(ContractEvent::blacklisted(&address).response())
+}
 
-    let escrow_balance = match balance {
-        Balance::Native(balance) => GenericBalance {
-            native: balance.0,
-            cw20: vec![],
-        },
-        Balance::Cw20(token) => {
-            // make sure the token sent is on the whitelist by default
-            if !cw20_whitelist.iter().any(|t| t == &token.address) {
-                cw20_whitelist.push(token.address.clone())
-            }
-            GenericBalance {
-                native: vec![],
-                cw20: vec![token],
-            }
-        }
+pub fn remove_from_blacklist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let address = deps.api.addr_validate(&address)?;
+    BLACKLIST.remove(deps.storage, &address);
+    let action = AdminAction::RemoveFromBlacklist {
+        address: address.clone(),
     };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
 
-    // TODO: Make sure this can be at max 7 days from now, since we don't want to keep contracts more than 7 days old
-    let end_time = msg.end_time;
-    
-    let escrow = Escrow {
-        arbiter: deps.api.addr_validate(&msg.arbiter)?,
-        fulfiller: sender.clone(),
-        creator: sender.clone(),
-        end_height: msg.end_height,
-        end_time: end_time,
-        balance: escrow_balance,
-        exchange_rate: msg.exchange_rate,
-        cw20_whitelist,
-        required_trust_metrics: msg.required_trust_metrics,
-        is_listed: true,
-        is_canceled: false,
-        is_accepted: false,
-        is_fulfilled: false,
-        is_in_arbitration: false,
-        is_completed: false,
-        time_created: Some(0),
-        time_accepted: Some(0),
-        time_fulfilled: Some(0),
-        time_arbitration_started: Some(0),
-    };
+    Ok(ContractEvent::unblacklisted(&address).response())
+}
 
-    // try to store it, fail if the id was already in use
-    ESCROWS.update(deps.storage, &msg.id, |existing| match existing {
-        None => Ok(escrow),
-        Some(_) => Err(ContractError::AlreadyInUse {}),
-    })?;
+pub fn admin_seed_trust_metrics(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    metrics: Vec<(String, TrustMetrics)>,
+) -> Result<Response, ContractError> {
+    let config = assert_admin(deps.storage, &info.sender)?;
+    if config.seeding_closed {
+        return Err(ContractError::SeedingClosed {});
+    }
+    let count = metrics.len();
+    let addresses = seed_trust_metrics(deps.branch(), metrics)?;
+    let action = AdminAction::SeedTrustMetrics { addresses };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
 
-    let res = Response::new().add_attributes(vec![("action", "create"), ("id", msg.id.as_str())]);
-    Ok(res)
+    Ok(ContractEvent::trust_metrics_seeded(count).response())
 }
 
-pub fn f_accept(
+pub fn import_metrics(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    id: String,
+    from_contract: String,
 ) -> Result<Response, ContractError> {
-    let mut escrow = ESCROWS.load(deps.storage, &id)?;
-    if info.sender == escrow.creator {
-        // The contract creator can't accept their own contract
-        return Err(ContractError::Unauthorized {});
-    } 
-    // We check if the contract is in a state where it can be accepted
-    else if !escrow.is_listed {
-        return Err(ContractError::NotListed {});
+    let config = CONFIG.load(deps.storage)?;
+    let peer = deps.api.addr_validate(&from_contract)?;
+    // importing from itself would count the wallet's record twice
+    if !config.peer_contracts.contains(&peer) || peer == env.contract.address {
+        return Err(ContractError::UnknownPeer { peer: peer.into() });
     }
-    // We have to check if trust metrics of the sender wallet are tolerable
-    else if escrow.required_trust_metrics.is_higher(get_trust_metrics(&info.sender)) {
-        return Err(ContractError::TrustMetricsInsufficient {});
-    } 
-    else {
-        // We set the message sender as the contract fulfiller
-        escrow.fulfiller = info.sender;
-        // TODO: Set escrow.is_accepted to true
-        let res = Response::new().add_attributes(vec![("action", "accept"), ("id", id.as_str())]);
-        return Ok(res)
+    if IMPORTED_METRICS.has(deps.storage, (&info.sender, &peer)) {
+        return Err(ContractError::MetricsAlreadyImported { peer: peer.into() });
+    }
+    let query = QueryMsg::TrustMetrics {
+        address: info.sender.to_string(),
+    };
+    // a peer answering with anything but metrics fails to parse here
+    let imported: TrustMetrics = deps
+        .querier
+        .query_wasm_smart(&peer, &query)
+        .map_err(|err| ContractError::PeerUnavailable {
+            peer: peer.to_string(),
+            reason: err.to_string(),
+        })?;
+    // the import is once, so it isn't spent on a wallet the peer doesn't know yet
+    if imported == TrustMetrics::default() {
+        return Err(ContractError::NothingToImport { peer: peer.into() });
+    }
+    if let Some(reason) = imported.implausibility() {
+        return Err(ContractError::ImplausiblePeerMetrics {
+            peer: peer.into(),
+            reason: reason.to_string(),
+        });
     }
+    update_trust_metrics(deps.storage, &info.sender, |m| m.merge(&imported))?;
+    IMPORTED_METRICS.save(deps.storage, (&info.sender, &peer), &imported)?;
+
+    Ok(ContractEvent::metrics_imported(&info.sender, &peer).response())
 }
 
-pub fn c_cancel(
+pub fn close_seeding(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    id: String,
 ) -> Result<Response, ContractError> {
-    let mut escrow = ESCROWS.load(deps.storage, &id)?;
-    if !escrow.is_accept_expired(&env) && info.sender != escrow.creator {
-        return Err(ContractError::Unauthorized {});
-    } else if !escrow.is_accepted {
-        return Err(ContractError::CantUnaccept {});
-    } else {
-        escrow.is_listed = false;
-        escrow.is_canceled = true;
-        // we delete the escrow
-        ESCROWS.remove(deps.storage, &id);
+    let mut config = assert_admin(deps.storage, &info.sender)?;
+    config.seeding_closed = true;
+    CONFIG.save(deps.storage, &config)?;
+    log_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        AdminAction::CloseSeeding {},
+    )?;
 
-        Ok(Response::new()
-            .add_attribute("action", "unaccept")
-            .add_attribute("id", id))
-    }
+    Ok(ContractEvent::seeding_closed().response())
 }
 
-pub fn f_unaccept(
+pub fn begin_sunset(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = assert_admin(deps.storage, &info.sender)?;
+    config.sunset = true;
+    CONFIG.save(deps.storage, &config)?;
+    log_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        AdminAction::BeginSunset {},
+    )?;
+
+    Ok(ContractEvent::sunset_begun().response())
+}
+
+pub fn recover_stray(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    id: String,
+    token: Option<String>,
+    denom: Option<String>,
+    to: String,
+    amount: Uint128,
 ) -> Result<Response, ContractError> {
-    let mut escrow = ESCROWS.load(deps.storage, &id)?;
-    if info.sender != escrow.fulfiller {
-        return Err(ContractError::Unauthorized {});
-    } else if !escrow.is_accepted {
-        return Err(ContractError::CantUnaccept {});
-    } else {
-        // Remove the fulfiller
-        escrow.fulfiller = info.sender;
+    assert_admin(deps.storage, &info.sender)?;
+    let to = deps.api.addr_validate(&to)?;
+    let asset = match (token, denom) {
+        (Some(token), None) => Denom::Cw20(deps.api.addr_validate(&token)?),
+        (None, Some(denom)) => Denom::Native(denom),
+        _ => return Err(ContractError::InvalidStrayAsset {}),
+    };
+    let name = match &asset {
+        Denom::Cw20(token) => token.to_string(),
+        Denom::Native(denom) => denom.clone(),
+    };
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount { denom: name });
+    }
 
-        Ok(Response::new()
-            .add_attribute("action", "unaccept")
-            .add_attribute("id", id))
+    // what the contract holds, against what the escrows account for
+    let unavailable = |err: StdError| ContractError::StrayBalanceUnavailable {
+        asset: name.clone(),
+        reason: err.to_string(),
+    };
+    let contract = env.contract.address.to_string();
+    let locked = TOTAL_LOCKED.may_load(deps.storage)?.unwrap_or_default();
+    let mut stray = GenericBalance::default();
+    let (held, tracked) = match &asset {
+        Denom::Native(denom) => {
+            let held = deps.querier.query_balance(&contract, denom);
+            let tracked = locked.native.iter().find(|c| c.denom == *denom);
+            stray.native.push(Coin::new(amount.u128(), denom));
+            (held.map_err(unavailable)?.amount, tracked.map(|c| c.amount))
+        }
+        Denom::Cw20(token) => {
+            let query = Cw20QueryMsg::Balance { address: contract };
+            let held: StdResult<BalanceResponse> = deps.querier.query_wasm_smart(token, &query);
+            let tracked = locked.cw20.iter().find(|t| t.address == *token);
+            stray.cw20.push(Cw20CoinVerified {
+                address: token.clone(),
+                amount,
+            });
+            (
+                held.map_err(unavailable)?.balance,
+                tracked.map(|t| t.amount),
+            )
+        }
+    };
+    let surplus = held.saturating_sub(tracked.unwrap_or_default());
+    if amount > surplus {
+        return Err(ContractError::ExceedsStraySurplus {
+            asset: name,
+            surplus,
+        });
     }
+
+    let action = AdminAction::RecoverStray {
+        denom: asset,
+        to: to.clone(),
+        amount,
+    };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
+    Ok(ContractEvent::stray_recovered(&name, &to, amount)
+        .response()
+        .add_submessages(payout_messages(deps.storage, &[(to, stray)])?))
 }
 
-pub fn c_change(
+pub fn reconcile_locked(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: CreateMsg,
 ) -> Result<Response, ContractError> {
-    // TODO: Implement contract changes
-    return Err(ContractError::Unauthorized {})
+    assert_admin(deps.storage, &info.sender)?;
+    let balances: Vec<GenericBalance> = ESCROWS
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(MAX_RECONCILE_ESCROWS + 1)
+        .map(|item| item.map(|(_, escrow)| escrow.locked()))
+        .collect::<StdResult<_>>()?;
+    if balances.len() > MAX_RECONCILE_ESCROWS {
+        return Err(ContractError::TooManyEscrows {
+            max: MAX_RECONCILE_ESCROWS,
+        });
+    }
+    let total = balances
+        .iter()
+        .fold(GenericBalance::default(), |total, balance| {
+            payments::merge(&total, balance)
+        });
+
+    let drifted = TOTAL_LOCKED.may_load(deps.storage)?.unwrap_or_default() != total;
+    TOTAL_LOCKED.save(deps.storage, &total)?;
+    ACTIVE_ESCROWS.save(deps.storage, &(balances.len() as u64))?;
+    log_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        AdminAction::ReconcileLocked { drifted },
+    )?;
+
+    Ok(ContractEvent::locked_reconciled(balances.len(), drifted).response())
 }
 
-pub fn f_complete(
+pub fn update_config(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    id: String,
+    msg: UpdateConfigMsg,
 ) -> Result<Response, ContractError> {
-    let escrow = ESCROWS.load(deps.storage, &id)?;
-    if info.sender != escrow.fulfiller {
-        return Err(ContractError::Unauthorized {});
-    } else if !escrow.is_accepted {
-        return Err(ContractError::CantFulfill {});
-    } else {
-        // TODO: Change state like below
-        // escrow.is_fulfilled = true;
-        Ok(Response::new()
-            .add_attribute("action", "fulfill")
-            .add_attribute("id", id))
+    let mut config = assert_admin(deps.storage, &info.sender)?;
+    if let Some(admin) = msg.admin {
+        config.admin = deps.api.addr_validate(&admin)?;
+    }
+    config.seconds_per_block = msg.seconds_per_block.unwrap_or(config.seconds_per_block);
+    config.max_whitelist_len = msg.max_whitelist_len.unwrap_or(config.max_whitelist_len);
+    config.bump_cooldown_seconds = msg
+        .bump_cooldown_seconds
+        .unwrap_or(config.bump_cooldown_seconds);
+    config.allow_mixed_funding = msg
+        .allow_mixed_funding
+        .unwrap_or(config.allow_mixed_funding);
+    config.post_accept_cancel_window_seconds = msg
+        .post_accept_cancel_window_seconds
+        .unwrap_or(config.post_accept_cancel_window_seconds);
+    config.cancel_penalty_bps = msg.cancel_penalty_bps.unwrap_or(config.cancel_penalty_bps);
+    config.split_native_payouts = msg
+        .split_native_payouts
+        .unwrap_or(config.split_native_payouts);
+    if let Some(listing_deposit) = msg.listing_deposit {
+        config.listing_deposit = listing_deposit;
+    }
+    if let Some(fee_collector) = msg.fee_collector {
+        config.fee_collector = deps.api.addr_validate(&fee_collector)?;
+    }
+    config.require_arbiter_ack = msg
+        .require_arbiter_ack
+        .unwrap_or(config.require_arbiter_ack);
+    config.arbiter_ack_window_seconds = msg
+        .arbiter_ack_window_seconds
+        .unwrap_or(config.arbiter_ack_window_seconds);
+    config.listing_grace_period_seconds = msg
+        .listing_grace_period_seconds
+        .unwrap_or(config.listing_grace_period_seconds);
+    config.arbitration_extension_seconds = msg
+        .arbitration_extension_seconds
+        .unwrap_or(config.arbitration_extension_seconds);
+    config.max_arbitration_extensions = msg
+        .max_arbitration_extensions
+        .unwrap_or(config.max_arbitration_extensions);
+    match msg.kyc_contract.as_deref() {
+        Some("") => config.kyc_contract = None,
+        Some(kyc_contract) => config.kyc_contract = Some(deps.api.addr_validate(kyc_contract)?),
+        None => {}
+    }
+    config.allow_third_party_topup = msg
+        .allow_third_party_topup
+        .unwrap_or(config.allow_third_party_topup);
+    if let Some(max) = msg.max_active_escrows {
+        config.max_active_escrows = Some(max).filter(|max| *max > 0);
+    }
+    if let Some(limit) = msg.daily_volume_limit_ust {
+        config.daily_volume_limit_ust = Some(limit).filter(|limit| !limit.is_zero());
+    }
+    if let Some(limit) = msg.daily_accept_limit_ust {
+        config.daily_accept_limit_ust = Some(limit).filter(|limit| !limit.is_zero());
+    }
+    config.dispute_window_seconds = msg
+        .dispute_window_seconds
+        .unwrap_or(config.dispute_window_seconds);
+    config.creator_ack_timeout_seconds = msg
+        .creator_ack_timeout_seconds
+        .unwrap_or(config.creator_ack_timeout_seconds);
+    config.max_open_to_all_seconds = msg
+        .max_open_to_all_seconds
+        .unwrap_or(config.max_open_to_all_seconds);
+    config.cancel_cooloff_seconds = msg
+        .cancel_cooloff_seconds
+        .unwrap_or(config.cancel_cooloff_seconds);
+    config.max_escrow_bytes = msg.max_escrow_bytes.unwrap_or(config.max_escrow_bytes);
+    config.mid_lifecycle_expiry = msg
+        .mid_lifecycle_expiry
+        .unwrap_or(config.mid_lifecycle_expiry);
+    config.risk_weights = msg.risk_weights.unwrap_or(config.risk_weights);
+    config.mediation_window_seconds = msg
+        .mediation_window_seconds
+        .unwrap_or(config.mediation_window_seconds);
+    config.min_remaining_on_accept_seconds = msg
+        .min_remaining_on_accept_seconds
+        .unwrap_or(config.min_remaining_on_accept_seconds);
+    if let Some(display_denoms) = msg.display_denoms {
+        config.display_denoms = display_denoms;
     }
+    if let Some(peers) = msg.peer_contracts {
+        config.peer_contracts = validate_peers(deps.api, peers)?;
+    }
+    config.max_actions_per_address = msg
+        .max_actions_per_address
+        .unwrap_or(config.max_actions_per_address);
+    // the merged config, not just the fields given
+    config.validate()?;
+    CONFIG.save(deps.storage, &config)?;
+    log_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        AdminAction::UpdateConfig {
+            config: Box::new(config),
+        },
+    )?;
+
+    Ok(ContractEvent::config_updated().response())
 }
 
-pub fn c_request_arbitration(
+fn validate_peers(api: &dyn Api, peers: Vec<String>) -> StdResult<Vec<Addr>> {
+    peers.iter().map(|peer| api.addr_validate(peer)).collect()
+}
+
+/// Writes imported trust metrics, overwriting what an address already has.
+/// Returns the addresses seeded.
+fn seed_trust_metrics(
+    deps: DepsMut,
+    metrics: Vec<(String, TrustMetrics)>,
+) -> Result<Vec<Addr>, ContractError> {
+    if metrics.len() > MAX_SEED_ENTRIES {
+        return Err(ContractError::TooManySeedEntries {
+            max: MAX_SEED_ENTRIES,
+        });
+    }
+    let mut seen: Vec<Addr> = Vec::with_capacity(metrics.len());
+    for (address, trust_metrics) in metrics {
+        let address = deps.api.addr_validate(&address)?;
+        if seen.contains(&address) {
+            return Err(ContractError::DuplicateSeed {
+                address: address.into(),
+            });
+        }
+        TRUST_METRICS.save(deps.storage, &address, &trust_metrics)?;
+        seen.push(address);
+    }
+    Ok(seen)
+}
+
+pub fn add_arbiter(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    id: String,
+    address: String,
 ) -> Result<Response, ContractError> {
-    let escrow = ESCROWS.load(deps.storage, &id)?;
-    if info.sender != escrow.creator {
-        return Err(ContractError::Unauthorized {});
-    } else if !escrow.is_fulfilled {
-        return Err(ContractError::NotFulfilled {});
-    } else {
-        // TODO: Change state like below
-        // escrow.is_in_arbitration = true;
-        Ok(Response::new()
-            .add_attribute("action", "request_arbitration")
-            .add_attribute("id", id))
-    }
+    assert_admin(deps.storage, &info.sender)?;
+    let address = deps.api.addr_validate(&address)?;
+    ARBITERS.save(deps.storage, &address, &Empty {})?;
+    let action = AdminAction::AddArbiter {
+        address: address.clone(),
+    };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
+
+    Ok(ContractEvent::arbiter_added(&address).response())
 }
 
-pub fn c_complete(
+pub fn remove_arbiter(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    id: String,
+    address: String,
 ) -> Result<Response, ContractError> {
-    let escrow = ESCROWS.load(deps.storage, &id)?;
-    if info.sender != escrow.creator {
-        Err(ContractError::Unauthorized {})
-    } 
-    else if !escrow.is_fulfilled | escrow.is_completed {
-        Err(ContractError::Expired {})
-    } else {
-        // we delete the escrow
-        ESCROWS.remove(deps.storage, &id);
+    assert_admin(deps.storage, &info.sender)?;
+    let address = deps.api.addr_validate(&address)?;
+    ARBITERS.remove(deps.storage, &address);
+    let action = AdminAction::RemoveArbiter {
+        address: address.clone(),
+    };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
 
-        // send all tokens out
-        let messages: Vec<SubMsg> = send_tokens(&escrow.fulfiller, &escrow.balance)?;
+    Ok(ContractEvent::arbiter_removed(&address).response())
+}
 
-        Ok(Response::new()
-            .add_attribute("action", "creator_complete")
-            .add_attribute("id", id)
-            .add_attribute("to", escrow.fulfiller)
-            .add_submessages(messages))
-    }
+pub fn set_trust_preset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    metrics: TrustMetrics,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    TRUST_PRESETS.save(deps.storage, &name, &metrics)?;
+    let action = AdminAction::SetTrustPreset {
+        name: name.clone(),
+        metrics,
+    };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
+
+    Ok(ContractEvent::trust_preset_set(&name).response())
 }
 
-pub fn c_feedback(
+pub fn remove_trust_preset(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: FeedbackMsg,
-    id: String,
+    name: String,
 ) -> Result<Response, ContractError> {
-    // TODO: Implement feedback state for contract
-    let escrow = ESCROWS.load(deps.storage, &id)?;
-    if info.sender != escrow.creator {
-        return Err(ContractError::Unauthorized {});
-    } else if !escrow.is_completed {
-        return Err(ContractError::NotComplete {});
-    } else {
-        Ok(Response::new()
-            .add_attribute("action", "creator_feedback")
-            .add_attribute("id", id)
-        )
+    assert_admin(deps.storage, &info.sender)?;
+    if !TRUST_PRESETS.has(deps.storage, &name) {
+        return Err(ContractError::UnknownPreset { name });
     }
+    TRUST_PRESETS.remove(deps.storage, &name);
+    let action = AdminAction::RemoveTrustPreset { name: name.clone() };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
+
+    Ok(ContractEvent::trust_preset_removed(&name).response())
 }
 
-pub fn f_feedback(
+pub fn reassign_arbiter(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: FeedbackMsg,
     id: String,
+    new_arbiter: String,
 ) -> Result<Response, ContractError> {
-    // TODO: Implement feedback state for contract
-    let escrow = ESCROWS.load(deps.storage, &id)?;
-    if info.sender != escrow.fulfiller {
-        return Err(ContractError::Unauthorized {});
-    } else if !escrow.is_completed {
-        return Err(ContractError::NotComplete {});
-    } else {
-        Ok(Response::new()
-            .add_attribute("action", "fulfiller_feedback")
-            .add_attribute("id", id)
-        )
+    assert_admin(deps.storage, &info.sender)?;
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    // A ruling removes the escrow, so being in arbitration means nobody has ruled yet.
+    // Before that only an arbiter who recused needs replacing.
+    if !escrow.is_in_arbitration && !escrow.is_pending_reassignment {
+        return Err(ContractError::NotInArbitration {});
+    }
+    let new_arbiter = deps.api.addr_validate(&new_arbiter)?;
+    if !ARBITERS.has(deps.storage, &new_arbiter) {
+        return Err(ContractError::ArbiterNotRegistered {});
+    }
+
+    // An escrow without an arbiter never gets into arbitration
+    let old_arbiter = escrow
+        .arbiter
+        .replace(new_arbiter.clone())
+        .ok_or(ContractError::ArbitrationDisabled {})?;
+    // The new arbiter gets the full window to rule, mediation keeps its own
+    if escrow.is_in_arbitration && escrow.ongoing_mediation(&env).is_none() {
+        escrow.time_arbitration_started = Some(env.block.time.seconds());
     }
+    escrow.is_pending_reassignment = false;
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "reassign_arbiter", &info.sender, &env)?;
+    let action = AdminAction::ReassignArbiter {
+        id: id.clone(),
+        old_arbiter: old_arbiter.clone(),
+        new_arbiter: new_arbiter.clone(),
+    };
+    log_admin_action(deps.storage, &env, &info.sender, action)?;
+
+    Ok(EscrowEvent::arbiter_reassigned(&id, &escrow, &old_arbiter).response())
 }
 
-fn get_trust_metrics(sender: &Addr) -> TrustMetrics {
-    return TrustMetrics {
-        percent_completed: 95,
-        percent_satisfied: 90,
-        avg_volume: 100,
-        avg_completion_speed: 600000,
-        total_volume: 2000,
-        total_completed: 20,
+/// Sorts and dedupes the whitelist, then checks it fits within max
+pub(crate) fn normalize_whitelist(
+    mut whitelist: Vec<Addr>,
+    max: u32,
+) -> Result<Vec<Addr>, ContractError> {
+    whitelist.sort();
+    whitelist.dedup();
+    if whitelist.len() > max as usize {
+        return Err(ContractError::WhitelistTooLarge { max });
     }
+    Ok(whitelist)
 }
 
-fn send_tokens(to: &Addr, balance: &GenericBalance) -> StdResult<Vec<SubMsg>> {
-    let native_balance = &balance.native;
-    let mut msgs: Vec<SubMsg> = if native_balance.is_empty() {
-        vec![]
-    } else {
-        vec![SubMsg::new(BankMsg::Send {
-            to_address: to.into(),
-            amount: native_balance.to_vec(),
-        })]
+/// Validates the addresses allowed to accept, sorted and without duplicates
+fn clean_allowed_fulfillers(
+    api: &dyn Api,
+    allowed: Option<&Vec<String>>,
+) -> Result<Option<Vec<Addr>>, ContractError> {
+    let allowed = match allowed {
+        Some(allowed) => allowed,
+        None => return Ok(None),
     };
-
-    let cw20_balance = &balance.cw20;
-    let cw20_msgs: StdResult<Vec<_>> = cw20_balance
+    let mut addresses = allowed
         .iter()
-        .map(|c| {
-            let msg = Cw20ExecuteMsg::Transfer {
-                recipient: to.into(),
-                amount: c.amount,
-            };
-            let exec = SubMsg::new(WasmMsg::Execute {
-                contract_addr: c.address.to_string(),
-                msg: to_binary(&msg)?,
-                funds: vec![],
-            });
-            Ok(exec)
-        })
-        .collect();
-    msgs.append(&mut cw20_msgs?);
-    Ok(msgs)
+        .map(|address| api.addr_validate(address))
+        .collect::<StdResult<Vec<_>>>()?;
+    addresses.sort();
+    addresses.dedup();
+    if addresses.is_empty() {
+        return Err(ContractError::EmptyFulfillerAllowlist {});
+    }
+    if addresses.len() > MAX_ALLOWED_FULFILLERS {
+        return Err(ContractError::FulfillerAllowlistTooLarge {
+            max: MAX_ALLOWED_FULFILLERS,
+        });
+    }
+    Ok(Some(addresses))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::List {} => to_binary(&query_list(deps)?),
-        QueryMsg::Details { id } => to_binary(&query_details(deps, id)?),
+/// Trims the description, treating a blank one as none
+pub(crate) fn clean_description(
+    description: Option<String>,
+) -> Result<Option<String>, ContractError> {
+    let description = match description {
+        Some(description) => description.trim().to_string(),
+        None => return Ok(None),
+    };
+    if description.len() > MAX_DESCRIPTION_BYTES {
+        return Err(ContractError::DescriptionTooLong {
+            max: MAX_DESCRIPTION_BYTES,
+        });
     }
+    Ok(Some(description).filter(|d| !d.is_empty()))
 }
 
-fn query_details(deps: Deps, id: String) -> StdResult<DetailsResponse> {
-    let escrow = ESCROWS.load(deps.storage, &id)?;
-
-    let cw20_whitelist = escrow.human_whitelist();
+/// Checks each tag, sorted and without duplicates so each is indexed once
+pub(crate) fn clean_tags(tags: Option<Vec<String>>) -> Result<Vec<String>, ContractError> {
+    let mut tags = tags.unwrap_or_default();
+    if let Some(tag) = tags.iter().find(|tag| {
+        !(MIN_TAG_LEN..=MAX_TAG_LEN).contains(&tag.len())
+            || !tag
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    }) {
+        return Err(ContractError::InvalidTag { tag: tag.clone() });
+    }
+    tags.sort();
+    tags.dedup();
+    if tags.len() > MAX_TAGS {
+        return Err(ContractError::TooManyTags { max: MAX_TAGS });
+    }
+    Ok(tags)
+}
 
-    // transform tokens
-    let native_balance = escrow.balance.native;
+/// Checks the creator's locale loosely as BCP-47: subtags of 1 to 8 letters or digits
+/// joined by dashes, the first of 2 to 8 letters. Kept as given, it's only shown.
+pub(crate) fn clean_locale(locale: Option<String>) -> Result<Option<String>, ContractError> {
+    let locale = match locale {
+        Some(locale) => locale,
+        None => return Ok(None),
+    };
+    let mut subtags = locale.split('-');
+    let language = subtags.next().unwrap_or_default();
+    let valid = locale.len() <= MAX_LOCALE_LEN
+        && (2..=8).contains(&language.len())
+        && language.bytes().all(|b| b.is_ascii_alphabetic())
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+        });
+    if !valid {
+        return Err(ContractError::InvalidLocaleHint {
+            field: "creator_locale".to_string(),
+        });
+    }
+    Ok(Some(locale))
+}
 
-    let cw20_balance: StdResult<Vec<_>> = escrow
-        .balance
-        .cw20
-        .into_iter()
-        .map(|token| {
-            Ok(Cw20Coin {
-                address: token.address.into(),
-                amount: token.amount,
+/// Checks the creator's UTC offset is one a timezone could have
+pub(crate) fn assert_utc_offset(offset: Option<i16>) -> Result<(), ContractError> {
+    match offset {
+        Some(offset) if !(-MAX_UTC_OFFSET_MINUTES..=MAX_UTC_OFFSET_MINUTES).contains(&offset) => {
+            Err(ContractError::InvalidLocaleHint {
+                field: "creator_utc_offset_minutes".to_string(),
             })
-        })
-        .collect();
+        }
+        _ => Ok(()),
+    }
+}
 
-    let details = DetailsResponse {
-        id,
-        arbiter: escrow.arbiter.into(),
-        fulfiller: escrow.fulfiller.into(),
-        creator: escrow.creator.into(),
-        end_height: escrow.end_height,
-        end_time: escrow.end_time,
-        native_balance,
-        cw20_balance: cw20_balance?,
-        cw20_whitelist,
-    };
-    Ok(details)
+/// The metrics a requirement stands for, looking presets up as they are now
+fn resolve_trust(
+    storage: &dyn Storage,
+    requirement: &TrustRequirement,
+) -> Result<TrustMetrics, ContractError> {
+    match requirement {
+        TrustRequirement::Metrics(metrics) => Ok(metrics.clone()),
+        TrustRequirement::Preset(name) => TRUST_PRESETS
+            .may_load(storage, name)?
+            .ok_or_else(|| ContractError::UnknownPreset { name: name.clone() }),
+    }
 }
 
-fn query_list(deps: Deps) -> StdResult<ListResponse> {
-    Ok(ListResponse {
-        escrows: all_escrow_ids(deps.storage)?,
-    })
+/// Upper-cases the currency code, which has to be 3 letters
+pub(crate) fn clean_currency(currency: &str) -> Result<String, ContractError> {
+    let currency = currency.trim().to_ascii_uppercase();
+    if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err(ContractError::InvalidCurrency {});
+    }
+    Ok(currency)
 }
 
-#[cfg(test)]
-mod tests {
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coin, coins, CosmosMsg, StdError, Uint128};
+/// A scheduled escrow has to go live before it expires
+pub(crate) fn assert_start_before_end(
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+) -> Result<(), ContractError> {
+    match (start_time, end_time) {
+        (Some(start_time), Some(end_time)) if start_time > end_time => {
+            Err(ContractError::StartAfterEnd {})
+        }
+        _ => Ok(()),
+    }
+}
 
-    use crate::msg::ExecuteMsg::TopUp;
+/// Escrows may not stay open past MAX_ESCROW_LIFETIME_SECONDS from their creation, an
+/// end_height going by when it's expected. Escrows created before time_created was kept
+/// have no limit.
+fn assert_within_lifetime(
+    env: &Env,
+    config: &Config,
+    time_created: Option<u64>,
+    end_time: Option<u64>,
+    end_height: Option<u64>,
+) -> Result<(), ContractError> {
+    let cap = time_created.map_or(u64::MAX, |t| t.saturating_add(MAX_ESCROW_LIFETIME_SECONDS));
+    let implied_time = end_height.map(|h| height_to_time(env, h, config.seconds_per_block));
+    if end_time.into_iter().chain(implied_time).any(|t| t > cap) {
+        return Err(ContractError::LifetimeExceeded {
+            max_seconds: MAX_ESCROW_LIFETIME_SECONDS,
+        });
+    }
+    Ok(())
+}
 
-    use super::*;
+/// Names who holds the escrow, so a fulfiller who lost the race can move on
+fn already_accepted(id: &str, escrow: &Escrow) -> ContractError {
+    ContractError::AlreadyAccepted {
+        id: id.to_string(),
+        accepted_by: escrow.fulfiller.to_string(),
+    }
+}
 
-    #[test]
+fn assert_admin(storage: &dyn Storage, sender: &Addr) -> Result<Config, ContractError> {
+    let config = CONFIG.load(storage)?;
+    if *sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(config)
+}
+
+/// Rejects funds with a zero-amount entry, which would otherwise be stored as a phantom denom
+fn assert_no_zero_amounts(balance: &Balance) -> Result<(), ContractError> {
+    let zero = match balance {
+        Balance::Native(balance) => balance
+            .0
+            .iter()
+            .find(|c| c.amount.is_zero())
+            .map(|c| c.denom.clone()),
+        Balance::Cw20(token) if token.amount.is_zero() => Some(token.address.to_string()),
+        Balance::Cw20(_) => None,
+    };
+    match zero {
+        Some(denom) => Err(ContractError::ZeroAmount { denom }),
+        None => Ok(()),
+    }
+}
+
+fn assert_not_self(env: &Env, sender: &Addr) -> Result<(), ContractError> {
+    if *sender == env.contract.address {
+        return Err(ContractError::SelfCall {});
+    }
+    Ok(())
+}
+
+/// Blacklisted addresses can't enter new escrows, but can still exit the ones they're in
+fn assert_not_blacklisted(storage: &dyn Storage, address: &Addr) -> Result<(), ContractError> {
+    if BLACKLIST.has(storage, address) {
+        return Err(ContractError::AddressBlacklisted {});
+    }
+    Ok(())
+}
+
+pub fn el_arbitrate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ArbitrateMsg,
+    id: String,
+) -> Result<Response, ContractError> {
+    // ArbitrateMsg contains the wallet of whom to send the funds to
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    let arbiter = escrow.assert_arbiter(&info.sender)?.clone();
+    let outcome = ReleaseOutcome::ArbitrateFor {
+        receiver: msg.receiver.clone(),
+        creator_bps: msg.creator_bps,
+    };
+    let payouts = release_plan(deps.api, &env, &escrow, &outcome)?;
+
+    // A split goes to whoever gets the larger share, an even split to neither
+    let creator_won = match msg.creator_bps {
+        None => Some(msg.receiver == escrow.creator.as_str()),
+        Some(bps) if bps * 2 == payments::MAX_BPS => None,
+        Some(bps) => Some(bps * 2 > payments::MAX_BPS),
+    };
+    if let Some(creator_won) = creator_won {
+        let (winner, loser) = if creator_won {
+            (&escrow.creator, &escrow.fulfiller)
+        } else {
+            (&escrow.fulfiller, &escrow.creator)
+        };
+        update_trust_metrics(deps.storage, winner, |m| m.disputes_won += 1)?;
+        update_trust_metrics(deps.storage, loser, |m| m.disputes_lost += 1)?;
+        if !creator_won {
+            update_creator_metrics(deps.storage, &escrow.creator, |m| m.disputes_lost += 1)?;
+        }
+    }
+    record_completion_time(deps.storage, &env, &escrow, true)?;
+    let started = escrow.time_arbitration_started.unwrap_or_default();
+    let ruling_seconds = env.block.time.seconds().saturating_sub(started);
+    update_arbiter_stats(deps.storage, &arbiter, |stats| {
+        stats.rulings += 1;
+        stats.total_ruling_seconds += ruling_seconds;
+        match msg.creator_bps {
+            Some(_) => stats.splits += 1,
+            None if msg.receiver == escrow.creator.as_str() => stats.for_creator += 1,
+            None => stats.for_fulfiller += 1,
+        }
+    })?;
+
+    // we delete the escrow
+    remove_escrow(deps.storage, &id, &escrow)?;
+    record_history(deps.storage, &id, "arbitrate", &info.sender, &env)?;
+    let receipt = issue_receipt(deps.storage, &env, &id, &escrow, "arbitrate", &payouts)?;
+
+    Ok(EscrowEvent::arbitrated(&id, &escrow, &msg.receiver)
+        .response()
+        .add_submessages(escrow_payout_messages(
+            deps.storage,
+            &id,
+            &escrow,
+            &payouts,
+        )?)
+        .set_data(receipt))
+}
+
+pub fn c_create(
+    deps: DepsMut,
+    env: Env,
+    msg: CreateMsg,
+    balance: Balance,
+    sender: &Addr,
+) -> Result<Response, ContractError> {
+    let balance = payments::normalize(balance);
+    let valid = validate_create(deps.as_ref(), &env, &msg, &balance, Some(sender))
+        .map_err(|mut violations| violations.swap_remove(0))?;
+    let funding_source = FundingSource::of(&balance);
+    let config = CONFIG.load(deps.storage)?;
+    // the arbiter has to agree to handle it before anyone can accept
+    let is_pending_arbiter_ack = valid.arbiter.is_some() && config.require_arbiter_ack;
+
+    let mut escrow = Escrow {
+        arbiter: valid.arbiter,
+        fulfiller: sender.clone(),
+        creator: sender.clone(),
+        end_height: msg.end_height,
+        end_time: msg.end_time,
+        balance: valid.balance,
+        funding_source,
+        exchange_rate: msg.exchange_rate,
+        fiat_currency: valid.fiat_currency,
+        cw20_whitelist: valid.cw20_whitelist,
+        required_trust_metrics: valid.required_trust_metrics,
+        auto_complete_trust: msg.auto_complete_trust,
+        listing_deposit: valid.listing_deposit,
+        allowed_fulfillers: valid.allowed_fulfillers,
+        description: valid.description,
+        delivered_fiat_amount: None,
+        dispute: None,
+        fingerprint: String::new(),
+        revision: 0,
+        listing_sort_key: env.block.time.seconds(),
+        last_bumped: None,
+        is_pending_arbiter_ack,
+        is_listed: !is_pending_arbiter_ack,
+        is_canceled: false,
+        is_accepted: false,
+        is_fulfilled: false,
+        is_in_arbitration: false,
+        is_completed: false,
+        time_created: Some(env.block.time.seconds()),
+        time_accepted: None,
+        time_fulfilled: None,
+        time_arbitration_started: None,
+        arbitration_extensions: 0,
+        arbitration_extended_seconds: 0,
+        contributors: vec![],
+        dispute_window_seconds: msg.dispute_window_seconds,
+        dispute_deadline: None,
+        rejected_fulfillers: vec![],
+        is_pending_reassignment: false,
+        require_creator_ack: msg.require_creator_ack,
+        is_awaiting_creator_ack: false,
+        open_to_all_until: None,
+        cancel_pending_until: None,
+        auto_extend_on_relist: msg.auto_extend_on_relist,
+        creator_payout_memo: msg.payout_memo,
+        fulfiller_payout_memo: false,
+        tags: valid.tags,
+        start_time: msg.start_time,
+        mediation_until: None,
+        settlement_proposal: None,
+        creator_locale: valid.creator_locale,
+        creator_utc_offset_minutes: msg.creator_utc_offset_minutes,
+        action_counts: vec![],
+    };
+
+    escrow.fingerprint =
+        reserve_fingerprint(deps.storage, &msg.id, sender, env.block.time.seconds())?;
+    save_escrow(deps.storage, &msg.id, &mut escrow)?;
+    record_history(deps.storage, &msg.id, "create", sender, &env)?;
+    let value = escrow.value_ust();
+    update_creator_metrics(deps.storage, sender, |m| {
+        m.escrows_created += 1;
+        m.value_created_ust += value;
+    })?;
+    if config.daily_volume_limit_ust.is_some() {
+        record_daily_volume(deps.storage, &CREATED_VOLUME, sender, &env, value)?;
+    }
+
+    let details = query_details(deps.as_ref(), env, msg.id.clone())?;
+    Ok(EscrowEvent::created(&msg.id, &escrow)
+        .response()
+        .set_data(to_binary(&details)?))
+}
+
+pub fn c_create_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    items: Vec<BatchCreateItem>,
+) -> Result<Response, ContractError> {
+    CONFIG.load(deps.storage)?.assert_not_sunsetting()?;
+    if items.is_empty() || items.len() > MAX_BATCH_ITEMS {
+        return Err(ContractError::InvalidBatchSize {
+            max: MAX_BATCH_ITEMS,
+        });
+    }
+    let carved: Vec<Coin> = items.iter().flat_map(|item| item.amount.clone()).collect();
+    if payments::normalize(Balance::from(carved)) != payments::normalize(Balance::from(info.funds))
+    {
+        return Err(ContractError::BatchFundsMismatch {});
+    }
+
+    let mut created = vec![];
+    let mut ids = vec![];
+    for (index, item) in items.into_iter().enumerate() {
+        let id = item.msg.id.clone();
+        c_create(
+            deps.branch(),
+            env.clone(),
+            item.msg,
+            Balance::from(item.amount),
+            &info.sender,
+        )
+        .map_err(|err| ContractError::BatchItem {
+            index,
+            error: Box::new(err),
+        })?;
+        created.push((id.clone(), ESCROWS.load(deps.storage, &id)?.fingerprint));
+        ids.push(id);
+    }
+    Ok(ContractEvent::batch_created(&created)
+        .response()
+        .set_data(to_binary(&BatchCreateResponse { ids })?))
+}
+
+/// The parts of a new escrow that validate_create derives from the CreateMsg
+pub struct ValidCreate {
+    pub arbiter: Option<Addr>,
+    pub balance: GenericBalance,
+    pub listing_deposit: Vec<Coin>,
+    pub cw20_whitelist: Vec<Addr>,
+    pub allowed_fulfillers: Option<Vec<Addr>>,
+    pub description: Option<String>,
+    pub fiat_currency: String,
+    pub required_trust_metrics: TrustMetrics,
+    pub tags: Vec<String>,
+    pub creator_locale: Option<String>,
+}
+
+/// Every check c_create makes before storing an escrow, shared with the ValidateCreate
+/// query so the two can't drift apart. Collects all violations, in the order c_create
+/// used to fail on them. The blacklist is only checked when the creator is known.
+pub fn validate_create(
+    deps: Deps,
+    env: &Env,
+    msg: &CreateMsg,
+    balance: &Balance,
+    creator: Option<&Addr>,
+) -> Result<ValidCreate, Vec<ContractError>> {
+    let mut violations = vec![];
+    if let Some(creator) = creator {
+        violations.extend(assert_not_blacklisted(deps.storage, creator).err());
+    }
+    let config = match CONFIG.load(deps.storage) {
+        Ok(config) => config,
+        Err(err) => {
+            violations.push(err.into());
+            return Err(violations);
+        }
+    };
+    violations.extend(config.assert_not_sunsetting().err());
+    if let Some(creator) = creator {
+        let kyc_contract = config.kyc_contract.as_ref();
+        violations.extend(kyc::assert_verified(&deps.querier, kyc_contract, creator).err());
+    }
+    if let Some(max) = config.max_active_escrows {
+        match ACTIVE_ESCROWS.may_load(deps.storage) {
+            Ok(active) if active.unwrap_or_default() >= max => {
+                violations.push(ContractError::CapacityReached {})
+            }
+            Ok(_) => {}
+            Err(err) => violations.push(err.into()),
+        }
+    }
+    let deposit = &config.listing_deposit;
+    let (balance, listing_deposit) = payments::take_deposit(balance.clone(), deposit)
+        .unwrap_or_else(|| {
+            let deposit: Vec<String> = deposit.iter().map(ToString::to_string).collect();
+            violations.push(ContractError::ListingDepositRequired {
+                deposit: deposit.join(","),
+            });
+            (balance.clone(), vec![])
+        });
+    let balance = &balance;
+    if balance.is_empty() {
+        violations.push(ContractError::EmptyBalance {});
+    }
+    violations.extend(assert_no_zero_amounts(balance).err());
+
+    let mut cw20_whitelist = msg.addr_whitelist(deps.api).unwrap_or_else(|err| {
+        violations.push(err.into());
+        vec![]
+    });
+    let escrow_balance = match balance.clone() {
+        Balance::Native(balance) => GenericBalance {
+            native: balance.0,
+            cw20: vec![],
+        },
+        Balance::Cw20(token) => {
+            // make sure the token sent is on the whitelist by default
+            cw20_whitelist.push(token.address.clone());
+            GenericBalance {
+                native: vec![],
+                cw20: vec![token],
+            }
+        }
+    };
+    if let Some(creator) = creator {
+        let value = escrow_balance.value_ust();
+        let limit = config.daily_volume_limit_ust;
+        let within = assert_daily_volume(deps.storage, &CREATED_VOLUME, creator, env, value, limit);
+        violations.extend(within.err());
+    }
+    let cw20_whitelist = normalize_whitelist(cw20_whitelist, config.max_whitelist_len)
+        .unwrap_or_else(|err| {
+            violations.push(err);
+            vec![]
+        });
+
+    violations.extend(assert_start_before_end(msg.start_time, msg.end_time).err());
+    let now = Some(env.block.time.seconds());
+    let within = assert_within_lifetime(env, &config, now, msg.end_time, msg.end_height);
+    violations.extend(within.err());
+    if let (Some(end_height), Some(end_time)) = (msg.end_height, msg.end_time) {
+        // Both bounds apply, so they should describe roughly the same moment
+        let implied_time = height_to_time(env, end_height, config.seconds_per_block);
+        if implied_time.abs_diff(end_time) > MAX_EXPIRY_DRIFT_SECONDS {
+            violations.push(ContractError::InconsistentExpiry {});
+        }
+    }
+
+    let arbiter = match (msg.arbitration_enabled, &msg.arbiter) {
+        (false, _) => None,
+        (true, Some(arbiter)) => deps
+            .api
+            .addr_validate(arbiter)
+            .map_err(|err| violations.push(err.into()))
+            .ok(),
+        (true, None) => {
+            violations.push(ContractError::MissingArbiter {});
+            None
+        }
+    };
+    let allowed_fulfillers = clean_allowed_fulfillers(deps.api, msg.allowed_fulfillers.as_ref())
+        .unwrap_or_else(|err| {
+            violations.push(err);
+            None
+        });
+    let description = clean_description(msg.description.clone()).unwrap_or_else(|err| {
+        violations.push(err);
+        None
+    });
+    let tags = clean_tags(msg.tags.clone()).unwrap_or_else(|err| {
+        violations.push(err);
+        vec![]
+    });
+    let creator_locale = clean_locale(msg.creator_locale.clone()).unwrap_or_else(|err| {
+        violations.push(err);
+        None
+    });
+    violations.extend(assert_utc_offset(msg.creator_utc_offset_minutes).err());
+    if msg.dispute_window_seconds == Some(0) {
+        violations.push(ContractError::InvalidDisputeWindow {});
+    }
+    let fiat_currency = clean_currency(&msg.fiat_currency).unwrap_or_else(|err| {
+        violations.push(err);
+        String::new()
+    });
+    let required_trust_metrics = resolve_trust(deps.storage, &msg.required_trust_metrics)
+        .unwrap_or_else(|err| {
+            violations.push(err);
+            TrustMetrics::default()
+        });
+
+    // fail if the id was already in use, settled escrows keep theirs for their archive
+    // and receipt
+    if is_id_taken(deps.storage, &msg.id) {
+        violations.push(ContractError::AlreadyInUse {});
+    }
+
+    if !violations.is_empty() {
+        return Err(violations);
+    }
+    Ok(ValidCreate {
+        arbiter,
+        balance: escrow_balance,
+        listing_deposit,
+        cw20_whitelist,
+        allowed_fulfillers,
+        description,
+        fiat_currency,
+        required_trust_metrics,
+        tags,
+        creator_locale,
+    })
+}
+
+pub fn c_clone(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    source_id: String,
+) -> Result<Response, ContractError> {
+    // a finished escrow's terms outlive it in the archive, so it can be listed again
+    let source = match ARCHIVED_ESCROWS.may_load(deps.storage, &source_id)? {
+        Some(archived) if !ESCROWS.has(deps.storage, &source_id) => archived,
+        _ => load_active_or_explain(deps.storage, &source_id)?,
+    };
+    source.assert_creator(&info.sender)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let end_time = match (
+        source.expires_at(&env, config.seconds_per_block),
+        source.time_created,
+    ) {
+        (Some((expiry, _)), Some(time_created)) => Some(now + expiry.saturating_sub(time_created)),
+        _ => None,
+    };
+    let start_time = match (source.start_time, source.time_created) {
+        (Some(start), Some(time_created)) => Some(now + start.saturating_sub(time_created)),
+        _ => None,
+    };
+    let msg = CreateMsg {
+        end_height: None,
+        end_time,
+        start_time,
+        ..create_msg_for(first_free_id(deps.storage, &source_id), source)
+    };
+
+    let id = msg.id.clone();
+    let mut res = c_create(
+        deps.branch(),
+        env,
+        msg,
+        Balance::from(info.funds),
+        &info.sender,
+    )?;
+    let escrow = ESCROWS.load(deps.storage, &id)?;
+    res.attributes = EscrowEvent::cloned(&id, &escrow, &source_id).attributes();
+    Ok(res)
+}
+
+/// Stops handlers appending to an escrow from growing it past the configured budget,
+/// where loading it could cost more gas than its payout can get
+fn assert_escrow_size(config: &Config, escrow: &Escrow) -> Result<(), ContractError> {
+    if escrow.approximate_size() > config.max_escrow_bytes {
+        return Err(ContractError::EscrowSizeLimit {});
+    }
+    Ok(())
+}
+
+/// The first free one of id, id-2, id-3, ...
+fn first_free_id(storage: &dyn Storage, id: &str) -> String {
+    std::iter::once(id.to_string())
+        .chain((2..).map(|n| format!("{}-{}", id, n)))
+        .find(|id| !is_id_taken(storage, id))
+        .unwrap()
+}
+
+fn is_id_taken(storage: &dyn Storage, id: &str) -> bool {
+    ESCROWS.has(storage, id) || ARCHIVED_ESCROWS.has(storage, id) || RECEIPTS.has(storage, id)
+}
+
+/// The CreateMsg that would create an escrow on the same terms under id, expiry as stored
+fn create_msg_for(id: String, escrow: Escrow) -> CreateMsg {
+    CreateMsg {
+        id,
+        arbitration_enabled: escrow.arbiter.is_some(),
+        arbiter: escrow.arbiter.map(String::from),
+        end_height: escrow.end_height,
+        end_time: escrow.end_time,
+        exchange_rate: escrow.exchange_rate,
+        fiat_currency: escrow.fiat_currency,
+        cw20_whitelist: Some(
+            escrow
+                .cw20_whitelist
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        ),
+        required_trust_metrics: escrow.required_trust_metrics.into(),
+        auto_complete_trust: escrow.auto_complete_trust,
+        allowed_fulfillers: escrow
+            .allowed_fulfillers
+            .map(|allowed| allowed.into_iter().map(String::from).collect()),
+        description: escrow.description,
+        dispute_window_seconds: escrow.dispute_window_seconds,
+        require_creator_ack: escrow.require_creator_ack,
+        auto_extend_on_relist: escrow.auto_extend_on_relist,
+        payout_memo: escrow.creator_payout_memo,
+        tags: Some(escrow.tags).filter(|tags| !tags.is_empty()),
+        start_time: escrow.start_time,
+        creator_locale: escrow.creator_locale,
+        creator_utc_offset_minutes: escrow.creator_utc_offset_minutes,
+    }
+}
+
+pub fn top_up(
+    deps: DepsMut,
+    env: Env,
+    id: String,
+    balance: Balance,
+    sender: &Addr,
+) -> Result<Response, ContractError> {
+    let balance = payments::normalize(balance);
+    assert_not_blacklisted(deps.storage, sender)?;
+    if balance.is_empty() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    assert_no_zero_amounts(&balance)?;
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    config.assert_not_sunsetting()?;
+    // Once accepted, the fulfiller has agreed to the current amount
+    if !escrow.is_listed {
+        return Err(ContractError::NotListed {});
+    }
+    if !escrow.is_creator(sender) && !config.allow_third_party_topup {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match (&balance, &escrow.funding_source) {
+        (Balance::Cw20(token), _) => {
+            // ensure the token is on the whitelist
+            if !escrow.cw20_whitelist.iter().any(|t| t == &token.address) {
+                return Err(ContractError::NotInWhitelist {});
+            }
+        }
+        (Balance::Native(_), FundingSource::Cw20Hook { .. }) => {
+            if !config.allow_mixed_funding {
+                return Err(ContractError::MixedFunding {});
+            }
+        }
+        (Balance::Native(_), FundingSource::NativeDirect) => {}
+    };
+
+    // a gift, kept apart so a refund goes back to whoever paid it
+    if !escrow.is_creator(sender) {
+        let mut gift = GenericBalance::default();
+        gift.add_tokens(balance.clone());
+        let contributors = &mut escrow.contributors;
+        match contributors.iter().position(|(c, _)| c == sender) {
+            Some(i) => contributors[i].1 = payments::merge(&contributors[i].1, &gift),
+            None if contributors.len() == MAX_CONTRIBUTORS => {
+                return Err(ContractError::TooManyContributors {
+                    max: MAX_CONTRIBUTORS,
+                })
+            }
+            None => contributors.push((sender.clone(), gift)),
+        }
+    }
+    let value_before = escrow.value_ust();
+    escrow.balance.add_tokens(balance);
+    if escrow.balance.cw20.len() > MAX_CW20_BALANCES {
+        return Err(ContractError::TooManyCw20Balances {
+            max: MAX_CW20_BALANCES,
+        });
+    }
+    escrow.assert_balance_consistent()?;
+    assert_escrow_size(&config, &escrow)?;
+    // topping up counts like creating, or a small escrow could grow past the limit
+    let added = escrow.value_ust() - value_before;
+    let limit = config.daily_volume_limit_ust;
+    assert_daily_volume(deps.storage, &CREATED_VOLUME, sender, &env, added, limit)?;
+
+    // and save
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "top_up", sender, &env)?;
+    if limit.is_some() {
+        record_daily_volume(deps.storage, &CREATED_VOLUME, sender, &env, added)?;
+    }
+
+    Ok(EscrowEvent::topped_up(&id, &escrow).response())
+}
+
+pub fn f_accept(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    expected_exchange_rate: Option<Decimal>,
+    required_creator_metrics: Option<CreatorRequirement>,
+    payout_memo: bool,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    // The creator never acked who accepted last, so the escrow is up for grabs again
+    let lapsed = match escrow.is_creator_ack_lapsed(&env, config.creator_ack_timeout_seconds) {
+        true => Some(escrow.clear_acceptance()),
+        false => None,
+    };
+    let extended = lapsed.as_ref().and_then(|_| escrow.extend_on_relist());
+    assert_can_accept(deps.storage, &env, &id, &escrow, &info.sender)?;
+    // Only when the fulfiller's client asks for it, the creator's record is no reason to
+    // keep anyone from accepting otherwise
+    if let Some(required) = required_creator_metrics {
+        let metrics = get_creator_metrics(deps.storage, &escrow.creator)?;
+        if !required.is_met_by(&metrics) {
+            return Err(ContractError::CreatorMetricsInsufficient {});
+        }
+    }
+    kyc::assert_verified(&deps.querier, config.kyc_contract.as_ref(), &info.sender)?;
+    let value = escrow.value_ust();
+    let limit = config.daily_accept_limit_ust;
+    assert_daily_volume(
+        deps.storage,
+        &ACCEPTED_VOLUME,
+        &info.sender,
+        &env,
+        value,
+        limit,
+    )?;
+    // The creator changed the rate after the fulfiller saw it
+    if expected_exchange_rate.is_some_and(|rate| rate != escrow.exchange_rate) {
+        Err(ContractError::RateChanged {
+            current: escrow.exchange_rate,
+        })
+    } else {
+        // We set the message sender as the contract fulfiller
+        escrow.fulfiller = info.sender;
+        escrow.is_listed = false;
+        escrow.is_accepted = true;
+        escrow.is_awaiting_creator_ack = escrow.require_creator_ack;
+        escrow.time_accepted = Some(env.block.time.seconds());
+        escrow.fulfiller_payout_memo = payout_memo;
+        save_escrow(deps.storage, &id, &mut escrow)?;
+        if let Some(lapsed) = &lapsed {
+            unindex_parties(deps.storage, &id, &escrow.creator, lapsed)?;
+            record_history(deps.storage, &id, "ack_lapsed", lapsed, &env)?;
+            record_extension(deps.storage, &id, &escrow, extended, &env)?;
+        }
+        index_parties(deps.storage, &id, &escrow)?;
+        record_history(deps.storage, &id, "accept", &escrow.fulfiller, &env)?;
+        if limit.is_some() {
+            record_daily_volume(
+                deps.storage,
+                &ACCEPTED_VOLUME,
+                &escrow.fulfiller,
+                &env,
+                value,
+            )?;
+        }
+
+        let details = query_details(deps.as_ref(), env, id.clone())?;
+        Ok(EscrowEvent::accepted(&id, &escrow, lapsed.as_ref())
+            .response()
+            .set_data(to_binary(&details)?))
+    }
+}
+
+/// Whether the address may accept the escrow right now, the first requirement not met
+/// is the error
+pub fn assert_can_accept(
+    storage: &dyn Storage,
+    env: &Env,
+    id: &str,
+    escrow: &Escrow,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    match accept_blockers(storage, env, id, escrow, address)?
+        .into_iter()
+        .next()
+    {
+        Some((_, err)) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Every requirement the address fails to accept the escrow right now, in the order FAccept
+/// checks them, each with a code for clients. The one place the rules for accepting live,
+/// FAccept and the listing queries go through it.
+pub fn accept_blockers(
+    storage: &dyn Storage,
+    env: &Env,
+    id: &str,
+    escrow: &Escrow,
+    address: &Addr,
+) -> StdResult<Vec<(&'static str, ContractError)>> {
+    let mut blockers = vec![];
+    let config = CONFIG.load(storage)?;
+    if config.sunset {
+        blockers.push(("sunset", ContractError::ContractSunsetting {}));
+    }
+    if BLACKLIST.has(storage, address) {
+        blockers.push(("blacklisted", ContractError::AddressBlacklisted {}));
+    }
+    // The contract creator can't accept their own contract
+    if escrow.is_creator(address) {
+        blockers.push(("creator", ContractError::Unauthorized {}));
+    }
+    // Nor may the arbiter, who would then rule on their own delivery
+    if escrow.arbiter.as_ref() == Some(address) {
+        blockers.push(("arbiter", ContractError::ArbiterCannotFulfill {}));
+    }
+    // Nobody may accept before the arbiter agreed to handle disputes
+    if escrow.is_pending_arbiter_ack {
+        blockers.push(("pending_arbiter_ack", ContractError::PendingArbiterAck {}));
+    }
+    // Nor while nobody would handle a dispute
+    if escrow.is_pending_reassignment {
+        blockers.push(("pending_reassignment", ContractError::ArbiterRecused {}));
+    }
+    // Another fulfiller got there first, possibly earlier in the same block
+    if escrow.is_accepted {
+        blockers.push(("accepted", already_accepted(id, escrow)));
+    }
+    // The creator canceled it, though they may still take that back
+    else if let Some(until) = escrow.cancel_pending_until {
+        blockers.push(("pending_cancel", ContractError::PendingCancel { until }));
+    }
+    // We check if the contract is in a state where it can be accepted
+    else if !escrow.is_listed && !escrow.is_pending_arbiter_ack {
+        blockers.push(("not_listed", ContractError::NotListed {}));
+    }
+    // An expired listing only waits for its creator to cancel it
+    if escrow.is_expired(env) {
+        blockers.push(("expired", ContractError::Expired {}));
+    }
+    // Nor may one about to expire be taken on, with no time left to deliver. Listings
+    // without an expiry never run short.
+    else if let Some((expiry, _)) = escrow.expires_at(env, config.seconds_per_block) {
+        let remaining = expiry.saturating_sub(env.block.time.seconds());
+        if remaining < config.min_remaining_on_accept_seconds {
+            blockers.push((
+                "insufficient_time",
+                ContractError::InsufficientTimeRemaining { remaining },
+            ));
+        }
+    }
+    // The creator still has a moment to take back a mistake
+    let grace_period_end = escrow.grace_period_end(config.listing_grace_period_seconds);
+    if let Some(until) = grace_period_end.filter(|until| env.block.time.seconds() < *until) {
+        blockers.push((
+            "grace_period",
+            ContractError::ListingInGracePeriod { until },
+        ));
+    }
+    // The creator scheduled it for later
+    if let Some(starts_at) = escrow.upcoming_start(env) {
+        blockers.push(("not_yet_active", ContractError::NotYetActive { starts_at }));
+    }
+    // A private escrow is only for the fulfillers the creator knows
+    if escrow
+        .allowed_fulfillers
+        .as_ref()
+        .is_some_and(|allowed| !allowed.contains(address))
+    {
+        blockers.push((
+            "not_on_allowlist",
+            ContractError::NotOnFulfillerAllowlist {},
+        ));
+    }
+    // The creator already turned this fulfiller away
+    if escrow.rejected_fulfillers.contains(address) {
+        blockers.push(("rejected", ContractError::FulfillerRejected {}));
+    }
+    // We have to check if trust metrics of the sender wallet are tolerable, unless the
+    // creator opened the listing to everyone for now
+    if escrow.open_to_all_until(env).is_none()
+        && !escrow
+            .required_trust_metrics
+            .is_higher(get_trust_metrics(storage, address)?)
+    {
+        blockers.push(("trust_metrics", ContractError::TrustMetricsInsufficient {}));
+    }
+    Ok(blockers)
+}
+
+pub fn c_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    let config = CONFIG.load(deps.storage)?;
+    escrow.assert_creator(&info.sender)?;
+    if let Some(until) = escrow.cancel_pending_until {
+        Err(ContractError::PendingCancel { until })
+    } else if escrow.is_in_arbitration {
+        Err(ContractError::InArbitration {})
+    }
+    // The fiat may already be on its way, so the creator must confirm or dispute instead
+    else if escrow.is_fulfilled {
+        Err(ContractError::AlreadyFulfilled {})
+    }
+    // Right after acceptance the creator may still back out, compensating the fulfiller
+    else if escrow.is_accepted
+        && escrow.in_post_accept_cancel_window(&env, config.post_accept_cancel_window_seconds)
+    {
+        let (penalty, refund) = payments::split(&escrow.balance, config.cancel_penalty_bps);
+        escrow.is_canceled = true;
+        remove_escrow(deps.storage, &id, &escrow)?;
+        record_history(deps.storage, &id, "cancel", &info.sender, &env)?;
+        update_creator_metrics(deps.storage, &escrow.creator, |m| {
+            m.cancels_after_accept += 1
+        })?;
+
+        let mut payouts = vec![(escrow.fulfiller.clone(), penalty)];
+        payouts.extend(escrow.refund_payouts(&refund));
+        payouts.extend(escrow.deposit_payout(&env, &config));
+        let messages = escrow_payout_messages(deps.storage, &id, &escrow, &payouts)?;
+        Ok(
+            EscrowEvent::canceled_with_penalty(&id, &escrow, config.cancel_penalty_bps)
+                .response()
+                .add_submessages(messages),
+        )
+    }
+    // The fulfiller keeps the escrow until their time to fulfill runs out
+    else if escrow.is_accepted && !escrow.is_accept_expired(&env) {
+        Err(already_accepted(&id, &escrow))
+    } else if !escrow.is_accepted && needs_cancel_cooloff(&env, &escrow, &config) {
+        let until = request_cancel(deps.storage, &env, &info.sender, &id, &mut escrow, &config)?;
+        Ok(EscrowEvent::cancel_requested(&id, &escrow, until).response())
+    } else {
+        refund_canceled(deps, &env, &info.sender, id, escrow, &config)
+    }
+}
+
+/// A listing canceled by mistake can still be restored for a while. Expired ones
+/// could never be accepted again anyway, nor could anything once sunsetting.
+fn needs_cancel_cooloff(env: &Env, escrow: &Escrow, config: &Config) -> bool {
+    config.cancel_cooloff_seconds > 0 && !escrow.is_expired(env) && !config.sunset
+}
+
+/// Unlists the escrow until the cancel cooloff runs out, returning when that is
+fn request_cancel(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    id: &str,
+    escrow: &mut Escrow,
+    config: &Config,
+) -> StdResult<u64> {
+    let until = env.block.time.seconds() + config.cancel_cooloff_seconds;
+    escrow.is_listed = false;
+    escrow.cancel_pending_until = Some(until);
+    save_escrow(storage, id, escrow)?;
+    record_history(storage, id, "request_cancel", sender, env)?;
+    Ok(until)
+}
+
+/// Deletes a canceled escrow and refunds the creator, whoever else topped up, and the
+/// listing deposit
+fn refund_canceled(
+    deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    id: String,
+    mut escrow: Escrow,
+    config: &Config,
+) -> Result<Response, ContractError> {
+    escrow.is_listed = false;
+    escrow.is_canceled = true;
+    remove_escrow(deps.storage, &id, &escrow)?;
+    record_history(deps.storage, &id, "cancel", sender, env)?;
+
+    let mut payouts = escrow.refund_payouts(&escrow.balance);
+    let deposit = escrow.deposit_payout(env, config);
+    let event = EscrowEvent::canceled(&id, &escrow, deposit.as_ref().map(|(to, _)| to));
+    payouts.extend(deposit);
+    Ok(event.response().add_submessages(escrow_payout_messages(
+        deps.storage,
+        &id,
+        &escrow,
+        &payouts,
+    )?))
+}
+
+pub fn c_undo_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_creator(&info.sender)?;
+    let until = escrow
+        .cancel_pending_until
+        .ok_or(ContractError::NotPendingCancel {})?;
+    if env.block.time.seconds() >= until {
+        return Err(ContractError::CancelCooloffOver {});
+    }
+
+    // back where it was, or still waiting for the arbiter
+    escrow.cancel_pending_until = None;
+    escrow.is_listed = !escrow.is_pending_arbiter_ack;
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "undo_cancel", &info.sender, &env)?;
+
+    Ok(EscrowEvent::cancel_undone(&id, &escrow).response())
+}
+
+pub fn finalize_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    let until = escrow
+        .cancel_pending_until
+        .ok_or(ContractError::NotPendingCancel {})?;
+    let config = CONFIG.load(deps.storage)?;
+    if env.block.time.seconds() < until && !config.sunset {
+        return Err(ContractError::CancelCooloffActive { until });
+    }
+    refund_canceled(deps, &env, &info.sender, id, escrow, &config)
+}
+
+pub fn c_cancel_all(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    only_expired: bool,
+) -> Result<Response, ContractError> {
+    // at most one page is looked at, whatever comes of it
+    let limit = page_limit(limit);
+    let (start, end) = page_bounds(start_after.map(String::into_bytes), Order::Ascending);
+    let ids: StdResult<Vec<String>> = CREATOR_ESCROWS
+        .prefix(&info.sender)
+        .keys(deps.storage, start, end, Order::Ascending)
+        .take(limit)
+        .map(|key| String::from_utf8(key).map_err(StdError::from))
+        .collect();
+    let ids = ids?;
+    let next_start_after = ids.last().filter(|_| ids.len() == limit).cloned();
+
+    let mut canceled = vec![];
+    let mut pending = vec![];
+    let config = CONFIG.load(deps.storage)?;
+    let mut messages = vec![];
+    for id in ids {
+        let mut escrow = ESCROWS.load(deps.storage, &id)?;
+        let is_open = escrow.is_listed || escrow.is_pending_arbiter_ack;
+        if !is_open
+            || escrow.is_accepted
+            || escrow.cancel_pending_until.is_some()
+            || (only_expired && !escrow.is_expired(&env))
+        {
+            continue;
+        }
+        // the same cooloff a single cancel would wait out
+        if needs_cancel_cooloff(&env, &escrow, &config) {
+            request_cancel(deps.storage, &env, &info.sender, &id, &mut escrow, &config)?;
+            pending.push((id, escrow.fingerprint));
+            continue;
+        }
+        remove_escrow(deps.storage, &id, &escrow)?;
+        record_history(deps.storage, &id, "cancel", &info.sender, &env)?;
+        // each escrow's own, so those asking for a payout memo get theirs
+        let mut payouts = escrow.refund_payouts(&escrow.balance);
+        payouts.extend(escrow.deposit_payout(&env, &config));
+        messages.append(&mut escrow_payout_messages(
+            deps.storage,
+            &id,
+            &escrow,
+            &payouts,
+        )?);
+        canceled.push((id, escrow.fingerprint));
+    }
+
+    Ok(
+        ContractEvent::canceled_all(&canceled, &pending, next_start_after)
+            .response()
+            .add_submessages(messages),
+    )
+}
+
+pub fn f_unaccept(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_fulfiller(&info.sender)?;
+    if escrow.is_in_arbitration {
+        Err(ContractError::InArbitration {})
+    } else if !escrow.is_accepted || escrow.is_fulfilled {
+        Err(ContractError::CantUnaccept {})
+    } else {
+        // Remove the fulfiller
+        escrow.clear_acceptance();
+        let extended = escrow.extend_on_relist();
+        save_escrow(deps.storage, &id, &mut escrow)?;
+        unindex_parties(deps.storage, &id, &escrow.creator, &info.sender)?;
+        record_history(deps.storage, &id, "unaccept", &info.sender, &env)?;
+        record_extension(deps.storage, &id, &escrow, extended, &env)?;
+
+        Ok(EscrowEvent::unaccepted(&id, &escrow).response())
+    }
+}
+
+pub fn c_reject(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_creator(&info.sender)?;
+    if escrow.is_in_arbitration {
+        return Err(ContractError::InArbitration {});
+    }
+    // The fiat may already be on its way
+    if !escrow.is_accepted || escrow.is_fulfilled {
+        return Err(ContractError::CantReject {});
+    }
+    if escrow.rejected_fulfillers.len() >= MAX_REJECTIONS {
+        return Err(ContractError::TooManyRejections {
+            max: MAX_REJECTIONS,
+        });
+    }
+    let reason = clean_description(reason)?;
+
+    // Back on the market, as before anyone accepted
+    let rejected = escrow.clear_acceptance();
+    escrow.rejected_fulfillers.push(rejected.clone());
+    let extended = escrow.extend_on_relist();
+    assert_escrow_size(&CONFIG.load(deps.storage)?, &escrow)?;
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    unindex_parties(deps.storage, &id, &escrow.creator, &rejected)?;
+    record_history(deps.storage, &id, "reject", &info.sender, &env)?;
+    record_extension(deps.storage, &id, &escrow, extended, &env)?;
+
+    Ok(EscrowEvent::rejected(&id, &escrow, &rejected, reason).response())
+}
+
+/// Notes in the history when relisting pushed the expiry back, see Escrow::extend_on_relist
+fn record_extension(
+    storage: &mut dyn Storage,
+    id: &str,
+    escrow: &Escrow,
+    extended: Option<u64>,
+    env: &Env,
+) -> StdResult<()> {
+    match extended {
+        Some(end_time) => {
+            let note = Some(format!("end_time {}", end_time));
+            record_history_note(storage, id, "auto_extend", &escrow.creator, env, note)
+        }
+        None => Ok(()),
+    }
+}
+
+pub fn c_ack_fulfiller(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_creator(&info.sender)?;
+    if !escrow.is_awaiting_creator_ack {
+        return Err(ContractError::NotAwaitingCreatorAck {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if escrow.is_creator_ack_lapsed(&env, config.creator_ack_timeout_seconds) {
+        return Err(ContractError::CreatorAckExpired {});
+    }
+
+    // the fulfiller couldn't fulfill until now, so their time starts over
+    escrow.is_awaiting_creator_ack = false;
+    escrow.time_accepted = Some(env.block.time.seconds());
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "ack_fulfiller", &info.sender, &env)?;
+
+    Ok(EscrowEvent::fulfiller_acked(&id, &escrow).response())
+}
+
+/// The later of a stored expiry bound and the one asked for. No bound stays unbounded.
+fn extended(stored: Option<u64>, requested: Option<u64>) -> Option<u64> {
+    stored.map(|stored| requested.map_or(stored, |requested| stored.max(requested)))
+}
+
+pub fn c_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CreateMsg,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &msg.id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_creator(&info.sender)?;
+    // The fulfiller accepted the terms as they were
+    if escrow.is_accepted {
+        return Err(already_accepted(&msg.id, &escrow));
+    }
+    if !escrow.is_listed {
+        return Err(ContractError::NotListed {});
+    }
+
+    escrow.exchange_rate = msg.exchange_rate;
+    escrow.fiat_currency = clean_currency(&msg.fiat_currency)?;
+    escrow.required_trust_metrics = resolve_trust(deps.storage, &msg.required_trust_metrics)?;
+    escrow.auto_complete_trust = msg.auto_complete_trust;
+    escrow.allowed_fulfillers =
+        clean_allowed_fulfillers(deps.api, msg.allowed_fulfillers.as_ref())?;
+    escrow.description = clean_description(msg.description)?;
+    escrow.require_creator_ack = msg.require_creator_ack;
+    escrow.auto_extend_on_relist = msg.auto_extend_on_relist;
+    escrow.creator_payout_memo = msg.payout_memo;
+    escrow.tags = clean_tags(msg.tags)?;
+    escrow.creator_locale = clean_locale(msg.creator_locale)?;
+    assert_utc_offset(msg.creator_utc_offset_minutes)?;
+    escrow.creator_utc_offset_minutes = msg.creator_utc_offset_minutes;
+    // the expiry may only be pushed back, e.g. to leave a fulfiller enough time, and only
+    // as far as the escrow's lifetime goes
+    let config = CONFIG.load(deps.storage)?;
+    let end_time = extended(escrow.end_time, msg.end_time);
+    let end_height = extended(escrow.end_height, msg.end_height);
+    assert_within_lifetime(
+        &env,
+        &config,
+        escrow.time_created,
+        end_time.filter(|_| end_time != escrow.end_time),
+        end_height.filter(|_| end_height != escrow.end_height),
+    )?;
+    escrow.end_time = end_time;
+    escrow.end_height = end_height;
+    assert_start_before_end(msg.start_time, escrow.end_time)?;
+    escrow.start_time = msg.start_time;
+    assert_escrow_size(&config, &escrow)?;
+    save_escrow(deps.storage, &msg.id, &mut escrow)?;
+    record_history(deps.storage, &msg.id, "change", &info.sender, &env)?;
+
+    Ok(EscrowEvent::changed(&msg.id, &escrow).response())
+}
+
+pub fn f_complete(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    delivered_fiat_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_fulfiller(&info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    if !escrow.is_accepted || escrow.is_fulfilled {
+        Err(ContractError::CantFulfill {})
+    } else if escrow.is_awaiting_creator_ack {
+        Err(ContractError::AwaitingCreatorAck {})
+    } else if escrow.is_hard_expired(&env, config.mid_lifecycle_expiry) {
+        Err(ContractError::Expired {})
+    } else {
+        let now = env.block.time.seconds();
+        let window = escrow
+            .dispute_window_seconds
+            .unwrap_or(config.dispute_window_seconds);
+        escrow.is_fulfilled = true;
+        escrow.time_fulfilled = Some(now);
+        escrow.dispute_deadline = Some(now + window);
+        escrow.delivered_fiat_amount = delivered_fiat_amount;
+        // The creator confirmed in advance for fulfillers this trusted, a short delivery
+        // still needs them to look at it
+        let auto_complete = match &escrow.auto_complete_trust {
+            Some(trust) => {
+                !escrow.is_rate_deviation_exceeded()
+                    && trust.is_higher(get_trust_metrics(deps.storage, &info.sender)?)
+            }
+            None => false,
+        };
+        if auto_complete {
+            let payouts = release_plan(deps.api, &env, &escrow, &ReleaseOutcome::CreatorComplete)?;
+            remove_escrow(deps.storage, &id, &escrow)?;
+            record_history(deps.storage, &id, "fulfill", &info.sender, &env)?;
+            record_history(deps.storage, &id, "auto_complete", &info.sender, &env)?;
+            credit_volume(deps.storage, &escrow, &payouts)?;
+            record_completion_time(deps.storage, &env, &escrow, false)?;
+            let receipt =
+                issue_receipt(deps.storage, &env, &id, &escrow, "auto_complete", &payouts)?;
+
+            return Ok(EscrowEvent::auto_completed(&id, &escrow)
+                .response()
+                .add_submessages(escrow_payout_messages(
+                    deps.storage,
+                    &id,
+                    &escrow,
+                    &payouts,
+                )?)
+                .set_data(receipt));
+        }
+        save_escrow(deps.storage, &id, &mut escrow)?;
+        record_history(deps.storage, &id, "fulfill", &info.sender, &env)?;
+        let deadline = now + window;
+
+        Ok(EscrowEvent::fulfilled(&id, &escrow, deadline)
+            .response()
+            .set_data(to_binary(&deadline)?))
+    }
+}
+
+pub fn f_claim_timed_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_fulfiller(&info.sender)?;
+    let payouts = release_plan(deps.api, &env, &escrow, &ReleaseOutcome::ClaimTimedOut)?;
+
+    // we delete the escrow
+    remove_escrow(deps.storage, &id, &escrow)?;
+    record_history(deps.storage, &id, "claim_timed_out", &info.sender, &env)?;
+    update_creator_metrics(deps.storage, &escrow.creator, |m| {
+        m.confirmations_missed += 1
+    })?;
+    let receipt = issue_receipt(
+        deps.storage,
+        &env,
+        &id,
+        &escrow,
+        "claim_timed_out",
+        &payouts,
+    )?;
+
+    Ok(EscrowEvent::claimed_timed_out(&id, &escrow)
+        .response()
+        .add_submessages(escrow_payout_messages(
+            deps.storage,
+            &id,
+            &escrow,
+            &payouts,
+        )?)
+        .set_data(receipt))
+}
+
+pub fn c_bump(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_creator(&info.sender)?;
+    if escrow.is_accepted {
+        return Err(already_accepted(&id, &escrow));
+    }
+    if !escrow.is_listed {
+        return Err(ContractError::NotListed {});
+    }
+    if escrow.is_expired(&env) {
+        return Err(ContractError::Expired {});
+    }
+
+    let now = env.block.time.seconds();
+    let config = CONFIG.load(deps.storage)?;
+    config.assert_not_sunsetting()?;
+    if let Some(last_bumped) = escrow.last_bumped {
+        let available_at = last_bumped + config.bump_cooldown_seconds;
+        if now < available_at {
+            return Err(ContractError::BumpTooSoon { available_at });
+        }
+    }
+    escrow.listing_sort_key = now;
+    escrow.last_bumped = Some(now);
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "bump", &info.sender, &env)?;
+
+    Ok(EscrowEvent::bumped(&id, &escrow).response())
+}
+
+pub fn c_open_to_all(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    duration_seconds: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_creator(&info.sender)?;
+    if escrow.is_accepted {
+        return Err(already_accepted(&id, &escrow));
+    }
+    if !escrow.is_listed {
+        return Err(ContractError::NotListed {});
+    }
+    if escrow.is_expired(&env) {
+        return Err(ContractError::Expired {});
+    }
+    let max = CONFIG.load(deps.storage)?.max_open_to_all_seconds;
+    if duration_seconds == 0 || duration_seconds > max {
+        return Err(ContractError::InvalidOpenToAllDuration { max });
+    }
+
+    let until = env.block.time.seconds() + duration_seconds;
+    escrow.open_to_all_until = Some(until);
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "open_to_all", &info.sender, &env)?;
+
+    Ok(EscrowEvent::opened_to_all(&id, &escrow, until).response())
+}
+
+pub fn c_set_payout_details(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    ciphertext: Binary,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_creator(&info.sender)?;
+    if ciphertext.len() > MAX_PAYOUT_DETAILS_BYTES {
+        return Err(ContractError::PayoutDetailsTooLarge {
+            max: MAX_PAYOUT_DETAILS_BYTES,
+        });
+    }
+    if ciphertext.is_empty() {
+        PAYOUT_DETAILS.remove(deps.storage, &id);
+    } else {
+        PAYOUT_DETAILS.save(deps.storage, &id, &ciphertext)?;
+    }
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "set_payout_details", &info.sender, &env)?;
+
+    Ok(EscrowEvent::payout_details_set(&id, &escrow).response())
+}
+
+pub fn ack_arbiter_role(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_arbiter(&info.sender)?;
+    if !escrow.is_pending_arbiter_ack {
+        return Err(ContractError::NotPendingArbiterAck {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    if escrow
+        .arbiter_ack_deadline(config.arbiter_ack_window_seconds)
+        .is_some_and(|deadline| now >= deadline)
+    {
+        return Err(ContractError::ArbiterAckExpired {});
+    }
+
+    // listed from now on, so it doesn't start out buried under newer listings
+    escrow.is_pending_arbiter_ack = false;
+    escrow.is_listed = true;
+    escrow.listing_sort_key = now;
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "ack_arbiter_role", &info.sender, &env)?;
+
+    Ok(EscrowEvent::arbiter_role_acked(&id, &escrow).response())
+}
+
+pub fn el_recuse(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    let arbiter = escrow.assert_arbiter(&info.sender)?.clone();
+    // Not having taken the role, they can let it lapse
+    if escrow.is_pending_arbiter_ack {
+        return Err(ContractError::PendingArbiterAck {});
+    }
+    let reason = clean_description(Some(reason))?;
+
+    escrow.is_pending_reassignment = true;
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history_note(deps.storage, &id, "recuse", &arbiter, &env, reason)?;
+    update_arbiter_stats(deps.storage, &arbiter, |stats| stats.recusals += 1)?;
+
+    Ok(EscrowEvent::recused(&id, &escrow, &arbiter).response())
+}
+
+pub fn resolve_expired_arbitration(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    let payouts = release_plan(
+        deps.api,
+        &env,
+        &escrow,
+        &ReleaseOutcome::ArbitrationTimedOut,
+    )?;
+    if let Some(arbiter) = &escrow.arbiter {
+        update_arbiter_stats(deps.storage, arbiter, |stats| stats.missed_deadlines += 1)?;
+    }
+    record_completion_time(deps.storage, &env, &escrow, true)?;
+
+    // we delete the escrow
+    remove_escrow(deps.storage, &id, &escrow)?;
+    record_history(
+        deps.storage,
+        &id,
+        "resolve_expired_arbitration",
+        &info.sender,
+        &env,
+    )?;
+    let action = "resolve_expired_arbitration";
+    let receipt = issue_receipt(deps.storage, &env, &id, &escrow, action, &payouts)?;
+
+    Ok(EscrowEvent::expired_arbitration_resolved(&id, &escrow)
+        .response()
+        .add_submessages(escrow_payout_messages(
+            deps.storage,
+            &id,
+            &escrow,
+            &payouts,
+        )?)
+        .set_data(receipt))
+}
+
+/// Refunds an accepted escrow that expired before the fulfiller reported, under HardExpire.
+/// Anyone may call it, so the funds don't wait on the creator.
+pub fn refund_expired(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+) -> Result<Response, ContractError> {
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    let config = CONFIG.load(deps.storage)?;
+    if !escrow.is_hard_expired(&env, config.mid_lifecycle_expiry) {
+        return Err(ContractError::NotHardExpired {});
+    }
+
+    remove_escrow(deps.storage, &id, &escrow)?;
+    record_history(deps.storage, &id, "refund_expired", &info.sender, &env)?;
+    let mut payouts = escrow.refund_payouts(&escrow.balance);
+    payouts.extend(escrow.deposit_payout(&env, &config));
+
+    Ok(EscrowEvent::refunded_expired(&id, &escrow)
+        .response()
+        .add_submessages(escrow_payout_messages(
+            deps.storage,
+            &id,
+            &escrow,
+            &payouts,
+        )?))
+}
+
+pub fn c_request_arbitration(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    reason_code: DisputeReason,
+    details: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_creator(&info.sender)?;
+    if escrow.arbiter.is_none() {
+        Err(ContractError::ArbitrationDisabled {})
+    } else if !escrow.is_fulfilled || escrow.is_in_arbitration {
+        Err(ContractError::NotFulfilled {})
+    } else if let Some(deadline) = escrow
+        .fulfill_deadline()
+        .filter(|deadline| env.block.time.seconds() > *deadline)
+    {
+        // past it the fulfiller may claim, a dispute can't take that back
+        Err(ContractError::DisputeWindowClosed { deadline })
+    } else {
+        let now = env.block.time.seconds();
+        let window = CONFIG.load(deps.storage)?.mediation_window_seconds;
+        // the arbiter's clock starts once the parties had their chance to settle
+        escrow.mediation_until = Some(now + window).filter(|_| window > 0);
+        escrow.settlement_proposal = None;
+        escrow.is_in_arbitration = true;
+        escrow.time_arbitration_started = Some(escrow.mediation_until.unwrap_or(now));
+        // not held to the size budget, the creator must always be able to dispute and
+        // the details are bounded anyway
+        escrow.dispute = Some(Dispute {
+            reason_code,
+            details: clean_description(details)?,
+            info_requests: vec![],
+        });
+        save_escrow(deps.storage, &id, &mut escrow)?;
+        record_history(deps.storage, &id, "request_arbitration", &info.sender, &env)?;
+        update_trust_metrics(deps.storage, &escrow.creator, |m| m.disputes_opened += 1)?;
+        update_creator_metrics(deps.storage, &escrow.creator, |m| m.disputes_opened += 1)?;
+        if let Some(arbiter) = &escrow.arbiter {
+            let value = escrow.value_ust();
+            update_arbiter_stats(deps.storage, arbiter, |s| s.disputed_volume_ust += value)?;
+        }
+
+        Ok(EscrowEvent::arbitration_requested(&id, &escrow, reason_code.as_str()).response())
+    }
+}
+
+pub fn propose_settlement(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    creator_bps: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    count_action(deps.storage, &mut escrow, &info.sender)?;
+    escrow.assert_party(&info.sender)?;
+    if escrow.ongoing_mediation(&env).is_none() {
+        return Err(ContractError::NotInMediation {});
+    }
+    if creator_bps > payments::MAX_BPS {
+        return Err(ContractError::InvalidBps {});
+    }
+
+    escrow.settlement_proposal = Some(SettlementProposal {
+        proposer: info.sender.clone(),
+        creator_bps,
+    });
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    let note = Some(format!("creator_bps {}", creator_bps));
+    record_history_note(
+        deps.storage,
+        &id,
+        "propose_settlement",
+        &info.sender,
+        &env,
+        note,
+    )?;
+
+    Ok(EscrowEvent::settlement_proposed(&id, &escrow, creator_bps).response())
+}
+
+/// Pays out the proposed split, settling the dispute without the arbiter. Nobody won it,
+/// so only the fulfiller's disputed resolution time is recorded.
+pub fn accept_settlement(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    expected_creator_bps: Option<u64>,
+) -> Result<Response, ContractError> {
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_party(&info.sender)?;
+    let proposal = escrow
+        .settlement_proposal
+        .clone()
+        .ok_or(ContractError::NoSettlementProposed {})?;
+    if proposal.proposer == info.sender {
+        return Err(ContractError::OwnSettlement {});
+    }
+    let creator_bps = proposal.creator_bps;
+    if expected_creator_bps.is_some_and(|bps| bps != creator_bps) {
+        return Err(ContractError::SettlementChanged { creator_bps });
+    }
+    let outcome = ReleaseOutcome::Settlement { creator_bps };
+    let payouts = release_plan(deps.api, &env, &escrow, &outcome)?;
+
+    record_completion_time(deps.storage, &env, &escrow, true)?;
+    remove_escrow(deps.storage, &id, &escrow)?;
+    record_history(deps.storage, &id, "accept_settlement", &info.sender, &env)?;
+    let action = "accept_settlement";
+    let receipt = issue_receipt(deps.storage, &env, &id, &escrow, action, &payouts)?;
+
+    Ok(EscrowEvent::settled(&id, &escrow, creator_bps)
+        .response()
+        .add_submessages(escrow_payout_messages(
+            deps.storage,
+            &id,
+            &escrow,
+            &payouts,
+        )?)
+        .set_data(receipt))
+}
+
+pub fn el_request_info(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    request: String,
+) -> Result<Response, ContractError> {
+    let mut escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_arbiter(&info.sender)?;
+    if !escrow.is_in_arbitration {
+        return Err(ContractError::NotInArbitration {});
+    }
+    if let Some(until) = escrow.ongoing_mediation(&env) {
+        return Err(ContractError::InMediation { until });
+    }
+    if escrow.is_arbitration_expired(&env) {
+        return Err(ContractError::ArbitrationExpired {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if escrow.arbitration_extensions >= config.max_arbitration_extensions {
+        return Err(ContractError::TooManyExtensions {
+            max: config.max_arbitration_extensions,
+        });
+    }
+    let request = clean_description(Some(request))?.ok_or(ContractError::EmptyInfoRequest {})?;
+
+    escrow.arbitration_extensions += 1;
+    escrow.arbitration_extended_seconds += config.arbitration_extension_seconds;
+    if let Some(dispute) = escrow.dispute.as_mut() {
+        dispute.info_requests.push(request.clone());
+    }
+    assert_escrow_size(&config, &escrow)?;
+    save_escrow(deps.storage, &id, &mut escrow)?;
+    record_history(deps.storage, &id, "request_info", &info.sender, &env)?;
+    let deadline = escrow.arbitration_deadline().unwrap_or_default();
+
+    Ok(EscrowEvent::info_requested(&id, &escrow, &request, deadline).response())
+}
+
+pub fn c_complete(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    accept_rate_deviation: bool,
+    release_bps: Option<u64>,
+) -> Result<Response, ContractError> {
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_creator(&info.sender)?;
+    // Settling proportionally is the creator's answer to a short delivery
+    let accept_rate_deviation = accept_rate_deviation || release_bps.is_some();
+    if escrow.is_rate_deviation_exceeded() && !accept_rate_deviation {
+        return Err(ContractError::RateDeviation {});
+    }
+    let outcome = match release_bps {
+        Some(release_bps) => ReleaseOutcome::PartialComplete { release_bps },
+        None => ReleaseOutcome::CreatorComplete,
+    };
+    let payouts = release_plan(deps.api, &env, &escrow, &outcome)?;
+
+    // we delete the escrow
+    remove_escrow(deps.storage, &id, &escrow)?;
+    record_history(deps.storage, &id, "creator_complete", &info.sender, &env)?;
+    credit_volume(deps.storage, &escrow, &payouts)?;
+    record_completion_time(deps.storage, &env, &escrow, false)?;
+    let fulfilled = escrow.time_fulfilled.unwrap_or_default();
+    let seconds = env.block.time.seconds().saturating_sub(fulfilled);
+    update_creator_metrics(deps.storage, &escrow.creator, |m| {
+        m.record_confirmation(seconds)
+    })?;
+
+    let receipt = issue_receipt(
+        deps.storage,
+        &env,
+        &id,
+        &escrow,
+        "creator_complete",
+        &payouts,
+    )?;
+
+    // send all tokens out
+    let messages: Vec<SubMsg> = escrow_payout_messages(deps.storage, &id, &escrow, &payouts)?;
+
+    Ok(EscrowEvent::completed(&id, &escrow, release_bps)
+        .response()
+        .add_submessages(messages)
+        .set_data(receipt))
+}
+
+/// Keeps the receipt of an escrow just settled, the response data of the settling handler
+fn issue_receipt(
+    storage: &mut dyn Storage,
+    env: &Env,
+    id: &str,
+    escrow: &Escrow,
+    action: &str,
+    payouts: &[(Addr, GenericBalance)],
+) -> StdResult<Binary> {
+    let receipt = Receipt {
+        escrow_id: id.to_string(),
+        fingerprint: escrow.fingerprint.clone(),
+        creator: escrow.creator.clone(),
+        fulfiller: escrow.fulfiller.clone(),
+        amounts_released: payouts
+            .iter()
+            .map(|(recipient, amount)| ReleasedAmount {
+                recipient: recipient.clone(),
+                amount: amount.clone(),
+            })
+            .collect(),
+        fee_taken: GenericBalance::default(),
+        exchange_rate: escrow.exchange_rate,
+        fiat_currency: escrow.fiat_currency.clone(),
+        terminal_action: action.to_string(),
+        block_time: env.block.time.seconds(),
+        block_height: env.block.height,
+    };
+    RECEIPTS.save(storage, id, &receipt)?;
+    to_binary(&receipt)
+}
+
+/// Both parties' volume counts what the fulfiller was paid, the first payout of a completion
+fn credit_volume(
+    storage: &mut dyn Storage,
+    escrow: &Escrow,
+    payouts: &[(Addr, GenericBalance)],
+) -> StdResult<()> {
+    let released = payouts[0].1.value_ust().u128() / UUSD_PER_UST;
+    let released = u32::try_from(released).unwrap_or(u32::MAX);
+    for party in [&escrow.creator, &escrow.fulfiller] {
+        update_trust_metrics(storage, party, |m| {
+            m.total_volume = m.total_volume.saturating_add(released)
+        })?;
+    }
+    Ok(())
+}
+
+pub fn c_feedback(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    _msg: FeedbackMsg,
+    id: String,
+) -> Result<Response, ContractError> {
+    // TODO: Implement feedback state for contract
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_creator(&info.sender)?;
+    if !escrow.is_completed {
+        Err(ContractError::NotComplete {})
+    } else {
+        Ok(EscrowEvent::creator_feedback(&id, &escrow).response())
+    }
+}
+
+pub fn f_feedback(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    _msg: FeedbackMsg,
+    id: String,
+) -> Result<Response, ContractError> {
+    // TODO: Implement feedback state for contract
+    let escrow = load_active_or_explain(deps.storage, &id)?;
+    escrow.assert_fulfiller(&info.sender)?;
+    if !escrow.is_completed {
+        Err(ContractError::NotComplete {})
+    } else {
+        Ok(EscrowEvent::fulfiller_feedback(&id, &escrow).response())
+    }
+}
+
+/// The address's trust metrics, all zero if it has none yet
+pub fn get_trust_metrics(storage: &dyn Storage, address: &Addr) -> StdResult<TrustMetrics> {
+    Ok(TRUST_METRICS
+        .may_load(storage, address)?
+        .unwrap_or_default())
+}
+
+fn update_arbiter_stats(
+    storage: &mut dyn Storage,
+    arbiter: &Addr,
+    update: impl FnOnce(&mut ArbiterStats),
+) -> StdResult<()> {
+    let mut stats = ARBITER_STATS
+        .may_load(storage, arbiter)?
+        .unwrap_or_default();
+    update(&mut stats);
+    ARBITER_STATS.save(storage, arbiter, &stats)
+}
+
+/// Times the fulfiller from acceptance to release. Arbitrated escrows are averaged apart,
+/// so a dispute the creator dragged out doesn't count against the fulfiller's speed.
+fn record_completion_time(
+    storage: &mut dyn Storage,
+    env: &Env,
+    escrow: &Escrow,
+    disputed: bool,
+) -> StdResult<()> {
+    let accepted = escrow.time_accepted.unwrap_or_default();
+    let millis = env.block.time.seconds().saturating_sub(accepted) * 1000;
+    update_trust_metrics(storage, &escrow.fulfiller, |m| match disputed {
+        true => m.record_disputed_resolution(millis),
+        false => m.record_clean_completion(millis),
+    })
+}
+
+/// The address's record as a creator, all zero if it never created an escrow
+pub fn get_creator_metrics(storage: &dyn Storage, address: &Addr) -> StdResult<CreatorMetrics> {
+    Ok(CREATOR_METRICS
+        .may_load(storage, address)?
+        .unwrap_or_default())
+}
+
+fn update_creator_metrics(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    update: impl FnOnce(&mut CreatorMetrics),
+) -> StdResult<()> {
+    let mut metrics = get_creator_metrics(storage, address)?;
+    update(&mut metrics);
+    CREATOR_METRICS.save(storage, address, &metrics)
+}
+
+fn update_trust_metrics(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    update: impl FnOnce(&mut TrustMetrics),
+) -> StdResult<()> {
+    let mut metrics = get_trust_metrics(storage, address)?;
+    update(&mut metrics);
+    TRUST_METRICS.save(storage, address, &metrics)
+}
+
+/// Works out who gets paid what when the escrow is released with the given outcome.
+/// Both the execute handlers and the SimulateRelease query go through here, so a
+/// simulation can never disagree with the real payout. Callers check who the sender is.
+pub fn release_plan(
+    api: &dyn Api,
+    env: &Env,
+    escrow: &Escrow,
+    outcome: &ReleaseOutcome,
+) -> Result<Vec<(Addr, GenericBalance)>, ContractError> {
+    let plan: Result<Vec<(Addr, GenericBalance)>, ContractError> = match outcome {
+        ReleaseOutcome::CreatorComplete => {
+            // Once disputed, only the arbiter decides
+            if escrow.is_in_arbitration {
+                return Err(ContractError::InArbitration {});
+            }
+            if !escrow.is_fulfilled || escrow.is_completed {
+                return Err(ContractError::Expired {});
+            }
+            Ok(vec![(escrow.fulfiller.clone(), escrow.balance.clone())])
+        }
+        ReleaseOutcome::PartialComplete { release_bps } => {
+            if escrow.is_in_arbitration {
+                return Err(ContractError::InArbitration {});
+            }
+            if !escrow.is_fulfilled || escrow.is_completed {
+                return Err(ContractError::Expired {});
+            }
+            // Releasing nothing is a refund, which is the arbiter's call
+            if *release_bps == 0 || *release_bps > payments::MAX_BPS {
+                return Err(ContractError::InvalidBps {});
+            }
+            let (to_fulfiller, to_creator) = payments::split(&escrow.balance, *release_bps);
+            Ok(vec![
+                (escrow.fulfiller.clone(), to_fulfiller),
+                (escrow.creator.clone(), to_creator),
+            ])
+        }
+        ReleaseOutcome::ClaimTimedOut => {
+            if escrow.is_in_arbitration {
+                return Err(ContractError::InArbitration {});
+            }
+            if !escrow.is_fulfilled {
+                return Err(ContractError::NotFulfilled {});
+            }
+            if !escrow.is_fulfill_expired(env) {
+                return Err(ContractError::NotTimedOut {});
+            }
+            Ok(vec![(escrow.fulfiller.clone(), escrow.balance.clone())])
+        }
+        ReleaseOutcome::ArbitrationTimedOut => {
+            if !escrow.is_in_arbitration {
+                return Err(ContractError::NotInArbitration {});
+            }
+            if !escrow.is_arbitration_expired(env) {
+                return Err(ContractError::ArbitrationNotExpired {});
+            }
+            Ok(vec![(escrow.creator.clone(), escrow.balance.clone())])
+        }
+        ReleaseOutcome::Settlement { creator_bps } => {
+            if escrow.ongoing_mediation(env).is_none() {
+                return Err(ContractError::NotInMediation {});
+            }
+            if *creator_bps > payments::MAX_BPS {
+                return Err(ContractError::InvalidBps {});
+            }
+            let (to_creator, to_fulfiller) = payments::split(&escrow.balance, *creator_bps);
+            Ok(vec![
+                (escrow.creator.clone(), to_creator),
+                (escrow.fulfiller.clone(), to_fulfiller),
+            ])
+        }
+        ReleaseOutcome::ArbitrateFor {
+            receiver,
+            creator_bps,
+        } => {
+            if !escrow.is_in_arbitration {
+                return Err(ContractError::NotInArbitration {});
+            }
+            // The parties get their chance to settle first
+            if let Some(until) = escrow.ongoing_mediation(env) {
+                return Err(ContractError::InMediation { until });
+            }
+            // Past the deadline the dispute resolves by default
+            if escrow.is_arbitration_expired(env) {
+                return Err(ContractError::ArbitrationExpired {});
+            }
+            // Nobody rules on their own dispute, older escrows may still name a party
+            if escrow.arbiter.as_ref().is_some_and(|a| escrow.is_party(a)) {
+                return Err(ContractError::ArbiterIsParty {});
+            }
+            let receiver = api.addr_validate(receiver)?;
+            escrow.assert_party(&receiver)?;
+            match creator_bps {
+                None => Ok(vec![(receiver, escrow.balance.clone())]),
+                Some(bps) => {
+                    if *bps > payments::MAX_BPS {
+                        return Err(ContractError::InvalidBps {});
+                    }
+                    let (to_creator, to_fulfiller) = payments::split(&escrow.balance, *bps);
+                    Ok(vec![
+                        (escrow.creator.clone(), to_creator),
+                        (escrow.fulfiller.clone(), to_fulfiller),
+                    ])
+                }
+            }
+        }
+    };
+    // what goes back to the creator goes back to whoever topped up, too
+    let mut plan: Vec<(Addr, GenericBalance)> = plan?
+        .into_iter()
+        .flat_map(|(to, balance)| {
+            if to == escrow.creator {
+                escrow.refund_payouts(&balance)
+            } else {
+                vec![(to, balance)]
+            }
+        })
+        .collect();
+    // an accepted escrow was no spam, the creator gets the listing deposit back
+    if !escrow.listing_deposit.is_empty() {
+        plan.push((escrow.creator.clone(), escrow.deposit()));
+    }
+    Ok(plan)
+}
+
+/// Every transfer out of the contract goes through here, so the config applies to all
+pub fn payout_messages(
+    storage: &dyn Storage,
+    payouts: &[(Addr, GenericBalance)],
+) -> StdResult<Vec<SubMsg>> {
+    transfers(storage, payouts, |_| None)
+}
+
+/// payout_messages for the payouts of one escrow, where cw20 tokens for a party that set
+/// payout_memo go with a PayoutHookMsg naming the escrow
+pub fn escrow_payout_messages(
+    storage: &dyn Storage,
+    id: &str,
+    escrow: &Escrow,
+    payouts: &[(Addr, GenericBalance)],
+) -> StdResult<Vec<SubMsg>> {
+    let hook = to_binary(&PayoutHookMsg::EscrowPayout {
+        escrow_id: id.to_string(),
+        fingerprint: escrow.fingerprint.clone(),
+    })?;
+    transfers(storage, payouts, |to| {
+        escrow.wants_payout_memo(to).then(|| hook.clone())
+    })
+}
+
+fn transfers(
+    storage: &dyn Storage,
+    payouts: &[(Addr, GenericBalance)],
+    hook_for: impl Fn(&Addr) -> Option<Binary>,
+) -> StdResult<Vec<SubMsg>> {
+    let split_native = CONFIG.load(storage)?.split_native_payouts;
+    let mut msgs = vec![];
+    for (to, balance) in payouts {
+        let hook = hook_for(to);
+        msgs.append(&mut payments::build_transfers_with_hook(
+            to,
+            balance,
+            split_native,
+            hook.as_ref(),
+        )?);
+    }
+    Ok(msgs)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::List {
+            include_expired,
+            include_upcoming,
+            order,
+        } => to_binary(&query_list(
+            deps,
+            env,
+            include_expired,
+            include_upcoming,
+            order,
+        )?),
+        QueryMsg::Listings {
+            start_after,
+            limit,
+            viewer,
+            include_expired,
+            include_upcoming,
+            order,
+        } => to_binary(&query_listings(
+            deps,
+            env,
+            start_after,
+            limit,
+            viewer,
+            include_expired,
+            include_upcoming,
+            order,
+        )?),
+        QueryMsg::EligibleListings {
+            address,
+            start_after,
+            limit,
+            include_expired,
+            include_upcoming,
+        } => to_binary(&query_eligible_listings(
+            deps,
+            env,
+            address,
+            start_after,
+            limit,
+            include_expired,
+            include_upcoming,
+        )?),
+        QueryMsg::ListByTag {
+            tag,
+            start_after,
+            limit,
+        } => to_binary(&query_list_by_tag(deps, env, tag, start_after, limit)?),
+        QueryMsg::Details { id } => to_binary(&query_details(deps, env, id)?),
+        QueryMsg::DetailsV2 { id } => to_binary(&query_details_v2(deps, env, id)?),
+        QueryMsg::AsCreateMsg { id } => to_binary(&query_as_create_msg(deps, id)?),
+        QueryMsg::Receipt { id } => to_binary(&RECEIPTS.load(deps.storage, &id)?),
+        QueryMsg::PayoutDetails { id } => {
+            let ciphertext = match ESCROWS.load(deps.storage, &id)?.withholds_payout_details() {
+                true => None,
+                false => PAYOUT_DETAILS.may_load(deps.storage, &id)?,
+            };
+            to_binary(&PayoutDetailsResponse { ciphertext })
+        }
+        QueryMsg::ByFingerprint { fingerprint } => {
+            to_binary(&query_by_fingerprint(deps, env, fingerprint)?)
+        }
+        QueryMsg::FindByParties {
+            creator,
+            fulfiller,
+            include_archived,
+            start_after,
+            limit,
+        } => to_binary(&query_find_by_parties(
+            deps,
+            creator,
+            fulfiller,
+            include_archived,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Deadlines { id } => to_binary(&query_deadlines(deps, env, id)?),
+        QueryMsg::SimulateRelease { id, outcome } => {
+            to_binary(&query_simulate_release(deps, env, id, outcome)?)
+        }
+        QueryMsg::ChangedSince { revision, limit } => {
+            to_binary(&query_changed_since(deps, env, revision, limit)?)
+        }
+        QueryMsg::TrustMetrics { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&get_trust_metrics(deps.storage, &address)?)
+        }
+        QueryMsg::CreatorMetrics { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&get_creator_metrics(deps.storage, &address)?)
+        }
+        QueryMsg::ValidateCreate {
+            msg,
+            funds,
+            creator,
+        } => to_binary(&query_validate_create(deps, env, msg, funds, creator)?),
+        QueryMsg::Capabilities {} => to_binary(&query_capabilities(deps)?),
+        QueryMsg::AdminLog { start_after, limit } => {
+            to_binary(&query_admin_log(deps, start_after, limit)?)
+        }
+        QueryMsg::History { id } => to_binary(&HistoryResponse {
+            entries: HISTORY.load(deps.storage, &id)?,
+        }),
+        QueryMsg::TotalLocked {} => to_binary(&query_total_locked(deps)?),
+        QueryMsg::Stats {} => {
+            let config = CONFIG.load(deps.storage)?;
+            to_binary(&StatsResponse {
+                active_escrows: ACTIVE_ESCROWS.may_load(deps.storage)?.unwrap_or_default(),
+                max_active_escrows: config.max_active_escrows,
+                sunset: config.sunset,
+            })
+        }
+        QueryMsg::OpenInterest { currency } => to_binary(&query_open_interest(deps, currency)?),
+        QueryMsg::CheckInvariants { start_after, limit } => {
+            to_binary(&query_check_invariants(deps, start_after, limit)?)
+        }
+        QueryMsg::ArbiterStats { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let stats = ARBITER_STATS
+                .may_load(deps.storage, &address)?
+                .unwrap_or_default();
+            to_binary(&arbiter_stats_response(deps, address, stats))
+        }
+        QueryMsg::AllArbiterStats { start_after, limit } => {
+            to_binary(&query_all_arbiter_stats(deps, start_after, limit)?)
+        }
+        QueryMsg::TrustPresets { start_after, limit } => {
+            to_binary(&query_trust_presets(deps, start_after, limit)?)
+        }
+        QueryMsg::Arbiters {
+            start_after,
+            limit,
+            sort_by,
+        } => to_binary(&query_arbiters(deps, start_after, limit, sort_by)?),
+        QueryMsg::Blacklist { start_after, limit } => {
+            to_binary(&query_blacklist(deps, start_after, limit)?)
+        }
+    }
+}
+
+/// An escrow along with what Details and DetailsV2 both show of it, converted in one place
+/// so the two can't disagree where they overlap
+struct EscrowView {
+    escrow: Escrow,
+    config: Config,
+    deadlines: DeadlinesResponse,
+    native_balance_display: Vec<String>,
+    cw20_balance: Vec<Cw20Coin>,
+    listing_deposit_display: Vec<String>,
+    cw20_whitelist: Vec<String>,
+    allowed_fulfillers: Option<Vec<String>>,
+    rejected_fulfillers: Vec<String>,
+    realized_exchange_rate: Option<Decimal>,
+    arbiter_ack_deadline: Option<u64>,
+    open_to_all_until: Option<u64>,
+    has_payout_details: bool,
+    approximate_size: u64,
+    risk_score: u8,
+}
+
+fn escrow_view(deps: Deps, env: &Env, id: &str) -> StdResult<EscrowView> {
+    let escrow = ESCROWS.load(deps.storage, id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let creator_metrics = get_creator_metrics(deps.storage, &escrow.creator)?;
+
+    Ok(EscrowView {
+        deadlines: escrow_deadlines(&escrow, env, &config),
+        native_balance_display: payments::display_amounts(
+            &escrow.balance.native,
+            &config.display_denoms,
+        ),
+        cw20_balance: escrow
+            .balance
+            .cw20
+            .iter()
+            .map(|token| Cw20Coin {
+                address: token.address.to_string(),
+                amount: token.amount,
+            })
+            .collect(),
+        listing_deposit_display: payments::display_amounts(
+            &escrow.listing_deposit,
+            &config.display_denoms,
+        ),
+        cw20_whitelist: escrow.human_whitelist(),
+        allowed_fulfillers: escrow
+            .allowed_fulfillers
+            .as_ref()
+            .map(|allowed| allowed.iter().map(Addr::to_string).collect()),
+        rejected_fulfillers: escrow
+            .rejected_fulfillers
+            .iter()
+            .map(Addr::to_string)
+            .collect(),
+        realized_exchange_rate: escrow.realized_exchange_rate(),
+        arbiter_ack_deadline: escrow.arbiter_ack_deadline(config.arbiter_ack_window_seconds),
+        open_to_all_until: escrow.open_to_all_until(env),
+        has_payout_details: PAYOUT_DETAILS.has(deps.storage, id),
+        approximate_size: escrow.approximate_size(),
+        risk_score: risk_score(&creator_metrics, &escrow, &config.risk_weights),
+        escrow,
+        config,
+    })
+}
+
+fn query_details(deps: Deps, env: Env, id: String) -> StdResult<DetailsResponse> {
+    let view = escrow_view(deps, &env, &id)?;
+    let escrow = view.escrow;
+
+    let details = DetailsResponse {
+        id,
+        arbiter: escrow.arbiter.map(String::from),
+        fulfiller: escrow.fulfiller.into(),
+        creator: escrow.creator.into(),
+        end_height: escrow.end_height,
+        end_time: escrow.end_time,
+        start_time: escrow.start_time,
+        expires_at_seconds: view.deadlines.expires_at_seconds,
+        expires_by: view.deadlines.expires_by,
+        native_balance: escrow.balance.native,
+        native_balance_display: view.native_balance_display,
+        cw20_balance: view.cw20_balance,
+        listing_deposit: escrow.listing_deposit,
+        listing_deposit_display: view.listing_deposit_display,
+        cw20_whitelist: view.cw20_whitelist,
+        allowed_fulfillers: view.allowed_fulfillers,
+        rejected_fulfillers: view.rejected_fulfillers,
+        auto_complete_trust: escrow.auto_complete_trust,
+        description: escrow.description,
+        creator_locale: escrow.creator_locale,
+        creator_utc_offset_minutes: escrow.creator_utc_offset_minutes,
+        exchange_rate: escrow.exchange_rate,
+        fiat_currency: escrow.fiat_currency,
+        delivered_fiat_amount: escrow.delivered_fiat_amount,
+        realized_exchange_rate: view.realized_exchange_rate,
+        dispute: escrow.dispute,
+        mediation_until: view.deadlines.mediation_until,
+        settlement_proposal: escrow.settlement_proposal,
+        fingerprint: escrow.fingerprint,
+        funding_source: escrow.funding_source,
+        pending_arbiter_ack: escrow.is_pending_arbiter_ack,
+        pending_reassignment: escrow.is_pending_reassignment,
+        arbiter_ack_deadline: view.arbiter_ack_deadline,
+        require_creator_ack: escrow.require_creator_ack,
+        awaiting_creator_ack: escrow.is_awaiting_creator_ack,
+        creator_ack_deadline: view.deadlines.creator_ack_deadline,
+        open_to_all_until: view.open_to_all_until,
+        cancel_pending_until: view.deadlines.cancel_pending_until,
+        has_payout_details: view.has_payout_details,
+        approximate_size: view.approximate_size,
+        risk_score: view.risk_score,
+    };
+    Ok(details)
+}
+
+fn query_details_v2(deps: Deps, env: Env, id: String) -> StdResult<DetailsV2Response> {
+    let view = escrow_view(deps, &env, &id)?;
+    let escrow = view.escrow;
+    let status = escrow.lifecycle();
+    // the fulfiller is the creator until someone accepts
+    let creator = escrow.creator;
+    let fulfiller = Some(escrow.fulfiller)
+        .filter(|fulfiller| *fulfiller != creator)
+        .map(String::from);
+
+    Ok(DetailsV2Response {
+        id,
+        fingerprint: escrow.fingerprint,
+        status,
+        creator: creator.into(),
+        fulfiller,
+        arbiter: escrow.arbiter.map(String::from),
+        pending_reassignment: escrow.is_pending_reassignment,
+        arbiter_ack_deadline: view.arbiter_ack_deadline,
+        funding_source: escrow.funding_source,
+        native_balance: escrow.balance.native,
+        native_balance_display: view.native_balance_display,
+        cw20_balance: view.cw20_balance,
+        cw20_whitelist: view.cw20_whitelist,
+        exchange_rate: escrow.exchange_rate,
+        fiat_currency: escrow.fiat_currency,
+        delivered_fiat_amount: escrow.delivered_fiat_amount,
+        realized_exchange_rate: view.realized_exchange_rate,
+        description: escrow.description,
+        creator_locale: escrow.creator_locale,
+        creator_utc_offset_minutes: escrow.creator_utc_offset_minutes,
+        timestamps: EscrowTimestamps {
+            created: escrow.time_created,
+            accepted: escrow.time_accepted,
+            fulfilled: escrow.time_fulfilled,
+            arbitration_started: escrow.time_arbitration_started,
+            last_bumped: escrow.last_bumped,
+        },
+        trust: EscrowTrust {
+            required_trust_metrics: escrow.required_trust_metrics,
+            auto_complete_trust: escrow.auto_complete_trust,
+            allowed_fulfillers: view.allowed_fulfillers,
+            rejected_fulfillers: view.rejected_fulfillers,
+            open_to_all_until: view.open_to_all_until,
+            require_creator_ack: escrow.require_creator_ack,
+            risk_score: view.risk_score,
+        },
+        fees: EscrowFees {
+            listing_deposit: escrow.listing_deposit,
+            listing_deposit_display: view.listing_deposit_display,
+            cancel_penalty_bps: view.config.cancel_penalty_bps,
+        },
+        deadlines: view.deadlines,
+        dispute: escrow.dispute,
+        settlement_proposal: escrow.settlement_proposal,
+        has_payout_details: view.has_payout_details,
+        approximate_size: view.approximate_size,
+        action_counts: escrow
+            .action_counts
+            .into_iter()
+            .map(|(address, actions)| ActionCount {
+                address: address.into(),
+                actions,
+            })
+            .collect(),
+    })
+}
+
+/// Whether what a page of escrows adds up to agrees with the stored counter
+fn counter_matches<T: PartialOrd>(counted: T, stored: T, is_whole: bool) -> bool {
+    match is_whole {
+        true => counted == stored,
+        false => counted <= stored,
+    }
+}
+
+fn query_check_invariants(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: u32,
+) -> StdResult<InvariantsResponse> {
+    let is_first_page = start_after.is_none();
+    let page = page_map(&ESCROWS, deps.storage, start_after, Some(limit))?;
+    let full_page = page.len() == page_limit(Some(limit));
+    let next_start_after = page.last().filter(|_| full_page).map(|(id, _)| id.clone());
+
+    let mut violations = vec![];
+    let mut open_interest: BTreeMap<String, OpenInterest> = BTreeMap::new();
+    for (id, escrow) in &page {
+        for code in escrow.invariant_violations() {
+            violations.push(InvariantViolation {
+                id: Some(id.clone()),
+                code: code.to_string(),
+            });
+        }
+        if escrow.is_listed {
+            let interest = open_interest
+                .entry(escrow.fiat_currency.clone())
+                .or_default();
+            interest.value_ust += escrow.value_ust();
+            interest.listings += 1;
+        }
+    }
+
+    // a page holding every escrow accounts for the counters in full, any other for part
+    let is_whole = is_first_page && next_start_after.is_none();
+    let mut counter_violation = |code: &str| {
+        violations.push(InvariantViolation {
+            id: None,
+            code: code.to_string(),
+        })
+    };
+    let active = ACTIVE_ESCROWS.may_load(deps.storage)?.unwrap_or_default();
+    if !counter_matches(page.len() as u64, active, is_whole) {
+        counter_violation("active_escrows_mismatch");
+    }
+    let mut open_interest_matches = true;
+    for (currency, counted) in &open_interest {
+        let stored = OPEN_INTEREST
+            .may_load(deps.storage, currency)?
+            .unwrap_or_default();
+        open_interest_matches &= counter_matches(counted.listings, stored.listings, is_whole)
+            && counter_matches(counted.value_ust, stored.value_ust, is_whole);
+    }
+    if is_whole {
+        // nor may currencies without listings have any interest left
+        for item in OPEN_INTEREST.range(deps.storage, None, None, Order::Ascending) {
+            let (currency, _) = item?;
+            open_interest_matches &= open_interest.contains_key(&String::from_utf8(currency)?);
+        }
+    }
+    if !open_interest_matches {
+        counter_violation("open_interest_mismatch");
+    }
+
+    Ok(InvariantsResponse {
+        violations,
+        scanned: page.len() as u32,
+        next_start_after,
+    })
+}
+
+fn query_total_locked(deps: Deps) -> StdResult<TotalLockedResponse> {
+    let total = TOTAL_LOCKED.may_load(deps.storage)?.unwrap_or_default();
+    Ok(TotalLockedResponse {
+        native: total.native,
+        cw20: total
+            .cw20
+            .into_iter()
+            .map(|token| Cw20Coin {
+                address: token.address.into(),
+                amount: token.amount,
+            })
+            .collect(),
+    })
+}
+
+fn query_open_interest(deps: Deps, currency: Option<String>) -> StdResult<OpenInterestResponse> {
+    let entry = |currency: String, interest: OpenInterest| CurrencyOpenInterest {
+        currency,
+        value_ust: interest.value_ust,
+        listings: interest.listings,
+    };
+    let currencies = match currency {
+        Some(currency) => {
+            let currency = currency.trim().to_ascii_uppercase();
+            let interest = OPEN_INTEREST
+                .may_load(deps.storage, &currency)?
+                .unwrap_or_default();
+            vec![entry(currency, interest)]
+        }
+        // one entry per currency with listings, a few hundred at most
+        None => OPEN_INTEREST
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (currency, interest) = item?;
+                Ok(entry(String::from_utf8(currency)?, interest))
+            })
+            .collect::<StdResult<_>>()?,
+    };
+    Ok(OpenInterestResponse { currencies })
+}
+
+fn query_by_fingerprint(deps: Deps, env: Env, fingerprint: String) -> StdResult<DetailsResponse> {
+    let fingerprint = fingerprint.to_uppercase();
+    let id = FINGERPRINTS.load(deps.storage, &fingerprint)?;
+    query_details(deps, env, id)
+}
+
+fn query_find_by_parties(
+    deps: Deps,
+    creator: String,
+    fulfiller: String,
+    include_archived: bool,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<FindByPartiesResponse> {
+    let creator = deps.api.addr_validate(&creator)?;
+    let fulfiller = deps.api.addr_validate(&fulfiller)?;
+    let (start, end) = page_bounds(
+        start_after.map(|revision| revision.to_be_bytes().to_vec()),
+        Order::Descending,
+    );
+
+    let mut escrows = vec![];
+    for item in PARTY_ESCROWS.prefix((&creator, &fulfiller)).range(
+        deps.storage,
+        start,
+        end,
+        Order::Descending,
+    ) {
+        if escrows.len() == page_limit(limit) {
+            break;
+        }
+        let (key, id) = item?;
+        let revision = revision_from_key(&key)?;
+        let live = ESCROWS.may_load(deps.storage, &id)?;
+        let status = match &live {
+            Some(escrow) => escrow.status().to_string(),
+            None if include_archived => HISTORY
+                .may_load(deps.storage, &id)?
+                .and_then(|mut history| history.pop())
+                .map(|entry| entry.action)
+                .unwrap_or_default(),
+            None => continue,
+        };
+        escrows.push(PartyEscrow {
+            archived: live.is_none(),
+            id,
+            revision,
+            status,
+        });
+    }
+    Ok(FindByPartiesResponse { escrows })
+}
+
+fn query_as_create_msg(deps: Deps, id: String) -> StdResult<AsCreateMsgResponse> {
+    let (escrow, archived) = match ESCROWS.may_load(deps.storage, &id)? {
+        Some(escrow) => (escrow, false),
+        None => (ARCHIVED_ESCROWS.load(deps.storage, &id)?, true),
+    };
+    Ok(AsCreateMsgResponse {
+        create_msg: create_msg_for(first_free_id(deps.storage, &id), escrow),
+        archived,
+    })
+}
+
+fn query_deadlines(deps: Deps, env: Env, id: String) -> StdResult<DeadlinesResponse> {
+    let escrow = ESCROWS.load(deps.storage, &id)?;
+    let config = CONFIG.load(deps.storage)?;
+    Ok(escrow_deadlines(&escrow, &env, &config))
+}
+
+fn escrow_deadlines(escrow: &Escrow, env: &Env, config: &Config) -> DeadlinesResponse {
+    let expiry = escrow.expires_at(env, config.seconds_per_block);
+
+    DeadlinesResponse {
+        expires_at_seconds: expiry.as_ref().map(|(at, _)| *at),
+        expires_by: expiry.map(|(_, by)| by),
+        accept_deadline: escrow.accept_deadline(),
+        effective_accept_deadline: escrow.effective_accept_deadline(
+            env,
+            config.seconds_per_block,
+            config.mid_lifecycle_expiry,
+        ),
+        fulfill_deadline: escrow.fulfill_deadline(),
+        mediation_until: escrow.mediation_until,
+        arbitration_deadline: escrow.arbitration_deadline(),
+        arbitration_extensions: escrow.arbitration_extensions,
+        grace_period_end: escrow.grace_period_end(config.listing_grace_period_seconds),
+        start_time: escrow.start_time,
+        creator_ack_deadline: escrow.creator_ack_deadline(config.creator_ack_timeout_seconds),
+        cancel_pending_until: escrow.cancel_pending_until,
+    }
+}
+
+fn query_list(
+    deps: Deps,
+    env: Env,
+    include_expired: bool,
+    include_upcoming: bool,
+    order: Option<ListOrder>,
+) -> StdResult<ListResponse> {
+    let escrows: Box<dyn Iterator<Item = StdResult<(String, Escrow)>> + '_> = match order {
+        Some(order) => Box::new(created_escrows(deps.storage, None, order)),
+        None => Box::new(
+            ESCROWS
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| -> StdResult<_> {
+                    let (key, escrow) = item?;
+                    Ok((String::from_utf8(key)?, escrow))
+                }),
+        ),
+    };
+    let escrows: StdResult<Vec<String>> = escrows
+        .filter(|item| match item {
+            Ok((_, escrow)) => {
+                escrow.cancel_pending_until.is_none()
+                    && (include_expired || !escrow.is_expired_listing(&env))
+                    && (include_upcoming || escrow.upcoming_start(&env).is_none())
+            }
+            Err(_) => true,
+        })
+        .map(|item| item.map(|(id, _)| id))
+        .collect();
+    Ok(ListResponse { escrows: escrows? })
+}
+
+/// Escrows by time_created in the order given, then by id, starting after the raw
+/// CREATED_ESCROWS key start_after
+fn created_escrows<'a>(
+    storage: &'a dyn Storage,
+    start_after: Option<Vec<u8>>,
+    order: ListOrder,
+) -> impl Iterator<Item = StdResult<(String, Escrow)>> + 'a {
+    let order = match order {
+        ListOrder::CreatedAsc => Order::Ascending,
+        ListOrder::CreatedDesc => Order::Descending,
+    };
+    let (start, end) = page_bounds(start_after, order);
+    CREATED_ESCROWS
+        .range(storage, start, end, order)
+        .map(move |item| -> StdResult<_> {
+            let (_, id) = item?;
+            let escrow = ESCROWS.load(storage, &id)?;
+            Ok((id, escrow))
+        })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn query_listings(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    viewer: Option<String>,
+    include_expired: bool,
+    include_upcoming: bool,
+    order: Option<ListOrder>,
+) -> StdResult<ListingsResponse> {
+    let viewer = viewer
+        .map(|viewer| deps.api.addr_validate(&viewer))
+        .transpose()?;
+    let config = CONFIG.load(deps.storage)?;
+    let listings: StdResult<Vec<_>> = listings_page(
+        deps,
+        &env,
+        start_after,
+        limit,
+        include_expired,
+        include_upcoming,
+        order,
+    )?
+    .into_iter()
+    .map(|(id, escrow)| {
+        let blockers = match &viewer {
+            Some(viewer) => Some(accept_blockers(deps.storage, &env, &id, &escrow, viewer)?),
+            None => None,
+        };
+        let mut summary = listing_summary(deps.storage, &env, id, escrow, &config)?;
+        if let Some(blockers) = blockers {
+            summary.acceptable = Some(blockers.is_empty());
+            summary.blockers = Some(blockers.into_iter().map(|(code, _)| code.into()).collect());
+        }
+        Ok(summary)
+    })
+    .collect();
+    Ok(ListingsResponse {
+        listings: listings?,
+    })
+}
+
+/// One page of the live listings carrying tag, by id
+fn query_list_by_tag(
+    deps: Deps,
+    env: Env,
+    tag: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListingsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let (start, end) = page_bounds(start_after.map(String::into_bytes), Order::Ascending);
+    let listings: StdResult<Vec<_>> = TAGGED_LISTINGS
+        .prefix(&tag)
+        .keys(deps.storage, start, end, Order::Ascending)
+        .map(|key| -> StdResult<_> {
+            let id = String::from_utf8(key)?;
+            let escrow = ESCROWS.load(deps.storage, &id)?;
+            Ok((id, escrow))
+        })
+        .filter(|item| match item {
+            Ok((_, escrow)) => {
+                !escrow.is_expired_listing(&env) && escrow.upcoming_start(&env).is_none()
+            }
+            Err(_) => true,
+        })
+        .take(page_limit(limit))
+        .map(|item| {
+            let (id, escrow) = item?;
+            listing_summary(deps.storage, &env, id, escrow, &config)
+        })
+        .collect();
+    Ok(ListingsResponse {
+        listings: listings?,
+    })
+}
+
+/// One page of the listing index, most recently created or bumped first, or of the
+/// listed escrows by creation time with an order. Expired listings don't count towards
+/// the page unless include_expired.
+fn listings_page(
+    deps: Deps,
+    env: &Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    include_expired: bool,
+    include_upcoming: bool,
+    order: Option<ListOrder>,
+) -> StdResult<Vec<(String, Escrow)>> {
+    let start_after = match start_after {
+        Some(id) => {
+            let escrow = ESCROWS.load(deps.storage, &id)?;
+            let sort_key = match order {
+                Some(_) => escrow.created_sort_key(),
+                None => escrow.listing_sort_key,
+            };
+            let key: (U64Key, &str) = (sort_key.into(), &id);
+            Some(key.joined_key())
+        }
+        None => None,
+    };
+    let escrows: Box<dyn Iterator<Item = StdResult<(String, Escrow)>> + '_> = match order {
+        Some(order) => Box::new(
+            created_escrows(deps.storage, start_after, order).filter(|item| match item {
+                Ok((_, escrow)) => escrow.is_listed,
+                Err(_) => true,
+            }),
+        ),
+        None => {
+            // the index is walked from the highest sort key down
+            let (start, end) = page_bounds(start_after, Order::Descending);
+            Box::new(
+                LISTINGS
+                    .range(deps.storage, start, end, Order::Descending)
+                    .map(|item| -> StdResult<_> {
+                        let (_, id) = item?;
+                        let escrow = ESCROWS.load(deps.storage, &id)?;
+                        Ok((id, escrow))
+                    }),
+            )
+        }
+    };
+
+    escrows
+        .filter(|item| match item {
+            Ok((_, escrow)) => {
+                (include_expired || !escrow.is_expired_listing(env))
+                    && (include_upcoming || escrow.upcoming_start(env).is_none())
+            }
+            Err(_) => true,
+        })
+        .take(page_limit(limit))
+        .collect()
+}
+
+fn listing_summary(
+    storage: &dyn Storage,
+    env: &Env,
+    id: String,
+    escrow: Escrow,
+    config: &Config,
+) -> StdResult<ListingSummary> {
+    let creator_metrics = get_creator_metrics(storage, &escrow.creator)?;
+    let display_denoms = &config.display_denoms;
+    Ok(ListingSummary {
+        risk_score: risk_score(&creator_metrics, &escrow, &config.risk_weights),
+        starts_at: escrow.upcoming_start(env),
+        expired: escrow.is_expired_listing(env),
+        open_to_all_until: escrow.open_to_all_until(env),
+        id,
+        fingerprint: escrow.fingerprint,
+        creator: escrow.creator.into(),
+        exchange_rate: escrow.exchange_rate,
+        fiat_currency: escrow.fiat_currency,
+        native_balance_display: payments::display_amounts(&escrow.balance.native, display_denoms),
+        native_balance: escrow.balance.native,
+        cw20_balance: escrow
+            .balance
+            .cw20
+            .into_iter()
+            .map(|token| Cw20Coin {
+                address: token.address.into(),
+                amount: token.amount,
+            })
+            .collect(),
+        description: escrow.description,
+        creator_locale: escrow.creator_locale,
+        creator_utc_offset_minutes: escrow.creator_utc_offset_minutes,
+        acceptable: None,
+        blockers: None,
+    })
+}
+
+fn query_eligible_listings(
+    deps: Deps,
+    env: Env,
+    address: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    include_expired: bool,
+    include_upcoming: bool,
+) -> StdResult<EligibleListingsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let page = listings_page(
+        deps,
+        &env,
+        start_after,
+        limit,
+        include_expired,
+        include_upcoming,
+        None,
+    )?;
+    let full_page = page.len() == page_limit(limit);
+    let next_start_after = page.last().filter(|_| full_page).map(|(id, _)| id.clone());
+
+    let mut listings = vec![];
+    let mut near_misses = vec![];
+    for (id, escrow) in page {
+        match assert_can_accept(deps.storage, &env, &id, &escrow, &address) {
+            Ok(()) => listings.push(ListingSummary {
+                acceptable: Some(true),
+                blockers: Some(vec![]),
+                ..listing_summary(deps.storage, &env, id, escrow, &config)?
+            }),
+            Err(ContractError::Std(err)) => return Err(err),
+            Err(err) => near_misses.push(NearMiss {
+                id,
+                reason: err.to_string(),
+            }),
+        }
+    }
+    Ok(EligibleListingsResponse {
+        listings,
+        near_misses,
+        next_start_after,
+    })
+}
+
+fn query_changed_since(
+    deps: Deps,
+    env: Env,
+    revision: u64,
+    limit: Option<u32>,
+) -> StdResult<ChangedSinceResponse> {
+    let start_after = Some(revision.to_be_bytes().to_vec());
+    let page = page_raw(
+        &REVISIONS,
+        deps.storage,
+        start_after,
+        Order::Ascending,
+        limit,
+    )?;
+
+    let changes: StdResult<Vec<_>> = page
+        .into_iter()
+        .map(|(key, entry)| {
+            let revision = revision_from_key(&key)?;
+            let escrow = if entry.removed {
+                None
+            } else {
+                Some(query_details(deps, env.clone(), entry.id.clone())?)
+            };
+            Ok(EscrowChange {
+                id: entry.id,
+                revision,
+                escrow,
+            })
+        })
+        .collect();
+    Ok(ChangedSinceResponse {
+        changes: changes?,
+        latest_revision: LATEST_REVISION.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_validate_create(
+    deps: Deps,
+    env: Env,
+    msg: CreateMsg,
+    funds: Vec<Coin>,
+    creator: Option<String>,
+) -> StdResult<ValidateCreateResponse> {
+    let mut violations = vec![];
+    let creator = creator.and_then(|creator| {
+        deps.api
+            .addr_validate(&creator)
+            .map_err(|err| violations.push(err.to_string()))
+            .ok()
+    });
+    let balance = payments::normalize(Balance::from(funds));
+    if let Err(errors) = validate_create(deps, &env, &msg, &balance, creator.as_ref()) {
+        violations.extend(errors.iter().map(ToString::to_string));
+    }
+    Ok(ValidateCreateResponse {
+        ok: violations.is_empty(),
+        violations,
+    })
+}
+
+fn query_capabilities(deps: Deps) -> StdResult<CapabilitiesResponse> {
+    let version = get_contract_version(deps.storage)?;
+    Ok(CapabilitiesResponse {
+        contract: version.contract,
+        version: version.version,
+        capabilities: capability_names(),
+    })
+}
+
+fn query_admin_log(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AdminLogResponse> {
+    let start_after = start_after.map(|id| id.to_be_bytes().to_vec());
+    let page = page_raw(
+        &ADMIN_LOG,
+        deps.storage,
+        start_after,
+        Order::Descending,
+        limit,
+    )?;
+
+    let entries = page.into_iter().map(|(_, entry)| entry).collect();
+    Ok(AdminLogResponse { entries })
+}
+
+fn arbiter_stats_response(deps: Deps, address: Addr, stats: ArbiterStats) -> ArbiterStatsResponse {
+    let average_ruling_seconds = match stats.rulings {
+        0 => None,
+        rulings => Some(stats.total_ruling_seconds / rulings as u64),
+    };
+    let missed_deadline_ratio = match stats.rulings + stats.missed_deadlines {
+        0 => None,
+        disputes => Some(Decimal::from_ratio(stats.missed_deadlines, disputes)),
+    };
+    ArbiterStatsResponse {
+        registered: ARBITERS.has(deps.storage, &address),
+        address: address.into(),
+        stats,
+        average_ruling_seconds,
+        missed_deadline_ratio,
+    }
+}
+
+fn query_all_arbiter_stats(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllArbiterStatsResponse> {
+    let arbiters = page_map(&ARBITER_STATS, deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(address, stats)| arbiter_stats_response(deps, Addr::unchecked(address), stats))
+        .collect();
+    Ok(AllArbiterStatsResponse { arbiters })
+}
+
+fn query_arbiters(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    sort_by: Option<ArbiterSort>,
+) -> StdResult<ArbitersResponse> {
+    let entry = |address: String| -> StdResult<ArbiterEntry> {
+        let stats = ARBITER_STATS
+            .may_load(deps.storage, &Addr::unchecked(&address))?
+            .unwrap_or_default();
+        Ok(ArbiterEntry {
+            address,
+            active_escrows: stats.active_escrows,
+            in_arbitration: stats.in_arbitration,
+            rulings: stats.rulings,
+            disputed_volume_ust: stats.disputed_volume_ust,
+        })
+    };
+    let sort_by = match sort_by {
+        Some(sort_by) => sort_by,
+        None => {
+            let arbiters = page_map(&ARBITERS, deps.storage, start_after, limit)?
+                .into_iter()
+                .map(|(address, _)| entry(address))
+                .collect::<StdResult<_>>()?;
+            return Ok(ArbitersResponse { arbiters });
+        }
+    };
+
+    // the registry is vetted by hand, so ranking all of it stays cheap
+    let mut arbiters = ARBITERS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|key| entry(String::from_utf8(key).map_err(StdError::from)?))
+        .collect::<StdResult<Vec<_>>>()?;
+    match sort_by {
+        ArbiterSort::LeastLoaded => {
+            arbiters.sort_by_key(|arbiter| (arbiter.active_escrows, arbiter.in_arbitration))
+        }
+        ArbiterSort::MostExperienced => arbiters.sort_by_key(|arbiter| Reverse(arbiter.rulings)),
+    }
+    let start = start_after
+        .and_then(|after| arbiters.iter().position(|arbiter| arbiter.address == after))
+        .map_or(0, |position| position + 1);
+    let arbiters = arbiters
+        .into_iter()
+        .skip(start)
+        .take(page_limit(limit))
+        .collect();
+    Ok(ArbitersResponse { arbiters })
+}
+
+fn query_trust_presets(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TrustPresetsResponse> {
+    let presets = page_map(&TRUST_PRESETS, deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(name, metrics)| TrustPreset { name, metrics })
+        .collect();
+    Ok(TrustPresetsResponse { presets })
+}
+
+fn query_blacklist(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<BlacklistResponse> {
+    let entries = page_map(&BLACKLIST, deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(address, entry)| BlacklistInfo {
+            address,
+            reason: entry.reason,
+            since: entry.since,
+        })
+        .collect();
+    Ok(BlacklistResponse { entries })
+}
+
+fn query_simulate_release(
+    deps: Deps,
+    env: Env,
+    id: String,
+    outcome: ReleaseOutcome,
+) -> StdResult<SimulateReleaseResponse> {
+    let escrow = ESCROWS.load(deps.storage, &id)?;
+    let payouts = release_plan(deps.api, &env, &escrow, &outcome)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    let mut transfers = vec![];
+    for (to, balance) in payouts {
+        for coin in balance.native {
+            transfers.push(Transfer {
+                recipient: to.to_string(),
+                denom: Denom::Native(coin.denom),
+                amount: coin.amount,
+            });
+        }
+        for token in balance.cw20 {
+            transfers.push(Transfer {
+                recipient: to.to_string(),
+                denom: Denom::Cw20(token.address),
+                amount: token.amount,
+            });
+        }
+    }
+    Ok(SimulateReleaseResponse { transfers })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        attr, coin, coins, BankMsg, CosmosMsg, OwnedDeps, StdError, Timestamp, Uint128, WasmMsg,
+    };
+    use cw20::Cw20ExecuteMsg;
+    use std::collections::BTreeMap;
+
+    use crate::msg::ExecuteMsg::TopUp;
+    use crate::state::{
+        all_escrow_ids, daily_volume_used, fingerprint, AdminLogEntry, EscrowStatus, ExpirySource,
+        MidLifecycleExpiry, ACCEPT_TIMEOUT_SECONDS, ARBITRATION_TIMEOUT_SECONDS,
+        DEFAULT_BUMP_COOLDOWN_SECONDS, FULFILL_TIMEOUT_SECONDS, MAX_ADMIN_LOG_ENTRIES, UST_DENOM,
+    };
+
+    use super::*;
+
+    fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(&[]);
+        let msg = instantiate_msg();
+        instantiate(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        deps
+    }
+
+    fn instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            admin: Some("admin".to_string()),
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            // most tests take listings moments before they expire
+            min_remaining_on_accept_seconds: Some(0),
+            peer_contracts: None,
+            max_actions_per_address: None,
+            initial_arbiters: vec![],
+            seed_metrics: vec![],
+        }
+    }
+
+    fn create_msg(id: &str) -> CreateMsg {
+        CreateMsg {
+            id: id.to_string(),
+            arbiter: Some(String::from("arbitrate")),
+            arbitration_enabled: true,
+            fiat_currency: "VES".to_string(),
+            end_time: None,
+            end_height: None,
+            exchange_rate: Decimal::percent(3650),
+            cw20_whitelist: None,
+            required_trust_metrics: TrustMetrics::default().into(),
+            auto_complete_trust: None,
+            allowed_fulfillers: None,
+            description: None,
+            dispute_window_seconds: None,
+            require_creator_ack: false,
+            auto_extend_on_relist: None,
+            payout_memo: false,
+            tags: None,
+            start_time: None,
+            creator_locale: None,
+            creator_utc_offset_minutes: None,
+        }
+    }
+
+    /// Creates an escrow funded by "creator", accepted and fulfilled by "fulfiller"
+    fn fulfilled_escrow(deps: DepsMut, env: Env, id: &str, funds: &[cosmwasm_std::Coin]) {
+        let mut deps = deps;
+        let info = mock_info("creator", funds);
+        execute(
+            deps.branch(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create_msg(id)),
+        )
+        .unwrap();
+        let info = mock_info("fulfiller", &[]);
+        let id = id.to_string();
+        execute(
+            deps.branch(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::FAccept {
+                id: id.clone(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        )
+        .unwrap();
+        execute(
+            deps,
+            env,
+            info,
+            ExecuteMsg::FComplete {
+                id,
+                delivered_fiat_amount: None,
+            },
+        )
+        .unwrap();
+    }
+
+    /// Reads the transfers back out of the messages a handler emitted
+    fn transfers_from_messages(messages: &[SubMsg]) -> Vec<Transfer> {
+        let mut transfers = vec![];
+        for msg in messages {
+            match &msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                    for c in amount {
+                        transfers.push(Transfer {
+                            recipient: to_address.clone(),
+                            denom: Denom::Native(c.denom.clone()),
+                            amount: c.amount,
+                        });
+                    }
+                }
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) => match from_binary(msg).unwrap() {
+                    Cw20ExecuteMsg::Transfer { recipient, amount } => transfers.push(Transfer {
+                        recipient,
+                        denom: Denom::Cw20(Addr::unchecked(contract_addr)),
+                        amount,
+                    }),
+                    other => panic!("unexpected cw20 message {:?}", other),
+                },
+                other => panic!("unexpected message {:?}", other),
+            }
+        }
+        transfers
+    }
+
+    fn simulate(deps: Deps, env: Env, id: &str, outcome: ReleaseOutcome) -> Vec<Transfer> {
+        query_simulate_release(deps, env, id.to_string(), outcome)
+            .unwrap()
+            .transfers
+    }
+
+    #[test]
     fn happy_path_native() {
         let mut deps = mock_dependencies(&[]);
 
-        // instantiate an empty contract
-        let instantiate_msg = InstantiateMsg {};
-        let info = mock_info(&String::from("anyone"), &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        // instantiate an empty contract
+        let instantiate_msg = InstantiateMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+            initial_arbiters: vec![],
+            seed_metrics: vec![],
+        };
+        let info = mock_info(&String::from("anyone"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // create an escrow
+        let create = CreateMsg {
+            end_height: Some(23456),
+            ..create_msg("foobar")
+        };
+        let sender = String::from("source");
+        let balance = coins(100, "tokens");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::CCreate(create.clone());
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "create"), res.attributes[0]);
+
+        // ensure the details is what we expect
+        let details = query_details(deps.as_ref(), mock_env(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details,
+            DetailsResponse {
+                id: "foobar".to_string(),
+                arbiter: Some(String::from("arbitrate")),
+                fulfiller: String::from("source"),
+                creator: String::from("source"),
+                end_height: Some(23456),
+                end_time: None,
+                start_time: None,
+                expires_at_seconds: Some(height_to_time(
+                    &mock_env(),
+                    23456,
+                    DEFAULT_SECONDS_PER_BLOCK
+                )),
+                expires_by: Some(ExpirySource::EndHeight),
+                native_balance: balance.clone(),
+                native_balance_display: vec!["100".to_string()],
+                cw20_balance: vec![],
+                listing_deposit: vec![],
+                listing_deposit_display: vec![],
+                cw20_whitelist: vec![],
+                allowed_fulfillers: None,
+                rejected_fulfillers: vec![],
+                auto_complete_trust: None,
+                description: None,
+                creator_locale: None,
+                creator_utc_offset_minutes: None,
+                exchange_rate: Decimal::percent(3650),
+                fiat_currency: "VES".to_string(),
+                delivered_fiat_amount: None,
+                realized_exchange_rate: None,
+                dispute: None,
+                mediation_until: None,
+                settlement_proposal: None,
+                fingerprint: fingerprint(
+                    "foobar",
+                    &Addr::unchecked("source"),
+                    mock_env().block.time.seconds(),
+                    0
+                ),
+                funding_source: FundingSource::NativeDirect,
+                pending_arbiter_ack: false,
+                pending_reassignment: false,
+                arbiter_ack_deadline: None,
+                require_creator_ack: false,
+                awaiting_creator_ack: false,
+                creator_ack_deadline: None,
+                open_to_all_until: None,
+                cancel_pending_until: None,
+                has_payout_details: false,
+                approximate_size: ESCROWS
+                    .load(&deps.storage, "foobar")
+                    .unwrap()
+                    .approximate_size(),
+                risk_score: 0,
+            }
+        );
+
+        // accept and fulfill it
+        let id = create.id.clone();
+        let info = mock_info("recd", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::FAccept {
+                id: id.clone(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(("action", "accept"), res.attributes[0]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::FComplete {
+                id,
+                delivered_fiat_amount: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(("action", "fulfill"), res.attributes[0]);
+
+        // complete it
+        let id = create.id.clone();
+        let info = mock_info(&sender, &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(("action", "creator_complete"), res.attributes[0]);
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("recd"),
+                amount: balance,
+            }))
+        );
+
+        // second attempt fails, the escrow is finished
+        let id = create.id.clone();
+        let info = mock_info(&sender, &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, ContractError::EscrowFinished { status, .. } if status == "creator_complete")
+        );
+    }
+
+    #[test]
+    fn happy_path_cw20() {
+        let mut deps = mock_dependencies(&[]);
+
+        // instantiate an empty contract
+        let instantiate_msg = InstantiateMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+            initial_arbiters: vec![],
+            seed_metrics: vec![],
+        };
+        let info = mock_info(&String::from("anyone"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // create an escrow
+        let create = CreateMsg {
+            cw20_whitelist: Some(vec![String::from("other-token")]),
+            ..create_msg("foobar")
+        };
+        let receive = Cw20ReceiveMsg {
+            sender: String::from("source"),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::CCreate(create.clone())).unwrap(),
+        };
+        let token_contract = String::from("my-cw20-token");
+        let info = mock_info(&token_contract, &[]);
+        let msg = ExecuteMsg::Receive(receive.clone());
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "create"), res.attributes[0]);
+
+        // ensure the whitelist is what we expect
+        let details = query_details(deps.as_ref(), mock_env(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details,
+            DetailsResponse {
+                id: "foobar".to_string(),
+                arbiter: Some(String::from("arbitrate")),
+                fulfiller: String::from("source"),
+                creator: String::from("source"),
+                end_height: None,
+                end_time: None,
+                start_time: None,
+                expires_at_seconds: None,
+                expires_by: None,
+                native_balance: vec![],
+                native_balance_display: vec![],
+                cw20_balance: vec![Cw20Coin {
+                    address: String::from("my-cw20-token"),
+                    amount: Uint128::new(100),
+                }],
+                listing_deposit: vec![],
+                listing_deposit_display: vec![],
+                cw20_whitelist: vec![String::from("my-cw20-token"), String::from("other-token")],
+                allowed_fulfillers: None,
+                rejected_fulfillers: vec![],
+                auto_complete_trust: None,
+                description: None,
+                creator_locale: None,
+                creator_utc_offset_minutes: None,
+                exchange_rate: Decimal::percent(3650),
+                fiat_currency: "VES".to_string(),
+                delivered_fiat_amount: None,
+                realized_exchange_rate: None,
+                dispute: None,
+                mediation_until: None,
+                settlement_proposal: None,
+                fingerprint: fingerprint(
+                    "foobar",
+                    &Addr::unchecked("source"),
+                    mock_env().block.time.seconds(),
+                    0
+                ),
+                funding_source: FundingSource::Cw20Hook {
+                    token: Addr::unchecked("my-cw20-token"),
+                },
+                pending_arbiter_ack: false,
+                pending_reassignment: false,
+                arbiter_ack_deadline: None,
+                require_creator_ack: false,
+                awaiting_creator_ack: false,
+                creator_ack_deadline: None,
+                open_to_all_until: None,
+                cancel_pending_until: None,
+                has_payout_details: false,
+                approximate_size: ESCROWS
+                    .load(&deps.storage, "foobar")
+                    .unwrap()
+                    .approximate_size(),
+                risk_score: 0,
+            }
+        );
+
+        // accept and fulfill it
+        let id = create.id.clone();
+        let info = mock_info("recd", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::FAccept {
+                id: id.clone(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::FComplete {
+                id,
+                delivered_fiat_amount: None,
+            },
+        )
+        .unwrap();
+
+        // complete it
+        let id = create.id.clone();
+        let info = mock_info("source", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(("action", "creator_complete"), res.attributes[0]);
+        let send_msg = Cw20ExecuteMsg::Transfer {
+            recipient: String::from("recd"),
+            amount: receive.amount,
+        };
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token_contract,
+                msg: to_binary(&send_msg).unwrap(),
+                funds: vec![]
+            }))
+        );
+
+        // second attempt fails, the escrow is finished
+        let id = create.id.clone();
+        let info = mock_info("source", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, ContractError::EscrowFinished { status, .. } if status == "creator_complete")
+        );
+    }
+
+    #[test]
+    fn add_tokens_proper() {
+        let mut tokens = GenericBalance::default();
+        tokens.add_tokens(Balance::from(vec![coin(123, "atom"), coin(789, "eth")]));
+        tokens.add_tokens(Balance::from(vec![coin(456, "atom"), coin(12, "btc")]));
+        assert_eq!(
+            tokens.native,
+            vec![coin(579, "atom"), coin(12, "btc"), coin(789, "eth")]
+        );
+    }
+
+    #[test]
+    fn add_cw_tokens_proper() {
+        let mut tokens = GenericBalance::default();
+        let bar_token = Addr::unchecked("bar_token");
+        let foo_token = Addr::unchecked("foo_token");
+        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
+            address: foo_token.clone(),
+            amount: Uint128::new(12345),
+        }));
+        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
+            address: bar_token.clone(),
+            amount: Uint128::new(777),
+        }));
+        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
+            address: foo_token.clone(),
+            amount: Uint128::new(23400),
+        }));
+        assert_eq!(
+            tokens.cw20,
+            vec![
+                Cw20CoinVerified {
+                    address: bar_token,
+                    amount: Uint128::new(777),
+                },
+                Cw20CoinVerified {
+                    address: foo_token,
+                    amount: Uint128::new(35745),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn top_up_mixed_tokens() {
+        let mut deps = mock_dependencies(&[]);
+
+        // instantiate an empty contract
+        let instantiate_msg = InstantiateMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+            initial_arbiters: vec![],
+            seed_metrics: vec![],
+        };
+        let info = mock_info(&String::from("anyone"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // only accept these tokens
+        let whitelist = vec![String::from("bar_token"), String::from("foo_token")];
+
+        // create an escrow with 2 native tokens
+        let create = CreateMsg {
+            cw20_whitelist: Some(whitelist),
+            ..create_msg("foobar")
+        };
+        let sender = String::from("source");
+        let balance = vec![coin(100, "fee"), coin(200, "stake")];
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::CCreate(create.clone());
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "create"), res.attributes[0]);
+
+        // top it up with 2 more native tokens
+        let extra_native = vec![coin(250, "random"), coin(300, "stake")];
+        let info = mock_info(&sender, &extra_native);
+        let top_up = ExecuteMsg::TopUp {
+            id: create.id.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "top_up"), res.attributes[0]);
+
+        // top up with one foreign token
+        let bar_token = String::from("bar_token");
+        let base = ReceiveMsg::TopUp {
+            id: create.id.clone(),
+        };
+        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("source"),
+            amount: Uint128::new(7890),
+            msg: to_binary(&base).unwrap(),
+        });
+        let info = mock_info(&bar_token, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "top_up"), res.attributes[0]);
+
+        // top with a foreign token not on the whitelist
+        // top up with one foreign token
+        let baz_token = String::from("baz_token");
+        let base = ReceiveMsg::TopUp {
+            id: create.id.clone(),
+        };
+        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("source"),
+            amount: Uint128::new(7890),
+            msg: to_binary(&base).unwrap(),
+        });
+        let info = mock_info(&baz_token, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, top_up).unwrap_err();
+        assert_eq!(err, ContractError::NotInWhitelist {});
+
+        // top up with second foreign token
+        let foo_token = String::from("foo_token");
+        let base = ReceiveMsg::TopUp {
+            id: create.id.clone(),
+        };
+        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("source"),
+            amount: Uint128::new(888),
+            msg: to_binary(&base).unwrap(),
+        });
+        let info = mock_info(&foo_token, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "top_up"), res.attributes[0]);
+
+        // accept and fulfill it
+        let id = create.id.clone();
+        let info = mock_info("recd", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::FAccept {
+                id: id.clone(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::FComplete {
+                id,
+                delivered_fiat_amount: None,
+            },
+        )
+        .unwrap();
+
+        // no more top ups once accepted
+        let info = mock_info(&sender, &extra_native);
+        let top_up = TopUp {
+            id: create.id.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, top_up).unwrap_err();
+        assert_eq!(err, ContractError::NotListed {});
+
+        // complete it
+        let id = create.id.clone();
+        let info = mock_info(&sender, &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(("action", "creator_complete"), res.attributes[0]);
+        assert_eq!(3, res.messages.len());
+
+        // first message releases all native coins
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("recd"),
+                amount: vec![coin(100, "fee"), coin(250, "random"), coin(500, "stake")],
+            }))
+        );
+
+        // second one release bar cw20 token
+        let send_msg = Cw20ExecuteMsg::Transfer {
+            recipient: String::from("recd"),
+            amount: Uint128::new(7890),
+        };
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: bar_token,
+                msg: to_binary(&send_msg).unwrap(),
+                funds: vec![]
+            }))
+        );
+
+        // third one release foo cw20 token
+        let send_msg = Cw20ExecuteMsg::Transfer {
+            recipient: String::from("recd"),
+            amount: Uint128::new(888),
+        };
+        assert_eq!(
+            res.messages[2],
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: foo_token,
+                msg: to_binary(&send_msg).unwrap(),
+                funds: vec![]
+            }))
+        );
+    }
+
+    #[test]
+    fn creator_calls_the_creator_complete_function() {
+        // We create a mutable variable named deps and set it equal to the state returned by the function named mock_dependencies
+        let mut deps = mock_dependencies(&[]);
+
+        // instantiate an empty contract
+        let instantiate_msg = InstantiateMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+            initial_arbiters: vec![],
+            seed_metrics: vec![],
+        };
+        // Our contract is instantiated by ElLib
+        let info = mock_info(&String::from("ElLib"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        // create an escrow
+        let create = CreateMsg {
+            end_height: Some(23456),
+            ..create_msg("foobar")
+        };
+        // We set the sender to "creator"
+        let sender = String::from("creator");
+        // We give the sender a balance of 100 tokens
+        let balance = coins(100, "tokens");
+        let info = mock_info(&sender, &balance);
+        // We called the Execute Message: CCreate and give it a copy of our CreateMsg
+        let msg = ExecuteMsg::CCreate(create.clone());
+        // We call the execute function with our ExecuteMsg::CCreate and unwrap it's result
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        // We make sure no error messages are returned
+        assert_eq!(0, res.messages.len());
+        // We check that the tuple with "action" and "create" are returned, signifying c_create returned Ok
+        assert_eq!(("action", "create"), res.attributes[0]);
+
+        // ensure the details is what we expect
+        let details = query_details(deps.as_ref(), mock_env(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details,
+            DetailsResponse {
+                id: "foobar".to_string(),
+                arbiter: Some(String::from("arbitrate")),
+                fulfiller: String::from("creator"),
+                // Check that "creator" is the creator
+                creator: String::from("creator"),
+                end_height: Some(23456),
+                end_time: None,
+                start_time: None,
+                expires_at_seconds: Some(height_to_time(
+                    &mock_env(),
+                    23456,
+                    DEFAULT_SECONDS_PER_BLOCK
+                )),
+                expires_by: Some(ExpirySource::EndHeight),
+                native_balance: balance.clone(),
+                native_balance_display: vec!["100".to_string()],
+                cw20_balance: vec![],
+                listing_deposit: vec![],
+                listing_deposit_display: vec![],
+                cw20_whitelist: vec![],
+                allowed_fulfillers: None,
+                rejected_fulfillers: vec![],
+                auto_complete_trust: None,
+                description: None,
+                creator_locale: None,
+                creator_utc_offset_minutes: None,
+                exchange_rate: Decimal::percent(3650),
+                fiat_currency: "VES".to_string(),
+                delivered_fiat_amount: None,
+                realized_exchange_rate: None,
+                dispute: None,
+                mediation_until: None,
+                settlement_proposal: None,
+                fingerprint: fingerprint(
+                    "foobar",
+                    &Addr::unchecked("creator"),
+                    mock_env().block.time.seconds(),
+                    0
+                ),
+                funding_source: FundingSource::NativeDirect,
+                pending_arbiter_ack: false,
+                pending_reassignment: false,
+                arbiter_ack_deadline: None,
+                require_creator_ack: false,
+                awaiting_creator_ack: false,
+                creator_ack_deadline: None,
+                open_to_all_until: None,
+                cancel_pending_until: None,
+                has_payout_details: false,
+                approximate_size: ESCROWS
+                    .load(&deps.storage, "foobar")
+                    .unwrap()
+                    .approximate_size(),
+                risk_score: 0,
+            }
+        );
+
+        // The fulfiller accepts and fulfills the escrow
+        let id = create.id.clone();
+        let fulfiller = String::from("fulfiller");
+        let info = mock_info(&fulfiller, &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::FAccept {
+                id: id.clone(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::FComplete {
+                id,
+                delivered_fiat_amount: None,
+            },
+        )
+        .unwrap();
+
+        /* Here we have the fulfiller try to call the creator complete method, which would be fraud */
+        // We get the contract id
+        let id = create.id.clone();
+        // We make a message coming from the fulfiller
+        let info = mock_info(&fulfiller, &[]);
+        // Get the results of calling execute with the fulfiller as the message signer
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap_err();
+        // We check that the response is
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        /* Here is where we call the CComplete Execution Method */
+        // We get the id of the contract we've created
+        let id = create.id.clone();
+        // We make our message info come from the creator
+        let info = mock_info(&sender, &[]);
+        // We send an ExecuteMsg of type CComplete
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap();
+        // We check that the response has a single message
+        assert_eq!(1, res.messages.len());
+        // We check the response attributes match the ones from c_complete
+        assert_eq!(("action", "creator_complete"), res.attributes[0]);
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: fulfiller,
+                amount: balance,
+            }))
+        );
+
+        // second attempt fails, the escrow is finished
+        let id = create.id.clone();
+        let info = mock_info(&sender, &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, ContractError::EscrowFinished { status, .. } if status == "creator_complete")
+        );
+    }
+
+    #[test]
+    fn simulate_creator_complete_matches_execution() {
+        let mut deps = setup();
+        let env = mock_env();
+        fulfilled_escrow(
+            deps.as_mut(),
+            env.clone(),
+            "foobar",
+            &[coin(100, "fee"), coin(200, "stake")],
+        );
+
+        let simulated = simulate(
+            deps.as_ref(),
+            env.clone(),
+            "foobar",
+            ReleaseOutcome::CreatorComplete,
+        );
+        assert_eq!(2, simulated.len());
+
+        let info = mock_info("creator", &[]);
+        let id = "foobar".to_string();
+        let res = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(simulated, transfers_from_messages(&res.messages));
+    }
+
+    #[test]
+    fn simulate_claim_timed_out_matches_execution() {
+        let mut deps = setup();
+        let env = mock_env();
+        fulfilled_escrow(deps.as_mut(), env.clone(), "foobar", &coins(100, "tokens"));
+
+        // the creator still has time to confirm or dispute
+        let err = query_simulate_release(
+            deps.as_ref(),
+            env.clone(),
+            "foobar".to_string(),
+            ReleaseOutcome::ClaimTimedOut,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(ContractError::NotTimedOut {}.to_string())
+        );
+
+        let mut later = env;
+        later.block.time = later
+            .block
+            .time
+            .plus_seconds(crate::state::FULFILL_TIMEOUT_SECONDS + 1);
+        let simulated = simulate(
+            deps.as_ref(),
+            later.clone(),
+            "foobar",
+            ReleaseOutcome::ClaimTimedOut,
+        );
+
+        let info = mock_info("fulfiller", &[]);
+        let id = "foobar".to_string();
+        let res = execute(deps.as_mut(), later, info, ExecuteMsg::ClaimTimedOut { id }).unwrap();
+        assert_eq!(("action", "claim_timed_out"), res.attributes[0]);
+        assert_eq!(simulated, transfers_from_messages(&res.messages));
+        assert_eq!(
+            simulated,
+            vec![Transfer {
+                recipient: "fulfiller".to_string(),
+                denom: Denom::Native("tokens".to_string()),
+                amount: Uint128::new(100),
+            }]
+        );
+    }
+
+    #[test]
+    fn simulate_arbitration_split_matches_execution() {
+        let mut deps = setup();
+        let env = mock_env();
+        fulfilled_escrow(
+            deps.as_mut(),
+            env.clone(),
+            "foobar",
+            &[coin(1001, "fee"), coin(3, "stake")],
+        );
+        let info = mock_info("creator", &[]);
+        let id = "foobar".to_string();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CReqArbitration {
+                id: id.clone(),
+                reason_code: DisputeReason::NotReceived,
+                details: None,
+            },
+        )
+        .unwrap();
+
+        let outcome = ReleaseOutcome::ArbitrateFor {
+            receiver: "creator".to_string(),
+            creator_bps: Some(2500),
+        };
+        let simulated = simulate(deps.as_ref(), env.clone(), "foobar", outcome);
+        // the creator's share rounds down, the dust goes to the fulfiller
+        assert_eq!(
+            simulated,
+            vec![
+                Transfer {
+                    recipient: "creator".to_string(),
+                    denom: Denom::Native("fee".to_string()),
+                    amount: Uint128::new(250),
+                },
+                Transfer {
+                    recipient: "fulfiller".to_string(),
+                    denom: Denom::Native("fee".to_string()),
+                    amount: Uint128::new(751),
+                },
+                Transfer {
+                    recipient: "fulfiller".to_string(),
+                    denom: Denom::Native("stake".to_string()),
+                    amount: Uint128::new(3),
+                },
+            ]
+        );
+
+        // only the arbiter may rule
+        let msg = ArbitrateMsg {
+            receiver: "creator".to_string(),
+            creator_bps: Some(2500),
+        };
+        let info = mock_info("creator", &[]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ElArbitrate {
+                id: id.clone(),
+                msg: msg.clone(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let info = mock_info("arbitrate", &[]);
+        let res = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ElArbitrate { id, msg },
+        )
+        .unwrap();
+        assert_eq!(("action", "arbitrate"), res.attributes[0]);
+        assert_eq!(simulated, transfers_from_messages(&res.messages));
+    }
+
+    #[test]
+    fn simulate_rejects_invalid_outcomes() {
+        let mut deps = setup();
+        let env = mock_env();
+        fulfilled_escrow(deps.as_mut(), env.clone(), "foobar", &coins(100, "tokens"));
+
+        // not in arbitration yet
+        let outcome = ReleaseOutcome::ArbitrateFor {
+            receiver: "creator".to_string(),
+            creator_bps: None,
+        };
+        let err = query_simulate_release(deps.as_ref(), env.clone(), "foobar".to_string(), outcome)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(ContractError::NotInArbitration {}.to_string())
+        );
+
+        let info = mock_info("creator", &[]);
+        let id = "foobar".to_string();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CReqArbitration {
+                id,
+                reason_code: DisputeReason::NotReceived,
+                details: None,
+            },
+        )
+        .unwrap();
+
+        // a stranger can't be the receiver
+        let outcome = ReleaseOutcome::ArbitrateFor {
+            receiver: "stranger".to_string(),
+            creator_bps: None,
+        };
+        let err = query_simulate_release(deps.as_ref(), env.clone(), "foobar".to_string(), outcome)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(ContractError::NotAParty {}.to_string())
+        );
+
+        // nor can the split exceed the whole balance
+        let outcome = ReleaseOutcome::ArbitrateFor {
+            receiver: "creator".to_string(),
+            creator_bps: Some(10_001),
+        };
+        let err = query_simulate_release(deps.as_ref(), env.clone(), "foobar".to_string(), outcome)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(ContractError::InvalidBps {}.to_string())
+        );
+
+        // the simulation never touches state
+        let outcome = ReleaseOutcome::ArbitrateFor {
+            receiver: "fulfiller".to_string(),
+            creator_bps: None,
+        };
+        simulate(deps.as_ref(), env, "foobar", outcome);
+        let details = query_details(deps.as_ref(), mock_env(), "foobar".to_string()).unwrap();
+        assert_eq!(coins(100, "tokens"), details.native_balance);
+    }
+
+    fn blacklist(deps: DepsMut, address: &str) {
+        let msg = ExecuteMsg::AddToBlacklist {
+            address: address.to_string(),
+            reason: "sanctioned".to_string(),
+        };
+        execute(deps, mock_env(), mock_info("admin", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn blacklisted_creator_cannot_create_or_top_up() {
+        let mut deps = setup();
+
+        // fund one escrow before being blacklisted
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create_msg("before"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // only the admin manages the blacklist
+        let msg = ExecuteMsg::AddToBlacklist {
+            address: "creator".to_string(),
+            reason: "sanctioned".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        blacklist(deps.as_mut(), "creator");
+
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create_msg("after"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AddressBlacklisted {});
+
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let id = "before".to_string();
+        let err = execute(deps.as_mut(), mock_env(), info, TopUp { id }).unwrap_err();
+        assert_eq!(err, ContractError::AddressBlacklisted {});
+
+        // cw20 hooks are checked against the original sender, not the token
+        let receive = Cw20ReceiveMsg {
+            sender: String::from("creator"),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::CCreate(create_msg("cw20"))).unwrap(),
+        };
+        let info = mock_info("my-cw20-token", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Receive(receive),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AddressBlacklisted {});
+
+        let res = query_blacklist(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            res.entries,
+            vec![BlacklistInfo {
+                address: "creator".to_string(),
+                reason: "sanctioned".to_string(),
+                since: mock_env().block.time.seconds(),
+            }]
+        );
+
+        // once removed, the creator is welcome again
+        let msg = ExecuteMsg::RemoveFromBlacklist {
+            address: "creator".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create_msg("after"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = query_blacklist(deps.as_ref(), None, None).unwrap();
+        assert!(res.entries.is_empty());
+    }
+
+    #[test]
+    fn blacklisted_fulfiller_can_finish_escrow_in_flight() {
+        let mut deps = setup();
+
+        let info = mock_info("creator", &coins(100, "tokens"));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::CCreate(create_msg("inflight")),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CCreate(create_msg("other")),
+        )
+        .unwrap();
+        let info = mock_info("fulfiller", &[]);
+        let id = "inflight".to_string();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::FAccept {
+                id: id.clone(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        )
+        .unwrap();
+
+        blacklist(deps.as_mut(), "fulfiller");
+
+        // no new escrows
+        let other = "other".to_string();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::FAccept {
+                id: other,
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AddressBlacklisted {});
+
+        // but the one in flight can still be finished and paid out
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::FComplete {
+                id: id.clone(),
+                delivered_fiat_amount: None,
+            },
+        )
+        .unwrap();
+        let info = mock_info("creator", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CComplete {
+                id,
+                accept_rate_deviation: false,
+                release_bps: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("fulfiller"),
+                amount: coins(100, "tokens"),
+            }))
+        );
+    }
+
+    #[test]
+    fn blacklist_query_paginates() {
+        let mut deps = setup();
+        for address in ["carol", "alice", "bob"] {
+            blacklist(deps.as_mut(), address);
+        }
+
+        let page = query_blacklist(deps.as_ref(), None, Some(2)).unwrap();
+        let addresses: Vec<_> = page.entries.iter().map(|e| e.address.as_str()).collect();
+        assert_eq!(addresses, vec!["alice", "bob"]);
+        let page = query_blacklist(deps.as_ref(), Some("bob".to_string()), Some(2)).unwrap();
+        let addresses: Vec<_> = page.entries.iter().map(|e| e.address.as_str()).collect();
+        assert_eq!(addresses, vec!["carol"]);
+    }
+
+    fn at(height: u64, seconds: u64) -> Env {
+        let mut env = mock_env();
+        env.block.height = height;
+        env.block.time = Timestamp::from_seconds(seconds);
+        env
+    }
+
+    #[test]
+    fn expiry_by_height_only() {
+        let mut deps = setup();
+        let env = at(1000, 1_000_000);
+        let create = CreateMsg {
+            end_height: Some(1100),
+            ..create_msg("height")
+        };
+        let info = mock_info("creator", &coins(100, "tokens"));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap();
+
+        let deadlines = query_deadlines(deps.as_ref(), env, "height".to_string()).unwrap();
+        assert_eq!(deadlines.expires_at_seconds, Some(1_000_600));
+        assert_eq!(deadlines.expires_by, Some(ExpirySource::EndHeight));
+
+        let escrow = ESCROWS.load(&deps.storage, "height").unwrap();
+        assert!(!escrow.is_expired(&at(1100, 1_000_600)));
+        assert!(escrow.is_expired(&at(1101, 1_000_606)));
+    }
+
+    #[test]
+    fn expiry_by_time_only() {
+        let mut deps = setup();
+        let env = at(1000, 1_000_000);
+        let create = CreateMsg {
+            end_time: Some(1_000_600),
+            ..create_msg("time")
+        };
+        let info = mock_info("creator", &coins(100, "tokens"));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap();
+
+        let deadlines = query_deadlines(deps.as_ref(), env, "time".to_string()).unwrap();
+        assert_eq!(deadlines.expires_at_seconds, Some(1_000_600));
+        assert_eq!(deadlines.expires_by, Some(ExpirySource::EndTime));
+
+        let escrow = ESCROWS.load(&deps.storage, "time").unwrap();
+        assert!(!escrow.is_expired(&at(1100, 1_000_600)));
+        assert!(escrow.is_expired(&at(1100, 1_000_601)));
+    }
+
+    #[test]
+    fn expiry_by_both_uses_whichever_comes_first() {
+        let mut deps = setup();
+        let env = at(1000, 1_000_000);
+        let info = mock_info("creator", &coins(100, "tokens"));
+
+        // the height resolves 5 minutes before the time
+        let create = CreateMsg {
+            end_height: Some(1100),
+            end_time: Some(1_000_900),
+            ..create_msg("height_first")
+        };
+        let msg = ExecuteMsg::CCreate(create);
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let details =
+            query_details(deps.as_ref(), env.clone(), "height_first".to_string()).unwrap();
+        assert_eq!(details.expires_at_seconds, Some(1_000_600));
+        assert_eq!(details.expires_by, Some(ExpirySource::EndHeight));
+
+        // the time comes 5 minutes before the height
+        let create = CreateMsg {
+            end_height: Some(1150),
+            end_time: Some(1_000_600),
+            ..create_msg("time_first")
+        };
+        let msg = ExecuteMsg::CCreate(create);
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "time_first".to_string()).unwrap();
+        assert_eq!(details.expires_at_seconds, Some(1_000_600));
+        assert_eq!(details.expires_by, Some(ExpirySource::EndTime));
+        let escrow = ESCROWS.load(&deps.storage, "time_first").unwrap();
+        assert!(!escrow.is_expired(&at(1100, 1_000_600)));
+        assert!(escrow.is_expired(&at(1100, 1_000_601)));
+
+        // a tie resolves to end_time
+        let create = CreateMsg {
+            end_height: Some(1100),
+            end_time: Some(1_000_600),
+            ..create_msg("tie")
+        };
+        let msg = ExecuteMsg::CCreate(create);
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "tie".to_string()).unwrap();
+        assert_eq!(details.expires_by, Some(ExpirySource::EndTime));
+
+        // bounds more than an hour apart are rejected
+        let create = CreateMsg {
+            end_height: Some(1100),
+            end_time: Some(1_000_600 + MAX_EXPIRY_DRIFT_SECONDS + 1),
+            ..create_msg("inconsistent")
+        };
+        let msg = ExecuteMsg::CCreate(create);
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InconsistentExpiry {});
+        let create = CreateMsg {
+            end_height: Some(1100),
+            end_time: Some(1_000_600 + MAX_EXPIRY_DRIFT_SECONDS),
+            ..create_msg("consistent")
+        };
+        execute(deps.as_mut(), env, info, ExecuteMsg::CCreate(create)).unwrap();
+    }
+
+    #[test]
+    fn admin_reassigns_arbiter_during_arbitration() {
+        let mut deps = setup();
+        let mut env = mock_env();
+        fulfilled_escrow(deps.as_mut(), env.clone(), "foobar", &coins(100, "tokens"));
+        let id = "foobar".to_string();
+        let reassign = ExecuteMsg::ReassignArbiter {
+            id: id.clone(),
+            new_arbiter: "backup".to_string(),
+        };
+
+        // only while in arbitration
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("admin", &[]),
+            reassign.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotInArbitration {});
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            ExecuteMsg::CReqArbitration {
+                id: id.clone(),
+                reason_code: DisputeReason::NotReceived,
+                details: None,
+            },
+        )
+        .unwrap();
+
+        // only the admin, and only to a registered arbiter
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            reassign.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("admin", &[]),
+            reassign.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ArbiterNotRegistered {});
+        let add = ExecuteMsg::AddArbiter {
+            address: "backup".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), add).unwrap();
+        let arbiters = query_arbiters(deps.as_ref(), None, None, None)
+            .unwrap()
+            .arbiters;
+        assert_eq!(arbiters[0].address, "backup");
+        assert_eq!(arbiters.len(), 1);
+
+        // the arbitration clock restarts for the new arbiter
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("admin", &[]),
+            reassign,
+        )
+        .unwrap();
+        assert_eq!(("old_arbiter", "arbitrate"), res.attributes[2]);
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), id.clone()).unwrap();
+        assert_eq!(
+            deadlines.arbitration_deadline,
+            Some(env.block.time.seconds() + ARBITRATION_TIMEOUT_SECONDS)
+        );
+
+        // the old arbiter can no longer rule, the new one can
+        let msg = ArbitrateMsg {
+            receiver: "fulfiller".to_string(),
+            creator_bps: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("arbitrate", &[]),
+            ExecuteMsg::ElArbitrate {
+                id: id.clone(),
+                msg: msg.clone(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("backup", &[]),
+            ExecuteMsg::ElArbitrate { id, msg },
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+    }
+
+    #[test]
+    fn description_is_trimmed_bounded_and_editable_until_accepted() {
+        let mut deps = setup();
+        let env = mock_env();
+
+        let create = CreateMsg {
+            description: Some("x".repeat(MAX_DESCRIPTION_BYTES + 1)),
+            ..create_msg("foobar")
+        };
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DescriptionTooLong {
+                max: MAX_DESCRIPTION_BYTES
+            }
+        );
+
+        let create = CreateMsg {
+            description: Some("  Caracas, Banesco transfers only\n".to_string()),
+            ..create_msg("foobar")
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details.description,
+            Some("Caracas, Banesco transfers only".to_string())
+        );
+
+        // only the creator may change the listing
+        let change = CreateMsg {
+            exchange_rate: Decimal::percent(3700),
+            fiat_currency: "VES".to_string(),
+            description: Some("Caracas, 9am-6pm".to_string()),
+            ..create_msg("foobar")
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("fulfiller", &[]),
+            ExecuteMsg::CChange(change.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            ExecuteMsg::CChange(change.clone()),
+        )
+        .unwrap();
+        assert_eq!(("action", "change"), res.attributes[0]);
+
+        let listings = query_listings(
+            deps.as_ref(),
+            mock_env(),
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+        )
+        .unwrap()
+        .listings;
+        assert_eq!(
+            listings,
+            vec![ListingSummary {
+                id: "foobar".to_string(),
+                fingerprint: details.fingerprint.clone(),
+                creator: "creator".to_string(),
+                exchange_rate: Decimal::percent(3700),
+                fiat_currency: "VES".to_string(),
+                native_balance: coins(100, "tokens"),
+                native_balance_display: vec!["100".to_string()],
+                cw20_balance: vec![],
+                description: Some("Caracas, 9am-6pm".to_string()),
+                creator_locale: None,
+                creator_utc_offset_minutes: None,
+                expired: false,
+                open_to_all_until: None,
+                starts_at: None,
+                risk_score: 0,
+                acceptable: None,
+                blockers: None,
+            }]
+        );
+
+        // once accepted the terms are fixed and the escrow is no longer listed
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("fulfiller", &[]),
+            ExecuteMsg::FAccept {
+                id: "foobar".to_string(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("creator", &[]),
+            ExecuteMsg::CChange(change),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AlreadyAccepted {
+                id: "foobar".to_string(),
+                accepted_by: "fulfiller".to_string(),
+            }
+        );
+        let listings = query_listings(
+            deps.as_ref(),
+            mock_env(),
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+        )
+        .unwrap()
+        .listings;
+        assert!(listings.is_empty());
+    }
+
+    #[test]
+    fn create_and_accept_return_the_escrow_details() {
+        let mut deps = setup();
+        let env = mock_env();
+        let create = CreateMsg {
+            end_time: Some(env.block.time.seconds() + 600),
+            ..create_msg("foobar")
+        };
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap();
+        let created: DetailsResponse = from_binary(&res.data.unwrap()).unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(created, details);
+        assert_eq!(created.expires_by, Some(ExpirySource::EndTime));
+
+        let info = mock_info("fulfiller", &[]);
+        let msg = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let accepted: DetailsResponse = from_binary(&res.data.unwrap()).unwrap();
+        let details = query_details(deps.as_ref(), env, "foobar".to_string()).unwrap();
+        assert_eq!(accepted, details);
+        assert_eq!(accepted.fulfiller, "fulfiller");
+    }
+
+    #[test]
+    fn zero_amount_coins_are_rejected() {
+        let mut deps = setup();
+        let env = mock_env();
+
+        let funds = vec![coin(100, "tokens"), coin(0, "phantom")];
+        let info = mock_info("creator", &funds);
+        let msg = ExecuteMsg::CCreate(create_msg("foobar"));
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ZeroAmount {
+                denom: "phantom".to_string()
+            }
+        );
+
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create_msg("foobar"));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let info = mock_info("creator", &[coin(0, "phantom"), coin(5, "tokens")]);
+        let msg = TopUp {
+            id: "foobar".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ZeroAmount {
+                denom: "phantom".to_string()
+            }
+        );
+
+        let receive = Cw20ReceiveMsg {
+            sender: "creator".to_string(),
+            amount: Uint128::zero(),
+            msg: to_binary(&ReceiveMsg::TopUp {
+                id: "foobar".to_string(),
+            })
+            .unwrap(),
+        };
+        let info = mock_info("my-cw20-token", &[]);
+        let err = execute(deps.as_mut(), env, info, ExecuteMsg::Receive(receive)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ZeroAmount {
+                denom: "my-cw20-token".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn whitelist_is_sorted_deduped_and_capped() {
+        let addrs = |names: &[&str]| {
+            names
+                .iter()
+                .map(|n| Addr::unchecked(*n))
+                .collect::<Vec<_>>()
+        };
+
+        let normalized = normalize_whitelist(addrs(&["zeta", "alpha", "zeta", "beta"]), 10);
+        assert_eq!(normalized.unwrap(), addrs(&["alpha", "beta", "zeta"]));
+        assert!(normalize_whitelist(vec![], 0).unwrap().is_empty());
+        // the cap applies after deduping
+        assert!(normalize_whitelist(addrs(&["alpha", "alpha"]), 1).is_ok());
+        let err = normalize_whitelist(addrs(&["alpha", "beta"]), 1).unwrap_err();
+        assert_eq!(err, ContractError::WhitelistTooLarge { max: 1 });
+
+        // the funding token is not listed twice when already whitelisted
+        let mut deps = setup();
+        let create = CreateMsg {
+            cw20_whitelist: Some(vec![
+                "other-token".to_string(),
+                "my-cw20-token".to_string(),
+                "other-token".to_string(),
+            ]),
+            ..create_msg("foobar")
+        };
+        let receive = Cw20ReceiveMsg {
+            sender: "creator".to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::CCreate(create)).unwrap(),
+        };
+        let info = mock_info("my-cw20-token", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Receive(receive),
+        )
+        .unwrap();
+        let details = query_details(deps.as_ref(), mock_env(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details.cw20_whitelist,
+            vec!["my-cw20-token".to_string(), "other-token".to_string()]
+        );
+
+        let create = CreateMsg {
+            cw20_whitelist: Some(
+                (0..=DEFAULT_MAX_WHITELIST_LEN)
+                    .map(|i| format!("token{}", i))
+                    .collect(),
+            ),
+            ..create_msg("toolong")
+        };
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let err =
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CCreate(create)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WhitelistTooLarge {
+                max: DEFAULT_MAX_WHITELIST_LEN
+            }
+        );
+    }
+
+    #[test]
+    fn changed_since_polls_incrementally() {
+        let mut deps = setup();
+        let env = mock_env();
+        let poll = |deps: Deps, revision: u64| {
+            let res = query_changed_since(deps, mock_env(), revision, None).unwrap();
+            let changes: Vec<_> = res
+                .changes
+                .iter()
+                .map(|c| (c.id.clone(), c.revision, c.escrow.is_some()))
+                .collect();
+            (changes, res.latest_revision)
+        };
+        assert_eq!(poll(deps.as_ref(), 0), (vec![], 0));
+
+        for id in ["first", "second"] {
+            let info = mock_info("creator", &coins(100, "tokens"));
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+        assert_eq!(
+            poll(deps.as_ref(), 0),
+            (
+                vec![
+                    ("first".to_string(), 1, true),
+                    ("second".to_string(), 2, true)
+                ],
+                2
+            )
+        );
+        let res = query_changed_since(deps.as_ref(), env.clone(), 0, Some(1)).unwrap();
+        assert_eq!(res.changes.len(), 1);
+        assert_eq!(
+            res.changes[0].escrow,
+            Some(query_details(deps.as_ref(), env.clone(), "first".to_string()).unwrap())
+        );
+
+        // a change moves the escrow to the newest revision
+        let info = mock_info("creator", &coins(5, "tokens"));
+        let msg = TopUp {
+            id: "second".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(
+            poll(deps.as_ref(), 2),
+            (vec![("second".to_string(), 3, true)], 3)
+        );
+        assert_eq!(
+            poll(deps.as_ref(), 0).0,
+            vec![
+                ("first".to_string(), 1, true),
+                ("second".to_string(), 3, true)
+            ]
+        );
+
+        // created, accepted and fulfilled, only the latest state shows
+        fulfilled_escrow(deps.as_mut(), env.clone(), "third", &coins(100, "tokens"));
+        assert_eq!(
+            poll(deps.as_ref(), 3),
+            (vec![("third".to_string(), 6, true)], 6)
+        );
+
+        // paying out leaves a tombstone
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::CComplete {
+            id: "third".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            poll(deps.as_ref(), 6),
+            (vec![("third".to_string(), 7, false)], 7)
+        );
+    }
+
+    #[test]
+    fn delivered_amount_is_reconciled_against_the_listed_rate() {
+        let mut deps = setup();
+        let env = mock_env();
+        // 100 UST at 36.50 Bolivares each, so 3650.00 Bolivares are expected
+        let deliver = |deps: DepsMut, id: &str, cents: u128| {
+            let mut deps = deps;
+            let info = mock_info("creator", &coins(100_000_000, UST_DENOM));
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.branch(), mock_env(), info, msg).unwrap();
+            let info = mock_info("fulfiller", &[]);
+            let id = id.to_string();
+            let msg = ExecuteMsg::FAccept {
+                id: id.clone(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            };
+            execute(deps.branch(), mock_env(), info.clone(), msg).unwrap();
+            let msg = ExecuteMsg::FComplete {
+                id,
+                delivered_fiat_amount: Some(Uint128::new(cents)),
+            };
+            execute(deps, mock_env(), info, msg).unwrap();
+        };
+
+        deliver(deps.as_mut(), "close", 360_000);
+        let details = query_details(deps.as_ref(), env.clone(), "close".to_string()).unwrap();
+        assert_eq!(details.exchange_rate, Decimal::percent(3650));
+        assert_eq!(details.delivered_fiat_amount, Some(Uint128::new(360_000)));
+        assert_eq!(details.realized_exchange_rate, Some(Decimal::percent(3600)));
+        let msg = ExecuteMsg::CComplete {
+            id: "close".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+
+        // 6.8% short needs the creator's explicit consent
+        deliver(deps.as_mut(), "short", 340_000);
+        let details = query_details(deps.as_ref(), env.clone(), "short".to_string()).unwrap();
+        assert_eq!(details.realized_exchange_rate, Some(Decimal::percent(3400)));
+        let msg = ExecuteMsg::CComplete {
+            id: "short".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::RateDeviation {});
+        let msg = ExecuteMsg::CComplete {
+            id: "short".to_string(),
+            accept_rate_deviation: true,
+            release_bps: None,
+        };
+        execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn arbitration_request_records_the_reason() {
+        let mut deps = setup();
+        let env = mock_env();
+        fulfilled_escrow(deps.as_mut(), env.clone(), "foobar", &coins(100, "tokens"));
+
+        let msg = ExecuteMsg::CReqArbitration {
+            id: "foobar".to_string(),
+            reason_code: DisputeReason::WrongAmount,
+            details: Some(" Only 3000 arrived ".to_string()),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(("reason_code", "wrong_amount"), res.attributes[2]);
+
+        let details = query_details(deps.as_ref(), env, "foobar".to_string()).unwrap();
+        assert_eq!(
+            details.dispute,
+            Some(Dispute {
+                reason_code: DisputeReason::WrongAmount,
+                details: Some("Only 3000 arrived".to_string()),
+                info_requests: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn only_the_arbiter_can_settle_a_dispute() {
+        let mut deps = setup();
+        let mut env = mock_env();
+        fulfilled_escrow(deps.as_mut(), env.clone(), "foobar", &coins(100, "tokens"));
+        let id = "foobar".to_string();
+        let msg = ExecuteMsg::CReqArbitration {
+            id: id.clone(),
+            reason_code: DisputeReason::NotReceived,
+            details: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        // even once the creator's confirmation window lapsed
+        env.block.time = env.block.time.plus_seconds(FULFILL_TIMEOUT_SECONDS + 1);
+
+        let rejected = vec![
+            (
+                "creator",
+                ExecuteMsg::CComplete {
+                    id: id.clone(),
+                    accept_rate_deviation: true,
+                    release_bps: None,
+                },
+                ContractError::InArbitration {},
+            ),
+            (
+                "creator",
+                ExecuteMsg::CCancel { id: id.clone() },
+                ContractError::InArbitration {},
+            ),
+            (
+                "fulfiller",
+                ExecuteMsg::FUnaccept { id: id.clone() },
+                ContractError::InArbitration {},
+            ),
+            (
+                "fulfiller",
+                ExecuteMsg::ClaimTimedOut { id: id.clone() },
+                ContractError::InArbitration {},
+            ),
+            (
+                "creator",
+                ExecuteMsg::CReqArbitration {
+                    id: id.clone(),
+                    reason_code: DisputeReason::Other,
+                    details: None,
+                },
+                ContractError::NotFulfilled {},
+            ),
+            (
+                "fulfiller",
+                ExecuteMsg::FComplete {
+                    id: id.clone(),
+                    delivered_fiat_amount: None,
+                },
+                ContractError::CantFulfill {},
+            ),
+            (
+                "other",
+                ExecuteMsg::FAccept {
+                    id: id.clone(),
+                    expected_exchange_rate: None,
+                    required_creator_metrics: None,
+                    payout_memo: false,
+                },
+                already_accepted(&id, &ESCROWS.load(&deps.storage, &id).unwrap()),
+            ),
+            (
+                "creator",
+                ExecuteMsg::CChange(create_msg("foobar")),
+                already_accepted(&id, &ESCROWS.load(&deps.storage, &id).unwrap()),
+            ),
+        ];
+        for (sender, msg, expected) in rejected {
+            let err = execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg).unwrap_err();
+            assert_eq!(err, expected);
+        }
+        let info = mock_info("creator", &coins(5, "tokens"));
+        let err = execute(deps.as_mut(), env.clone(), info, TopUp { id: id.clone() }).unwrap_err();
+        assert_eq!(err, ContractError::NotListed {});
+        let outcome = ReleaseOutcome::CreatorComplete;
+        let err = query_simulate_release(deps.as_ref(), env.clone(), id.clone(), outcome);
+        assert!(err.is_err());
+
+        // the ruling is the way out
+        let msg = ArbitrateMsg {
+            receiver: "creator".to_string(),
+            creator_bps: None,
+        };
+        let info = mock_info("arbitrate", &[]);
+        execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ElArbitrate { id, msg },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn instantiate_rejects_funds() {
+        let mut deps = mock_dependencies(&[]);
+        // funds sent along would be tracked by no escrow
+        let info = mock_info("anyone", &coins(100, UST_DENOM));
+        let err = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap_err();
+        assert_eq!(err, ContractError::InstantiateWithFunds {});
+        assert!(CONFIG.may_load(&deps.storage).unwrap().is_none());
+    }
+
+    #[test]
+    fn instantiate_seeds_arbiters_and_trust_metrics() {
+        let veteran = TrustMetrics {
+            percent_completed: 98,
+            total_completed: 40,
+            ..TrustMetrics::default()
+        };
+        let msg = |seed_metrics: Vec<(String, TrustMetrics)>| InstantiateMsg {
+            admin: Some("admin".to_string()),
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+            initial_arbiters: vec!["arbitrate".to_string()],
+            seed_metrics,
+        };
+
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("anyone", &[]);
+        let seeds = vec![
+            ("veteran".to_string(), veteran.clone()),
+            ("veteran".to_string(), TrustMetrics::default()),
+        ];
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg(seeds)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateSeed {
+                address: "veteran".to_string()
+            }
+        );
+        let seeds = (0..=MAX_SEED_ENTRIES)
+            .map(|i| (format!("wallet{}", i), TrustMetrics::default()))
+            .collect();
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg(seeds)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TooManySeedEntries {
+                max: MAX_SEED_ENTRIES
+            }
+        );
+
+        let seeds = vec![("veteran".to_string(), veteran.clone())];
+        let mut deps = mock_dependencies(&[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg(seeds)).unwrap();
+        let arbiters = query_arbiters(deps.as_ref(), None, None, None)
+            .unwrap()
+            .arbiters;
+        assert_eq!(arbiters[0].address, "arbitrate");
+        assert_eq!(arbiters.len(), 1);
+        let msg = QueryMsg::TrustMetrics {
+            address: "veteran".to_string(),
+        };
+        let metrics: TrustMetrics =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(metrics, veteran);
+
+        // seeded metrics decide who may accept
+        let create = CreateMsg {
+            required_trust_metrics: TrustRequirement::Metrics(TrustMetrics {
+                total_completed: 10,
+                ..TrustMetrics::default()
+            }),
+            ..create_msg("foobar")
+        };
+        let info = mock_info("creator", &coins(100, "tokens"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CCreate(create)).unwrap();
+        let accept = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let info = mock_info("newcomer", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, accept.clone()).unwrap_err();
+        assert_eq!(err, ContractError::TrustMetricsInsufficient {});
+        execute(deps.as_mut(), mock_env(), mock_info("veteran", &[]), accept).unwrap();
+    }
+
+    #[test]
+    fn admin_seeds_until_closed() {
+        let mut deps = setup();
+        let seed = ExecuteMsg::SeedTrustMetrics {
+            metrics: vec![("veteran".to_string(), TrustMetrics::default())],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("veteran", &[]),
+            seed.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let admin = mock_info("admin", &[]);
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), seed.clone()).unwrap();
+        assert_eq!(("count", "1"), res.attributes[1]);
+
+        let close = ExecuteMsg::CloseSeeding {};
+        execute(deps.as_mut(), mock_env(), admin.clone(), close).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), admin, seed).unwrap_err();
+        assert_eq!(err, ContractError::SeedingClosed {});
+    }
+
+    #[test]
+    fn cancel_refunds_until_fulfilled() {
+        let mut deps = setup();
+        let mut env = mock_env();
+        let cancel = |id: &str| ExecuteMsg::CCancel { id: id.to_string() };
+        let creator = mock_info("creator", &[]);
+        let fulfiller = mock_info("fulfiller", &[]);
+
+        // a listing can be canceled by its creator only
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create_msg("listed"));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            fulfiller.clone(),
+            cancel("listed"),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            cancel("listed"),
+        )
+        .unwrap();
+        assert_eq!(("action", "cancel"), res.attributes[0]);
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(100, "tokens"),
+            })]
+        );
+
+        // fulfill then cancel: the creator has to confirm or dispute
+        fulfilled_escrow(
+            deps.as_mut(),
+            env.clone(),
+            "fulfilled",
+            &coins(100, "tokens"),
+        );
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            cancel("fulfilled"),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::AlreadyFulfilled {});
+
+        // cancel then fulfill: only once the fulfiller's time ran out, and the escrow is gone
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create_msg("accepted"));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = ExecuteMsg::FAccept {
+            id: "accepted".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(deps.as_mut(), env.clone(), fulfiller.clone(), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            cancel("accepted"),
+        );
+        assert_eq!(
+            err.unwrap_err(),
+            ContractError::AlreadyAccepted {
+                id: "accepted".to_string(),
+                accepted_by: "fulfiller".to_string(),
+            }
+        );
+        env.block.time = env.block.time.plus_seconds(ACCEPT_TIMEOUT_SECONDS + 1);
+        execute(deps.as_mut(), env.clone(), creator, cancel("accepted")).unwrap();
+        let msg = ExecuteMsg::FComplete {
+            id: "accepted".to_string(),
+            delivered_fiat_amount: None,
+        };
+        let err = execute(deps.as_mut(), env, fulfiller, msg).unwrap_err();
+        assert!(matches!(err, ContractError::EscrowFinished { status, .. } if status == "cancel"));
+    }
+
+    #[test]
+    fn escrows_resolve_by_fingerprint() {
+        let mut deps = setup();
+        let env = mock_env();
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create_msg("foobar"));
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            ("fingerprint", details.fingerprint.as_str()),
+            res.attributes[2]
+        );
+
+        // the fingerprint doesn't change as the escrow does
+        let msg = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("fulfiller", &[]), msg).unwrap();
+        let lowercase = details.fingerprint.to_lowercase();
+        let found = query_by_fingerprint(deps.as_ref(), env.clone(), lowercase.clone()).unwrap();
+        assert_eq!(found.fingerprint, details.fingerprint);
+        assert_eq!(found.fulfiller, "fulfiller");
+
+        let err = query_by_fingerprint(deps.as_ref(), env, "AAAAAAAA".to_string());
+        assert!(matches!(err, Err(StdError::NotFound { .. })));
+    }
+
+    #[test]
+    fn rulings_count_disputes_won_and_lost() {
+        let mut deps = setup();
+        let env = mock_env();
+        let metrics = |deps: Deps, address: &str| {
+            get_trust_metrics(deps.storage, &Addr::unchecked(address)).unwrap()
+        };
+        let dispute = |deps: DepsMut, id: &str, receiver: &str, creator_bps: Option<u64>| {
+            let mut deps = deps;
+            fulfilled_escrow(deps.branch(), mock_env(), id, &coins(100, "tokens"));
+            let msg = ExecuteMsg::CReqArbitration {
+                id: id.to_string(),
+                reason_code: DisputeReason::NotReceived,
+                details: None,
+            };
+            execute(deps.branch(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let msg = ArbitrateMsg {
+                receiver: receiver.to_string(),
+                creator_bps,
+            };
+            let msg = ExecuteMsg::ElArbitrate {
+                id: id.to_string(),
+                msg,
+            };
+            execute(deps, mock_env(), mock_info("arbitrate", &[]), msg).unwrap();
+        };
+
+        dispute(deps.as_mut(), "first", "fulfiller", None);
+        // an even split counts for neither
+        dispute(deps.as_mut(), "second", "creator", Some(5000));
+        // the creator gets the larger share
+        dispute(deps.as_mut(), "third", "fulfiller", Some(8000));
+
+        let creator = metrics(deps.as_ref(), "creator");
+        assert_eq!(
+            (
+                creator.disputes_opened,
+                creator.disputes_won,
+                creator.disputes_lost
+            ),
+            (3, 1, 1)
+        );
+        let fulfiller = metrics(deps.as_ref(), "fulfiller");
+        assert_eq!(
+            (
+                fulfiller.disputes_opened,
+                fulfiller.disputes_won,
+                fulfiller.disputes_lost
+            ),
+            (0, 1, 1)
+        );
+
+        // the default requirements accept nobody who lost a dispute
+        let info = mock_info("someone", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create_msg("fourth"));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = ExecuteMsg::FAccept {
+            id: "fourth".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let err = execute(deps.as_mut(), env, mock_info("fulfiller", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::TrustMetricsInsufficient {});
+    }
+
+    #[test]
+    fn arbiter_stats_track_rulings_and_missed_deadlines() {
+        let mut deps = setup();
+        let dispute = |deps: DepsMut, id: &str| {
+            let mut deps = deps;
+            fulfilled_escrow(deps.branch(), mock_env(), id, &coins(100, "tokens"));
+            let msg = ExecuteMsg::CReqArbitration {
+                id: id.to_string(),
+                reason_code: DisputeReason::NotReceived,
+                details: None,
+            };
+            execute(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        };
+        let rule = |deps: DepsMut, id: &str, receiver: &str, creator_bps: Option<u64>| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(600);
+            let msg = ArbitrateMsg {
+                receiver: receiver.to_string(),
+                creator_bps,
+            };
+            let msg = ExecuteMsg::ElArbitrate {
+                id: id.to_string(),
+                msg,
+            };
+            execute(deps, env, mock_info("arbitrate", &[]), msg).unwrap();
+        };
+
+        // the fulfiller can't accept anymore once they lost a dispute
+        for id in ["first", "second", "third"] {
+            dispute(deps.as_mut(), id);
+        }
+        rule(deps.as_mut(), "first", "creator", None);
+        rule(deps.as_mut(), "second", "fulfiller", Some(3000));
+
+        // nobody can resolve a dispute by default before the deadline
+        let msg = ExecuteMsg::ResolveExpiredArbitration {
+            id: "third".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ArbitrationNotExpired {});
+
+        // past it, the arbiter can't rule anymore and anyone refunds the creator
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(ARBITRATION_TIMEOUT_SECONDS + 1);
+        let ruling = ExecuteMsg::ElArbitrate {
+            id: "third".to_string(),
+            msg: ArbitrateMsg {
+                receiver: "fulfiller".to_string(),
+                creator_bps: None,
+            },
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("arbitrate", &[]),
+            ruling,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ArbitrationExpired {});
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(100, "tokens"),
+            })]
+        );
+
+        let msg = QueryMsg::ArbiterStats {
+            address: "arbitrate".to_string(),
+        };
+        let res: ArbiterStatsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!res.registered);
+        assert_eq!(
+            res.stats,
+            ArbiterStats {
+                rulings: 2,
+                total_ruling_seconds: 1200,
+                missed_deadlines: 1,
+                for_creator: 1,
+                for_fulfiller: 0,
+                splits: 1,
+                recusals: 0,
+                disputed_volume_ust: Uint128::zero(),
+                active_escrows: 0,
+                in_arbitration: 0,
+            }
+        );
+        assert_eq!(res.average_ruling_seconds, Some(600));
+        assert_eq!(
+            res.missed_deadline_ratio,
+            Some(Decimal::from_ratio(1u128, 3u128))
+        );
+    }
+
+    #[test]
+    fn arbiter_stats_survive_leaving_the_registry() {
+        let mut deps = setup();
+        let admin = mock_info("admin", &[]);
+        for address in ["arbitrate", "other"] {
+            let msg = ExecuteMsg::AddArbiter {
+                address: address.to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        }
+        fulfilled_escrow(deps.as_mut(), mock_env(), "foobar", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CReqArbitration {
+            id: "foobar".to_string(),
+            reason_code: DisputeReason::Late,
+            details: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ElArbitrate {
+            id: "foobar".to_string(),
+            msg: ArbitrateMsg {
+                receiver: "fulfiller".to_string(),
+                creator_bps: None,
+            },
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("arbitrate", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::RemoveArbiter {
+            address: "arbitrate".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+
+        // an arbiter who never ruled has no record yet
+        let msg = QueryMsg::AllArbiterStats {
+            start_after: None,
+            limit: None,
+        };
+        let res: AllArbiterStatsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.arbiters.len(), 1);
+        let stats = &res.arbiters[0];
+        assert_eq!(stats.address, "arbitrate");
+        assert!(!stats.registered);
+        assert_eq!((stats.stats.rulings, stats.stats.for_fulfiller), (1, 1));
+        assert_eq!(stats.average_ruling_seconds, Some(0));
+        assert_eq!(stats.missed_deadline_ratio, Some(Decimal::zero()));
+
+        let msg = QueryMsg::ArbiterStats {
+            address: "other".to_string(),
+        };
+        let res: ArbiterStatsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.registered);
+        assert_eq!(res.average_ruling_seconds, None);
+        assert_eq!(res.missed_deadline_ratio, None);
+    }
+
+    #[test]
+    fn bumping_moves_a_listing_to_the_top() {
+        let mut deps = setup();
+        let ids = |deps: Deps, start_after: Option<&str>, limit: u32| -> Vec<String> {
+            query_listings(
+                deps,
+                mock_env(),
+                start_after.map(String::from),
+                Some(limit),
+                None,
+                false,
+                false,
+                None,
+            )
+            .unwrap()
+            .listings
+            .into_iter()
+            .map(|listing| listing.id)
+            .collect()
+        };
+        let info = mock_info("creator", &coins(100, "tokens"));
+        for (i, id) in ["first", "second", "third"].iter().enumerate() {
+            let env = at(12_345, 1_000_000 + i as u64);
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+        assert_eq!(
+            ids(deps.as_ref(), None, 10),
+            vec!["third", "second", "first"]
+        );
+
+        let bump = ExecuteMsg::Bump {
+            id: "first".to_string(),
+        };
+        let env = at(12_345, 1_000_100);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("someone", &[]),
+            bump.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), env, info.clone(), bump.clone()).unwrap();
+        assert_eq!(("action", "bump"), res.attributes[0]);
+        assert_eq!(
+            ids(deps.as_ref(), None, 10),
+            vec!["first", "third", "second"]
+        );
+        assert_eq!(ids(deps.as_ref(), Some("first"), 1), vec!["third"]);
+        assert_eq!(ids(deps.as_ref(), Some("third"), 10), vec!["second"]);
+
+        // once per cooldown
+        let available_at = 1_000_100 + DEFAULT_BUMP_COOLDOWN_SECONDS;
+        let env = at(12_345, available_at - 1);
+        let err = execute(deps.as_mut(), env, info.clone(), bump.clone()).unwrap_err();
+        assert_eq!(err, ContractError::BumpTooSoon { available_at });
+        execute(deps.as_mut(), at(12_345, available_at), info.clone(), bump).unwrap();
+
+        // accepted and expired escrows stay where they are
+        let msg = ExecuteMsg::FAccept {
+            id: "second".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("fulfiller", &[]), msg).unwrap();
+        let bump = ExecuteMsg::Bump {
+            id: "second".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), bump).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AlreadyAccepted {
+                id: "second".to_string(),
+                accepted_by: "fulfiller".to_string(),
+            }
+        );
+        assert_eq!(ids(deps.as_ref(), None, 10), vec!["first", "third"]);
+
+        let create = CreateMsg {
+            end_time: Some(1_000_200),
+            ..create_msg("fourth")
+        };
+        let msg = ExecuteMsg::CCreate(create);
+        execute(deps.as_mut(), at(12_345, 1_000_000), info.clone(), msg).unwrap();
+        let bump = ExecuteMsg::Bump {
+            id: "fourth".to_string(),
+        };
+        let err = execute(deps.as_mut(), at(12_345, 1_000_201), info, bump).unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+    }
+
+    #[test]
+    fn clone_copies_the_terms_into_a_new_escrow() {
+        let mut deps = setup();
+        let create = CreateMsg {
+            end_time: Some(1_000_600),
+            cw20_whitelist: Some(vec!["cash".to_string()]),
+            description: Some("Maracaibo, Mercantil".to_string()),
+            ..create_msg("rent")
+        };
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let msg = ExecuteMsg::CCreate(create);
+        execute(deps.as_mut(), at(12_345, 1_000_000), info, msg).unwrap();
+
+        let clone = ExecuteMsg::CloneEscrow {
+            source_id: "rent".to_string(),
+        };
+        let env = at(12_400, 1_000_100);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("someone", &coins(50, "tokens")),
+            clone.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let info = mock_info("creator", &coins(50, "tokens"));
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), clone.clone()).unwrap();
+        assert_eq!(("source_id", "rent"), res.attributes[2]);
+        let details: DetailsResponse = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(details.id, "rent-2");
+        assert_eq!(details.creator, "creator");
+        assert_eq!(details.arbiter, Some("arbitrate".to_string()));
+        assert_eq!(details.end_time, Some(1_000_700));
+        assert_eq!(details.cw20_whitelist, vec!["cash".to_string()]);
+        assert_eq!(
+            details.description,
+            Some("Maracaibo, Mercantil".to_string())
+        );
+        assert_eq!(details.native_balance, coins(50, "tokens"));
+
+        let res = execute(deps.as_mut(), env, info, clone).unwrap();
+        let details: DetailsResponse = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(details.id, "rent-3");
+    }
+
+    #[test]
+    fn completed_escrows_can_be_cloned() {
+        let mut deps = setup();
+        let env = mock_env();
+        let funds = coins(100, UST_DENOM);
+        fulfilled_escrow(deps.as_mut(), env.clone(), "done", &funds);
+        let msg = ExecuteMsg::CComplete {
+            id: "done".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        let creator = mock_info("creator", &[]);
+        execute(deps.as_mut(), env.clone(), creator, msg).unwrap();
+        assert!(!ESCROWS.has(&deps.storage, "done"));
+
+        let clone = ExecuteMsg::CloneEscrow {
+            source_id: "done".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("someone", &funds),
+            clone.clone(),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        let info = mock_info("creator", &funds);
+        let res = execute(deps.as_mut(), env.clone(), info, clone).unwrap();
+        let details: DetailsResponse = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(details.id, "done-2");
+        assert_eq!(details.native_balance, funds);
+
+        // one that never existed still says so
+        let clone = ExecuteMsg::CloneEscrow {
+            source_id: "never".to_string(),
+        };
+        let err = execute(deps.as_mut(), env, mock_info("creator", &funds), clone);
+        assert_eq!(
+            err.unwrap_err(),
+            ContractError::EscrowNotFound {
+                id: "never".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn admin_actions_are_logged_newest_first() {
+        let mut deps = setup();
+        let admin = mock_info("admin", &[]);
+        let msgs = vec![
+            ExecuteMsg::AddArbiter {
+                address: "arbitrate".to_string(),
+            },
+            ExecuteMsg::AddToBlacklist {
+                address: "scammer".to_string(),
+                reason: "chargebacks".to_string(),
+            },
+            ExecuteMsg::SeedTrustMetrics {
+                metrics: vec![("veteran".to_string(), TrustMetrics::default())],
+            },
+            ExecuteMsg::CloseSeeding {},
+        ];
+        for (i, msg) in msgs.into_iter().enumerate() {
+            let env = at(12_345, 1_000_000 + i as u64);
+            execute(deps.as_mut(), env, admin.clone(), msg).unwrap();
+        }
+        // rejected calls leave no trace
+        let msg = ExecuteMsg::RemoveArbiter {
+            address: "arbitrate".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("someone", &[]), msg).unwrap_err();
+
+        let msg = QueryMsg::AdminLog {
+            start_after: None,
+            limit: Some(2),
+        };
+        let res: AdminLogResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.entries,
+            vec![
+                AdminLogEntry {
+                    sequence: 4,
+                    admin: Addr::unchecked("admin"),
+                    time: 1_000_003,
+                    action: AdminAction::CloseSeeding {},
+                },
+                AdminLogEntry {
+                    sequence: 3,
+                    admin: Addr::unchecked("admin"),
+                    time: 1_000_002,
+                    action: AdminAction::SeedTrustMetrics {
+                        addresses: vec![Addr::unchecked("veteran")],
+                    },
+                },
+            ]
+        );
+        let entries = query_admin_log(deps.as_ref(), Some(3), None)
+            .unwrap()
+            .entries;
+        let actions: Vec<_> = entries.into_iter().map(|entry| entry.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AdminAction::AddToBlacklist {
+                    address: Addr::unchecked("scammer"),
+                    reason: "chargebacks".to_string(),
+                },
+                AdminAction::AddArbiter {
+                    address: Addr::unchecked("arbitrate"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn admin_log_keeps_the_last_entries_only() {
+        let mut deps = setup();
+        for _ in 0..MAX_ADMIN_LOG_ENTRIES + 5 {
+            let msg = ExecuteMsg::AddArbiter {
+                address: "arbitrate".to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        }
+        let sequences: Vec<u64> = ADMIN_LOG
+            .range(&deps.storage, None, None, Order::Ascending)
+            .map(|item| item.unwrap().1.sequence)
+            .collect();
+        assert_eq!(sequences.len() as u64, MAX_ADMIN_LOG_ENTRIES);
+        assert_eq!(sequences[0], 6);
+        assert_eq!(*sequences.last().unwrap(), MAX_ADMIN_LOG_ENTRIES + 5);
+    }
+
+    #[test]
+    fn config_updates_are_validated_as_a_whole() {
+        let mut deps = setup();
+        let msg = InstantiateMsg {
+            admin: None,
+            seconds_per_block: Some(0),
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+            initial_arbiters: vec![],
+            seed_metrics: vec![],
+        };
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidConfig {
+                field: "seconds_per_block".to_string()
+            }
+        );
+
+        let update = |max_whitelist_len: Option<u32>, bump_cooldown_seconds: Option<u64>| {
+            ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                admin: None,
+                seconds_per_block: None,
+                max_whitelist_len,
+                bump_cooldown_seconds,
+                allow_mixed_funding: None,
+                post_accept_cancel_window_seconds: None,
+                cancel_penalty_bps: None,
+                split_native_payouts: None,
+                listing_deposit: None,
+                fee_collector: None,
+                require_arbiter_ack: None,
+                arbiter_ack_window_seconds: None,
+                display_denoms: None,
+                listing_grace_period_seconds: None,
+                arbitration_extension_seconds: None,
+                max_arbitration_extensions: None,
+                kyc_contract: None,
+                allow_third_party_topup: None,
+                max_active_escrows: None,
+                daily_volume_limit_ust: None,
+                daily_accept_limit_ust: None,
+                dispute_window_seconds: None,
+                creator_ack_timeout_seconds: None,
+                max_open_to_all_seconds: None,
+                cancel_cooloff_seconds: None,
+                max_escrow_bytes: None,
+                mid_lifecycle_expiry: None,
+                risk_weights: None,
+                mediation_window_seconds: None,
+                min_remaining_on_accept_seconds: None,
+                peer_contracts: None,
+                max_actions_per_address: None,
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("someone", &[]),
+            update(Some(5), None),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            update(Some(5), Some(0)),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidConfig {
+                field: "bump_cooldown_seconds".to_string()
+            }
+        );
+
+        // untouched fields keep their values
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            update(Some(5), None),
+        )
+        .unwrap();
+        let config = Box::new(CONFIG.load(&deps.storage).unwrap());
+        assert_eq!(config.max_whitelist_len, 5);
+        assert_eq!(config.seconds_per_block, DEFAULT_SECONDS_PER_BLOCK);
+        assert_eq!(config.bump_cooldown_seconds, DEFAULT_BUMP_COOLDOWN_SECONDS);
+        let log = query_admin_log(deps.as_ref(), None, None).unwrap().entries;
+        assert_eq!(log[0].action, AdminAction::UpdateConfig { config });
+    }
+
+    #[test]
+    fn escrows_without_arbitration_settle_by_timeout_only() {
+        let mut deps = setup();
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let create = CreateMsg {
+            arbiter: None,
+            ..create_msg("family")
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::CCreate(create.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::MissingArbiter {});
+
+        let create = CreateMsg {
+            arbitration_enabled: false,
+            fiat_currency: "VES".to_string(),
+            ..create
+        };
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CCreate(create)).unwrap();
+        let id = "family".to_string();
+        let details = query_details(deps.as_ref(), mock_env(), id.clone()).unwrap();
+        assert_eq!(details.arbiter, None);
+
+        let fulfiller = mock_info("fulfiller", &[]);
+        let msg = ExecuteMsg::FAccept {
+            id: id.clone(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(deps.as_mut(), mock_env(), fulfiller.clone(), msg).unwrap();
+        let msg = ExecuteMsg::FComplete {
+            id: id.clone(),
+            delivered_fiat_amount: None,
+        };
+        execute(deps.as_mut(), mock_env(), fulfiller.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CReqArbitration {
+            id: id.clone(),
+            reason_code: DisputeReason::NotReceived,
+            details: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::ArbitrationDisabled {});
+        let msg = ExecuteMsg::ElArbitrate {
+            id: id.clone(),
+            msg: ArbitrateMsg {
+                receiver: "creator".to_string(),
+                creator_bps: None,
+            },
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("arbitrate", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::ArbitrationDisabled {});
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(FULFILL_TIMEOUT_SECONDS + 1);
+        let msg = ExecuteMsg::ClaimTimedOut { id };
+        let res = execute(deps.as_mut(), env, fulfiller, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "fulfiller".to_string(),
+                amount: coins(100, "tokens"),
+            })]
+        );
+    }
+
+    #[test]
+    fn second_accept_in_a_block_names_the_winner() {
+        let mut deps = setup();
+        let env = mock_env();
+        let info = mock_info("creator", &coins(100, "tokens"));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create_msg("foobar")),
+        )
+        .unwrap();
+
+        let accept = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("fulfiller", &[]),
+            accept.clone(),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("runner-up", &[]),
+            accept,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AlreadyAccepted {
+                id: "foobar".to_string(),
+                accepted_by: "fulfiller".to_string(),
+            }
+        );
+
+        // the winner keeps the escrow
+        let escrow = ESCROWS.load(&deps.storage, "foobar").unwrap();
+        assert_eq!(escrow.fulfiller, "fulfiller");
+        assert_eq!(escrow.time_accepted, Some(env.block.time.seconds()));
+    }
+
+    #[test]
+    fn creator_can_release_part_of_the_balance() {
+        let mut deps = setup();
+        let env = mock_env();
+        let creator = mock_info("creator", &[]);
+        let complete = |id: &str, release_bps: u64| ExecuteMsg::CComplete {
+            id: id.to_string(),
+            accept_rate_deviation: false,
+            release_bps: Some(release_bps),
+        };
+
+        fulfilled_escrow(
+            deps.as_mut(),
+            env.clone(),
+            "short",
+            &coins(5_000_000, UST_DENOM),
+        );
+        for release_bps in [0, 10_001] {
+            let msg = complete("short", release_bps);
+            let err = execute(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidBps {});
+        }
+        let msg = complete("short", 9_000);
+        let res = execute(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+        assert_eq!(("release_bps", "9000"), res.attributes[3]);
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "fulfiller".to_string(),
+                    amount: coins(4_500_000, UST_DENOM),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "creator".to_string(),
+                    amount: coins(500_000, UST_DENOM),
+                }),
+            ]
+        );
+        for party in ["creator", "fulfiller"] {
+            let metrics = get_trust_metrics(&deps.storage, &Addr::unchecked(party)).unwrap();
+            assert_eq!(metrics.total_volume, 4);
+        }
+
+        // releasing everything is a plain completion
+        fulfilled_escrow(deps.as_mut(), env.clone(), "full", &coins(100, "tokens"));
+        let res = execute(deps.as_mut(), env, creator, complete("full", 10_000)).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "fulfiller".to_string(),
+                amount: coins(100, "tokens"),
+            })]
+        );
+    }
+
+    #[test]
+    fn capabilities_report_the_version_and_features() {
+        let deps = setup();
+        let msg = QueryMsg::Capabilities {};
+        let res: CapabilitiesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.contract, CONTRACT_NAME);
+        assert_eq!(res.version, CONTRACT_VERSION);
+        assert!(res.capabilities.contains(&"topup".to_string()));
+        assert!(res.capabilities.contains(&"arbitration_split".to_string()));
+    }
+
+    #[test]
+    fn validate_create_reports_what_create_rejects() {
+        let mut deps = setup();
+        let env = mock_env();
+        let validate = |deps: Deps, msg: CreateMsg, funds: Vec<cosmwasm_std::Coin>| {
+            let msg = QueryMsg::ValidateCreate {
+                msg,
+                funds,
+                creator: Some("creator".to_string()),
+            };
+            let res: ValidateCreateResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res
+        };
+
+        let res = validate(deps.as_ref(), create_msg("foobar"), coins(100, "tokens"));
+        assert!(res.ok);
+        assert!(res.violations.is_empty());
+
+        // break one check and both paths report it
+        let long = CreateMsg {
+            description: Some("x".repeat(MAX_DESCRIPTION_BYTES + 1)),
+            ..create_msg("foobar")
+        };
+        let res = validate(deps.as_ref(), long.clone(), coins(100, "tokens"));
+        let expected = ContractError::DescriptionTooLong {
+            max: MAX_DESCRIPTION_BYTES,
+        };
+        assert_eq!(res.violations, vec![expected.to_string()]);
+        let info = mock_info("creator", &coins(100, "tokens"));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::CCreate(long),
+        )
+        .unwrap_err();
+        assert_eq!(err, expected);
+
+        // the query lists every violation, create fails on the first
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create_msg("foobar")),
+        )
+        .unwrap();
+        let bad = CreateMsg {
+            arbiter: None,
+            ..create_msg("foobar")
+        };
+        let res = validate(deps.as_ref(), bad.clone(), vec![]);
+        assert!(!res.ok);
+        assert_eq!(
+            res.violations,
+            vec![
+                ContractError::EmptyBalance {}.to_string(),
+                ContractError::MissingArbiter {}.to_string(),
+                ContractError::AlreadyInUse {}.to_string(),
+            ]
+        );
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("creator", &[]),
+            ExecuteMsg::CCreate(bad),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::EmptyBalance {});
+    }
+
+    #[test]
+    fn cancel_all_refunds_expired_listings_in_one_go() {
+        let mut deps = setup();
+        let env = at(12_345, 1_000_000);
+        let create = |deps: DepsMut, creator: &str, id: &str, end_time: u64| {
+            let msg = CreateMsg {
+                end_time: Some(end_time),
+                ..create_msg(id)
+            };
+            let info = mock_info(creator, &coins(100, "tokens"));
+            execute(deps, at(12_345, 1_000_000), info, ExecuteMsg::CCreate(msg)).unwrap();
+        };
+        create(deps.as_mut(), "creator", "expired", 1_000_100);
+        create(deps.as_mut(), "creator", "accepted", 1_000_100);
+        create(deps.as_mut(), "creator", "open", 1_500_000);
+        create(deps.as_mut(), "someone", "theirs", 1_000_100);
+        let msg = ExecuteMsg::FAccept {
+            id: "accepted".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(deps.as_mut(), env, mock_info("fulfiller", &[]), msg).unwrap();
+
+        let cancel_all = |limit: Option<u32>, only_expired: bool| ExecuteMsg::CancelAll {
+            start_after: None,
+            limit,
+            only_expired,
+        };
+        let creator = mock_info("creator", &[]);
+        let env = at(12_345, 1_000_101);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            cancel_all(None, true),
+        )
+        .unwrap();
+        assert_eq!(("escrow_id", "expired"), res.attributes[1]);
+        assert_eq!(res.attributes.len(), 3);
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(100, "tokens"),
+            })]
+        );
+        assert!(!ESCROWS.has(&deps.storage, "expired"));
+        assert!(ESCROWS.has(&deps.storage, "accepted"));
+        assert!(ESCROWS.has(&deps.storage, "theirs"));
+
+        // nothing left to do is not an error
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            cancel_all(None, true),
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+
+        // the accepted one still counts toward the limit, the next page goes on after it
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            cancel_all(Some(1), false),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1..], [attr("next_start_after", "accepted")]);
+        let msg = ExecuteMsg::CancelAll {
+            start_after: Some("accepted".to_string()),
+            limit: Some(1),
+            only_expired: false,
+        };
+        let res = execute(deps.as_mut(), env, creator, msg).unwrap();
+        assert_eq!(("escrow_id", "open"), res.attributes[1]);
+        assert!(ESCROWS.has(&deps.storage, "accepted"));
+    }
+
+    #[test]
+    fn funding_source_follows_the_creating_message() {
+        let mut deps = setup();
+
+        let info = mock_info("creator", &coins(100, "uusd"));
+        let msg = ExecuteMsg::CCreate(create_msg("native"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let details = query_details(deps.as_ref(), mock_env(), "native".to_string()).unwrap();
+        assert_eq!(details.funding_source, FundingSource::NativeDirect);
+
+        let create = ReceiveMsg::CCreate(CreateMsg {
+            cw20_whitelist: Some(vec!["other_token".to_string()]),
+            ..create_msg("cw20")
+        });
+        let receive = |msg: &ReceiveMsg| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "creator".to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(msg).unwrap(),
+            })
+        };
+        let info = mock_info("cash_token", &[]);
+        execute(deps.as_mut(), mock_env(), info, receive(&create)).unwrap();
+        let details = query_details(deps.as_ref(), mock_env(), "cw20".to_string()).unwrap();
+        assert_eq!(
+            details.funding_source,
+            FundingSource::Cw20Hook {
+                token: Addr::unchecked("cash_token")
+            }
+        );
+
+        // the token escrow takes whitelisted tokens, but no coins
+        let top_up = ReceiveMsg::TopUp {
+            id: "cw20".to_string(),
+        };
+        let info = mock_info("other_token", &[]);
+        execute(deps.as_mut(), mock_env(), info, receive(&top_up)).unwrap();
+        let top_up = ExecuteMsg::TopUp {
+            id: "cw20".to_string(),
+        };
+        let info = mock_info("creator", &coins(50, "uusd"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), top_up.clone()).unwrap_err();
+        assert_eq!(err, ContractError::MixedFunding {});
+
+        // unless the admin allows mixing
+        let update = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: Some(true),
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update).unwrap();
+        execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
+        let escrow = ESCROWS.load(&deps.storage, "cw20").unwrap();
+        assert_eq!(escrow.balance.native, coins(50, "uusd"));
+    }
+
+    fn open_interest(deps: Deps, currency: Option<&str>) -> Vec<(String, u128, u64)> {
+        query_open_interest(deps, currency.map(String::from))
+            .unwrap()
+            .currencies
+            .into_iter()
+            .map(|c| (c.currency, c.value_ust.u128(), c.listings))
+            .collect()
+    }
+
+    #[test]
+    fn open_interest_tracks_listings_per_currency() {
+        let mut deps = setup();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(create_msg("bolivares"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[coin(50, UST_DENOM), coin(10, "uluna")]);
+        let msg = ExecuteMsg::CCreate(CreateMsg {
+            fiat_currency: "cop".to_string(),
+            ..create_msg("pesos")
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            ContractError::InvalidCurrency {},
+            clean_currency("pesos").unwrap_err()
+        );
+
+        assert_eq!(
+            open_interest(deps.as_ref(), None),
+            vec![("COP".to_string(), 50, 1), ("VES".to_string(), 100, 1)]
+        );
+        assert_eq!(
+            open_interest(deps.as_ref(), Some("cop")),
+            vec![("COP".to_string(), 50, 1)]
+        );
+        assert_eq!(
+            open_interest(deps.as_ref(), Some("ARS")),
+            vec![("ARS".to_string(), 0, 0)]
+        );
+
+        // accepted escrows are no longer open
+        let msg = ExecuteMsg::FAccept {
+            id: "bolivares".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("fulfiller", &[]), msg).unwrap();
+        assert_eq!(
+            open_interest(deps.as_ref(), None),
+            vec![("COP".to_string(), 50, 1)]
+        );
+    }
+
+    /// Replays a pseudo-random mix of operations, checking the running totals
+    /// against a scan of every escrow after each one
+    #[test]
+    fn open_interest_matches_a_full_scan() {
+        let mut deps = setup();
+        let currencies = ["VES", "COP", "ARS"];
+        let mut seed: u64 = 42;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+
+        for step in 0..300u64 {
+            let ids = all_escrow_ids(&deps.storage).unwrap();
+            let id = if ids.is_empty() {
+                "none".to_string()
+            } else {
+                ids[next(ids.len() as u64) as usize].clone()
+            };
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(step * 600);
+            let creator = mock_info("creator", &[]);
+            let fulfiller = mock_info("fulfiller", &[]);
+            let msg = match next(8) {
+                0 | 1 => {
+                    let currency = currencies[next(3) as usize];
+                    let funds = vec![
+                        coin(u128::from(next(1000)) + 1, UST_DENOM),
+                        coin(5, "uluna"),
+                    ];
+                    let msg = ExecuteMsg::CCreate(CreateMsg {
+                        fiat_currency: currency.to_string(),
+                        end_time: Some(env.block.time.seconds() + next(3) * 600),
+                        ..create_msg(&format!("escrow-{}", step))
+                    });
+                    let _ = execute(deps.as_mut(), env, mock_info("creator", &funds), msg);
+                    continue;
+                }
+                2 => (
+                    fulfiller,
+                    ExecuteMsg::FAccept {
+                        id,
+                        expected_exchange_rate: None,
+                        required_creator_metrics: None,
+                        payout_memo: false,
+                    },
+                ),
+                3 => (fulfiller, ExecuteMsg::FUnaccept { id }),
+                4 => (creator, ExecuteMsg::CCancel { id }),
+                5 => {
+                    let msg = ExecuteMsg::CChange(CreateMsg {
+                        fiat_currency: currencies[next(3) as usize].to_string(),
+                        ..create_msg(&id)
+                    });
+                    (creator, msg)
+                }
+                6 => {
+                    let funds = coins(u128::from(next(100)) + 1, UST_DENOM);
+                    (mock_info("creator", &funds), ExecuteMsg::TopUp { id })
+                }
+                _ => {
+                    let msg = ExecuteMsg::CancelAll {
+                        start_after: None,
+                        limit: Some(2),
+                        only_expired: true,
+                    };
+                    (creator, msg)
+                }
+            };
+            let (info, msg) = msg;
+            let _ = execute(deps.as_mut(), env, info, msg);
+
+            let mut scanned: BTreeMap<String, (u128, u64)> = BTreeMap::new();
+            for item in ESCROWS.range(&deps.storage, None, None, Order::Ascending) {
+                let (_, escrow) = item.unwrap();
+                if escrow.is_listed {
+                    let entry = scanned.entry(escrow.fiat_currency.clone()).or_default();
+                    entry.0 += escrow.value_ust().u128();
+                    entry.1 += 1;
+                }
+            }
+            let scanned: Vec<_> = scanned
+                .into_iter()
+                .map(|(currency, (value, listings))| (currency, value, listings))
+                .collect();
+            assert_eq!(open_interest(deps.as_ref(), None), scanned, "step {}", step);
+        }
+    }
+
+    #[test]
+    fn accept_fails_if_the_rate_changed_since_it_was_shown() {
+        let mut deps = setup();
+        for id in ["first", "second"] {
+            let info = mock_info("creator", &coins(100, UST_DENOM));
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+        let shown = Decimal::percent(3650);
+        let changed = Decimal::percent(3900);
+        for id in ["first", "second"] {
+            let msg = ExecuteMsg::CChange(CreateMsg {
+                exchange_rate: changed,
+                ..create_msg(id)
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        }
+
+        let accept = |id: &str, expected_exchange_rate| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let info = mock_info("fulfiller", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            accept("first", Some(shown)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::RateChanged { current: changed });
+        assert!(ESCROWS.load(&deps.storage, "first").unwrap().is_listed);
+
+        // the rate they see now
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            accept("first", Some(changed)),
+        )
+        .unwrap();
+        // or whatever it is, without an expectation
+        execute(deps.as_mut(), mock_env(), info, accept("second", None)).unwrap();
+        assert!(ESCROWS.load(&deps.storage, "second").unwrap().is_accepted);
+    }
+
+    /// Adds up every stored escrow's balance the slow way
+    fn scanned_total_locked(deps: Deps) -> TotalLockedResponse {
+        let mut total = GenericBalance::default();
+        for item in ESCROWS.range(deps.storage, None, None, Order::Ascending) {
+            let (_, escrow) = item.unwrap();
+            total = payments::merge(&total, &escrow.locked());
+        }
+        TotalLockedResponse {
+            native: total.native,
+            cw20: total
+                .cw20
+                .into_iter()
+                .map(|token| Cw20Coin {
+                    address: token.address.into(),
+                    amount: token.amount,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn total_locked_matches_a_full_scan() {
+        let mut deps = setup();
+        let cw20_create = |id: &str, amount: u128| {
+            let create = ReceiveMsg::CCreate(CreateMsg {
+                cw20_whitelist: Some(vec!["bolos".to_string()]),
+                ..create_msg(id)
+            });
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "creator".to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&create).unwrap(),
+            })
+        };
+        let steps: Vec<(MessageInfo, ExecuteMsg)> = vec![
+            (
+                mock_info("creator", &[coin(100, UST_DENOM), coin(7, "uluna")]),
+                ExecuteMsg::CCreate(create_msg("native")),
+            ),
+            (mock_info("cash", &[]), cw20_create("tokens", 40)),
+            (
+                mock_info("bolos", &[]),
+                ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: "creator".to_string(),
+                    amount: Uint128::new(15),
+                    msg: to_binary(&ReceiveMsg::TopUp {
+                        id: "tokens".to_string(),
+                    })
+                    .unwrap(),
+                }),
+            ),
+            (mock_info("bolos", &[]), cw20_create("other", 9)),
+            (
+                mock_info("creator", &[]),
+                ExecuteMsg::CCancel {
+                    id: "other".to_string(),
+                },
+            ),
+            (
+                mock_info("fulfiller", &[]),
+                ExecuteMsg::FAccept {
+                    id: "native".to_string(),
+                    expected_exchange_rate: None,
+                    required_creator_metrics: None,
+                    payout_memo: false,
+                },
+            ),
+            (
+                mock_info("fulfiller", &[]),
+                ExecuteMsg::FComplete {
+                    id: "native".to_string(),
+                    delivered_fiat_amount: None,
+                },
+            ),
+            (
+                mock_info("creator", &[]),
+                ExecuteMsg::CComplete {
+                    id: "native".to_string(),
+                    accept_rate_deviation: false,
+                    release_bps: Some(5000),
+                },
+            ),
+        ];
+        for (step, (info, msg)) in steps.into_iter().enumerate() {
+            let res = execute(deps.as_mut(), mock_env(), info, msg);
+            assert!(res.is_ok(), "step {}: {:?}", step, res);
+            assert_eq!(
+                query_total_locked(deps.as_ref()).unwrap(),
+                scanned_total_locked(deps.as_ref()),
+                "step {}",
+                step
+            );
+        }
+        // only the cw20 escrow and its top up are left
+        assert_eq!(
+            query_total_locked(deps.as_ref()).unwrap(),
+            TotalLockedResponse {
+                native: vec![],
+                cw20: vec![
+                    Cw20Coin {
+                        address: "bolos".to_string(),
+                        amount: Uint128::new(15),
+                    },
+                    Cw20Coin {
+                        address: "cash".to_string(),
+                        amount: Uint128::new(40),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn reconcile_locked_rebuilds_the_total() {
+        let mut deps = setup();
+        for id in ["first", "second"] {
+            let info = mock_info("creator", &coins(100, UST_DENOM));
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::ReconcileLocked {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let reconcile = |deps: DepsMut| {
+            let info = mock_info("admin", &[]);
+            execute(deps, mock_env(), info, ExecuteMsg::ReconcileLocked {}).unwrap()
+        };
+        let res = reconcile(deps.as_mut());
+        assert_eq!(res.attributes[1], attr("escrows", "2"));
+        assert_eq!(res.attributes[2], attr("drifted", "false"));
+
+        let drifted = GenericBalance {
+            native: coins(1, UST_DENOM),
+            cw20: vec![],
+        };
+        TOTAL_LOCKED.save(&mut deps.storage, &drifted).unwrap();
+        ACTIVE_ESCROWS.save(&mut deps.storage, &0).unwrap();
+        let res = reconcile(deps.as_mut());
+        assert_eq!(res.attributes[2], attr("drifted", "true"));
+        assert_eq!(ACTIVE_ESCROWS.load(&deps.storage).unwrap(), 2);
+        assert_eq!(
+            query_total_locked(deps.as_ref()).unwrap().native,
+            coins(200, UST_DENOM)
+        );
+        let log = query_admin_log(deps.as_ref(), None, Some(1)).unwrap();
+        assert_eq!(
+            log.entries[0].action,
+            AdminAction::ReconcileLocked { drifted: true }
+        );
+    }
+
+    #[test]
+    fn receive_rejects_malformed_hooks_without_touching_escrows() {
+        let mut deps = setup();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(CreateMsg {
+            cw20_whitelist: Some(vec!["cash".to_string()]),
+            ..create_msg("existing")
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let before = ESCROWS.load(&deps.storage, "existing").unwrap();
+
+        let hook = |amount: u128, msg: Binary| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "creator".to_string(),
+                amount: Uint128::new(amount),
+                msg,
+            })
+        };
+        let top_up = to_binary(&ReceiveMsg::TopUp {
+            id: "existing".to_string(),
+        })
+        .unwrap();
+        let create = to_binary(&ReceiveMsg::CCreate(create_msg("new"))).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("cash", &[]),
+            hook(0, top_up.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ZeroAmount {
+                denom: "cash".to_string()
+            }
+        );
+
+        let wrong_type = to_binary(&ExecuteMsg::FAccept {
+            id: "existing".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        })
+        .unwrap();
+        let malformed = vec![
+            Binary::default(),
+            Binary::from(b"\xff\xfe\x00garbage".to_vec()),
+            Binary::from(&top_up.as_slice()[..top_up.len() - 3]),
+            Binary::from(&create.as_slice()[..create.len() / 2]),
+            Binary::from(b"null".to_vec()),
+            Binary::from(b"{\"top_up\":{}}".to_vec()),
+            wrong_type,
+        ];
+        for msg in malformed {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("cash", &[]),
+                hook(50, msg.clone()),
+            )
+            .unwrap_err();
+            match err {
+                ContractError::InvalidReceiveMsg { token, reason } => {
+                    assert_eq!(token, "cash");
+                    assert!(!reason.is_empty());
+                }
+                err => panic!("{:?} for {:?}", err, msg),
+            }
+        }
+
+        assert_eq!(ESCROWS.load(&deps.storage, "existing").unwrap(), before);
+        assert_eq!(all_escrow_ids(&deps.storage).unwrap(), vec!["existing"]);
+    }
+
+    #[test]
+    fn creator_cancels_within_the_window_paying_a_penalty() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InstantiateMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: Some(600),
+            cancel_penalty_bps: Some(250),
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+            initial_arbiters: vec![],
+            seed_metrics: vec![],
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        for id in ["early", "late", "fulfilled"] {
+            let info = mock_info("creator", &coins(1000, UST_DENOM));
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let msg = ExecuteMsg::FAccept {
+                id: id.to_string(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("fulfiller", &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::FComplete {
+            id: "fulfilled".to_string(),
+            delivered_fiat_amount: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("fulfiller", &[]), msg).unwrap();
+        let cancel = |id: &str| ExecuteMsg::CCancel { id: id.to_string() };
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(599);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            cancel("early"),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("penalty_bps", "250"));
+        assert_eq!(res.attributes[3], attr("penalty_to", "fulfiller"));
+        assert_eq!(res.attributes[4].key, "fingerprint");
+        assert_eq!(
+            transfers_from_messages(&res.messages),
+            vec![
+                Transfer {
+                    recipient: "fulfiller".to_string(),
+                    denom: Denom::Native(UST_DENOM.to_string()),
+                    amount: Uint128::new(25),
+                },
+                Transfer {
+                    recipient: "creator".to_string(),
+                    denom: Denom::Native(UST_DENOM.to_string()),
+                    amount: Uint128::new(975),
+                },
+            ]
+        );
+        assert!(!ESCROWS.has(&deps.storage, "early"));
+
+        // too late, and too late once fulfilled, as before
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            cancel("fulfilled"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyFulfilled {});
+        env.block.time = env.block.time.plus_seconds(1);
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("creator", &[]),
+            cancel("late"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AlreadyAccepted {
+                id: "late".to_string(),
+                accepted_by: "fulfiller".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn history_records_the_whole_lifecycle() {
+        let mut deps = setup();
+        let mut env = mock_env();
+        let start = env.block.time.seconds();
+        let steps = vec![
+            (
+                mock_info("creator", &coins(100, UST_DENOM)),
+                ExecuteMsg::CCreate(create_msg("foobar")),
+            ),
+            (
+                mock_info("creator", &coins(50, UST_DENOM)),
+                ExecuteMsg::TopUp {
+                    id: "foobar".to_string(),
+                },
+            ),
+            (
+                mock_info("fulfiller", &[]),
+                ExecuteMsg::FAccept {
+                    id: "foobar".to_string(),
+                    expected_exchange_rate: None,
+                    required_creator_metrics: None,
+                    payout_memo: false,
+                },
+            ),
+            (
+                mock_info("fulfiller", &[]),
+                ExecuteMsg::FComplete {
+                    id: "foobar".to_string(),
+                    delivered_fiat_amount: None,
+                },
+            ),
+            (
+                mock_info("creator", &[]),
+                ExecuteMsg::CReqArbitration {
+                    id: "foobar".to_string(),
+                    reason_code: DisputeReason::NotReceived,
+                    details: None,
+                },
+            ),
+            (
+                mock_info("arbitrate", &[]),
+                ExecuteMsg::ElArbitrate {
+                    id: "foobar".to_string(),
+                    msg: ArbitrateMsg {
+                        receiver: "fulfiller".to_string(),
+                        creator_bps: None,
+                    },
+                },
+            ),
+        ];
+        for (info, msg) in steps {
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+            env.block.time = env.block.time.plus_seconds(60);
+        }
+
+        // still there after the escrow settled
+        assert!(!ESCROWS.has(&deps.storage, "foobar"));
+        let res: HistoryResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::History {
+                    id: "foobar".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let entries: Vec<_> = res
+            .entries
+            .into_iter()
+            .map(|e| (e.action, e.actor.to_string(), e.time - start))
+            .collect();
+        let expected = [
+            ("create", "creator"),
+            ("top_up", "creator"),
+            ("accept", "fulfiller"),
+            ("fulfill", "fulfiller"),
+            ("request_arbitration", "creator"),
+            ("arbitrate", "arbitrate"),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .enumerate()
+            .map(|(i, (action, actor))| (action.to_string(), actor.to_string(), i as u64 * 60))
+            .collect();
+        assert_eq!(entries, expected);
+
+        // the id stays taken, so the history stays whole
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(create_msg("foobar"));
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyInUse {});
+        assert_eq!(HISTORY.load(&deps.storage, "foobar").unwrap().len(), 6);
+    }
+
+    /// How far along an escrow is when a handler is tried on it
+    #[derive(Clone, Copy, PartialEq, PartialOrd)]
+    enum Stage {
+        Listed,
+        Accepted,
+        Fulfilled,
+        InArbitration,
+    }
+
+    /// A fresh contract holding "foobar" at the stage given
+    fn escrow_at(stage: Stage) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = setup();
+        let id = || "foobar".to_string();
+        let mut steps = vec![(
+            Stage::Listed,
+            mock_info("creator", &coins(100, UST_DENOM)),
+            ExecuteMsg::CCreate(create_msg("foobar")),
+        )];
+        steps.push((
+            Stage::Accepted,
+            mock_info("fulfiller", &[]),
+            ExecuteMsg::FAccept {
+                id: id(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            },
+        ));
+        steps.push((
+            Stage::Fulfilled,
+            mock_info("fulfiller", &[]),
+            ExecuteMsg::FComplete {
+                id: id(),
+                delivered_fiat_amount: None,
+            },
+        ));
+        steps.push((
+            Stage::InArbitration,
+            mock_info("creator", &[]),
+            ExecuteMsg::CReqArbitration {
+                id: id(),
+                reason_code: DisputeReason::NotReceived,
+                details: None,
+            },
+        ));
+        for (reached, info, msg) in steps {
+            if reached <= stage {
+                execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            }
+        }
+        deps
+    }
+
+    #[test]
+    fn every_handler_authorizes_the_right_roles() {
+        let id = || "foobar".to_string();
+        let feedback = || FeedbackMsg {
+            comment: String::new(),
+            satisfied: true,
+        };
+        let roles = ["creator", "fulfiller", "arbitrate", "stranger"];
+        let anyone: &[&str] = &roles;
+        let matrix: Vec<(Stage, ExecuteMsg, &[&str])> = vec![
+            (
+                Stage::Listed,
+                ExecuteMsg::CCancel { id: id() },
+                &["creator"],
+            ),
+            (
+                Stage::Listed,
+                ExecuteMsg::CChange(create_msg("foobar")),
+                &["creator"],
+            ),
+            (Stage::Listed, ExecuteMsg::Bump { id: id() }, &["creator"]),
+            (
+                Stage::Listed,
+                ExecuteMsg::CloneEscrow { source_id: id() },
+                &["creator"],
+            ),
+            (
+                Stage::Listed,
+                ExecuteMsg::FAccept {
+                    id: id(),
+                    expected_exchange_rate: None,
+                    required_creator_metrics: None,
+                    payout_memo: false,
+                },
+                &["fulfiller", "arbitrate", "stranger"],
+            ),
+            (Stage::Listed, ExecuteMsg::TopUp { id: id() }, &["creator"]),
+            (
+                Stage::Listed,
+                ExecuteMsg::CancelAll {
+                    start_after: None,
+                    limit: None,
+                    only_expired: false,
+                },
+                anyone,
+            ),
+            (
+                Stage::Listed,
+                ExecuteMsg::CFeedback {
+                    id: id(),
+                    msg: feedback(),
+                },
+                &["creator"],
+            ),
+            (
+                Stage::Accepted,
+                ExecuteMsg::FUnaccept { id: id() },
+                &["fulfiller"],
+            ),
+            (
+                Stage::Accepted,
+                ExecuteMsg::CReject {
+                    id: id(),
+                    reason: None,
+                },
+                &["creator"],
+            ),
+            (
+                Stage::Accepted,
+                ExecuteMsg::FComplete {
+                    id: id(),
+                    delivered_fiat_amount: None,
+                },
+                &["fulfiller"],
+            ),
+            (
+                Stage::Accepted,
+                ExecuteMsg::FFeedback {
+                    id: id(),
+                    msg: feedback(),
+                },
+                &["fulfiller"],
+            ),
+            (
+                Stage::Accepted,
+                ExecuteMsg::RefundExpired { id: id() },
+                anyone,
+            ),
+            (
+                Stage::Fulfilled,
+                ExecuteMsg::CComplete {
+                    id: id(),
+                    accept_rate_deviation: false,
+                    release_bps: None,
+                },
+                &["creator"],
+            ),
+            (
+                Stage::Fulfilled,
+                ExecuteMsg::CReqArbitration {
+                    id: id(),
+                    reason_code: DisputeReason::NotReceived,
+                    details: None,
+                },
+                &["creator"],
+            ),
+            (
+                Stage::Fulfilled,
+                ExecuteMsg::ClaimTimedOut { id: id() },
+                &["fulfiller"],
+            ),
+            (
+                Stage::InArbitration,
+                ExecuteMsg::ElArbitrate {
+                    id: id(),
+                    msg: ArbitrateMsg {
+                        receiver: "creator".to_string(),
+                        creator_bps: None,
+                    },
+                },
+                &["arbitrate"],
+            ),
+            (
+                Stage::InArbitration,
+                ExecuteMsg::ResolveExpiredArbitration { id: id() },
+                anyone,
+            ),
+        ];
+
+        for (stage, msg, allowed) in matrix {
+            for role in roles {
+                let mut deps = escrow_at(stage);
+                let info = mock_info(role, &coins(10, UST_DENOM));
+                let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+                let unauthorized = res == Err(ContractError::Unauthorized {});
+                assert_eq!(
+                    unauthorized,
+                    !allowed.contains(&role),
+                    "{:?} by {}: {:?}",
+                    msg,
+                    role,
+                    res
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_denoms_are_merged_before_any_check() {
+        let mut deps = setup();
+        let funds = vec![coin(40, UST_DENOM), coin(5, "stake"), coin(60, UST_DENOM)];
+        let msg = ExecuteMsg::CCreate(create_msg("foobar"));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &funds), msg).unwrap();
+        let escrow = ESCROWS.load(&deps.storage, "foobar").unwrap();
+        assert_eq!(
+            escrow.balance.native,
+            vec![coin(5, "stake"), coin(100, UST_DENOM)]
+        );
+
+        let funds = vec![coin(10, UST_DENOM), coin(0, UST_DENOM), coin(15, UST_DENOM)];
+        let msg = ExecuteMsg::TopUp {
+            id: "foobar".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &funds), msg).unwrap();
+        let escrow = ESCROWS.load(&deps.storage, "foobar").unwrap();
+        assert_eq!(
+            escrow.balance.native,
+            vec![coin(5, "stake"), coin(125, UST_DENOM)]
+        );
+
+        // a zero only passes as part of a real amount, however often it is repeated
+        let funds = vec![coin(100, UST_DENOM), coin(0, "phantom"), coin(0, "phantom")];
+        let msg = ExecuteMsg::CCreate(create_msg("other"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &funds),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ZeroAmount {
+                denom: "phantom".to_string()
+            }
+        );
+        let funds = vec![coin(0, UST_DENOM), coin(100, UST_DENOM)];
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &funds), msg).unwrap();
+        let escrow = ESCROWS.load(&deps.storage, "other").unwrap();
+        assert_eq!(escrow.balance.native, coins(100, UST_DENOM));
+    }
+
+    #[test]
+    fn split_native_payouts_sends_one_bank_message_per_denom() {
+        let funds = [coin(100, UST_DENOM), coin(5, "stake")];
+        for split in [false, true] {
+            let mut deps = setup();
+            let env = mock_env();
+            if split {
+                let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                    admin: None,
+                    seconds_per_block: None,
+                    max_whitelist_len: None,
+                    bump_cooldown_seconds: None,
+                    allow_mixed_funding: None,
+                    post_accept_cancel_window_seconds: None,
+                    cancel_penalty_bps: None,
+                    split_native_payouts: Some(true),
+                    listing_deposit: None,
+                    fee_collector: None,
+                    require_arbiter_ack: None,
+                    arbiter_ack_window_seconds: None,
+                    display_denoms: None,
+                    listing_grace_period_seconds: None,
+                    arbitration_extension_seconds: None,
+                    max_arbitration_extensions: None,
+                    kyc_contract: None,
+                    allow_third_party_topup: None,
+                    max_active_escrows: None,
+                    daily_volume_limit_ust: None,
+                    daily_accept_limit_ust: None,
+                    dispute_window_seconds: None,
+                    creator_ack_timeout_seconds: None,
+                    max_open_to_all_seconds: None,
+                    cancel_cooloff_seconds: None,
+                    max_escrow_bytes: None,
+                    mid_lifecycle_expiry: None,
+                    risk_weights: None,
+                    mediation_window_seconds: None,
+                    min_remaining_on_accept_seconds: None,
+                    peer_contracts: None,
+                    max_actions_per_address: None,
+                });
+                execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+            }
+            let per_recipient = if split { 2 } else { 1 };
+
+            // completion
+            fulfilled_escrow(deps.as_mut(), env.clone(), "done", &funds);
+            let msg = ExecuteMsg::CComplete {
+                id: "done".to_string(),
+                accept_rate_deviation: false,
+                release_bps: None,
+            };
+            let res = execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+            assert_eq!(res.messages.len(), per_recipient);
+
+            // refund
+            let msg = ExecuteMsg::CCreate(create_msg("listed"));
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("creator", &funds),
+                msg,
+            )
+            .unwrap();
+            let msg = ExecuteMsg::CCancel {
+                id: "listed".to_string(),
+            };
+            let res = execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+            assert_eq!(res.messages.len(), per_recipient);
+
+            // arbitration, split between both parties
+            fulfilled_escrow(deps.as_mut(), env.clone(), "disputed", &funds);
+            let msg = ExecuteMsg::CReqArbitration {
+                id: "disputed".to_string(),
+                reason_code: DisputeReason::NotReceived,
+                details: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+            let ruling = ArbitrateMsg {
+                receiver: "creator".to_string(),
+                creator_bps: Some(5000),
+            };
+            let msg = ExecuteMsg::ElArbitrate {
+                id: "disputed".to_string(),
+                msg: ruling,
+            };
+            let res = execute(deps.as_mut(), env, mock_info("arbitrate", &[]), msg).unwrap();
+            assert_eq!(res.messages.len(), 2 * per_recipient);
+            assert!(transfers_from_messages(&res.messages)
+                .iter()
+                .all(|t| !t.amount.is_zero()));
+        }
+    }
+
+    #[test]
+    fn only_allowed_fulfillers_may_accept() {
+        let mut deps = setup();
+        let env = mock_env();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let private = |allowed: Vec<&str>| CreateMsg {
+            required_trust_metrics: TrustRequirement::Metrics(TrustMetrics {
+                total_completed: 10,
+                ..TrustMetrics::default()
+            }),
+            allowed_fulfillers: Some(allowed.into_iter().map(String::from).collect()),
+            ..create_msg("foobar")
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::CCreate(private(vec![])),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::EmptyFulfillerAllowlist {});
+        let many: Vec<String> = (0..=MAX_ALLOWED_FULFILLERS)
+            .map(|n| format!("agent{}", n))
+            .collect();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::CCreate(private(many.iter().map(String::as_str).collect())),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::FulfillerAllowlistTooLarge {
+                max: MAX_ALLOWED_FULFILLERS
+            }
+        );
+
+        let msg = ExecuteMsg::CCreate(private(vec!["agent", "banker", "agent"]));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details.allowed_fulfillers,
+            Some(vec!["agent".to_string(), "banker".to_string()])
+        );
+
+        // strangers are turned away before their trust metrics are looked at
+        let accept = || ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("stranger", &[]),
+            accept(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotOnFulfillerAllowlist {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("agent", &[]),
+            accept(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TrustMetricsInsufficient {});
+
+        // the creator can open it up while it's unaccepted
+        let change = CreateMsg {
+            allowed_fulfillers: None,
+            ..create_msg("foobar")
+        };
+        let creator = mock_info("creator", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            ExecuteMsg::CChange(change.clone()),
+        )
+        .unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(details.allowed_fulfillers, None);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("stranger", &[]),
+            accept(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), env, creator, ExecuteMsg::CChange(change)).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyAccepted { .. }));
+    }
+
+    #[test]
+    fn listing_deposit_is_refunded_unless_the_listing_expires() {
+        let mut deps = setup();
+        let env = mock_env();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: Some(coins(10, UST_DENOM)),
+            fee_collector: Some("collector".to_string()),
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let funds = coins(110, UST_DENOM);
+        let creator = mock_info("creator", &[]);
+        let paid = |res: &Response, to: &str| -> u128 {
+            transfers_from_messages(&res.messages)
+                .iter()
+                .filter(|t| t.recipient == to)
+                .map(|t| t.amount.u128())
+                .sum()
+        };
+        let create = |deps: DepsMut, id: &str, end_time: Option<u64>| {
+            let msg = ExecuteMsg::CCreate(CreateMsg {
+                end_time,
+                ..create_msg(id)
+            });
+            execute(deps, mock_env(), mock_info("creator", &funds), msg).unwrap();
+        };
+        let cancel = |id: &str| ExecuteMsg::CCancel { id: id.to_string() };
+
+        // the deposit comes on top of the escrowed funds
+        let msg = ExecuteMsg::CCreate(create_msg("short"));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &coins(5, UST_DENOM)),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ListingDepositRequired {
+                deposit: "10uusd".to_string()
+            }
+        );
+        create(deps.as_mut(), "listed", None);
+        let details = query_details(deps.as_ref(), env.clone(), "listed".to_string()).unwrap();
+        assert_eq!(details.native_balance, coins(100, UST_DENOM));
+        assert_eq!(details.native_balance_display, vec!["0.000100 UST"]);
+        assert_eq!(details.listing_deposit, coins(10, UST_DENOM));
+        assert_eq!(details.listing_deposit_display, vec!["0.000010 UST"]);
+        assert_eq!(
+            query_total_locked(deps.as_ref()).unwrap().native,
+            coins(110, UST_DENOM)
+        );
+
+        // cancelled in time, the creator gets it all back
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            cancel("listed"),
+        )
+        .unwrap();
+        assert_eq!(paid(&res, "creator"), 110);
+        assert_eq!(res.attributes[2], attr("deposit_to", "creator"));
+
+        // left to expire, the deposit is forfeited
+        let end_time = env.block.time.seconds() + 100;
+        create(deps.as_mut(), "stale", Some(end_time));
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(101);
+        let res = execute(
+            deps.as_mut(),
+            later.clone(),
+            creator.clone(),
+            cancel("stale"),
+        )
+        .unwrap();
+        assert_eq!(paid(&res, "creator"), 100);
+        assert_eq!(paid(&res, "collector"), 10);
+
+        // cancel_all sorts them out the same way
+        create(deps.as_mut(), "fresh", None);
+        create(deps.as_mut(), "stale-2", Some(end_time));
+        let msg = ExecuteMsg::CancelAll {
+            start_after: None,
+            limit: None,
+            only_expired: false,
+        };
+        let res = execute(deps.as_mut(), later, creator.clone(), msg).unwrap();
+        assert_eq!(paid(&res, "creator"), 210);
+        assert_eq!(paid(&res, "collector"), 10);
+
+        // completed, the creator gets the deposit back
+        fulfilled_escrow(deps.as_mut(), env.clone(), "done", &funds);
+        let msg = ExecuteMsg::CComplete {
+            id: "done".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+        assert_eq!(paid(&res, "fulfiller"), 100);
+        assert_eq!(paid(&res, "creator"), 10);
+
+        // and when arbitrated, whoever wins
+        fulfilled_escrow(deps.as_mut(), env.clone(), "disputed", &funds);
+        let msg = ExecuteMsg::CReqArbitration {
+            id: "disputed".to_string(),
+            reason_code: DisputeReason::NotReceived,
+            details: None,
+        };
+        execute(deps.as_mut(), env.clone(), creator, msg).unwrap();
+        let ruling = ArbitrateMsg {
+            receiver: "fulfiller".to_string(),
+            creator_bps: None,
+        };
+        let msg = ExecuteMsg::ElArbitrate {
+            id: "disputed".to_string(),
+            msg: ruling,
+        };
+        let res = execute(deps.as_mut(), env, mock_info("arbitrate", &[]), msg).unwrap();
+        assert_eq!(paid(&res, "fulfiller"), 100);
+        assert_eq!(paid(&res, "creator"), 10);
+
+        assert!(query_total_locked(deps.as_ref()).unwrap().native.is_empty());
+        assert_eq!(
+            query_total_locked(deps.as_ref()).unwrap(),
+            scanned_total_locked(deps.as_ref())
+        );
+    }
+
+    #[test]
+    fn arbiter_takes_the_role_before_the_escrow_is_listed() {
+        let mut deps = setup();
+        let env = mock_env();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: Some(true),
+            arbiter_ack_window_seconds: Some(600),
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        for id in ["foobar", "ignored"] {
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert!(details.pending_arbiter_ack);
+        assert_eq!(
+            details.arbiter_ack_deadline,
+            Some(env.block.time.seconds() + 600)
+        );
+        assert!(query_listings(
+            deps.as_ref(),
+            mock_env(),
+            None,
+            None,
+            None,
+            false,
+            false,
+            None
+        )
+        .unwrap()
+        .listings
+        .is_empty());
+        let accept = || ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("fulfiller", &[]),
+            accept(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PendingArbiterAck {});
+
+        // only the named arbiter may take the role
+        let ack = |id: &str| ExecuteMsg::AckArbiterRole { id: id.to_string() };
+        for sender in ["stranger", "creator"] {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(sender, &[]),
+                ack("foobar"),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+        let arbiter = mock_info("arbitrate", &[]);
+        let res = execute(deps.as_mut(), env.clone(), arbiter.clone(), ack("foobar")).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "ack_arbiter_role"));
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert!(!details.pending_arbiter_ack);
+        assert_eq!(details.arbiter_ack_deadline, None);
+        let listings = query_listings(
+            deps.as_ref(),
+            mock_env(),
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+        )
+        .unwrap()
+        .listings;
+        assert_eq!(listings.len(), 1);
+        let err = execute(deps.as_mut(), env.clone(), arbiter.clone(), ack("foobar")).unwrap_err();
+        assert_eq!(err, ContractError::NotPendingArbiterAck {});
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("fulfiller", &[]),
+            accept(),
+        )
+        .unwrap();
+
+        // once the window passes, the arbiter is too late and the creator takes the funds back
+        let mut later = env;
+        later.block.time = later.block.time.plus_seconds(600);
+        let err = execute(deps.as_mut(), later.clone(), arbiter, ack("ignored")).unwrap_err();
+        assert_eq!(err, ContractError::ArbiterAckExpired {});
+        let msg = ExecuteMsg::CCancel {
+            id: "ignored".to_string(),
+        };
+        let res = execute(deps.as_mut(), later, mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(100, UST_DENOM),
+            })]
+        );
+    }
+
+    #[test]
+    fn create_batch_carves_the_funds_sent() {
+        let mut deps = setup();
+        let env = mock_env();
+        let item = |id: &str, amount: Vec<cosmwasm_std::Coin>| BatchCreateItem {
+            msg: create_msg(id),
+            amount,
+        };
+        let batch = |items: Vec<BatchCreateItem>| ExecuteMsg::CCreateBatch { items };
+        let funds = vec![coin(300, UST_DENOM), coin(5, "stake")];
+        let info = mock_info("creator", &funds);
+        let items = || {
+            vec![
+                item("first", coins(100, UST_DENOM)),
+                item("second", vec![coin(150, UST_DENOM), coin(5, "stake")]),
+                item("third", coins(50, UST_DENOM)),
+            ]
+        };
+
+        // the amounts have to add up to the funds exactly
+        for funds in [coins(299, UST_DENOM), coins(400, UST_DENOM)] {
+            let info = mock_info("creator", &[funds, coins(5, "stake")].concat());
+            let err = execute(deps.as_mut(), env.clone(), info, batch(items())).unwrap_err();
+            assert_eq!(err, ContractError::BatchFundsMismatch {});
+        }
+        let too_many = (0..=MAX_BATCH_ITEMS)
+            .map(|n| item(&format!("item{}", n), coins(1, UST_DENOM)))
+            .collect();
+        for items in [vec![], too_many] {
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), batch(items)).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidBatchSize {
+                    max: MAX_BATCH_ITEMS
+                }
+            );
+        }
+
+        // any item failing fails the batch, naming the item
+        let mut broken = items();
+        broken[1].msg.arbiter = None;
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), batch(broken)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::BatchItem {
+                index: 1,
+                error: Box::new(ContractError::MissingArbiter {}),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Batch item 1: An arbiter is required unless arbitration is disabled"
+        );
+
+        let mut deps = setup();
+        let res = execute(deps.as_mut(), env.clone(), info, batch(items())).unwrap();
+        let created: BatchCreateResponse = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(created.ids, vec!["first", "second", "third"]);
+        assert_eq!(res.attributes.len(), 1 + 2 * 3);
+        assert_eq!(res.attributes[1], attr("escrow_id", "first"));
+        let second = query_details(deps.as_ref(), env, "second".to_string()).unwrap();
+        assert_eq!(
+            second.native_balance,
+            vec![coin(5, "stake"), coin(150, UST_DENOM)]
+        );
+        assert_eq!(
+            query_total_locked(deps.as_ref()).unwrap().native,
+            vec![coin(5, "stake"), coin(300, UST_DENOM)]
+        );
+    }
+
+    #[test]
+    fn arbiter_and_parties_stay_apart() {
+        let mut deps = escrow_at(Stage::Listed);
+        let msg = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("arbitrate", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::ArbiterCannotFulfill {});
+
+        // escrows from before the check may name a party as arbiter
+        for party in ["creator", "fulfiller"] {
+            let mut deps = escrow_at(Stage::InArbitration);
+            let mut escrow = ESCROWS.load(&deps.storage, "foobar").unwrap();
+            escrow.arbiter = Some(Addr::unchecked(party));
+            ESCROWS.save(&mut deps.storage, "foobar", &escrow).unwrap();
+
+            let ruling = ArbitrateMsg {
+                receiver: party.to_string(),
+                creator_bps: None,
+            };
+            let msg = ExecuteMsg::ElArbitrate {
+                id: "foobar".to_string(),
+                msg: ruling,
+            };
+            let err = execute(deps.as_mut(), mock_env(), mock_info(party, &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::ArbiterIsParty {});
+
+            // they resolve by default instead
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(ARBITRATION_TIMEOUT_SECONDS + 1);
+            let msg = ExecuteMsg::ResolveExpiredArbitration {
+                id: "foobar".to_string(),
+            };
+            let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+            assert_eq!(res.attributes[2], attr("to", "creator"));
+        }
+    }
+
+    #[test]
+    fn trusted_fulfillers_are_paid_on_fulfilling() {
+        let mut deps = setup();
+        let env = mock_env();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: Some(coins(10, UST_DENOM)),
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let veteran = TrustMetrics {
+            total_completed: 50,
+            ..TrustMetrics::default()
+        };
+        let msg = ExecuteMsg::SeedTrustMetrics {
+            metrics: vec![("veteran".to_string(), veteran)],
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+        for (id, fulfiller) in [("trusted", "veteran"), ("untrusted", "newcomer")] {
+            let msg = ExecuteMsg::CCreate(CreateMsg {
+                auto_complete_trust: Some(TrustMetrics {
+                    total_completed: 20,
+                    ..TrustMetrics::default()
+                }),
+                ..create_msg(id)
+            });
+            let info = mock_info("creator", &coins(110, UST_DENOM));
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+            let msg = ExecuteMsg::FAccept {
+                id: id.to_string(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(fulfiller, &[]), msg).unwrap();
+        }
+        let fulfill = |id: &str| ExecuteMsg::FComplete {
+            id: id.to_string(),
+            delivered_fiat_amount: None,
+        };
+
+        // the veteran meets the bar and is paid right away, the deposit goes back
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("veteran", &[]),
+            fulfill("trusted"),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("auto_completed", "true"));
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "veteran".to_string(),
+                    amount: coins(100, UST_DENOM),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "creator".to_string(),
+                    amount: coins(10, UST_DENOM),
+                }),
+            ]
+        );
+        assert!(!ESCROWS.has(&deps.storage, "trusted"));
+        let history = HISTORY.load(&deps.storage, "trusted").unwrap();
+        let actions: Vec<_> = history.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["create", "accept", "fulfill", "auto_complete"]);
+
+        // the newcomer waits for the creator as usual
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("newcomer", &[]),
+            fulfill("untrusted"),
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert!(!res.attributes.iter().any(|a| a.key == "auto_completed"));
+        assert!(
+            ESCROWS
+                .load(&deps.storage, "untrusted")
+                .unwrap()
+                .is_fulfilled
+        );
+    }
+
+    #[test]
+    fn payout_details_stay_out_of_details() {
+        let mut deps = setup();
+        let env = mock_env();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create_msg("foobar")),
+        )
+        .unwrap();
+        let ciphertext = Binary::from(b"sealed bank account".to_vec());
+        let set = |ciphertext: Binary| ExecuteMsg::SetPayoutDetails {
+            id: "foobar".to_string(),
+            ciphertext,
+        };
+        let payout_details = |deps: Deps| -> PayoutDetailsResponse {
+            let id = "foobar".to_string();
+            from_binary(&query(deps, mock_env(), QueryMsg::PayoutDetails { id }).unwrap()).unwrap()
+        };
+
+        // only the creator sets them, and only so much
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("fulfiller", &[]),
+            set(ciphertext.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            set(Binary::from(vec![0; MAX_PAYOUT_DETAILS_BYTES + 1])),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::PayoutDetailsTooLarge {
+                max: MAX_PAYOUT_DETAILS_BYTES
+            }
+        );
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            set(ciphertext.clone()),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "set_payout_details"));
+
+        // Details only says there are some, the dedicated query returns them
+        let raw = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Details {
+                id: "foobar".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(!String::from_utf8(raw.to_vec())
+            .unwrap()
+            .contains(&ciphertext.to_base64()));
+        let details: DetailsResponse = from_binary(&raw).unwrap();
+        assert!(details.has_payout_details);
+        assert_eq!(payout_details(deps.as_ref()).ciphertext, Some(ciphertext));
+
+        // gone once the escrow settles
+        let info = mock_info("fulfiller", &[]);
+        let msg = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::FComplete {
+            id: "foobar".to_string(),
+            delivered_fiat_amount: None,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = ExecuteMsg::CComplete {
+            id: "foobar".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+        assert!(!PAYOUT_DETAILS.has(&deps.storage, "foobar"));
+    }
+
+    #[test]
+    fn eligible_listings_splits_each_page() {
+        let mut deps = setup();
+        let env = mock_env();
+        let now = env.block.time.seconds();
+        let listings = [
+            ("open", "creator", create_msg("open")),
+            ("own", "fulfiller", create_msg("own")),
+            (
+                "private",
+                "creator",
+                CreateMsg {
+                    allowed_fulfillers: Some(vec!["friend".to_string()]),
+                    ..create_msg("private")
+                },
+            ),
+            (
+                "picky",
+                "creator",
+                CreateMsg {
+                    required_trust_metrics: TrustRequirement::Metrics(TrustMetrics {
+                        total_completed: 5,
+                        ..TrustMetrics::default()
+                    }),
+                    ..create_msg("picky")
+                },
+            ),
+            (
+                "late",
+                "creator",
+                CreateMsg {
+                    end_time: Some(now + 100),
+                    ..create_msg("late")
+                },
+            ),
+        ];
+        for (_, creator, msg) in listings {
+            let info = mock_info(creator, &coins(100, UST_DENOM));
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CCreate(msg)).unwrap();
+        }
+
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(200);
+        let eligible = |start_after: Option<&str>| -> EligibleListingsResponse {
+            let msg = QueryMsg::EligibleListings {
+                address: "fulfiller".to_string(),
+                start_after: start_after.map(String::from),
+                limit: Some(3),
+                include_expired: true,
+                include_upcoming: false,
+            };
+            from_binary(&query(deps.as_ref(), later.clone(), msg).unwrap()).unwrap()
+        };
+        let reason = |id: &str, err: ContractError| NearMiss {
+            id: id.to_string(),
+            reason: err.to_string(),
+        };
+
+        // same sort key everywhere, so the page is ordered by id, highest first
+        let first = eligible(None);
+        assert!(first.listings.is_empty());
+        assert_eq!(
+            first.near_misses,
+            vec![
+                reason("private", ContractError::NotOnFulfillerAllowlist {}),
+                reason("picky", ContractError::TrustMetricsInsufficient {}),
+                reason("own", ContractError::Unauthorized {}),
+            ]
+        );
+        assert_eq!(first.next_start_after, Some("own".to_string()));
+
+        let second = eligible(first.next_start_after.as_deref());
+        let ids: Vec<_> = second.listings.iter().map(|l| l.id.as_str()).collect();
+        assert_eq!(ids, ["open"]);
+        assert_eq!(
+            second.near_misses,
+            vec![reason("late", ContractError::Expired {})]
+        );
+        assert_eq!(second.next_start_after, None);
+
+        // the check is the one accepting goes through
+        let msg = ExecuteMsg::FAccept {
+            id: "late".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let err = execute(deps.as_mut(), later, mock_info("fulfiller", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+    }
+
+    #[test]
+    fn the_contract_cannot_call_itself() {
+        let mut deps = setup();
+        let env = mock_env();
+        let own = env.contract.address.to_string();
+        let info = mock_info(&own, &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(create_msg("foobar"));
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::SelfCall {});
+
+        // nor through a token claiming the contract sent it
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: own,
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::CCreate(create_msg("foobar"))).unwrap(),
+        });
+        let err = execute(deps.as_mut(), env, mock_info("token", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::SelfCall {});
+    }
+
+    #[test]
+    fn new_listings_wait_out_the_grace_period() {
+        let accept = |id: &str| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let deadlines = |deps: Deps, id: &str| -> DeadlinesResponse {
+            let msg = QueryMsg::Deadlines { id: id.to_string() };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // without one, a listing may be accepted the second it's created
+        let mut deps = setup();
+        let env = mock_env();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(create_msg("foobar"));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(deadlines(deps.as_ref(), "foobar").grace_period_end, None);
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info("fulfiller", &[]),
+            accept("foobar"),
+        )
+        .unwrap();
+
+        let mut deps = setup();
+        let env = mock_env();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: Some(60),
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        for id in ["foobar", "oops"] {
+            let info = mock_info("creator", &coins(100, UST_DENOM));
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+        let until = env.block.time.seconds() + 60;
+        assert_eq!(
+            deadlines(deps.as_ref(), "foobar").grace_period_end,
+            Some(until)
+        );
+
+        // the last second of the grace period, only the creator may act
+        let mut last = mock_env();
+        last.block.time = Timestamp::from_seconds(until - 1);
+        let err = execute(
+            deps.as_mut(),
+            last.clone(),
+            mock_info("fulfiller", &[]),
+            accept("foobar"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ListingInGracePeriod { until });
+        let change = CreateMsg {
+            exchange_rate: Decimal::percent(3700),
+            ..create_msg("foobar")
+        };
+        let info = mock_info("creator", &[]);
+        execute(
+            deps.as_mut(),
+            last.clone(),
+            info.clone(),
+            ExecuteMsg::CChange(change),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::CCancel {
+            id: "oops".to_string(),
+        };
+        execute(deps.as_mut(), last, info, msg).unwrap();
+
+        // once it's over, the changed listing is up for grabs
+        let mut over = mock_env();
+        over.block.time = Timestamp::from_seconds(until);
+        execute(
+            deps.as_mut(),
+            over,
+            mock_info("fulfiller", &[]),
+            accept("foobar"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn listings_tell_a_viewer_what_keeps_them_from_accepting() {
+        let mut deps = setup();
+        let env = mock_env();
+        let friend = TrustMetrics {
+            total_completed: 5,
+            ..TrustMetrics::default()
+        };
+        let msg = ExecuteMsg::SeedTrustMetrics {
+            metrics: vec![("friend".to_string(), friend)],
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let msg = ExecuteMsg::CCreate(CreateMsg {
+            allowed_fulfillers: Some(vec!["friend".to_string()]),
+            required_trust_metrics: TrustRequirement::Metrics(TrustMetrics {
+                total_completed: 3,
+                ..TrustMetrics::default()
+            }),
+            ..create_msg("foobar")
+        });
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let listing = |viewer: Option<&str>| {
+            let viewer = viewer.map(String::from);
+            let listings = query_listings(
+                deps.as_ref(),
+                env.clone(),
+                None,
+                None,
+                viewer,
+                false,
+                false,
+                None,
+            );
+            listings.unwrap().listings.remove(0)
+        };
+        let friend = listing(Some("friend"));
+        assert_eq!(friend.acceptable, Some(true));
+        assert_eq!(friend.blockers, Some(vec![]));
+        let stranger = listing(Some("stranger"));
+        assert_eq!(stranger.acceptable, Some(false));
+        assert_eq!(
+            stranger.blockers,
+            Some(vec![
+                "not_on_allowlist".to_string(),
+                "trust_metrics".to_string()
+            ])
+        );
+
+        // without a viewer the fields aren't there at all
+        let raw = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Listings {
+                start_after: None,
+                limit: None,
+                viewer: None,
+                include_expired: false,
+                include_upcoming: false,
+                order: None,
+            },
+        )
+        .unwrap();
+        let json = String::from_utf8(raw.to_vec()).unwrap();
+        assert!(!json.contains("acceptable") && !json.contains("blockers"));
+        assert_eq!(listing(None).blockers, None);
+    }
+
+    #[test]
+    fn escrows_hold_a_bounded_number_of_cw20_tokens() {
+        let mut deps = setup();
+        let env = mock_env();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: Some(MAX_CW20_BALANCES as u32 + 1),
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let tokens: Vec<_> = (0..=MAX_CW20_BALANCES)
+            .map(|i| format!("token{:02}", i))
+            .collect();
+        let create = CreateMsg {
+            cw20_whitelist: Some(tokens.clone()),
+            ..create_msg("foobar")
+        };
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap();
+
+        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "creator".to_string(),
+            amount: Uint128::new(10),
+            msg: to_binary(&ReceiveMsg::TopUp {
+                id: "foobar".to_string(),
+            })
+            .unwrap(),
+        });
+        for token in &tokens[..MAX_CW20_BALANCES] {
+            let info = mock_info(token, &[]);
+            execute(deps.as_mut(), env.clone(), info, top_up.clone()).unwrap();
+        }
+        // more of a token already held is fine, one more token isn't
+        let info = mock_info(&tokens[0], &[]);
+        execute(deps.as_mut(), env.clone(), info, top_up.clone()).unwrap();
+        let info = mock_info(&tokens[MAX_CW20_BALANCES], &[]);
+        let err = execute(deps.as_mut(), env, info, top_up).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TooManyCw20Balances {
+                max: MAX_CW20_BALANCES
+            }
+        );
+    }
+
+    #[test]
+    fn arbiter_requests_for_information_extend_the_deadline() {
+        let mut deps = escrow_at(Stage::InArbitration);
+        let env = mock_env();
+        let started = env.block.time.seconds();
+        let request = |text: &str| ExecuteMsg::ArbiterRequestInfo {
+            id: "foobar".to_string(),
+            request: text.to_string(),
+        };
+        let deadlines = |deps: Deps| -> DeadlinesResponse {
+            let msg = QueryMsg::Deadlines {
+                id: "foobar".to_string(),
+            };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            request("anything"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info("arbitrate", &[]),
+            request("Bank statement for the 12th, please"),
+        )
+        .unwrap();
+        let extended =
+            started + ARBITRATION_TIMEOUT_SECONDS + DEFAULT_ARBITRATION_EXTENSION_SECONDS;
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "request_info"),
+                attr("escrow_id", "foobar"),
+                attr("creator", "creator"),
+                attr("fulfiller", "fulfiller"),
+                attr("request", "Bank statement for the 12th, please"),
+                attr("arbitration_deadline", extended.to_string()),
+                attr("fingerprint", res.attributes[6].value.clone()),
+            ]
+        );
+        let response = deadlines(deps.as_ref());
+        assert_eq!(response.arbitration_deadline, Some(extended));
+        assert_eq!(response.arbitration_extensions, 1);
+        let details = query_details(deps.as_ref(), mock_env(), "foobar".to_string()).unwrap();
+        assert_eq!(
+            details.dispute.unwrap().info_requests,
+            vec!["Bank statement for the 12th, please".to_string()]
+        );
+
+        // past the original deadline the dispute isn't resolved by default yet
+        let mut later = mock_env();
+        later.block.time = Timestamp::from_seconds(started + ARBITRATION_TIMEOUT_SECONDS + 1);
+        let msg = ExecuteMsg::ResolveExpiredArbitration {
+            id: "foobar".to_string(),
+        };
+        let err = execute(deps.as_mut(), later.clone(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::ArbitrationNotExpired {});
+
+        // and the arbiter may ask once more, but not a third time
+        let info = mock_info("arbitrate", &[]);
+        execute(
+            deps.as_mut(),
+            later.clone(),
+            info.clone(),
+            request("And the 13th"),
+        )
+        .unwrap();
+        assert_eq!(deadlines(deps.as_ref()).arbitration_extensions, 2);
+        let err = execute(deps.as_mut(), later, info, request("And the 14th")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TooManyExtensions {
+                max: DEFAULT_MAX_ARBITRATION_EXTENSIONS
+            }
+        );
+    }
+
+    #[test]
+    fn completion_speed_leaves_out_arbitrated_escrows() {
+        let mut deps = setup();
+        let env = mock_env();
+        let at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+        let metrics = |deps: Deps| get_trust_metrics(deps.storage, &Addr::unchecked("fulfiller"));
+
+        // two clean completions, confirmed 10 and 20 minutes after acceptance
+        for (id, seconds) in [("quick", 600), ("slower", 1200)] {
+            fulfilled_escrow(deps.as_mut(), env.clone(), id, &coins(100, UST_DENOM));
+            let msg = ExecuteMsg::CComplete {
+                id: id.to_string(),
+                accept_rate_deviation: false,
+                release_bps: None,
+            };
+            execute(deps.as_mut(), at(seconds), mock_info("creator", &[]), msg).unwrap();
+        }
+        let clean = metrics(deps.as_ref()).unwrap();
+        assert_eq!(clean.avg_completion_speed, 900_000);
+        assert_eq!(clean.clean_completions, 2);
+
+        // a dispute the fulfiller wins two days in doesn't slow them down
+        fulfilled_escrow(deps.as_mut(), env, "disputed", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CReqArbitration {
+            id: "disputed".to_string(),
+            reason_code: DisputeReason::NotReceived,
+            details: None,
+        };
+        execute(deps.as_mut(), at(1_800), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ElArbitrate {
+            id: "disputed".to_string(),
+            msg: ArbitrateMsg {
+                receiver: "fulfiller".to_string(),
+                creator_bps: None,
+            },
+        };
+        execute(deps.as_mut(), at(172_800), mock_info("arbitrate", &[]), msg).unwrap();
+        let after = metrics(deps.as_ref()).unwrap();
+        assert_eq!(after.avg_completion_speed, 900_000);
+        assert_eq!(after.clean_completions, 2);
+        assert_eq!(after.avg_disputed_resolution_time, 172_800_000);
+        assert_eq!(after.disputed_resolutions, 1);
+
+        // both show up in the metrics query
+        let msg = QueryMsg::TrustMetrics {
+            address: "fulfiller".to_string(),
+        };
+        let queried: TrustMetrics =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(queried, after);
+    }
+
+    #[test]
+    fn canceling_refunds_each_gift_to_its_giver() {
+        let mut deps = setup();
+        let env = mock_env();
+        let create = CreateMsg {
+            cw20_whitelist: Some(vec!["cash".to_string()]),
+            ..create_msg("family")
+        };
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap();
+        let top_up = ExecuteMsg::TopUp {
+            id: "family".to_string(),
+        };
+        let gift = |sender: &str| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: sender.to_string(),
+                amount: Uint128::new(40),
+                msg: to_binary(&ReceiveMsg::TopUp {
+                    id: "family".to_string(),
+                })
+                .unwrap(),
+            })
+        };
+
+        // only the creator tops up until the admin allows gifts
+        let info = mock_info("cousin", &coins(50, UST_DENOM));
+        let err = execute(deps.as_mut(), env.clone(), info, top_up.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: Some(true),
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+        // a cousin gives two denoms, an aunt gives twice, once in a token
+        let funds = vec![coin(50, UST_DENOM), coin(20, "uluna")];
+        let info = mock_info("cousin", &funds);
+        execute(deps.as_mut(), env.clone(), info, top_up.clone()).unwrap();
+        let info = mock_info("aunt", &coins(30, UST_DENOM));
+        execute(deps.as_mut(), env.clone(), info, top_up.clone()).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("cash", &[]),
+            gift("aunt"),
+        )
+        .unwrap();
+        let info = mock_info("creator", &coins(5, UST_DENOM));
+        execute(deps.as_mut(), env.clone(), info, top_up).unwrap();
+        let escrow = ESCROWS.load(&deps.storage, "family").unwrap();
+        assert_eq!(escrow.contributors.len(), 2);
+        assert_eq!(
+            escrow.balance.native,
+            vec![coin(20, "uluna"), coin(185, UST_DENOM)]
+        );
+
+        let msg = ExecuteMsg::CCancel {
+            id: "family".to_string(),
+        };
+        let res = execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "creator".to_string(),
+                    amount: coins(105, UST_DENOM),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "cousin".to_string(),
+                    amount: vec![coin(20, "uluna"), coin(50, UST_DENOM)],
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "aunt".to_string(),
+                    amount: coins(30, UST_DENOM),
+                }),
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: "cash".to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: "aunt".to_string(),
+                        amount: Uint128::new(40),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn active_escrow_count_matches_a_full_scan() {
+        let mut deps = setup();
+        let stats = |deps: Deps| -> StatsResponse {
+            from_binary(&query(deps, mock_env(), QueryMsg::Stats {}).unwrap()).unwrap()
+        };
+        let id = |id: &str| id.to_string();
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(3_600);
+        let expiring = CreateMsg {
+            end_time: Some(mock_env().block.time.seconds() + 60),
+            ..create_msg("expiring")
+        };
+        let steps: Vec<(Env, MessageInfo, ExecuteMsg)> = vec![
+            (
+                mock_env(),
+                mock_info("creator", &coins(100, UST_DENOM)),
+                ExecuteMsg::CCreate(create_msg("first")),
+            ),
+            (
+                mock_env(),
+                mock_info("creator", &coins(300, UST_DENOM)),
+                ExecuteMsg::CCreateBatch {
+                    items: ["second", "third", "fourth"]
+                        .iter()
+                        .map(|id| BatchCreateItem {
+                            msg: create_msg(id),
+                            amount: coins(100, UST_DENOM),
+                        })
+                        .collect(),
+                },
+            ),
+            (
+                mock_env(),
+                mock_info("creator", &coins(100, UST_DENOM)),
+                ExecuteMsg::CCreate(expiring),
+            ),
+            (
+                mock_env(),
+                mock_info("creator", &[]),
+                ExecuteMsg::CCancel { id: id("first") },
+            ),
+            (
+                mock_env(),
+                mock_info("creator", &coins(100, UST_DENOM)),
+                ExecuteMsg::CloneEscrow {
+                    source_id: id("second"),
+                },
+            ),
+            (
+                mock_env(),
+                mock_info("fulfiller", &[]),
+                ExecuteMsg::FAccept {
+                    id: id("second"),
+                    expected_exchange_rate: None,
+                    required_creator_metrics: None,
+                    payout_memo: false,
+                },
+            ),
+            (
+                mock_env(),
+                mock_info("fulfiller", &[]),
+                ExecuteMsg::FComplete {
+                    id: id("second"),
+                    delivered_fiat_amount: None,
+                },
+            ),
+            (
+                mock_env(),
+                mock_info("creator", &[]),
+                ExecuteMsg::CComplete {
+                    id: id("second"),
+                    accept_rate_deviation: false,
+                    release_bps: None,
+                },
+            ),
+            (
+                mock_env(),
+                mock_info("fulfiller", &[]),
+                ExecuteMsg::FAccept {
+                    id: id("third"),
+                    expected_exchange_rate: None,
+                    required_creator_metrics: None,
+                    payout_memo: false,
+                },
+            ),
+            (
+                mock_env(),
+                mock_info("fulfiller", &[]),
+                ExecuteMsg::FComplete {
+                    id: id("third"),
+                    delivered_fiat_amount: None,
+                },
+            ),
+            (
+                mock_env(),
+                mock_info("creator", &[]),
+                ExecuteMsg::CReqArbitration {
+                    id: id("third"),
+                    reason_code: DisputeReason::NotReceived,
+                    details: None,
+                },
+            ),
+            (
+                mock_env(),
+                mock_info("arbitrate", &[]),
+                ExecuteMsg::ElArbitrate {
+                    id: id("third"),
+                    msg: ArbitrateMsg {
+                        receiver: "creator".to_string(),
+                        creator_bps: None,
+                    },
+                },
+            ),
+            (
+                later,
+                mock_info("creator", &[]),
+                ExecuteMsg::CancelAll {
+                    start_after: None,
+                    limit: None,
+                    only_expired: true,
+                },
+            ),
+        ];
+        for (step, (env, info, msg)) in steps.into_iter().enumerate() {
+            let res = execute(deps.as_mut(), env, info, msg);
+            assert!(res.is_ok(), "step {}: {:?}", step, res);
+            let scanned = all_escrow_ids(&deps.storage).unwrap().len() as u64;
+            assert_eq!(
+                stats(deps.as_ref()).active_escrows,
+                scanned,
+                "step {}",
+                step
+            );
+        }
+        // fourth and the clone of second are left
+        assert_eq!(
+            stats(deps.as_ref()),
+            StatsResponse {
+                active_escrows: 2,
+                max_active_escrows: None,
+                sunset: false,
+            }
+        );
+
+        // at the cap, creating fails until an escrow settles
+        let cap = |max: u64| {
+            ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                admin: None,
+                seconds_per_block: None,
+                max_whitelist_len: None,
+                bump_cooldown_seconds: None,
+                allow_mixed_funding: None,
+                post_accept_cancel_window_seconds: None,
+                cancel_penalty_bps: None,
+                split_native_payouts: None,
+                listing_deposit: None,
+                fee_collector: None,
+                require_arbiter_ack: None,
+                arbiter_ack_window_seconds: None,
+                display_denoms: None,
+                listing_grace_period_seconds: None,
+                arbitration_extension_seconds: None,
+                max_arbitration_extensions: None,
+                kyc_contract: None,
+                allow_third_party_topup: None,
+                max_active_escrows: Some(max),
+                daily_volume_limit_ust: None,
+                daily_accept_limit_ust: None,
+                dispute_window_seconds: None,
+                creator_ack_timeout_seconds: None,
+                max_open_to_all_seconds: None,
+                cancel_cooloff_seconds: None,
+                max_escrow_bytes: None,
+                mid_lifecycle_expiry: None,
+                risk_weights: None,
+                mediation_window_seconds: None,
+                min_remaining_on_accept_seconds: None,
+                peer_contracts: None,
+                max_actions_per_address: None,
+            })
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), cap(2)).unwrap();
+        assert_eq!(stats(deps.as_ref()).max_active_escrows, Some(2));
+        let create = |id: &str| ExecuteMsg::CCreate(create_msg(id));
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), create("fifth")).unwrap_err();
+        assert_eq!(err, ContractError::CapacityReached {});
+        let cancel = ExecuteMsg::CCancel { id: id("fourth") };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), cancel).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), create("fifth")).unwrap();
+
+        // 0 lifts the cap again
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), cap(0)).unwrap();
+        execute(deps.as_mut(), mock_env(), info, create("sixth")).unwrap();
+        assert_eq!(
+            stats(deps.as_ref()),
+            StatsResponse {
+                active_escrows: 3,
+                max_active_escrows: None,
+                sunset: false,
+            }
+        );
+    }
+
+    #[test]
+    fn expired_listings_drop_out_of_listing_queries() {
+        let mut deps = setup();
+        let env = mock_env();
+        let stale = CreateMsg {
+            end_time: Some(env.block.time.seconds() + 100),
+            ..create_msg("stale")
+        };
+        for msg in [create_msg("fresh"), stale] {
+            let info = mock_info("creator", &coins(100, UST_DENOM));
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CCreate(msg)).unwrap();
+        }
+        let ids = |listings: Vec<ListingSummary>| -> Vec<(String, bool)> {
+            listings.into_iter().map(|l| (l.id, l.expired)).collect()
+        };
+        let list = |env: Env, include_expired: bool| -> Vec<String> {
+            let msg = QueryMsg::List {
+                include_expired,
+                include_upcoming: false,
+                order: None,
+            };
+            let res: ListResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+            res.escrows
+        };
+
+        // while it runs, both are listed, stale first by id
+        let listings = query_listings(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+        );
+        assert_eq!(
+            ids(listings.unwrap().listings),
+            [("stale".to_string(), false), ("fresh".to_string(), false)]
+        );
+        assert_eq!(list(env, false), ["fresh", "stale"]);
+
+        // once it ran out, it's gone without anyone canceling it, and doesn't use up a page
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(200);
+        let listings = query_listings(
+            deps.as_ref(),
+            later.clone(),
+            None,
+            Some(1),
+            None,
+            false,
+            false,
+            None,
+        );
+        assert_eq!(
+            ids(listings.unwrap().listings),
+            [("fresh".to_string(), false)]
+        );
+        assert_eq!(list(later.clone(), false), ["fresh"]);
+        let msg = QueryMsg::EligibleListings {
+            address: "fulfiller".to_string(),
+            start_after: None,
+            limit: None,
+            include_expired: false,
+            include_upcoming: false,
+        };
+        let eligible: EligibleListingsResponse =
+            from_binary(&query(deps.as_ref(), later.clone(), msg).unwrap()).unwrap();
+        assert_eq!(ids(eligible.listings), [("fresh".to_string(), false)]);
+        assert_eq!(eligible.near_misses, vec![]);
+
+        // asked for, it comes back marked as expired
+        let listings = query_listings(
+            deps.as_ref(),
+            later.clone(),
+            None,
+            None,
+            None,
+            true,
+            false,
+            None,
+        );
+        assert_eq!(
+            ids(listings.unwrap().listings),
+            [("stale".to_string(), true), ("fresh".to_string(), false)]
+        );
+        assert_eq!(list(later, true), ["fresh", "stale"]);
+    }
+
+    #[test]
+    fn finished_escrows_are_told_apart_from_unknown_ones() {
+        let mut deps = setup();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(create_msg("done"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::CCancel {
+            id: "done".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let feedback = || FeedbackMsg {
+            comment: String::new(),
+            satisfied: true,
+        };
+        let handlers = |id: &str| {
+            let id = || id.to_string();
+            vec![
+                ExecuteMsg::ElArbitrate {
+                    id: id(),
+                    msg: ArbitrateMsg {
+                        receiver: "creator".to_string(),
+                        creator_bps: None,
+                    },
+                },
+                ExecuteMsg::ReassignArbiter {
+                    id: id(),
+                    new_arbiter: "other".to_string(),
+                },
+                ExecuteMsg::TopUp { id: id() },
+                ExecuteMsg::FAccept {
+                    id: id(),
+                    expected_exchange_rate: None,
+                    required_creator_metrics: None,
+                    payout_memo: false,
+                },
+                ExecuteMsg::CCancel { id: id() },
+                ExecuteMsg::FUnaccept { id: id() },
+                ExecuteMsg::CReject {
+                    id: id(),
+                    reason: None,
+                },
+                ExecuteMsg::CChange(create_msg(&id())),
+                ExecuteMsg::FComplete {
+                    id: id(),
+                    delivered_fiat_amount: None,
+                },
+                ExecuteMsg::ClaimTimedOut { id: id() },
+                ExecuteMsg::Bump { id: id() },
+                ExecuteMsg::SetPayoutDetails {
+                    id: id(),
+                    ciphertext: Binary::from(b"sealed".to_vec()),
+                },
+                ExecuteMsg::AckArbiterRole { id: id() },
+                ExecuteMsg::ResolveExpiredArbitration { id: id() },
+                ExecuteMsg::CReqArbitration {
+                    id: id(),
+                    reason_code: DisputeReason::NotReceived,
+                    details: None,
+                },
+                ExecuteMsg::ArbiterRequestInfo {
+                    id: id(),
+                    request: "a bank statement".to_string(),
+                },
+                ExecuteMsg::ArbiterRecuse {
+                    id: id(),
+                    reason: "knows the creator".to_string(),
+                },
+                ExecuteMsg::CComplete {
+                    id: id(),
+                    accept_rate_deviation: false,
+                    release_bps: None,
+                },
+                ExecuteMsg::CFeedback {
+                    id: id(),
+                    msg: feedback(),
+                },
+                ExecuteMsg::FFeedback {
+                    id: id(),
+                    msg: feedback(),
+                },
+            ]
+        };
+
+        let info = mock_info("admin", &coins(10, UST_DENOM));
+        for msg in handlers("done") {
+            let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+            let finished = ContractError::EscrowFinished {
+                id: "done".to_string(),
+                status: "cancel".to_string(),
+            };
+            assert_eq!(res, Err(finished), "{:?}", msg);
+        }
+        for msg in handlers("never") {
+            let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+            let unknown = ContractError::EscrowNotFound {
+                id: "never".to_string(),
+            };
+            assert_eq!(res, Err(unknown), "{:?}", msg);
+        }
+    }
+
+    #[test]
+    fn trust_presets_are_resolved_when_creating() {
+        let mut deps = setup();
+        let env = mock_env();
+        let strict = TrustMetrics {
+            total_completed: 10,
+            ..TrustMetrics::default()
+        };
+        let set = |metrics: TrustMetrics| ExecuteMsg::SetTrustPreset {
+            name: "strict".to_string(),
+            metrics,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            set(strict.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let admin = mock_info("admin", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            set(strict.clone()),
+        )
+        .unwrap();
+
+        let create = |id: &str, preset: &str| {
+            ExecuteMsg::CCreate(CreateMsg {
+                required_trust_metrics: TrustRequirement::Preset(preset.to_string()),
+                ..create_msg(id)
+            })
+        };
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let err =
+            execute(deps.as_mut(), env.clone(), info.clone(), create("a", "lax")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownPreset {
+                name: "lax".to_string()
+            }
+        );
+        execute(deps.as_mut(), env.clone(), info, create("a", "strict")).unwrap();
+        let escrow = ESCROWS.load(&deps.storage, "a").unwrap();
+        assert_eq!(escrow.required_trust_metrics, strict);
+
+        // loosening the preset leaves the live listing as strict as it was
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            set(TrustMetrics::default()),
+        )
+        .unwrap();
+        let escrow = ESCROWS.load(&deps.storage, "a").unwrap();
+        assert_eq!(escrow.required_trust_metrics, strict);
+        let msg = QueryMsg::TrustPresets {
+            start_after: None,
+            limit: None,
+        };
+        let presets: TrustPresetsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            presets.presets,
+            vec![TrustPreset {
+                name: "strict".to_string(),
+                metrics: TrustMetrics::default(),
+            }]
+        );
+
+        // removed, it can't be named anymore
+        let remove = ExecuteMsg::RemoveTrustPreset {
+            name: "strict".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), remove.clone()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), admin, remove).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownPreset {
+                name: "strict".to_string()
+            }
+        );
+        let change = ExecuteMsg::CChange(CreateMsg {
+            required_trust_metrics: TrustRequirement::Preset("strict".to_string()),
+            ..create_msg("a")
+        });
+        let err = execute(deps.as_mut(), env, mock_info("creator", &[]), change).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownPreset {
+                name: "strict".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn daily_limits_roll_over_past_midnight() {
+        let mut deps = setup();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: Some(Uint128::new(1_000)),
+            daily_accept_limit_ust: Some(Uint128::new(700)),
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        // the first escrow comes ten minutes before midnight
+        let midnight = (mock_env().block.time.seconds() / 86_400 + 1) * 86_400;
+        let at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(seconds);
+            env
+        };
+        let create = |id: &str| ExecuteMsg::CCreate(create_msg(id));
+        let exceeded = |used: u128, limit: u128| ContractError::DailyLimitExceeded {
+            used: Uint128::new(used),
+            limit: Uint128::new(limit),
+        };
+
+        let info = |amount: u128| mock_info("creator", &coins(amount, UST_DENOM));
+        execute(
+            deps.as_mut(),
+            at(midnight - 600),
+            info(600),
+            create("first"),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            at(midnight - 600),
+            info(500),
+            create("second"),
+        );
+        assert_eq!(err.unwrap_err(), exceeded(600, 1_000));
+        // topping up counts too, and a new calendar day doesn't reset the window
+        let top_up = ExecuteMsg::TopUp {
+            id: "first".to_string(),
+        };
+        let err = execute(deps.as_mut(), at(midnight + 600), info(500), top_up.clone());
+        assert_eq!(err.unwrap_err(), exceeded(600, 1_000));
+        execute(deps.as_mut(), at(midnight + 600), info(400), top_up).unwrap();
+
+        // a day after the first, only the top up still counts
+        let next_day = midnight - 600 + 86_400;
+        execute(deps.as_mut(), at(next_day), info(500), create("second")).unwrap();
+        let creator = Addr::unchecked("creator");
+        let buckets = CREATED_VOLUME.load(&deps.storage, &creator).unwrap();
+        assert_eq!(buckets.len(), 2);
+        let used = daily_volume_used(&deps.storage, &CREATED_VOLUME, &creator, &at(next_day));
+        assert_eq!(used.unwrap(), Uint128::new(900));
+
+        // fulfillers have a limit of their own
+        let accept = |id: &str| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let fulfiller = mock_info("fulfiller", &[]);
+        let err = execute(
+            deps.as_mut(),
+            at(next_day),
+            fulfiller.clone(),
+            accept("first"),
+        );
+        assert_eq!(err.unwrap_err(), exceeded(0, 700));
+        execute(
+            deps.as_mut(),
+            at(next_day),
+            fulfiller.clone(),
+            accept("second"),
+        )
+        .unwrap();
+        let info = mock_info("other", &coins(300, UST_DENOM));
+        execute(deps.as_mut(), at(next_day), info, create("third")).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            at(next_day),
+            fulfiller.clone(),
+            accept("third"),
+        );
+        assert_eq!(err.unwrap_err(), exceeded(500, 700));
+        execute(
+            deps.as_mut(),
+            at(next_day + 86_400),
+            fulfiller,
+            accept("third"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn dispute_deadline_is_fixed_when_fulfilling() {
+        let mut deps = setup();
+        let env = mock_env();
+        let update = |window: u64| {
+            ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                admin: None,
+                seconds_per_block: None,
+                max_whitelist_len: None,
+                bump_cooldown_seconds: None,
+                allow_mixed_funding: None,
+                post_accept_cancel_window_seconds: None,
+                cancel_penalty_bps: None,
+                split_native_payouts: None,
+                listing_deposit: None,
+                fee_collector: None,
+                require_arbiter_ack: None,
+                arbiter_ack_window_seconds: None,
+                display_denoms: None,
+                listing_grace_period_seconds: None,
+                arbitration_extension_seconds: None,
+                max_arbitration_extensions: None,
+                kyc_contract: None,
+                allow_third_party_topup: None,
+                max_active_escrows: None,
+                daily_volume_limit_ust: None,
+                daily_accept_limit_ust: None,
+                dispute_window_seconds: Some(window),
+                creator_ack_timeout_seconds: None,
+                max_open_to_all_seconds: None,
+                cancel_cooloff_seconds: None,
+                max_escrow_bytes: None,
+                mid_lifecycle_expiry: None,
+                risk_weights: None,
+                mediation_window_seconds: None,
+                min_remaining_on_accept_seconds: None,
+                peer_contracts: None,
+                max_actions_per_address: None,
+            })
+        };
+        let admin = mock_info("admin", &[]);
+        execute(deps.as_mut(), env.clone(), admin.clone(), update(7_200)).unwrap();
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let create = CreateMsg {
+            dispute_window_seconds: Some(0),
+            ..create_msg("custom")
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::CCreate(create),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::InvalidDisputeWindow {});
+        let create = CreateMsg {
+            dispute_window_seconds: Some(600),
+            ..create_msg("custom")
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap();
+        fulfilled_escrow(deps.as_mut(), env.clone(), "foobar", &coins(100, UST_DENOM));
+        let now = env.block.time.seconds();
+        let deadline = query_deadlines(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(deadline.fulfill_deadline, Some(now + 7_200));
+
+        // the fulfiller is told when they may claim
+        let fulfiller = mock_info("fulfiller", &[]);
+        let accept = ExecuteMsg::FAccept {
+            id: "custom".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        execute(deps.as_mut(), env.clone(), fulfiller.clone(), accept).unwrap();
+        let fulfill = ExecuteMsg::FComplete {
+            id: "custom".to_string(),
+            delivered_fiat_amount: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), fulfiller.clone(), fulfill).unwrap();
+        let expected = (now + 600).to_string();
+        assert_eq!(("dispute_deadline", expected.as_str()), res.attributes[2]);
+        assert_eq!(res.data, Some(to_binary(&(now + 600)).unwrap()));
+
+        // shrinking the window later doesn't shorten what the creator was given
+        execute(deps.as_mut(), env.clone(), admin, update(60)).unwrap();
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(7_200);
+        let claim = |id: &str| ExecuteMsg::ClaimTimedOut { id: id.to_string() };
+        let err = execute(
+            deps.as_mut(),
+            later.clone(),
+            fulfiller.clone(),
+            claim("foobar"),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::NotTimedOut {});
+        execute(
+            deps.as_mut(),
+            later.clone(),
+            fulfiller.clone(),
+            claim("custom"),
+        )
+        .unwrap();
+        later.block.time = later.block.time.plus_seconds(1);
+        execute(deps.as_mut(), later, fulfiller, claim("foobar")).unwrap();
+    }
+
+    #[test]
+    fn rejected_fulfillers_cannot_accept_again() {
+        let mut deps = escrow_at(Stage::Accepted);
+        let env = mock_env();
+        let reject = || ExecuteMsg::CReject {
+            id: "foobar".to_string(),
+            reason: Some(" met them before ".to_string()),
+        };
+        let accept = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let creator = mock_info("creator", &[]);
+        let res = execute(deps.as_mut(), env.clone(), creator.clone(), reject()).unwrap();
+        assert_eq!(("rejected", "fulfiller"), res.attributes[2]);
+        assert_eq!(("reason", "met them before"), res.attributes[3]);
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(details.rejected_fulfillers, vec!["fulfiller".to_string()]);
+        assert_eq!(details.fulfiller, "creator");
+        let listings = query_listings(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+        );
+        assert_eq!(listings.unwrap().listings.len(), 1);
+
+        // the rejected fulfiller is turned away, their metrics untouched
+        let metrics = get_trust_metrics(&deps.storage, &Addr::unchecked("fulfiller")).unwrap();
+        assert_eq!(metrics, TrustMetrics::default());
+        let fulfiller = mock_info("fulfiller", &[]);
+        let err = execute(deps.as_mut(), env.clone(), fulfiller, accept.clone());
+        assert_eq!(err.unwrap_err(), ContractError::FulfillerRejected {});
+        // nothing left to reject until someone else accepts
+        let err = execute(deps.as_mut(), env.clone(), creator.clone(), reject());
+        assert_eq!(err.unwrap_err(), ContractError::CantReject {});
+
+        for other in ["other1", "other2"] {
+            let info = mock_info(other, &[]);
+            execute(deps.as_mut(), env.clone(), info, accept.clone()).unwrap();
+            execute(deps.as_mut(), env.clone(), creator.clone(), reject()).unwrap();
+        }
+        let info = mock_info("other3", &[]);
+        execute(deps.as_mut(), env.clone(), info, accept).unwrap();
+        let err = execute(deps.as_mut(), env, creator, reject());
+        assert_eq!(
+            err.unwrap_err(),
+            ContractError::TooManyRejections {
+                max: MAX_REJECTIONS
+            }
+        );
+
+        // once fulfilled, the creator confirms or disputes instead
+        let mut deps = escrow_at(Stage::Fulfilled);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            reject(),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::CantReject {});
+    }
+
+    #[test]
+    fn arbiters_can_recuse_themselves() {
+        let mut deps = escrow_at(Stage::Listed);
+        let mut env = mock_env();
+        let admin = mock_info("admin", &[]);
+        let arbiter = mock_info("arbitrate", &[]);
+        let add_backup = || ExecuteMsg::AddArbiter {
+            address: "backup".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), add_backup()).unwrap();
+        let recuse = || ExecuteMsg::ArbiterRecuse {
+            id: "foobar".to_string(),
+            reason: " knows the creator ".to_string(),
+        };
+        let reassign = ExecuteMsg::ReassignArbiter {
+            id: "foobar".to_string(),
+            new_arbiter: "backup".to_string(),
+        };
+        let accept = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+
+        // before a dispute nobody may accept until the admin found a replacement
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            recuse(),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), env.clone(), arbiter.clone(), recuse()).unwrap();
+        assert_eq!(("needs_reassignment", "true"), res.attributes[4]);
+        let history = HISTORY.load(&deps.storage, "foobar").unwrap();
+        let entry = history.last().unwrap();
+        assert_eq!(entry.action, "recuse");
+        assert_eq!(entry.note, Some("knows the creator".to_string()));
+        let err = execute(deps.as_mut(), env.clone(), arbiter.clone(), recuse());
+        assert_eq!(err.unwrap_err(), ContractError::ArbiterRecused {});
+        let fulfiller = mock_info("fulfiller", &[]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            fulfiller.clone(),
+            accept.clone(),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::ArbiterRecused {});
+        execute(deps.as_mut(), env.clone(), admin.clone(), reassign.clone()).unwrap();
+        execute(deps.as_mut(), env.clone(), fulfiller, accept).unwrap();
+
+        // during a dispute the deadline waits for the replacement
+        let mut deps = escrow_at(Stage::InArbitration);
+        execute(deps.as_mut(), env.clone(), admin.clone(), add_backup()).unwrap();
+        execute(deps.as_mut(), env.clone(), arbiter.clone(), recuse()).unwrap();
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), "foobar".to_string());
+        assert_eq!(deadlines.unwrap().arbitration_deadline, None);
+        env.block.time = env.block.time.plus_seconds(ARBITRATION_TIMEOUT_SECONDS + 1);
+        let resolve = ExecuteMsg::ResolveExpiredArbitration {
+            id: "foobar".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            resolve,
+        );
+        assert_eq!(err.unwrap_err(), ContractError::ArbitrationNotExpired {});
+        let rule = ExecuteMsg::ElArbitrate {
+            id: "foobar".to_string(),
+            msg: ArbitrateMsg {
+                receiver: "creator".to_string(),
+                creator_bps: None,
+            },
+        };
+        let err = execute(deps.as_mut(), env.clone(), arbiter, rule.clone());
+        assert_eq!(err.unwrap_err(), ContractError::ArbiterRecused {});
+        execute(deps.as_mut(), env.clone(), admin, reassign).unwrap();
+        execute(deps.as_mut(), env, mock_info("backup", &[]), rule).unwrap();
+        let stats = ARBITER_STATS
+            .load(&deps.storage, &Addr::unchecked("arbitrate"))
+            .unwrap();
+        assert_eq!(stats.recusals, 1);
+    }
+
+    #[test]
+    fn escrows_are_found_by_their_parties() {
+        let mut deps = setup();
+        let env = mock_env();
+        let creator = mock_info("creator", &[]);
+        let fulfiller = mock_info("fulfiller", &[]);
+        let accept = |id: &str| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let find = |deps: Deps, include_archived: bool, start_after: Option<u64>| {
+            let escrows = query_find_by_parties(
+                deps,
+                "creator".to_string(),
+                "fulfiller".to_string(),
+                include_archived,
+                start_after,
+                Some(1),
+            );
+            let escrows = escrows.unwrap().escrows;
+            escrows
+                .into_iter()
+                .map(|e| (e.id, e.status, e.archived, e.revision))
+                .collect::<Vec<_>>()
+        };
+
+        fulfilled_escrow(deps.as_mut(), env.clone(), "first", &coins(100, UST_DENOM));
+        let complete = ExecuteMsg::CComplete {
+            id: "first".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        execute(deps.as_mut(), env.clone(), creator.clone(), complete).unwrap();
+        // an unaccepted escrow doesn't pair them any more
+        for id in ["second", "third"] {
+            let info = mock_info("creator", &coins(100, UST_DENOM));
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                info,
+                ExecuteMsg::CCreate(create_msg(id)),
+            )
+            .unwrap();
+            execute(deps.as_mut(), env.clone(), fulfiller.clone(), accept(id)).unwrap();
+        }
+        let unaccept = ExecuteMsg::FUnaccept {
+            id: "second".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), fulfiller, unaccept).unwrap();
+        let second = query_details(deps.as_ref(), env, "second".to_string()).unwrap();
+        assert_eq!(second.fulfiller, "creator");
+
+        let page = find(deps.as_ref(), false, None);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].0, "third");
+        assert_eq!(page[0].1, "accepted");
+        assert!(find(deps.as_ref(), false, Some(page[0].3)).is_empty());
+        // settled escrows only when asked for, newest first
+        let first_page = find(deps.as_ref(), true, None);
+        assert_eq!(first_page, page);
+        let next_page = find(deps.as_ref(), true, Some(page[0].3));
+        assert_eq!(next_page.len(), 1);
+        let (id, status, archived, _) = &next_page[0];
+        assert_eq!(
+            (id.as_str(), status.as_str(), *archived),
+            ("first", "creator_complete", true)
+        );
+        assert!(find(deps.as_ref(), true, Some(next_page[0].3)).is_empty());
+        let pair = (&Addr::unchecked("creator"), &Addr::unchecked("fulfiller"));
+        let keys = PARTY_ESCROWS
+            .prefix(pair)
+            .keys(&deps.storage, None, None, Order::Ascending);
+        assert_eq!(keys.count(), 2);
+    }
+
+    #[test]
+    fn feedback_and_arbitration_take_named_fields() {
+        let named = br#"{"c_feedback":{"id":"demo","msg":{"comment":"ok","satisfied":true}}}"#;
+        let msg: ExecuteMsg = cosmwasm_std::from_slice(named).unwrap();
+        assert_eq!(
+            msg,
+            ExecuteMsg::CFeedback {
+                id: "demo".to_string(),
+                msg: FeedbackMsg {
+                    comment: "ok".to_string(),
+                    satisfied: true,
+                },
+            }
+        );
+        assert_eq!(to_binary(&msg).unwrap().as_slice(), &named[..]);
+
+        // the old positional shape is no longer understood
+        let positional = br#"{"c_feedback":["demo",{"comment":"ok","satisfied":true}]}"#;
+        assert!(cosmwasm_std::from_slice::<ExecuteMsg>(positional).is_err());
+    }
+
+    #[test]
+    fn creator_acks_fulfillers_before_they_fulfill() {
+        let mut deps = setup();
+        let mut env = mock_env();
+        let id = || "gated".to_string();
+        let creator = mock_info("creator", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(CreateMsg {
+            require_creator_ack: true,
+            ..create_msg("gated")
+        });
+        execute(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+        let set_details = ExecuteMsg::SetPayoutDetails {
+            id: id(),
+            ciphertext: Binary::from(b"sealed".to_vec()),
+        };
+        execute(deps.as_mut(), env.clone(), creator.clone(), set_details).unwrap();
+        let payout_details = |deps: Deps| -> PayoutDetailsResponse {
+            from_binary(&query(deps, mock_env(), QueryMsg::PayoutDetails { id: id() }).unwrap())
+                .unwrap()
+        };
+        assert_eq!(payout_details(deps.as_ref()).ciphertext, None);
+
+        let accept = ExecuteMsg::FAccept {
+            id: id(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let fulfill = || ExecuteMsg::FComplete {
+            id: id(),
+            delivered_fiat_amount: None,
+        };
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("fulfiller", &[]),
+            accept.clone(),
+        )
+        .unwrap();
+        assert_eq!(("awaiting_creator_ack", "true"), res.attributes[2]);
+        let details = query_details(deps.as_ref(), env.clone(), id()).unwrap();
+        assert!(details.awaiting_creator_ack);
+        let timeout = ACCEPT_TIMEOUT_SECONDS;
+        let deadline = env.block.time.seconds() + timeout;
+        assert_eq!(details.creator_ack_deadline, Some(deadline));
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), id()).unwrap();
+        assert_eq!(deadlines.creator_ack_deadline, Some(deadline));
+        let fulfiller = mock_info("fulfiller", &[]);
+        let err = execute(deps.as_mut(), env.clone(), fulfiller.clone(), fulfill());
+        assert_eq!(err.unwrap_err(), ContractError::AwaitingCreatorAck {});
+        assert_eq!(payout_details(deps.as_ref()).ciphertext, None);
+
+        // never acked, the acceptance lapses and the next fulfiller takes the escrow
+        env.block.time = env.block.time.plus_seconds(timeout);
+        let ack = || ExecuteMsg::CAckFulfiller { id: id() };
+        let err = execute(deps.as_mut(), env.clone(), creator.clone(), ack());
+        assert_eq!(err.unwrap_err(), ContractError::CreatorAckExpired {});
+        let res = execute(deps.as_mut(), env.clone(), mock_info("other", &[]), accept).unwrap();
+        assert_eq!(("lapsed", "fulfiller"), res.attributes[2]);
+        let err = execute(deps.as_mut(), env.clone(), fulfiller, fulfill());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+
+        // acked, the new fulfiller sees the details and gets a full window to fulfill
+        env.block.time = env.block.time.plus_seconds(10);
+        execute(deps.as_mut(), env.clone(), creator.clone(), ack()).unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), id()).unwrap();
+        assert!(!details.awaiting_creator_ack);
+        assert_eq!(details.creator_ack_deadline, None);
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), id()).unwrap();
+        assert_eq!(
+            deadlines.accept_deadline,
+            Some(env.block.time.seconds() + ACCEPT_TIMEOUT_SECONDS)
+        );
+        let ciphertext = payout_details(deps.as_ref()).ciphertext;
+        assert_eq!(ciphertext, Some(Binary::from(b"sealed".to_vec())));
+        let err = execute(deps.as_mut(), env.clone(), creator, ack());
+        assert_eq!(err.unwrap_err(), ContractError::NotAwaitingCreatorAck {});
+        execute(deps.as_mut(), env, mock_info("other", &[]), fulfill()).unwrap();
+    }
+
+    #[test]
+    fn check_invariants_reports_corrupted_state() {
+        let mut deps = escrow_at(Stage::InArbitration);
+        let env = mock_env();
+        let creator = mock_info("creator", &coins(100, UST_DENOM));
+        for id in ["listed1", "listed2"] {
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+        }
+        let check = |deps: Deps, start_after: Option<&str>, limit: u32| {
+            let start_after = start_after.map(String::from);
+            query_check_invariants(deps, start_after, limit).unwrap()
+        };
+        let codes = |res: InvariantsResponse| -> Vec<(Option<String>, String)> {
+            res.violations.into_iter().map(|v| (v.id, v.code)).collect()
+        };
+        let res = check(deps.as_ref(), None, 10);
+        assert_eq!(res.scanned, 3);
+        assert_eq!(res.next_start_after, None);
+        assert_eq!(codes(res), vec![]);
+
+        // written around save_escrow, so neither the flags nor the counters hold up
+        let mut escrow = ESCROWS.load(&deps.storage, "listed2").unwrap();
+        escrow.is_accepted = true;
+        escrow.time_accepted = Some(escrow.time_created.unwrap() - 1);
+        escrow.balance.native.push(coin(0, "ukrw"));
+        ESCROWS.save(&mut deps.storage, "listed2", &escrow).unwrap();
+        let stray = ESCROWS.load(&escrow_at(Stage::Listed).storage, "foobar");
+        ESCROWS
+            .save(&mut deps.storage, "stray", &stray.unwrap())
+            .unwrap();
+
+        let violation = |id: Option<&str>, code: &str| (id.map(String::from), code.to_string());
+        let page = check(deps.as_ref(), None, 3);
+        assert_eq!(page.next_start_after, Some("listed2".to_string()));
+        assert_eq!(
+            codes(page),
+            vec![
+                violation(Some("listed2"), "contradictory_status"),
+                violation(Some("listed2"), "zero_native_amount"),
+                violation(Some("listed2"), "timestamps_out_of_order"),
+                violation(Some("listed2"), "fulfiller_is_creator"),
+            ]
+        );
+        // a partial page only catches counters smaller than what it saw
+        let page = check(deps.as_ref(), Some("listed2"), 3);
+        assert_eq!(page.scanned, 1);
+        assert_eq!(codes(page), vec![]);
+        let everything = check(deps.as_ref(), None, 10);
+        assert_eq!(everything.scanned, 4);
+        let codes = codes(everything);
+        assert_eq!(
+            codes[4..],
+            [
+                violation(None, "active_escrows_mismatch"),
+                violation(None, "open_interest_mismatch"),
+            ]
+        );
+    }
+
+    #[test]
+    fn open_to_all_waives_trust_requirements_for_a_while() {
+        let mut deps = setup();
+        let mut env = mock_env();
+        let create = CreateMsg {
+            required_trust_metrics: TrustRequirement::Metrics(TrustMetrics {
+                total_completed: 10,
+                ..TrustMetrics::default()
+            }),
+            ..create_msg("foobar")
+        };
+        let creator = mock_info("creator", &coins(100, UST_DENOM));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            ExecuteMsg::CCreate(create),
+        )
+        .unwrap();
+        let open = |duration_seconds: u64| ExecuteMsg::OpenToAll {
+            id: "foobar".to_string(),
+            duration_seconds,
+        };
+        let accept = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let newcomer = mock_info("newcomer", &[]);
+
+        let err = execute(deps.as_mut(), env.clone(), newcomer.clone(), open(60));
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        let max = DEFAULT_MAX_OPEN_TO_ALL_SECONDS;
+        for duration in [0, max + 1] {
+            let err = execute(deps.as_mut(), env.clone(), creator.clone(), open(duration));
+            assert_eq!(
+                err.unwrap_err(),
+                ContractError::InvalidOpenToAllDuration { max }
+            );
+        }
+        let six_hours = 6 * 60 * 60;
+        let res = execute(deps.as_mut(), env.clone(), creator.clone(), open(six_hours)).unwrap();
+        let until = env.block.time.seconds() + six_hours;
+        assert_eq!(("until", until.to_string().as_str()), res.attributes[2]);
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(details.open_to_all_until, Some(until));
+        let eligible = query_eligible_listings(
+            deps.as_ref(),
+            env.clone(),
+            "newcomer".to_string(),
+            None,
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(eligible.listings[0].open_to_all_until, Some(until));
+
+        // once the window closes the stored requirements are back
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(six_hours);
+        let details = query_details(deps.as_ref(), later.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(details.open_to_all_until, None);
+        let eligible = query_eligible_listings(
+            deps.as_ref(),
+            later.clone(),
+            "newcomer".to_string(),
+            None,
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            eligible.near_misses[0].reason,
+            ContractError::TrustMetricsInsufficient {}.to_string()
+        );
+        let err = execute(deps.as_mut(), later, newcomer.clone(), accept.clone());
+        assert_eq!(err.unwrap_err(), ContractError::TrustMetricsInsufficient {});
+
+        // while it's open anyone gets in, and an accepted escrow can't be opened any more
+        env.block.time = env.block.time.plus_seconds(six_hours - 1);
+        execute(deps.as_mut(), env.clone(), newcomer, accept).unwrap();
+        let err = execute(deps.as_mut(), env, creator, open(60)).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyAccepted { .. }));
+    }
+
+    #[test]
+    fn canceled_listings_can_be_restored_during_the_cooloff() {
+        let mut deps = escrow_at(Stage::Listed);
+        let env = mock_env();
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: Some(600),
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        let id = || "foobar".to_string();
+        let creator = mock_info("creator", &[]);
+        let cancel = ExecuteMsg::CCancel { id: id() };
+        let undo = ExecuteMsg::UndoCancel { id: id() };
+        let finalize = ExecuteMsg::FinalizeCancel { id: id() };
+        let accept = ExecuteMsg::FAccept {
+            id: id(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let listed = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| -> Vec<String> {
+            let listings = query_listings(
+                deps.as_ref(),
+                mock_env(),
+                None,
+                None,
+                None,
+                false,
+                false,
+                None,
+            );
+            listings
+                .unwrap()
+                .listings
+                .into_iter()
+                .map(|listing| listing.id)
+                .collect()
+        };
+
+        // canceling only sets the listing aside, funds and all
+        let err = execute(deps.as_mut(), env.clone(), creator.clone(), undo.clone());
+        assert_eq!(err.unwrap_err(), ContractError::NotPendingCancel {});
+        let res = execute(deps.as_mut(), env.clone(), creator.clone(), cancel.clone()).unwrap();
+        let until = env.block.time.seconds() + 600;
+        assert_eq!(
+            ("pending_until", until.to_string().as_str()),
+            res.attributes[2]
+        );
+        assert!(res.messages.is_empty());
+        let details = query_details(deps.as_ref(), env.clone(), id()).unwrap();
+        assert_eq!(details.cancel_pending_until, Some(until));
+        assert_eq!(details.native_balance, coins(100, UST_DENOM));
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), id()).unwrap();
+        assert_eq!(deadlines.cancel_pending_until, Some(until));
+        assert_eq!(listed(&deps), Vec::<String>::new());
+        let list = query_list(deps.as_ref(), env.clone(), true, false, None).unwrap();
+        assert_eq!(list.escrows, Vec::<String>::new());
+
+        // nobody may accept it meanwhile, nor may it be canceled twice
+        let fulfiller = mock_info("fulfiller", &[]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            fulfiller.clone(),
+            accept.clone(),
+        );
+        assert_eq!(err.unwrap_err(), ContractError::PendingCancel { until });
+        let err = execute(deps.as_mut(), env.clone(), creator.clone(), cancel.clone());
+        assert_eq!(err.unwrap_err(), ContractError::PendingCancel { until });
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            fulfiller.clone(),
+            finalize.clone(),
+        );
+        assert_eq!(
+            err.unwrap_err(),
+            ContractError::CancelCooloffActive { until }
+        );
+
+        // the creator takes it back and it's listed again
+        let err = execute(deps.as_mut(), env.clone(), fulfiller.clone(), undo.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), creator.clone(), undo.clone()).unwrap();
+        assert_eq!(listed(&deps), vec![id()]);
+        let details = query_details(deps.as_ref(), env.clone(), id()).unwrap();
+        assert_eq!(details.cancel_pending_until, None);
+        let history = HISTORY.load(&deps.storage, "foobar").unwrap();
+        let actions: Vec<&str> = history.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(
+            actions[actions.len() - 2..],
+            ["request_cancel", "undo_cancel"]
+        );
+
+        // canceling them all at once waits out the same cooloff
+        let cancel_all = ExecuteMsg::CancelAll {
+            start_after: None,
+            limit: None,
+            only_expired: false,
+        };
+        let res = execute(deps.as_mut(), env.clone(), creator.clone(), cancel_all).unwrap();
+        assert_eq!(res.attributes[1], attr("pending_escrow_id", "foobar"));
+        assert!(res.messages.is_empty());
+        assert_eq!(listed(&deps), Vec::<String>::new());
+        execute(deps.as_mut(), env.clone(), creator.clone(), undo.clone()).unwrap();
+
+        // once the cooloff runs out only the refund is left, for anyone to trigger
+        execute(deps.as_mut(), env.clone(), creator.clone(), cancel).unwrap();
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(600);
+        let err = execute(deps.as_mut(), later.clone(), creator, undo);
+        assert_eq!(err.unwrap_err(), ContractError::CancelCooloffOver {});
+        let err = execute(deps.as_mut(), later.clone(), fulfiller, accept);
+        assert_eq!(err.unwrap_err(), ContractError::PendingCancel { until });
+        let res = execute(deps.as_mut(), later, mock_info("anyone", &[]), finalize).unwrap();
+        assert_eq!(("action", "cancel"), res.attributes[0]);
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(100, UST_DENOM),
+            }))]
+        );
+        assert!(ESCROWS.may_load(&deps.storage, &id()).unwrap().is_none());
+    }
+
+    #[test]
+    fn as_create_msg_recreates_the_same_escrow() {
+        let mut deps = setup();
+        let env = mock_env();
+        let create = CreateMsg {
+            end_time: Some(env.block.time.seconds() + 3_600),
+            cw20_whitelist: Some(vec!["bar_token".to_string()]),
+            required_trust_metrics: TrustRequirement::Metrics(TrustMetrics {
+                total_completed: 3,
+                ..TrustMetrics::default()
+            }),
+            allowed_fulfillers: Some(vec!["fulfiller".to_string()]),
+            description: Some("Transferencia a Banesco".to_string()),
+            dispute_window_seconds: Some(600),
+            require_creator_ack: true,
+            ..create_msg("foobar")
+        };
+        let creator = mock_info("creator", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(create.clone());
+        execute(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+        let original = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+
+        // a live escrow's id is taken, so the reconstruction takes the next free one
+        let res = query_as_create_msg(deps.as_ref(), "foobar".to_string()).unwrap();
+        assert!(!res.archived);
+        assert_eq!(
+            res.create_msg,
+            CreateMsg {
+                id: "foobar-2".to_string(),
+                ..create.clone()
+            }
+        );
+
+        // once canceled it's read from the archive, its own id staying taken
+        let cancel = ExecuteMsg::CCancel {
+            id: "foobar".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &[]),
+            cancel,
+        )
+        .unwrap();
+        let res = query_as_create_msg(deps.as_ref(), "foobar".to_string()).unwrap();
+        assert!(res.archived);
+        assert_eq!(
+            res.create_msg,
+            CreateMsg {
+                id: "foobar-2".to_string(),
+                ..create.clone()
+            }
+        );
+
+        let msg = ExecuteMsg::CCreate(res.create_msg);
+        execute(deps.as_mut(), env.clone(), creator, msg).unwrap();
+        let recreated = query_details(deps.as_ref(), env.clone(), "foobar-2".to_string()).unwrap();
+        assert_eq!(
+            recreated,
+            DetailsResponse {
+                id: "foobar-2".to_string(),
+                fingerprint: recreated.fingerprint.clone(),
+                ..original
+            }
+        );
+        // and live again, with the archive left as it was
+        let res = query_as_create_msg(deps.as_ref(), "foobar-2".to_string()).unwrap();
+        assert!(!res.archived);
+        let res = query_as_create_msg(deps.as_ref(), "foobar".to_string()).unwrap();
+        assert!(res.archived);
+        assert_eq!(res.create_msg.id, "foobar-3");
+
+        let err = query_as_create_msg(deps.as_ref(), "unknown".to_string()).unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+    }
+
+    #[test]
+    fn escrows_stop_growing_at_the_size_budget_and_still_pay_out() {
+        let mut deps = escrow_at(Stage::InArbitration);
+        let env = mock_env();
+        let size = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| {
+            query_details(deps.as_ref(), mock_env(), "foobar".to_string())
+                .unwrap()
+                .approximate_size
+        };
+        let configure = |max_escrow_bytes: u64| {
+            ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                admin: None,
+                seconds_per_block: None,
+                max_whitelist_len: None,
+                bump_cooldown_seconds: None,
+                allow_mixed_funding: None,
+                post_accept_cancel_window_seconds: None,
+                cancel_penalty_bps: None,
+                split_native_payouts: None,
+                listing_deposit: None,
+                fee_collector: None,
+                require_arbiter_ack: None,
+                arbiter_ack_window_seconds: None,
+                display_denoms: None,
+                listing_grace_period_seconds: None,
+                arbitration_extension_seconds: None,
+                max_arbitration_extensions: Some(50),
+                kyc_contract: None,
+                allow_third_party_topup: None,
+                max_active_escrows: None,
+                daily_volume_limit_ust: None,
+                daily_accept_limit_ust: None,
+                dispute_window_seconds: None,
+                creator_ack_timeout_seconds: None,
+                max_open_to_all_seconds: None,
+                cancel_cooloff_seconds: None,
+                max_escrow_bytes: Some(max_escrow_bytes),
+                mid_lifecycle_expiry: None,
+                risk_weights: None,
+                mediation_window_seconds: None,
+                min_remaining_on_accept_seconds: None,
+                peer_contracts: None,
+                max_actions_per_address: None,
+            })
+        };
+        let admin = mock_info("admin", &[]);
+        let arbiter = mock_info("arbitrate", &[]);
+        let stored = ESCROWS.load(&deps.storage, "foobar").unwrap();
+        assert_eq!(size(&deps), stored.approximate_size());
+
+        // room for two requests of a kilobyte, not three
+        let budget = size(&deps) + 2_500;
+        execute(deps.as_mut(), env.clone(), admin.clone(), configure(budget)).unwrap();
+        let request = ExecuteMsg::ArbiterRequestInfo {
+            id: "foobar".to_string(),
+            request: "x".repeat(1_000),
+        };
+        let mut requests = 0;
+        let err = loop {
+            match execute(deps.as_mut(), env.clone(), arbiter.clone(), request.clone()) {
+                Ok(_) => requests += 1,
+                Err(err) => break err,
+            }
+        };
+        assert_eq!(err, ContractError::EscrowSizeLimit {});
+        assert_eq!(requests, 2);
+        assert!(size(&deps) <= budget);
+
+        // even once the budget shrinks below it, the escrow loads and pays out
+        execute(deps.as_mut(), env.clone(), admin, configure(1)).unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(details.dispute.unwrap().info_requests.len(), 2);
+        let arbitrate = ExecuteMsg::ElArbitrate {
+            id: "foobar".to_string(),
+            msg: ArbitrateMsg {
+                receiver: "fulfiller".to_string(),
+                creator_bps: None,
+            },
+        };
+        let res = execute(deps.as_mut(), env, arbiter, arbitrate).unwrap();
+        assert_eq!(("action", "arbitrate"), res.attributes[0]);
+        assert_eq!(res.messages.len(), 1);
+        assert!(!ESCROWS.has(&deps.storage, "foobar"));
+    }
+
+    /// Indexers parse these, a change to the keys or their order must be deliberate
+    #[test]
+    fn lifecycle_events_keep_their_attribute_keys() {
+        let mut deps = setup();
+        let id = |id: &str| id.to_string();
+        let accept = |id: &str| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let fulfill = |id: &str| ExecuteMsg::FComplete {
+            id: id.to_string(),
+            delivered_fiat_amount: None,
+        };
+        let funds = coins(100, UST_DENOM);
+        let steps: Vec<(&str, &[Coin], ExecuteMsg, &[&str])> = vec![
+            (
+                "creator",
+                &funds,
+                ExecuteMsg::CCreate(create_msg("disputed")),
+                &["create", "escrow_id", "fingerprint"],
+            ),
+            (
+                "creator",
+                &funds,
+                ExecuteMsg::TopUp { id: id("disputed") },
+                &["top_up", "escrow_id", "fingerprint"],
+            ),
+            (
+                "creator",
+                &[],
+                ExecuteMsg::CChange(create_msg("disputed")),
+                &["change", "escrow_id", "fingerprint"],
+            ),
+            (
+                "fulfiller",
+                &[],
+                accept("disputed"),
+                &["accept", "escrow_id", "fingerprint"],
+            ),
+            (
+                "fulfiller",
+                &[],
+                ExecuteMsg::FUnaccept { id: id("disputed") },
+                &["unaccept", "escrow_id", "fingerprint"],
+            ),
+            (
+                "fulfiller",
+                &[],
+                accept("disputed"),
+                &["accept", "escrow_id", "fingerprint"],
+            ),
+            (
+                "fulfiller",
+                &[],
+                fulfill("disputed"),
+                &["fulfill", "escrow_id", "dispute_deadline", "fingerprint"],
+            ),
+            (
+                "creator",
+                &[],
+                ExecuteMsg::CReqArbitration {
+                    id: id("disputed"),
+                    reason_code: DisputeReason::NotReceived,
+                    details: None,
+                },
+                &[
+                    "request_arbitration",
+                    "escrow_id",
+                    "reason_code",
+                    "fingerprint",
+                ],
+            ),
+            (
+                "arbitrate",
+                &[],
+                ExecuteMsg::ArbiterRequestInfo {
+                    id: id("disputed"),
+                    request: "Receipt, please".to_string(),
+                },
+                &[
+                    "request_info",
+                    "escrow_id",
+                    "creator",
+                    "fulfiller",
+                    "request",
+                    "arbitration_deadline",
+                    "fingerprint",
+                ],
+            ),
+            (
+                "arbitrate",
+                &[],
+                ExecuteMsg::ElArbitrate {
+                    id: id("disputed"),
+                    msg: ArbitrateMsg {
+                        receiver: "fulfiller".to_string(),
+                        creator_bps: None,
+                    },
+                },
+                &["arbitrate", "escrow_id", "to", "fingerprint"],
+            ),
+            (
+                "creator",
+                &funds,
+                ExecuteMsg::CCreate(create_msg("completed")),
+                &["create", "escrow_id", "fingerprint"],
+            ),
+            (
+                "fulfiller",
+                &[],
+                accept("completed"),
+                &["accept", "escrow_id", "fingerprint"],
+            ),
+            (
+                "creator",
+                &[],
+                ExecuteMsg::CReject {
+                    id: id("completed"),
+                    reason: Some("Wrong bank".to_string()),
+                },
+                &["reject", "escrow_id", "rejected", "reason", "fingerprint"],
+            ),
+            (
+                "other",
+                &[],
+                accept("completed"),
+                &["accept", "escrow_id", "fingerprint"],
+            ),
+            (
+                "other",
+                &[],
+                fulfill("completed"),
+                &["fulfill", "escrow_id", "dispute_deadline", "fingerprint"],
+            ),
+            (
+                "creator",
+                &[],
+                ExecuteMsg::CComplete {
+                    id: id("completed"),
+                    accept_rate_deviation: false,
+                    release_bps: None,
+                },
+                &["creator_complete", "escrow_id", "to", "fingerprint"],
+            ),
+            (
+                "creator",
+                &funds,
+                ExecuteMsg::CCreate(create_msg("canceled")),
+                &["create", "escrow_id", "fingerprint"],
+            ),
+            (
+                "creator",
+                &[],
+                ExecuteMsg::CCancel { id: id("canceled") },
+                &["cancel", "escrow_id", "fingerprint"],
+            ),
+        ];
+        for (sender, funds, msg, expected) in steps {
+            let res = execute(deps.as_mut(), mock_env(), mock_info(sender, funds), msg).unwrap();
+            let keys: Vec<&str> = res.attributes.iter().map(|a| a.key.as_str()).collect();
+            assert_eq!(res.attributes[0].value, expected[0]);
+            assert_eq!(keys[0], "action");
+            assert_eq!(keys[1..], expected[1..]);
+        }
+
+        // those covering several escrows pair each escrow_id with its fingerprint
+        let escrows = [
+            (id("a"), "AAAAAAAA".to_string()),
+            (id("b"), "BBBBBBBB".to_string()),
+        ];
+        let attributes = ContractEvent::canceled_all(&escrows, &[], None).attributes();
+        let keys: Vec<&str> = attributes.iter().map(|a| a.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "action",
+                "escrow_id",
+                "fingerprint",
+                "escrow_id",
+                "fingerprint"
+            ]
+        );
+        let attributes =
+            ContractEvent::stray_recovered("uluna", &Addr::unchecked("to"), 5u128.into())
+                .attributes();
+        assert_eq!(attributes[2], attr("amount_uluna", "5"));
+    }
+
+    #[test]
+    fn relisting_extends_expiry_up_to_max_lifetime() {
+        let mut deps = setup();
+        let env = mock_env();
+        let created = env.block.time.seconds();
+        let day = 24 * 60 * 60;
+        let mut msg = create_msg("foobar");
+        msg.end_time = Some(created + 6 * day);
+        msg.auto_extend_on_relist = Some(18 * 60 * 60);
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CCreate(msg)).unwrap();
+        let accept = |deps: DepsMut, fulfiller: &str| {
+            let msg = ExecuteMsg::FAccept {
+                id: "foobar".to_string(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            };
+            execute(deps, mock_env(), mock_info(fulfiller, &[]), msg).unwrap();
+        };
+        let extensions = |deps: Deps| {
+            let history = HISTORY.load(deps.storage, "foobar").unwrap();
+            history
+                .into_iter()
+                .filter(|entry| entry.action == "auto_extend")
+                .count()
+        };
+
+        // the whole extension fits
+        accept(deps.as_mut(), "fulfiller");
+        let msg = ExecuteMsg::FUnaccept {
+            id: "foobar".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("fulfiller", &[]), msg).unwrap();
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), "foobar".into()).unwrap();
+        let extended = created + 6 * day + 18 * 60 * 60;
+        assert_eq!(deadlines.expires_at_seconds, Some(extended));
+        assert_eq!(extensions(deps.as_ref()), 1);
 
-        // create an escrow
-        let create = CreateMsg {
+        // clamped to seven days after creation
+        accept(deps.as_mut(), "fulfiller");
+        let msg = ExecuteMsg::CReject {
             id: "foobar".to_string(),
-            arbiter: String::from("arbitrate"),
-            recipient: String::from("recd"),
-            end_time: None,
-            end_height: Some(123456),
-            cw20_whitelist: None,
+            reason: None,
         };
-        let sender = String::from("source");
-        let balance = coins(100, "tokens");
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::Create(create.clone());
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(("action", "create"), res.attributes[0]);
+        execute(deps.as_mut(), env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), "foobar".into()).unwrap();
+        assert_eq!(deadlines.expires_at_seconds, Some(created + 7 * day));
+        assert_eq!(extensions(deps.as_ref()), 2);
 
-        // ensure the details is what we expect
-        let details = query_details(deps.as_ref(), "foobar".to_string()).unwrap();
+        // nothing left to extend, so nothing recorded
+        accept(deps.as_mut(), "other");
+        let msg = ExecuteMsg::FUnaccept {
+            id: "foobar".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("other", &[]), msg).unwrap();
+        let deadlines = query_deadlines(deps.as_ref(), env, "foobar".into()).unwrap();
+        assert_eq!(deadlines.expires_at_seconds, Some(created + 7 * day));
+        assert_eq!(extensions(deps.as_ref()), 2);
+    }
+
+    #[test]
+    fn escrows_expire_within_their_max_lifetime() {
+        let mut deps = setup();
+        let env = mock_env();
+        let created = env.block.time.seconds();
+        let day = 24 * 60 * 60;
+        let creator = mock_info("creator", &coins(100, UST_DENOM));
+        let exceeded = ContractError::LifetimeExceeded {
+            max_seconds: MAX_ESCROW_LIFETIME_SECONDS,
+        };
+
+        // by time, or by height at the configured block time
+        let msg = CreateMsg {
+            end_time: Some(created + 7 * day + 1),
+            ..create_msg("foobar")
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            ExecuteMsg::CCreate(msg),
+        );
+        assert_eq!(err.unwrap_err(), exceeded);
+        let blocks = 7 * day / DEFAULT_SECONDS_PER_BLOCK + 1;
+        let msg = CreateMsg {
+            end_height: Some(env.block.height + blocks),
+            ..create_msg("foobar")
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            ExecuteMsg::CCreate(msg),
+        );
+        assert_eq!(err.unwrap_err(), exceeded);
+        let msg = CreateMsg {
+            end_time: Some(created + 6 * day),
+            ..create_msg("foobar")
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator,
+            ExecuteMsg::CCreate(msg),
+        )
+        .unwrap();
+
+        // changing the listing pushes the expiry back only up to seven days after creation
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_seconds(day);
+        let change = |end_time: u64| {
+            ExecuteMsg::CChange(CreateMsg {
+                end_time: Some(end_time),
+                ..create_msg("foobar")
+            })
+        };
+        let creator = mock_info("creator", &[]);
+        let msg = change(created + 7 * day + 1);
+        let err = execute(deps.as_mut(), later.clone(), creator.clone(), msg);
+        assert_eq!(err.unwrap_err(), exceeded);
+        let msg = change(created + 7 * day);
+        execute(deps.as_mut(), later.clone(), creator, msg).unwrap();
+        let deadlines = query_deadlines(deps.as_ref(), later, "foobar".into()).unwrap();
+        assert_eq!(deadlines.expires_at_seconds, Some(created + 7 * day));
+    }
+
+    #[test]
+    fn creator_metrics_follow_the_creator() {
+        let mut deps = escrow_at(Stage::InArbitration);
+        let creator = Addr::unchecked("creator");
+        let metrics = |deps: Deps| get_creator_metrics(deps.storage, &creator).unwrap();
+
+        // a dispute the creator lost
+        let msg = ExecuteMsg::ElArbitrate {
+            id: "foobar".to_string(),
+            msg: ArbitrateMsg {
+                receiver: "fulfiller".to_string(),
+                creator_bps: None,
+            },
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("arbitrate", &[]), msg).unwrap();
+        let after_dispute = metrics(deps.as_ref());
+        assert_eq!(after_dispute.escrows_created, 1);
+        assert_eq!(after_dispute.disputes_opened, 1);
+        assert_eq!(after_dispute.disputes_lost, 1);
+
+        // confirming promptly
+        let funds = coins(100, UST_DENOM);
+        fulfilled_escrow(deps.as_mut(), mock_env(), "good", &funds);
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(120);
+        let msg = ExecuteMsg::CComplete {
+            id: "good".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+        let after_confirming = metrics(deps.as_ref());
+        assert_eq!(after_confirming.confirmations, 1);
+        assert_eq!(after_confirming.avg_confirmation_seconds, 120);
+
+        // going silent after the fulfiller reported
+        fulfilled_escrow(deps.as_mut(), mock_env(), "silent", &funds);
+        let deadline = query_deadlines(deps.as_ref(), mock_env(), "silent".into())
+            .unwrap()
+            .fulfill_deadline
+            .unwrap();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(deadline + 1);
+        let msg = ExecuteMsg::ClaimTimedOut {
+            id: "silent".to_string(),
+        };
+        execute(deps.as_mut(), env, mock_info("fulfiller", &[]), msg).unwrap();
+        assert_eq!(metrics(deps.as_ref()).confirmations_missed, 1);
+
+        // backing out after someone accepted
+        let update = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: Some(600),
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update).unwrap();
+        let info = mock_info("creator", &funds);
+        let msg = ExecuteMsg::CCreate(create_msg("backed_out"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let accept = |id: &str, required_creator_metrics| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics,
+            payout_memo: false,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("fulfiller", &[]),
+            accept("backed_out", None),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::CCancel {
+            id: "backed_out".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let record = metrics(deps.as_ref());
+        assert_eq!(record.cancels_after_accept, 1);
+        assert_eq!(record.escrows_created, 4);
+        let msg = QueryMsg::CreatorMetrics {
+            address: "creator".to_string(),
+        };
+        let queried: CreatorMetrics =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(queried, record);
+
+        // a fulfiller whose client asks for a clean record turns the creator down
+        let info = mock_info("creator", &funds);
+        let msg = ExecuteMsg::CCreate(create_msg("screened"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let strict = CreatorRequirement {
+            max_disputes_lost: Some(0),
+            ..CreatorRequirement::default()
+        };
+        let info = mock_info("other", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            accept("screened", Some(strict)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CreatorMetricsInsufficient {});
+        let lenient = CreatorRequirement {
+            min_escrows_created: Some(4),
+            max_avg_confirmation_seconds: Some(3600),
+            max_disputes_lost: Some(1),
+            ..CreatorRequirement::default()
+        };
+        let info = mock_info("other", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            accept("screened", Some(lenient)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn settling_responds_with_the_stored_receipt() {
+        let receipt = |deps: Deps, id: &str| -> Receipt {
+            let msg = QueryMsg::Receipt { id: id.to_string() };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // confirmed by the creator
+        let mut deps = escrow_at(Stage::Fulfilled);
+        let msg = ExecuteMsg::CComplete {
+            id: "foobar".to_string(),
+            accept_rate_deviation: false,
+            release_bps: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let stored = receipt(deps.as_ref(), "foobar");
+        assert_eq!(from_binary::<Receipt>(&res.data.unwrap()).unwrap(), stored);
+        let env = mock_env();
+        let expected = Receipt {
+            escrow_id: "foobar".to_string(),
+            fingerprint: stored.fingerprint.clone(),
+            creator: Addr::unchecked("creator"),
+            fulfiller: Addr::unchecked("fulfiller"),
+            amounts_released: vec![ReleasedAmount {
+                recipient: Addr::unchecked("fulfiller"),
+                amount: GenericBalance {
+                    native: coins(100, UST_DENOM),
+                    cw20: vec![],
+                },
+            }],
+            fee_taken: GenericBalance::default(),
+            exchange_rate: Decimal::percent(3650),
+            fiat_currency: "VES".to_string(),
+            terminal_action: "creator_complete".to_string(),
+            block_time: env.block.time.seconds(),
+            block_height: env.block.height,
+        };
+        assert_eq!(stored, expected);
+
+        // claimed once the creator went silent
+        let mut deps = escrow_at(Stage::Fulfilled);
+        let deadline = query_deadlines(deps.as_ref(), mock_env(), "foobar".into())
+            .unwrap()
+            .fulfill_deadline
+            .unwrap();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(deadline + 1);
+        let msg = ExecuteMsg::ClaimTimedOut {
+            id: "foobar".to_string(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("fulfiller", &[]), msg).unwrap();
+        let stored = receipt(deps.as_ref(), "foobar");
+        assert_eq!(from_binary::<Receipt>(&res.data.unwrap()).unwrap(), stored);
+        assert_eq!(stored.terminal_action, "claim_timed_out");
+        assert_eq!(stored.block_time, deadline + 1);
+
+        // split by the arbiter
+        let mut deps = escrow_at(Stage::InArbitration);
+        let msg = ExecuteMsg::ElArbitrate {
+            id: "foobar".to_string(),
+            msg: ArbitrateMsg {
+                receiver: "fulfiller".to_string(),
+                creator_bps: Some(2500),
+            },
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("arbitrate", &[]), msg).unwrap();
+        let stored = receipt(deps.as_ref(), "foobar");
+        assert_eq!(from_binary::<Receipt>(&res.data.unwrap()).unwrap(), stored);
+        assert_eq!(stored.terminal_action, "arbitrate");
+        let released: Vec<_> = stored
+            .amounts_released
+            .iter()
+            .map(|released| {
+                (
+                    released.recipient.as_str(),
+                    released.amount.value_ust().u128(),
+                )
+            })
+            .collect();
+        assert_eq!(released, vec![("creator", 25), ("fulfiller", 75)]);
+
+        // the id stays taken along with its receipt, canceling leaves none
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let msg = ExecuteMsg::CCreate(create_msg("foobar"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyInUse {});
+        assert_eq!(receipt(deps.as_ref(), "foobar"), stored);
+        let msg = ExecuteMsg::CCreate(create_msg("listed"));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::CCancel {
+            id: "listed".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = QueryMsg::Receipt {
+            id: "listed".to_string(),
+        };
+        query(deps.as_ref(), mock_env(), msg).unwrap_err();
+    }
+
+    #[test]
+    fn listing_queries_order_by_creation_time() {
+        let mut deps = setup();
+        let mut env = mock_env();
+        for id in ["bravo", "charlie", "alpha"] {
+            let info = mock_info("creator", &coins(100, UST_DENOM));
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+            env.block.time = env.block.time.plus_seconds(10);
+        }
+        let list = |deps: Deps, order: Option<ListOrder>| -> Vec<String> {
+            let msg = QueryMsg::List {
+                include_expired: false,
+                include_upcoming: false,
+                order,
+            };
+            let res: ListResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.escrows
+        };
+        let listings = |deps: Deps, start_after: Option<&str>, order: Option<ListOrder>| {
+            let start_after = start_after.map(String::from);
+            query_listings(
+                deps,
+                mock_env(),
+                start_after,
+                None,
+                None,
+                false,
+                false,
+                order,
+            )
+            .unwrap()
+            .listings
+            .into_iter()
+            .map(|listing| listing.id)
+            .collect::<Vec<_>>()
+        };
+
+        // without an order, List stays by id
+        assert_eq!(list(deps.as_ref(), None), ["alpha", "bravo", "charlie"]);
+        let asc = Some(ListOrder::CreatedAsc);
+        let desc = Some(ListOrder::CreatedDesc);
+        assert_eq!(list(deps.as_ref(), asc), ["bravo", "charlie", "alpha"]);
+        assert_eq!(list(deps.as_ref(), desc), ["alpha", "charlie", "bravo"]);
         assert_eq!(
-            details,
-            DetailsResponse {
-                id: "foobar".to_string(),
-                arbiter: String::from("arbitrate"),
-                recipient: String::from("recd"),
-                source: String::from("source"),
-                end_height: Some(123456),
-                end_time: None,
-                native_balance: balance.clone(),
-                cw20_balance: vec![],
-                cw20_whitelist: vec![],
+            listings(deps.as_ref(), None, asc),
+            ["bravo", "charlie", "alpha"]
+        );
+        assert_eq!(listings(deps.as_ref(), Some("charlie"), desc), ["bravo"]);
+        assert_eq!(
+            listings(deps.as_ref(), Some("bravo"), asc),
+            ["charlie", "alpha"]
+        );
+
+        // settled escrows leave the index
+        let msg = ExecuteMsg::CCancel {
+            id: "charlie".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(list(deps.as_ref(), desc), ["alpha", "bravo"]);
+        let indexed = CREATED_ESCROWS
+            .range(&deps.storage, None, None, Order::Ascending)
+            .count();
+        assert_eq!(indexed, 2);
+    }
+
+    #[test]
+    fn accepted_escrows_expire_by_the_configured_policy() {
+        let id = || "foobar".to_string();
+        let created = mock_env().block.time.seconds();
+        let at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(seconds);
+            env
+        };
+        for policy in [
+            MidLifecycleExpiry::GraceUntilAcceptTimeout,
+            MidLifecycleExpiry::HardExpire,
+        ] {
+            let mut deps = setup();
+            let update = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                admin: None,
+                seconds_per_block: None,
+                max_whitelist_len: None,
+                bump_cooldown_seconds: None,
+                allow_mixed_funding: None,
+                post_accept_cancel_window_seconds: None,
+                cancel_penalty_bps: None,
+                split_native_payouts: None,
+                listing_deposit: None,
+                fee_collector: None,
+                require_arbiter_ack: None,
+                arbiter_ack_window_seconds: None,
+                display_denoms: None,
+                listing_grace_period_seconds: None,
+                arbitration_extension_seconds: None,
+                max_arbitration_extensions: None,
+                kyc_contract: None,
+                allow_third_party_topup: None,
+                max_active_escrows: None,
+                daily_volume_limit_ust: None,
+                daily_accept_limit_ust: None,
+                dispute_window_seconds: None,
+                creator_ack_timeout_seconds: None,
+                max_open_to_all_seconds: None,
+                cancel_cooloff_seconds: None,
+                max_escrow_bytes: None,
+                mid_lifecycle_expiry: Some(policy),
+                risk_weights: None,
+                mediation_window_seconds: None,
+                min_remaining_on_accept_seconds: None,
+                peer_contracts: None,
+                max_actions_per_address: None,
+            });
+            execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update).unwrap();
+            let msg = ExecuteMsg::CCreate(CreateMsg {
+                end_time: Some(created + 100),
+                ..create_msg("foobar")
+            });
+            let info = mock_info("creator", &coins(100, UST_DENOM));
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let accept = ExecuteMsg::FAccept {
+                id: id(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo: false,
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("fulfiller", &[]),
+                accept,
+            )
+            .unwrap();
+
+            let deadlines = query_deadlines(deps.as_ref(), mock_env(), id()).unwrap();
+            let expected = match policy {
+                MidLifecycleExpiry::GraceUntilAcceptTimeout => created + ACCEPT_TIMEOUT_SECONDS,
+                MidLifecycleExpiry::HardExpire => created + 100,
+            };
+            assert_eq!(deadlines.effective_accept_deadline, Some(expected));
+
+            // up to end_time, nothing changes under either policy
+            let refund = ExecuteMsg::RefundExpired { id: id() };
+            let stranger = mock_info("stranger", &[]);
+            let err = execute(
+                deps.as_mut(),
+                at(created + 100),
+                stranger.clone(),
+                refund.clone(),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::NotHardExpired {});
+
+            let fulfill = ExecuteMsg::FComplete {
+                id: id(),
+                delivered_fiat_amount: None,
+            };
+            let fulfiller = mock_info("fulfiller", &[]);
+            let past = at(created + 101);
+            match policy {
+                MidLifecycleExpiry::GraceUntilAcceptTimeout => {
+                    let res = execute(deps.as_mut(), past.clone(), stranger, refund);
+                    assert_eq!(res.unwrap_err(), ContractError::NotHardExpired {});
+                    execute(deps.as_mut(), past, fulfiller, fulfill).unwrap();
+                    assert!(ESCROWS.load(&deps.storage, &id()).unwrap().is_fulfilled);
+                }
+                MidLifecycleExpiry::HardExpire => {
+                    let res = execute(deps.as_mut(), past.clone(), fulfiller, fulfill);
+                    assert_eq!(res.unwrap_err(), ContractError::Expired {});
+                    let res = execute(deps.as_mut(), past, stranger, refund).unwrap();
+                    assert_eq!(
+                        res.messages,
+                        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                            to_address: "creator".to_string(),
+                            amount: coins(100, UST_DENOM),
+                        }))]
+                    );
+                    assert!(ESCROWS.may_load(&deps.storage, &id()).unwrap().is_none());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cw20_payouts_name_the_escrow_to_parties_asking_for_it() {
+        let mut deps = setup();
+        let create = |deps: DepsMut, id: &str, payout_memo: bool| {
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "creator".to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(&ReceiveMsg::CCreate(CreateMsg {
+                    payout_memo,
+                    ..create_msg(id)
+                }))
+                .unwrap(),
+            });
+            execute(deps, mock_env(), mock_info("token", &[]), msg).unwrap();
+        };
+        let settle = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+                      id: &str,
+                      payout_memo: bool| {
+            let accept = ExecuteMsg::FAccept {
+                id: id.to_string(),
+                expected_exchange_rate: None,
+                required_creator_metrics: None,
+                payout_memo,
+            };
+            let fulfill = ExecuteMsg::FComplete {
+                id: id.to_string(),
+                delivered_fiat_amount: None,
+            };
+            for msg in [accept, fulfill] {
+                execute(deps.as_mut(), mock_env(), mock_info("fulfiller", &[]), msg).unwrap();
             }
+            let msg = ExecuteMsg::CComplete {
+                id: id.to_string(),
+                accept_rate_deviation: false,
+                release_bps: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap()
+        };
+        let paid = |msg: Cw20ExecuteMsg| {
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: to_binary(&msg).unwrap(),
+                funds: vec![],
+            })]
+        };
+        let hook = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, id: &str| {
+            let escrow = ESCROWS.load(&deps.storage, id).unwrap();
+            to_binary(&PayoutHookMsg::EscrowPayout {
+                escrow_id: id.to_string(),
+                fingerprint: escrow.fingerprint,
+            })
+            .unwrap()
+        };
+
+        // without the flag, a plain transfer
+        create(deps.as_mut(), "plain", false);
+        let res = settle(&mut deps, "plain", false);
+        let transfer = Cw20ExecuteMsg::Transfer {
+            recipient: "fulfiller".to_string(),
+            amount: Uint128::new(100),
+        };
+        assert_eq!(res.messages, paid(transfer));
+
+        // a fulfiller asking for it on accept gets a Send naming the escrow
+        create(deps.as_mut(), "hooked", false);
+        let msg = hook(&deps, "hooked");
+        let res = settle(&mut deps, "hooked", true);
+        let send = Cw20ExecuteMsg::Send {
+            contract: "fulfiller".to_string(),
+            amount: Uint128::new(100),
+            msg,
+        };
+        assert_eq!(res.messages, paid(send));
+
+        // and so does a creator asking for it on create, when refunded
+        create(deps.as_mut(), "refunded", true);
+        let msg = hook(&deps, "refunded");
+        let cancel = ExecuteMsg::CCancel {
+            id: "refunded".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), cancel).unwrap();
+        let send = Cw20ExecuteMsg::Send {
+            contract: "creator".to_string(),
+            amount: Uint128::new(100),
+            msg,
+        };
+        assert_eq!(res.messages, paid(send));
+
+        // canceled along with the rest, too
+        create(deps.as_mut(), "swept", true);
+        let msg = hook(&deps, "swept");
+        let cancel_all = ExecuteMsg::CancelAll {
+            start_after: None,
+            limit: None,
+            only_expired: false,
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            cancel_all,
         );
+        let send = Cw20ExecuteMsg::Send {
+            contract: "creator".to_string(),
+            amount: Uint128::new(100),
+            msg,
+        };
+        assert_eq!(res.unwrap().messages, paid(send));
+    }
 
-        // approve it
-        let id = create.id.clone();
-        let info = mock_info(&create.arbiter, &[]);
-        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap();
-        assert_eq!(1, res.messages.len());
-        assert_eq!(("action", "approve"), res.attributes[0]);
+    #[test]
+    fn listings_are_found_by_their_tags() {
+        let mut deps = setup();
+        let tagged = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, tag: &str| {
+            let msg = QueryMsg::ListByTag {
+                tag: tag.to_string(),
+                start_after: None,
+                limit: None,
+            };
+            let res: ListingsResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.listings
+                .into_iter()
+                .map(|listing| listing.id)
+                .collect::<Vec<_>>()
+        };
+        let create =
+            |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, id: &str, tags: &[&str]| {
+                let msg = ExecuteMsg::CCreate(CreateMsg {
+                    tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+                    ..create_msg(id)
+                });
+                let info = mock_info("creator", &coins(100, UST_DENOM));
+                execute(deps.as_mut(), mock_env(), info, msg)
+            };
+
+        create(&mut deps, "first", &["caracas", "zelle-ok", "caracas"]).unwrap();
+        create(&mut deps, "second", &["urgent", "caracas"]).unwrap();
+        let escrow = ESCROWS.load(&deps.storage, "first").unwrap();
+        assert_eq!(escrow.tags, vec!["caracas", "zelle-ok"]);
+        assert_eq!(tagged(&deps, "caracas"), vec!["first", "second"]);
+        assert_eq!(tagged(&deps, "zelle-ok"), vec!["first"]);
+        assert_eq!(tagged(&deps, "urgent"), vec!["second"]);
+
+        // the error names the offending tag
+        for tag in ["Caracas", "x", "zelle_ok", "much-too-long-tag"] {
+            let err = create(&mut deps, "third", &["urgent", tag]).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidTag {
+                    tag: tag.to_string()
+                }
+            );
+        }
+        let err = create(&mut deps, "third", &["a1", "b1", "c1", "d1", "e1", "f1"]).unwrap_err();
+        assert_eq!(err, ContractError::TooManyTags { max: MAX_TAGS });
+
+        // a tag dropped by a change leaves the index
+        let change = ExecuteMsg::CChange(CreateMsg {
+            tags: Some(vec!["caracas".to_string()]),
+            ..create_msg("first")
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), change).unwrap();
+        assert_eq!(tagged(&deps, "zelle-ok"), Vec::<String>::new());
+        assert_eq!(tagged(&deps, "caracas"), vec!["first", "second"]);
+
+        // and so do all of them when the escrow is canceled
+        let cancel = ExecuteMsg::CCancel {
+            id: "second".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), cancel).unwrap();
+        assert_eq!(tagged(&deps, "caracas"), vec!["first"]);
+        assert_eq!(tagged(&deps, "urgent"), Vec::<String>::new());
+        let remaining: Vec<_> = TAGGED_LISTINGS
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect();
+        assert_eq!(remaining.len(), 1);
+    }
+
+    #[test]
+    fn scheduled_listings_go_live_at_their_start_time() {
+        let accept = |id: &str| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let listed = |deps: Deps, env: Env, include_upcoming: bool| {
+            let msg = QueryMsg::Listings {
+                start_after: None,
+                limit: None,
+                viewer: None,
+                include_expired: false,
+                include_upcoming,
+                order: None,
+            };
+            let res: ListingsResponse = from_binary(&query(deps, env, msg).unwrap()).unwrap();
+            res.listings
+                .into_iter()
+                .map(|listing| (listing.id, listing.starts_at))
+                .collect::<Vec<_>>()
+        };
+        let mut deps = setup();
+        let env = mock_env();
+        let starts_at = env.block.time.seconds() + 600;
+        let scheduled = CreateMsg {
+            start_time: Some(starts_at),
+            ..create_msg("foobar")
+        };
+
+        // it can't go live after it expires
+        let msg = ExecuteMsg::CCreate(CreateMsg {
+            end_time: Some(starts_at - 1),
+            ..scheduled.clone()
+        });
+        let info = mock_info("creator", &coins(100, UST_DENOM));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::StartAfterEnd {});
+        let msg = ExecuteMsg::CCreate(scheduled.clone());
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(deadlines.start_time, Some(starts_at));
+
+        // the last second before it starts, it's only there for those who ask
+        let mut last = mock_env();
+        last.block.time = Timestamp::from_seconds(starts_at - 1);
+        assert!(listed(deps.as_ref(), last.clone(), false).is_empty());
         assert_eq!(
-            res.messages[0],
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: create.recipient,
-                amount: balance,
-            }))
+            listed(deps.as_ref(), last.clone(), true),
+            vec![("foobar".to_string(), Some(starts_at))]
         );
+        let err = execute(
+            deps.as_mut(),
+            last.clone(),
+            mock_info("fulfiller", &[]),
+            accept("foobar"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotYetActive { starts_at });
 
-        // second attempt fails (not found)
-        let id = create.id.clone();
-        let info = mock_info(&create.arbiter, &[]);
-        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap_err();
-        assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+        // while the creator may still change it
+        let change = CreateMsg {
+            exchange_rate: Decimal::percent(3700),
+            ..scheduled
+        };
+        execute(
+            deps.as_mut(),
+            last,
+            mock_info("creator", &[]),
+            ExecuteMsg::CChange(change),
+        )
+        .unwrap();
+
+        // once it starts, it's listed and up for grabs
+        let mut live = mock_env();
+        live.block.time = Timestamp::from_seconds(starts_at);
+        assert_eq!(
+            listed(deps.as_ref(), live.clone(), false),
+            vec![("foobar".to_string(), None)]
+        );
+        execute(
+            deps.as_mut(),
+            live,
+            mock_info("fulfiller", &[]),
+            accept("foobar"),
+        )
+        .unwrap();
     }
 
     #[test]
-    fn happy_path_cw20() {
-        let mut deps = mock_dependencies(&[]);
+    fn disputes_settle_in_mediation_or_go_on_to_the_arbiter() {
+        let window = 3600;
+        let mut deps = setup();
+        let update = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: Some(window),
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update).unwrap();
+        let env = mock_env();
+        let now = env.block.time.seconds();
+        let dispute = |id: &str| ExecuteMsg::CReqArbitration {
+            id: id.to_string(),
+            reason_code: DisputeReason::NotReceived,
+            details: None,
+        };
+        let rule = |id: &str| ExecuteMsg::ElArbitrate {
+            id: id.to_string(),
+            msg: ArbitrateMsg {
+                receiver: "creator".to_string(),
+                creator_bps: None,
+            },
+        };
+        let accept = |id: &str| ExecuteMsg::AcceptSettlement {
+            id: id.to_string(),
+            expected_creator_bps: None,
+        };
 
-        // instantiate an empty contract
-        let instantiate_msg = InstantiateMsg {};
-        let info = mock_info(&String::from("anyone"), &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        // settled: the parties agree on a split and the arbiter never steps in
+        fulfilled_escrow(deps.as_mut(), env.clone(), "settled", &coins(100, "tokens"));
+        let creator = mock_info("creator", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            dispute("settled"),
+        )
+        .unwrap();
+        let deadlines = query_deadlines(deps.as_ref(), env.clone(), "settled".to_string()).unwrap();
+        assert_eq!(deadlines.mediation_until, Some(now + window));
+        assert_eq!(
+            deadlines.arbitration_deadline,
+            Some(now + window + ARBITRATION_TIMEOUT_SECONDS)
+        );
+        let propose = ExecuteMsg::ProposeSettlement {
+            id: "settled".to_string(),
+            creator_bps: 7000,
+        };
+        let info = mock_info("fulfiller", &[]);
+        execute(deps.as_mut(), env.clone(), info.clone(), propose).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info, accept("settled")).unwrap_err();
+        assert_eq!(err, ContractError::OwnSettlement {});
+        let arbiter = mock_info("arbitrate", &[]);
+        let err = execute(deps.as_mut(), env.clone(), arbiter.clone(), rule("settled"));
+        let until = now + window;
+        assert_eq!(err.unwrap_err(), ContractError::InMediation { until });
+        let changed = ExecuteMsg::AcceptSettlement {
+            id: "settled".to_string(),
+            expected_creator_bps: Some(8000),
+        };
+        let err = execute(deps.as_mut(), env.clone(), creator.clone(), changed).unwrap_err();
+        assert_eq!(err, ContractError::SettlementChanged { creator_bps: 7000 });
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            accept("settled"),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "creator".to_string(),
+                    amount: coins(70, "tokens"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "fulfiller".to_string(),
+                    amount: coins(30, "tokens"),
+                }),
+            ]
+        );
+        assert!(!ESCROWS.has(&deps.storage, "settled"));
+        let history = HISTORY.load(&deps.storage, "settled").unwrap();
+        let actions: Vec<_> = history.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(
+            actions[actions.len() - 3..],
+            [
+                "request_arbitration",
+                "propose_settlement",
+                "accept_settlement"
+            ]
+        );
+        assert_eq!(
+            history[history.len() - 2].note.as_deref(),
+            Some("creator_bps 7000")
+        );
 
-        // create an escrow
-        let create = CreateMsg {
-            id: "foobar".to_string(),
-            arbiter: String::from("arbitrate"),
-            recipient: String::from("recd"),
-            end_time: None,
-            end_height: None,
-            cw20_whitelist: Some(vec![String::from("other-token")]),
+        // unsettled: once the window lapses it's up to the arbiter
+        fulfilled_escrow(deps.as_mut(), env.clone(), "ruled", &coins(100, "tokens"));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            dispute("ruled"),
+        )
+        .unwrap();
+        let propose = ExecuteMsg::ProposeSettlement {
+            id: "ruled".to_string(),
+            creator_bps: 5000,
         };
-        let receive = Cw20ReceiveMsg {
-            sender: String::from("source"),
-            amount: Uint128::new(100),
-            msg: to_binary(&ExecuteMsg::Create(create.clone())).unwrap(),
+        execute(deps.as_mut(), env.clone(), creator, propose.clone()).unwrap();
+        let mut lapsed = env;
+        lapsed.block.time = lapsed.block.time.plus_seconds(window);
+        let info = mock_info("fulfiller", &[]);
+        let err = execute(deps.as_mut(), lapsed.clone(), info.clone(), accept("ruled"));
+        assert_eq!(err.unwrap_err(), ContractError::NotInMediation {});
+        let err = execute(deps.as_mut(), lapsed.clone(), info, propose).unwrap_err();
+        assert_eq!(err, ContractError::NotInMediation {});
+        execute(deps.as_mut(), lapsed, arbiter, rule("ruled")).unwrap();
+        assert!(!ESCROWS.has(&deps.storage, "ruled"));
+    }
+
+    #[test]
+    fn sunset_winds_escrows_down_and_lets_nothing_new_start() {
+        let mut deps = setup();
+        let env = mock_env();
+        let update = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: Some(600),
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), update).unwrap();
+        let funded = mock_info("creator", &coins(100, "tokens"));
+        for id in ["listed", "pending", "accepted"] {
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), env.clone(), funded.clone(), msg).unwrap();
+        }
+        let accept = |id: &str| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let fulfiller = mock_info("fulfiller", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            fulfiller.clone(),
+            accept("accepted"),
+        )
+        .unwrap();
+        fulfilled_escrow(
+            deps.as_mut(),
+            env.clone(),
+            "fulfilled",
+            &coins(100, "tokens"),
+        );
+        fulfilled_escrow(
+            deps.as_mut(),
+            env.clone(),
+            "disputed",
+            &coins(100, "tokens"),
+        );
+        let creator = mock_info("creator", &[]);
+        let dispute = ExecuteMsg::CReqArbitration {
+            id: "disputed".to_string(),
+            reason_code: DisputeReason::NotReceived,
+            details: None,
+        };
+        execute(deps.as_mut(), env.clone(), creator.clone(), dispute).unwrap();
+        // before, a canceled listing waits out the cooloff
+        let cancel = |id: &str| ExecuteMsg::CCancel { id: id.to_string() };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            cancel("pending"),
+        )
+        .unwrap();
+        let finalize = ExecuteMsg::FinalizeCancel {
+            id: "pending".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            finalize.clone(),
+        );
+        let until = env.block.time.seconds() + 600;
+        assert_eq!(
+            err.unwrap_err(),
+            ContractError::CancelCooloffActive { until }
+        );
+
+        // only the admin may begin it, and there's no way back
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            ExecuteMsg::BeginSunset {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let admin = mock_info("admin", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin,
+            ExecuteMsg::BeginSunset {},
+        )
+        .unwrap();
+        let stats: StatsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Stats {}).unwrap()).unwrap();
+        assert!(stats.sunset);
+
+        let rejected = vec![
+            ("creator", ExecuteMsg::CCreate(create_msg("new"))),
+            (
+                "creator",
+                ExecuteMsg::CCreateBatch {
+                    items: vec![BatchCreateItem {
+                        msg: create_msg("new"),
+                        amount: coins(100, "tokens"),
+                    }],
+                },
+            ),
+            (
+                "creator",
+                ExecuteMsg::CloneEscrow {
+                    source_id: "listed".to_string(),
+                },
+            ),
+            (
+                "creator",
+                TopUp {
+                    id: "listed".to_string(),
+                },
+            ),
+            (
+                "creator",
+                ExecuteMsg::Bump {
+                    id: "listed".to_string(),
+                },
+            ),
+            ("other", accept("listed")),
+        ];
+        for (sender, msg) in rejected {
+            let info = mock_info(sender, &coins(100, "tokens"));
+            let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+            assert_eq!(err, ContractError::ContractSunsetting {});
+        }
+
+        // whatever is open may still settle
+        let wind_down = vec![
+            (
+                fulfiller,
+                ExecuteMsg::FComplete {
+                    id: "accepted".to_string(),
+                    delivered_fiat_amount: None,
+                },
+            ),
+            (
+                creator.clone(),
+                ExecuteMsg::CComplete {
+                    id: "fulfilled".to_string(),
+                    accept_rate_deviation: true,
+                    release_bps: None,
+                },
+            ),
+            (
+                mock_info("arbitrate", &[]),
+                ExecuteMsg::ElArbitrate {
+                    id: "disputed".to_string(),
+                    msg: ArbitrateMsg {
+                        receiver: "creator".to_string(),
+                        creator_bps: None,
+                    },
+                },
+            ),
+            (creator.clone(), finalize),
+        ];
+        for (info, msg) in wind_down {
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+        // and listings are refunded right away, skipping the cooloff
+        let res = execute(deps.as_mut(), env, creator, cancel("listed")).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(100, "tokens"),
+            })]
+        );
+        for id in ["listed", "pending", "fulfilled", "disputed"] {
+            assert!(!ESCROWS.has(&deps.storage, id));
+        }
+    }
+
+    #[test]
+    fn arbiters_report_their_load_as_escrows_come_and_go() {
+        let mut deps = setup();
+        let env = mock_env();
+        for address in ["arbitrate", "backup"] {
+            let msg = ExecuteMsg::AddArbiter {
+                address: address.to_string(),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+        }
+        let creator = mock_info("creator", &[]);
+        let funded = mock_info("creator", &coins(100, UST_DENOM));
+        let create = |id: &str, arbiter: &str| {
+            ExecuteMsg::CCreate(CreateMsg {
+                arbiter: Some(arbiter.to_string()),
+                ..create_msg(id)
+            })
+        };
+        fulfilled_escrow(
+            deps.as_mut(),
+            env.clone(),
+            "disputed",
+            &coins(5000, UST_DENOM),
+        );
+        let dispute = ExecuteMsg::CReqArbitration {
+            id: "disputed".to_string(),
+            reason_code: DisputeReason::NotReceived,
+            details: None,
+        };
+        execute(deps.as_mut(), env.clone(), creator.clone(), dispute).unwrap();
+        for (id, arbiter) in [("listed", "arbitrate"), ("other", "backup")] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                funded.clone(),
+                create(id, arbiter),
+            )
+            .unwrap();
+        }
+        let arbiters = |deps: Deps, start_after: Option<&str>, sort_by| {
+            let start_after = start_after.map(String::from);
+            query_arbiters(deps, start_after, None, Some(sort_by))
+                .unwrap()
+                .arbiters
         };
-        let token_contract = String::from("my-cw20-token");
-        let info = mock_info(&token_contract, &[]);
-        let msg = ExecuteMsg::Receive(receive.clone());
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(("action", "create"), res.attributes[0]);
+        let entry =
+            |address: &str, active_escrows, in_arbitration, rulings, volume: u128| ArbiterEntry {
+                address: address.to_string(),
+                active_escrows,
+                in_arbitration,
+                rulings,
+                disputed_volume_ust: Uint128::new(volume),
+            };
 
-        // ensure the whitelist is what we expect
-        let details = query_details(deps.as_ref(), "foobar".to_string()).unwrap();
         assert_eq!(
-            details,
-            DetailsResponse {
-                id: "foobar".to_string(),
-                arbiter: String::from("arbitrate"),
-                recipient: String::from("recd"),
-                source: String::from("source"),
-                end_height: None,
-                end_time: None,
-                native_balance: vec![],
-                cw20_balance: vec![Cw20Coin {
-                    address: String::from("my-cw20-token"),
-                    amount: Uint128::new(100),
-                }],
-                cw20_whitelist: vec![String::from("other-token"), String::from("my-cw20-token")],
-            }
+            arbiters(deps.as_ref(), None, ArbiterSort::LeastLoaded),
+            vec![
+                entry("backup", 1, 0, 0, 0),
+                entry("arbitrate", 2, 1, 0, 5000)
+            ]
+        );
+        assert_eq!(
+            arbiters(deps.as_ref(), Some("backup"), ArbiterSort::LeastLoaded),
+            vec![entry("arbitrate", 2, 1, 0, 5000)]
         );
 
-        // approve it
-        let id = create.id.clone();
-        let info = mock_info(&create.arbiter, &[]);
-        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap();
-        assert_eq!(1, res.messages.len());
-        assert_eq!(("action", "approve"), res.attributes[0]);
-        let send_msg = Cw20ExecuteMsg::Transfer {
-            recipient: create.recipient,
-            amount: receive.amount,
+        // escrows settled without a dispute leave the load too
+        let cancel = |id: &str| ExecuteMsg::CCancel { id: id.to_string() };
+        for id in ["listed", "other"] {
+            execute(deps.as_mut(), env.clone(), creator.clone(), cancel(id)).unwrap();
+        }
+        let rule = ExecuteMsg::ElArbitrate {
+            id: "disputed".to_string(),
+            msg: ArbitrateMsg {
+                receiver: "creator".to_string(),
+                creator_bps: None,
+            },
         };
+        execute(deps.as_mut(), env, mock_info("arbitrate", &[]), rule).unwrap();
         assert_eq!(
-            res.messages[0],
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: token_contract,
-                msg: to_binary(&send_msg).unwrap(),
-                funds: vec![]
-            }))
+            arbiters(deps.as_ref(), None, ArbiterSort::MostExperienced),
+            vec![
+                entry("arbitrate", 0, 0, 1, 5000),
+                entry("backup", 0, 0, 0, 0)
+            ]
         );
-
-        // second attempt fails (not found)
-        let id = create.id.clone();
-        let info = mock_info(&create.arbiter, &[]);
-        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap_err();
-        assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
     }
 
     #[test]
-    fn add_tokens_proper() {
-        let mut tokens = GenericBalance::default();
-        tokens.add_tokens(Balance::from(vec![coin(123, "atom"), coin(789, "eth")]));
-        tokens.add_tokens(Balance::from(vec![coin(456, "atom"), coin(12, "btc")]));
+    fn listings_about_to_expire_cant_be_accepted() {
+        let mut deps = setup();
+        let env = mock_env();
+        let update = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: Some(1800),
+            peer_contracts: None,
+            max_actions_per_address: None,
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), update).unwrap();
+        let now = env.block.time.seconds();
+        let listing = |id: &str, end_time: Option<u64>| CreateMsg {
+            end_time,
+            ..create_msg(id)
+        };
+        let funded = mock_info("creator", &coins(100, UST_DENOM));
+        for msg in [
+            listing("roomy", Some(now + 1800)),
+            listing("tight", Some(now + 1799)),
+            listing("open", None),
+        ] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                funded.clone(),
+                ExecuteMsg::CCreate(msg),
+            )
+            .unwrap();
+        }
+        let accept = |id: &str| ExecuteMsg::FAccept {
+            id: id.to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let fulfiller = mock_info("fulfiller", &[]);
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            fulfiller.clone(),
+            accept("tight"),
+        )
+        .unwrap_err();
         assert_eq!(
-            tokens.native,
-            vec![coin(579, "atom"), coin(789, "eth"), coin(12, "btc")]
+            err,
+            ContractError::InsufficientTimeRemaining { remaining: 1799 }
         );
-    }
+        let listings = query_listings(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            None,
+            Some("fulfiller".to_string()),
+            false,
+            false,
+            None,
+        )
+        .unwrap()
+        .listings;
+        let tight = listings.iter().find(|l| l.id == "tight").unwrap();
+        assert_eq!(tight.blockers, Some(vec!["insufficient_time".to_string()]));
 
-    #[test]
-    fn add_cw_tokens_proper() {
-        let mut tokens = GenericBalance::default();
-        let bar_token = Addr::unchecked("bar_token");
-        let foo_token = Addr::unchecked("foo_token");
-        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
-            address: foo_token.clone(),
-            amount: Uint128::new(12345),
-        }));
-        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
-            address: bar_token.clone(),
-            amount: Uint128::new(777),
-        }));
-        tokens.add_tokens(Balance::Cw20(Cw20CoinVerified {
-            address: foo_token.clone(),
-            amount: Uint128::new(23400),
-        }));
+        // exactly at the threshold, or with no expiry at all, is fine
+        for id in ["roomy", "open"] {
+            execute(deps.as_mut(), env.clone(), fulfiller.clone(), accept(id)).unwrap();
+        }
+
+        // an earlier end_time can't cut the listing short, a later one extends it
+        let change = |end_time| ExecuteMsg::CChange(listing("tight", Some(end_time)));
+        let creator = mock_info("creator", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            change(now + 60),
+        )
+        .unwrap();
         assert_eq!(
-            tokens.cw20,
-            vec![
-                Cw20CoinVerified {
-                    address: foo_token,
-                    amount: Uint128::new(35745),
-                },
-                Cw20CoinVerified {
-                    address: bar_token,
-                    amount: Uint128::new(777),
-                }
-            ]
+            ESCROWS.load(&deps.storage, "tight").unwrap().end_time,
+            Some(now + 1799)
         );
+        execute(deps.as_mut(), env.clone(), creator, change(now + 3600)).unwrap();
+        execute(deps.as_mut(), env, fulfiller, accept("tight")).unwrap();
     }
 
     #[test]
-    fn top_up_mixed_tokens() {
-        let mut deps = mock_dependencies(&[]);
+    fn creators_tell_fulfillers_their_locale_and_utc_offset() {
+        let mut deps = setup();
+        let env = mock_env();
+        let expiry = env.block.time.seconds() + 3600;
+        let hinted = |id: &str, locale: &str, offset| CreateMsg {
+            end_time: Some(expiry),
+            creator_locale: Some(locale.to_string()),
+            creator_utc_offset_minutes: Some(offset),
+            ..create_msg(id)
+        };
+        let invalid = |field: &str| ContractError::InvalidLocaleHint {
+            field: field.to_string(),
+        };
+        let funded = mock_info("creator", &coins(100, UST_DENOM));
+        let rejected = vec![
+            (hinted("foobar", "es_VE", 0), "creator_locale"),
+            (hinted("foobar", "e", 0), "creator_locale"),
+            (hinted("foobar", "es-VE-x-caracas-1", 0), "creator_locale"),
+            (hinted("foobar", "es-VE", 841), "creator_utc_offset_minutes"),
+            (
+                hinted("foobar", "es-VE", -841),
+                "creator_utc_offset_minutes",
+            ),
+        ];
+        for (msg, field) in rejected {
+            let msg = ExecuteMsg::CCreate(msg);
+            let err = execute(deps.as_mut(), env.clone(), funded.clone(), msg).unwrap_err();
+            assert_eq!(err, invalid(field));
+        }
+        for (id, offset) in [("kiritimati", 840), ("baker", -840), ("foobar", -240)] {
+            let msg = ExecuteMsg::CCreate(hinted(id, "es-VE", offset));
+            execute(deps.as_mut(), env.clone(), funded.clone(), msg).unwrap();
+        }
 
-        // instantiate an empty contract
-        let instantiate_msg = InstantiateMsg {};
-        let info = mock_info(&String::from("anyone"), &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        // shown as given, the deadlines stay in UTC
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(details.creator_locale, Some("es-VE".to_string()));
+        assert_eq!(details.creator_utc_offset_minutes, Some(-240));
+        assert_eq!(details.expires_at_seconds, Some(expiry));
+        let listings = query_listings(
+            deps.as_ref(),
+            env.clone(),
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+        )
+        .unwrap()
+        .listings;
+        let listing = listings.iter().find(|l| l.id == "foobar").unwrap();
+        assert_eq!(listing.creator_locale, Some("es-VE".to_string()));
+        assert_eq!(listing.creator_utc_offset_minutes, Some(-240));
 
-        // only accept these tokens
-        let whitelist = vec![String::from("bar_token"), String::from("foo_token")];
+        // the creator may change them until someone accepts
+        let creator = mock_info("creator", &[]);
+        let change = |msg| ExecuteMsg::CChange(msg);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            change(hinted("foobar", "pt-BR", 900)),
+        )
+        .unwrap_err();
+        assert_eq!(err, invalid("creator_utc_offset_minutes"));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator.clone(),
+            change(hinted("foobar", "pt-BR", -180)),
+        )
+        .unwrap();
+        let details = query_details(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        assert_eq!(details.creator_locale, Some("pt-BR".to_string()));
+        assert_eq!(details.creator_utc_offset_minutes, Some(-180));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator,
+            change(create_msg("foobar")),
+        )
+        .unwrap();
+        let details = query_details(deps.as_ref(), env, "foobar".to_string()).unwrap();
+        assert_eq!(details.creator_locale, None);
+        assert_eq!(details.creator_utc_offset_minutes, None);
+    }
 
-        // create an escrow with 2 native tokens
-        let create = CreateMsg {
-            id: "foobar".to_string(),
-            arbiter: String::from("arbitrate"),
-            recipient: String::from("recd"),
-            end_time: None,
-            end_height: None,
-            cw20_whitelist: Some(whitelist),
-        };
-        let sender = String::from("source");
-        let balance = vec![coin(100, "fee"), coin(200, "stake")];
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::Create(create.clone());
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(("action", "create"), res.attributes[0]);
+    #[test]
+    fn details_v2_agrees_with_details() {
+        let mut deps = setup();
+        let env = mock_env();
+        let funds = coins(100, UST_DENOM);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("creator", &funds),
+            ExecuteMsg::CCreate(create_msg("listed")),
+        )
+        .unwrap();
+        fulfilled_escrow(deps.as_mut(), env.clone(), "fulfilled", &funds);
 
-        // top it up with 2 more native tokens
-        let extra_native = vec![coin(250, "random"), coin(300, "stake")];
-        let info = mock_info(&sender, &extra_native);
-        let top_up = ExecuteMsg::TopUp {
-            id: create.id.clone(),
-        };
-        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(("action", "top_up"), res.attributes[0]);
+        let listed = query_details_v2(deps.as_ref(), env.clone(), "listed".to_string()).unwrap();
+        assert_eq!(listed.status, EscrowStatus::Listed);
+        assert_eq!(listed.fulfiller, None);
 
-        // top up with one foreign token
-        let bar_token = String::from("bar_token");
-        let base = TopUp {
-            id: create.id.clone(),
-        };
-        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: String::from("random"),
-            amount: Uint128::new(7890),
-            msg: to_binary(&base).unwrap(),
-        });
-        let info = mock_info(&bar_token, &[]);
-        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(("action", "top_up"), res.attributes[0]);
+        let v1 = query_details(deps.as_ref(), env.clone(), "fulfilled".to_string()).unwrap();
+        let v2 = query_details_v2(deps.as_ref(), env.clone(), "fulfilled".to_string()).unwrap();
+        assert_eq!(v2.status, EscrowStatus::Fulfilled);
+        assert_eq!(v2.fulfiller, Some(v1.fulfiller));
+        assert_eq!(v2.timestamps.fulfilled, Some(env.block.time.seconds()));
+        assert_eq!(v2.fees.listing_deposit, v1.listing_deposit);
+        assert_eq!(v2.fees.listing_deposit_display, v1.listing_deposit_display);
+        assert_eq!(v2.native_balance, v1.native_balance);
+        assert_eq!(v2.native_balance_display, v1.native_balance_display);
+        assert_eq!(v2.trust.risk_score, v1.risk_score);
+        assert_eq!(v2.trust.rejected_fulfillers, v1.rejected_fulfillers);
+        assert_eq!(v2.deadlines.expires_at_seconds, v1.expires_at_seconds);
+        assert_eq!(v2.approximate_size, v1.approximate_size);
+        let deadlines = query_deadlines(deps.as_ref(), env, "fulfilled".to_string()).unwrap();
+        assert_eq!(v2.deadlines, deadlines);
+    }
 
-        // top with a foreign token not on the whitelist
-        // top up with one foreign token
-        let baz_token = String::from("baz_token");
-        let base = TopUp {
-            id: create.id.clone(),
-        };
-        let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: String::from("random"),
-            amount: Uint128::new(7890),
-            msg: to_binary(&base).unwrap(),
+    #[test]
+    fn actions_past_the_ceiling_leave_only_the_ways_out() {
+        let mut deps = setup();
+        let env = mock_env();
+        let update = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            admin: None,
+            seconds_per_block: None,
+            max_whitelist_len: None,
+            bump_cooldown_seconds: None,
+            allow_mixed_funding: None,
+            post_accept_cancel_window_seconds: None,
+            cancel_penalty_bps: None,
+            split_native_payouts: None,
+            listing_deposit: None,
+            fee_collector: None,
+            require_arbiter_ack: None,
+            arbiter_ack_window_seconds: None,
+            display_denoms: None,
+            listing_grace_period_seconds: None,
+            arbitration_extension_seconds: None,
+            max_arbitration_extensions: None,
+            kyc_contract: None,
+            allow_third_party_topup: None,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: None,
+            creator_ack_timeout_seconds: None,
+            max_open_to_all_seconds: None,
+            cancel_cooloff_seconds: None,
+            max_escrow_bytes: None,
+            mid_lifecycle_expiry: None,
+            risk_weights: None,
+            mediation_window_seconds: None,
+            min_remaining_on_accept_seconds: None,
+            peer_contracts: None,
+            max_actions_per_address: Some(3),
         });
-        let info = mock_info(&baz_token, &[]);
-        let err = execute(deps.as_mut(), mock_env(), info, top_up).unwrap_err();
-        assert_eq!(err, ContractError::NotInWhitelist {});
-
-        // top up with second foreign token
-        let foo_token = String::from("foo_token");
-        let base = TopUp {
-            id: create.id.clone(),
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), update).unwrap();
+        let funded = mock_info("creator", &coins(100, UST_DENOM));
+        for id in ["foobar", "other"] {
+            let msg = ExecuteMsg::CCreate(create_msg(id));
+            execute(deps.as_mut(), env.clone(), funded.clone(), msg).unwrap();
+        }
+        let creator = mock_info("creator", &[]);
+        let fulfiller = mock_info("fulfiller", &[]);
+        let change = |id: &str| ExecuteMsg::CChange(create_msg(id));
+        let accept = ExecuteMsg::FAccept {
+            id: "foobar".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        };
+        let unaccept = ExecuteMsg::FUnaccept {
+            id: "foobar".to_string(),
         };
+
+        // each side runs out on its own, and only on that escrow
+        for id in ["foobar", "other"] {
+            for _ in 0..3 {
+                execute(deps.as_mut(), env.clone(), creator.clone(), change(id)).unwrap();
+            }
+            let err = execute(deps.as_mut(), env.clone(), creator.clone(), change(id)).unwrap_err();
+            assert_eq!(err, ContractError::ActionLimitReached {});
+        }
+        // a cw20 top-up counts the same
         let top_up = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: String::from("random"),
-            amount: Uint128::new(888),
-            msg: to_binary(&base).unwrap(),
+            sender: "creator".to_string(),
+            amount: Uint128::new(10),
+            msg: to_binary(&ReceiveMsg::TopUp {
+                id: "other".to_string(),
+            })
+            .unwrap(),
         });
-        let info = mock_info(&foo_token, &[]);
-        let res = execute(deps.as_mut(), mock_env(), info, top_up).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(("action", "top_up"), res.attributes[0]);
-
-        // approve it
-        let id = create.id.clone();
-        let info = mock_info(&create.arbiter, &[]);
-        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Approve { id }).unwrap();
-        assert_eq!(("action", "approve"), res.attributes[0]);
-        assert_eq!(3, res.messages.len());
-
-        // first message releases all native coins
-        assert_eq!(
-            res.messages[0],
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: create.recipient.clone(),
-                amount: vec![coin(100, "fee"), coin(500, "stake"), coin(250, "random")],
-            }))
-        );
+        let err = execute(deps.as_mut(), env.clone(), mock_info("token", &[]), top_up);
+        assert_eq!(err.unwrap_err(), ContractError::ActionLimitReached {});
+        for msg in [accept.clone(), unaccept.clone(), accept] {
+            execute(deps.as_mut(), env.clone(), fulfiller.clone(), msg).unwrap();
+        }
+        let err = execute(deps.as_mut(), env.clone(), fulfiller.clone(), unaccept).unwrap_err();
+        assert_eq!(err, ContractError::ActionLimitReached {});
+        let details = query_details_v2(deps.as_ref(), env.clone(), "foobar".to_string()).unwrap();
+        let counts: Vec<_> = details
+            .action_counts
+            .iter()
+            .map(|count| (count.address.as_str(), count.actions))
+            .collect();
+        assert_eq!(counts, vec![("creator", 3), ("fulfiller", 3)]);
 
-        // second one release bar cw20 token
-        let send_msg = Cw20ExecuteMsg::Transfer {
-            recipient: create.recipient.clone(),
-            amount: Uint128::new(7890),
+        // fulfilling, disputing and refunding still go through
+        let complete = ExecuteMsg::FComplete {
+            id: "foobar".to_string(),
+            delivered_fiat_amount: None,
         };
-        assert_eq!(
-            res.messages[1],
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: bar_token,
-                msg: to_binary(&send_msg).unwrap(),
-                funds: vec![]
-            }))
-        );
-
-        // third one release foo cw20 token
-        let send_msg = Cw20ExecuteMsg::Transfer {
-            recipient: create.recipient,
-            amount: Uint128::new(888),
+        execute(deps.as_mut(), env.clone(), fulfiller, complete).unwrap();
+        let dispute = ExecuteMsg::CReqArbitration {
+            id: "foobar".to_string(),
+            reason_code: DisputeReason::NotReceived,
+            details: None,
+        };
+        execute(deps.as_mut(), env.clone(), creator.clone(), dispute).unwrap();
+        let cancel = ExecuteMsg::CCancel {
+            id: "other".to_string(),
         };
+        let res = execute(deps.as_mut(), env, creator, cancel).unwrap();
         assert_eq!(
-            res.messages[2],
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: foo_token,
-                msg: to_binary(&send_msg).unwrap(),
-                funds: vec![]
-            }))
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(100, UST_DENOM),
+            })]
         );
     }
 
     #[test]
-    fn creator_calls_the_creator_complete_function() {
-        // We create a mutable variable named deps and set it equal to the state returned by the function named mock_dependencies
-        let mut deps = mock_dependencies(&[]);
-
-        // instantiate an empty contract
-        let instantiate_msg = InstantiateMsg {};
-        // Our contract is instantiated by ElLib
-        let info = mock_info(&String::from("ElLib"), &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        // create an escrow
-        let create = CreateMsg {
+    fn disputes_close_with_the_dispute_window() {
+        let mut deps = escrow_at(Stage::Fulfilled);
+        let deadline = query_deadlines(deps.as_ref(), mock_env(), "foobar".into())
+            .unwrap()
+            .fulfill_deadline
+            .unwrap();
+        let dispute = ExecuteMsg::CReqArbitration {
             id: "foobar".to_string(),
-            arbiter: String::from("arbitrate"),
-            recipient: String::from("fulfiller"),
-            end_time: None,
-            end_height: Some(123456),
-            cw20_whitelist: None,
+            reason_code: DisputeReason::NotReceived,
+            details: None,
         };
-        // We set the sender to "creator"
-        let sender = String::from("creator");
-        // We give the sender a balance of 100 tokens
-        let balance = coins(100, "tokens");
-        let info = mock_info(&sender, &balance);
-        // We called the Execute Message: Create and give it a copy of our CreateMsg
-        let msg = ExecuteMsg::Create(create.clone());
-        // We call the execute function with our ExecuteMsg::Create and unwrap it's result
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        // We make sure no error messages are returned
-        assert_eq!(0, res.messages.len());
-        // We check that the tuple with "action" and "create" are returned, signifying execute_create returned Ok
-        assert_eq!(("action", "create"), res.attributes[0]);
+        let creator = mock_info("creator", &[]);
+        let mut env = mock_env();
 
-        // ensure the details is what we expect
-        let details = query_details(deps.as_ref(), "foobar".to_string()).unwrap();
-        assert_eq!(
-            details,
-            DetailsResponse {
-                id: "foobar".to_string(),
-                arbiter: String::from("arbitrate"),
-                recipient: String::from("fulfiller"),
-                // Check that "creator" is the source
-                source: String::from("creator"),
-                end_height: Some(123456),
-                end_time: None,
-                native_balance: balance.clone(),
-                cw20_balance: vec![],
-                cw20_whitelist: vec![],
-            }
-        );
-
-        /* Here we have the fulfiller try to call the creator complete method, which would be fraud */
-        // We get the contract id
-        let id = create.id.clone();
-        // We make a message coming from the fulfiller
-        let info = mock_info(&create.recipient, &[]);
-        // Get the results of calling execute with the fulfiller as the message signer
-        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CreatorComplete { id }).unwrap_err();
-        // We check that the response is 
-        assert_eq!(err, ContractError::Unauthorized {});
-
-        /* Here is where we call the CreatorComplete Execution Method */
-        // We get the id of the contract we've created
-        let id = create.id.clone();
-        // We make our message info come from the creator
-        let info = mock_info(&sender, &[]);
-        // We send an ExecuteMsg of type CreatorComplete
-        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CreatorComplete { id }).unwrap();
-        // We check that the response has a single message
-        assert_eq!(1, res.messages.len());
-        // We check the response attributes match the ones from creator_complete
-        assert_eq!(("action", "creator_complete"), res.attributes[0]);
+        env.block.time = Timestamp::from_seconds(deadline + 1);
+        let err = execute(deps.as_mut(), env.clone(), creator.clone(), dispute.clone());
         assert_eq!(
-            res.messages[0],
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: create.recipient,
-                amount: balance,
-            }))
+            err.unwrap_err(),
+            ContractError::DisputeWindowClosed { deadline }
         );
 
-        // second attempt fails (not found)
-        let id = create.id.clone();
-        let info = mock_info(&sender, &[]);
-        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CreatorComplete { id }).unwrap_err();
-        assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+        // up to the deadline itself the creator may still dispute
+        env.block.time = Timestamp::from_seconds(deadline);
+        execute(deps.as_mut(), env, creator, dispute).unwrap();
     }
 }
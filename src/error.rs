@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Decimal, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -9,18 +9,63 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("The contract can't call itself")]
+    SelfCall {},
+
+    #[error("No escrow {id}")]
+    EscrowNotFound { id: String },
+
+    #[error("Escrow {id} is finished, its last step was {status}")]
+    EscrowFinished { id: String, status: String },
+
     #[error("Contract is not listed as available for acceptance")]
     NotListed {},
 
     #[error("Sender Trust Metrics Not High Enough To Accept This Contract")]
     TrustMetricsInsufficient {},
 
-    #[error("Escrow has already been accepted")]
-    AlreadyAccepted {},
+    #[error("The escrow's creator doesn't meet the required creator metrics")]
+    CreatorMetricsInsufficient {},
+
+    #[error("Escrow {id} has already been accepted by {accepted_by}")]
+    AlreadyAccepted { id: String, accepted_by: String },
+
+    #[error("The exchange rate changed to {current}")]
+    RateChanged { current: Decimal },
+
+    #[error("Escrow has already been fulfilled")]
+    AlreadyFulfilled {},
 
     #[error("Escrow can not be unaccepted now")]
     CantUnaccept {},
 
+    #[error("Only an accepted escrow's fulfiller can be rejected, before fulfilling")]
+    CantReject {},
+
+    #[error("The creator rejected this fulfiller for the escrow")]
+    FulfillerRejected {},
+
+    #[error("The creator hasn't acked the fulfiller yet")]
+    AwaitingCreatorAck {},
+
+    #[error("The escrow isn't waiting for its creator to ack the fulfiller")]
+    NotAwaitingCreatorAck {},
+
+    #[error("The time to ack the fulfiller has run out")]
+    CreatorAckExpired {},
+
+    #[error("The escrow was canceled, its creator may undo that until {until}")]
+    PendingCancel { until: u64 },
+
+    #[error("The escrow isn't waiting out a cancellation")]
+    NotPendingCancel {},
+
+    #[error("The cancellation can't be finalized before {until}")]
+    CancelCooloffActive { until: u64 },
+
+    #[error("The time to undo the cancellation has run out")]
+    CancelCooloffOver {},
+
     #[error("The escrow either hasn't been accepted, or has already been fulfilled")]
     CantFulfill {},
 
@@ -33,6 +78,9 @@ pub enum ContractError {
     #[error("Only accepts tokens in the cw20_whitelist")]
     NotInWhitelist {},
 
+    #[error("Escrows funded with a cw20 token only take top-ups in tokens")]
+    MixedFunding {},
+
     #[error("Escrow is expired")]
     Expired {},
 
@@ -41,4 +89,241 @@ pub enum ContractError {
 
     #[error("Escrow id already in use")]
     AlreadyInUse {},
+
+    #[error("The creator's confirmation window hasn't elapsed yet")]
+    NotTimedOut {},
+
+    #[error("The escrow is not in arbitration")]
+    NotInArbitration {},
+
+    #[error("The escrow is in arbitration, only the arbiter can settle it")]
+    InArbitration {},
+
+    #[error("Config field {field} must not be zero")]
+    InvalidConfig { field: String },
+
+    #[error("The dispute window must not be zero")]
+    InvalidDisputeWindow {},
+
+    #[error("The dispute window closed at {deadline}, the fulfiller may claim the funds")]
+    DisputeWindowClosed { deadline: u64 },
+
+    #[error("The listing can't be bumped again before {available_at}")]
+    BumpTooSoon { available_at: u64 },
+
+    #[error("A listing can be opened to everyone for 1 to {max} seconds")]
+    InvalidOpenToAllDuration { max: u64 },
+
+    #[error("The listing can't be accepted before {until}")]
+    ListingInGracePeriod { until: u64 },
+
+    #[error("The listing expires in {remaining} seconds, too soon to fulfill it")]
+    InsufficientTimeRemaining { remaining: u64 },
+
+    #[error("The escrow goes live at {starts_at}")]
+    NotYetActive { starts_at: u64 },
+
+    #[error("Arbitration is disabled for this escrow")]
+    ArbitrationDisabled {},
+
+    #[error("The arbiter can't fulfill an escrow they arbitrate")]
+    ArbiterCannotFulfill {},
+
+    #[error("The arbiter is a party to this escrow, disputes resolve once the deadline passes")]
+    ArbiterIsParty {},
+
+    #[error("The arbiter hasn't taken the role yet")]
+    PendingArbiterAck {},
+
+    #[error("The escrow isn't waiting for its arbiter")]
+    NotPendingArbiterAck {},
+
+    #[error("The time to take the arbiter role has run out")]
+    ArbiterAckExpired {},
+
+    #[error("The arbiter recused themselves, the admin has to reassign the escrow")]
+    ArbiterRecused {},
+
+    #[error("An arbiter is required unless arbitration is disabled")]
+    MissingArbiter {},
+
+    #[error("The arbitration deadline has passed")]
+    ArbitrationExpired {},
+
+    #[error("The dispute is in mediation until {until}, the parties may still settle it")]
+    InMediation { until: u64 },
+
+    #[error("The dispute is not in mediation")]
+    NotInMediation {},
+
+    #[error("No settlement has been proposed")]
+    NoSettlementProposed {},
+
+    #[error("The settlement is yours, the other party accepts it")]
+    OwnSettlement {},
+
+    #[error("The proposed settlement changed to {creator_bps} basis points for the creator")]
+    SettlementChanged { creator_bps: u64 },
+
+    #[error("The arbitration deadline hasn't passed yet")]
+    ArbitrationNotExpired {},
+
+    #[error("Only accepted escrows that expired unfulfilled under HardExpire can be refunded")]
+    NotHardExpired {},
+
+    #[error("The arbitration deadline can be extended at most {max} times")]
+    TooManyExtensions { max: u32 },
+
+    #[error("The request for information is empty")]
+    EmptyInfoRequest {},
+
+    #[error("Funds can only be released to the creator or the fulfiller")]
+    NotAParty {},
+
+    #[error("Basis points must be between 0 and 10000")]
+    InvalidBps {},
+
+    #[error("An attestation from the KYC contract is required")]
+    KycRequired {},
+
+    #[error("The KYC contract can't be queried: {reason}")]
+    KycUnavailable { reason: String },
+
+    #[error("Address is blacklisted")]
+    AddressBlacklisted {},
+
+    #[error("end_height and end_time are too far apart")]
+    InconsistentExpiry {},
+
+    #[error("start_time is after end_time")]
+    StartAfterEnd {},
+
+    #[error("Escrows expire within {max_seconds} seconds of their creation")]
+    LifetimeExceeded { max_seconds: u64 },
+
+    #[error("No trust preset named {name}")]
+    UnknownPreset { name: String },
+
+    #[error("Arbiter is not registered")]
+    ArbiterNotRegistered {},
+
+    #[error("At most {max} entries can be seeded at once")]
+    TooManySeedEntries { max: usize },
+
+    #[error("A batch creates between 1 and {max} escrows")]
+    InvalidBatchSize { max: usize },
+
+    #[error("The batch amounts must add up to exactly the funds sent")]
+    BatchFundsMismatch {},
+
+    #[error("Instantiating takes no funds, escrows are funded by creating them")]
+    InstantiateWithFunds {},
+
+    #[error("Batch item {index}: {error}")]
+    BatchItem {
+        index: usize,
+        error: Box<ContractError>,
+    },
+
+    #[error("More than {max} escrows to add up")]
+    TooManyEscrows { max: usize },
+
+    #[error("Give either a cw20 token or a native denom to recover")]
+    InvalidStrayAsset {},
+
+    #[error("The contract's balance of {asset} can't be queried: {reason}")]
+    StrayBalanceUnavailable { asset: String, reason: String },
+
+    #[error("Only {surplus} of {asset} is held outside the escrows")]
+    ExceedsStraySurplus { asset: String, surplus: Uint128 },
+
+    #[error("{address} is seeded more than once")]
+    DuplicateSeed { address: String },
+
+    #[error("Seeding trust metrics is closed")]
+    SeedingClosed {},
+
+    #[error("The contract is sunsetting, open escrows may only be settled or refunded")]
+    ContractSunsetting {},
+
+    #[error("{peer} is not a peer contract")]
+    UnknownPeer { peer: String },
+
+    #[error("Trust metrics were already imported from {peer}")]
+    MetricsAlreadyImported { peer: String },
+
+    #[error("{peer} has no trust metrics to import")]
+    NothingToImport { peer: String },
+
+    #[error("The peer contract {peer} can't be queried: {reason}")]
+    PeerUnavailable { peer: String, reason: String },
+
+    #[error("The trust metrics {peer} reported are implausible: {reason}")]
+    ImplausiblePeerMetrics { peer: String, reason: String },
+
+    #[error("Description is longer than {max} bytes")]
+    DescriptionTooLong { max: usize },
+
+    #[error("Tag {tag} isn't 2 to 16 lowercase letters, digits or dashes")]
+    InvalidTag { tag: String },
+
+    #[error("An escrow carries at most {max} tags")]
+    TooManyTags { max: usize },
+
+    #[error("{field} isn't a valid locale hint, see CreateMsg")]
+    InvalidLocaleHint { field: String },
+
+    #[error("Payout details are longer than {max} bytes")]
+    PayoutDetailsTooLarge { max: usize },
+
+    #[error("Fiat currency must be a 3 letter ISO 4217 code")]
+    InvalidCurrency {},
+
+    #[error("Sent a zero amount of {denom}")]
+    ZeroAmount { denom: String },
+
+    #[error("An escrow holds at most {max} cw20 tokens")]
+    TooManyCw20Balances { max: usize },
+
+    #[error("That's over the limit of {limit} uusd in 24 hours, {used} uusd is already used")]
+    DailyLimitExceeded { used: Uint128, limit: Uint128 },
+
+    #[error("The contract holds as many escrows as it may, try again once some settle")]
+    CapacityReached {},
+
+    #[error("An escrow takes top-ups from at most {max} others than its creator")]
+    TooManyContributors { max: usize },
+
+    #[error("A creator can reject at most {max} fulfillers of an escrow")]
+    TooManyRejections { max: usize },
+
+    #[error("The escrow would grow past the configured size budget")]
+    EscrowSizeLimit {},
+
+    #[error("The sender took as many actions on this escrow as allowed, only settling, refunding and disputing remain")]
+    ActionLimitReached {},
+
+    #[error("Escrow balance is inconsistent: {reason}")]
+    InconsistentBalance { reason: String },
+
+    #[error("Unreadable receive message from token {token}: {reason}")]
+    InvalidReceiveMsg { token: String, reason: String },
+
+    #[error("The cw20 whitelist can have at most {max} tokens")]
+    WhitelistTooLarge { max: u32 },
+
+    #[error("Listing takes a deposit of {deposit} on top of the escrowed funds")]
+    ListingDepositRequired { deposit: String },
+
+    #[error("Only the fulfillers the creator allowed may accept this escrow")]
+    NotOnFulfillerAllowlist {},
+
+    #[error("The fulfiller allowlist can't be empty, leave it unset to allow anyone")]
+    EmptyFulfillerAllowlist {},
+
+    #[error("The fulfiller allowlist can have at most {max} addresses")]
+    FulfillerAllowlistTooLarge { max: usize },
+
+    #[error("The delivered amount strays too far from the listed exchange rate, set accept_rate_deviation to complete anyway")]
+    RateDeviation {},
 }
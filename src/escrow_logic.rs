@@ -0,0 +1,32 @@
+//! The escrow rules for contracts embedding them, built with the library feature so this
+//! crate exports no entry points of its own. The embedding contract calls
+//! contract::{instantiate, execute, query} from its own entry points, or the pieces
+//! below to compose its own messages. There is no migrate to call: nothing converts
+//! escrows stored by an earlier version yet.
+//!
+//! Everything re-exported here is part of the crate's public API and follows semver:
+//! a breaking change to any of it bumps the minor version while we're below 1.0.
+//! Whatever isn't reachable from here or contract's handlers may change in any release.
+
+/// Checks a CreateMsg as CCreate would, without storing anything
+pub use crate::contract::{validate_create, ValidCreate};
+
+/// Whether an address may accept an escrow, the first reason it may not, or all of them
+pub use crate::contract::{accept_blockers, assert_can_accept, get_trust_metrics};
+
+/// Who a release pays what, and the messages paying it out under the stored config
+pub use crate::contract::{escrow_payout_messages, payout_messages, release_plan};
+pub use crate::payments::{build_transfers, build_transfers_with_hook};
+
+/// How risky a listing is for fulfillers, as Details and Listings report it
+pub use crate::state::{risk_score, RiskWeights};
+
+/// The stored escrows and config. Write escrows only through save_escrow and remove_escrow,
+/// which keep the listings, counters and totals in step.
+pub use crate::state::{
+    load_active_or_explain, remove_escrow, save_escrow, Config, Dispute, DisputeReason, Escrow,
+    ExpirySource, FundingSource, GenericBalance, HistoryEntry, SettlementProposal, TrustMetrics,
+    CONFIG, ESCROWS, HISTORY,
+};
+
+pub use crate::error::ContractError;
@@ -0,0 +1,335 @@
+use cosmwasm_std::{attr, Addr, Attribute, Response, Uint128};
+
+use crate::state::Escrow;
+
+/// The attribute key for an amount of the denom, or the cw20 token address
+pub fn amount_key(denom: &str) -> String {
+    format!("amount_{}", denom)
+}
+
+/// What an escrow handler responds with. The attributes always come in the same order:
+/// action, escrow_id, whatever the action adds, fingerprint. Indexers rely on the keys,
+/// so handlers respond through these constructors rather than adding attributes of their own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EscrowEvent {
+    attributes: Vec<Attribute>,
+}
+
+impl EscrowEvent {
+    fn new(action: &str, id: &str, escrow: &Escrow, details: Vec<Attribute>) -> Self {
+        let mut attributes = vec![attr("action", action), attr("escrow_id", id)];
+        attributes.extend(details);
+        attributes.push(attr("fingerprint", &escrow.fingerprint));
+        EscrowEvent { attributes }
+    }
+
+    pub fn created(id: &str, escrow: &Escrow) -> Self {
+        Self::new("create", id, escrow, vec![])
+    }
+
+    pub fn cloned(id: &str, escrow: &Escrow, source_id: &str) -> Self {
+        Self::new("create", id, escrow, vec![attr("source_id", source_id)])
+    }
+
+    pub fn topped_up(id: &str, escrow: &Escrow) -> Self {
+        Self::new("top_up", id, escrow, vec![])
+    }
+
+    /// lapsed is the fulfiller whose acceptance the creator never acked
+    pub fn accepted(id: &str, escrow: &Escrow, lapsed: Option<&Addr>) -> Self {
+        let mut details = vec![];
+        details.extend(lapsed.map(|lapsed| attr("lapsed", lapsed)));
+        if escrow.is_awaiting_creator_ack {
+            details.push(attr("awaiting_creator_ack", "true"));
+        }
+        Self::new("accept", id, escrow, details)
+    }
+
+    pub fn unaccepted(id: &str, escrow: &Escrow) -> Self {
+        Self::new("unaccept", id, escrow, vec![])
+    }
+
+    pub fn rejected(id: &str, escrow: &Escrow, rejected: &Addr, reason: Option<String>) -> Self {
+        let mut details = vec![attr("rejected", rejected)];
+        details.extend(reason.map(|reason| attr("reason", reason)));
+        Self::new("reject", id, escrow, details)
+    }
+
+    pub fn fulfiller_acked(id: &str, escrow: &Escrow) -> Self {
+        Self::new(
+            "ack_fulfiller",
+            id,
+            escrow,
+            vec![attr("fulfiller", &escrow.fulfiller)],
+        )
+    }
+
+    pub fn changed(id: &str, escrow: &Escrow) -> Self {
+        Self::new("change", id, escrow, vec![])
+    }
+
+    pub fn bumped(id: &str, escrow: &Escrow) -> Self {
+        Self::new("bump", id, escrow, vec![])
+    }
+
+    pub fn opened_to_all(id: &str, escrow: &Escrow, until: u64) -> Self {
+        let details = vec![attr("until", until.to_string())];
+        Self::new("open_to_all", id, escrow, details)
+    }
+
+    pub fn payout_details_set(id: &str, escrow: &Escrow) -> Self {
+        Self::new("set_payout_details", id, escrow, vec![])
+    }
+
+    /// deposit_to is who got the listing deposit, if there was one
+    pub fn canceled(id: &str, escrow: &Escrow, deposit_to: Option<&Addr>) -> Self {
+        let details = deposit_to.map(|to| attr("deposit_to", to));
+        Self::new("cancel", id, escrow, details.into_iter().collect())
+    }
+
+    /// Canceled within the post accept cancel window, the fulfiller got the penalty
+    pub fn canceled_with_penalty(id: &str, escrow: &Escrow, penalty_bps: u64) -> Self {
+        let details = vec![
+            attr("penalty_bps", penalty_bps.to_string()),
+            attr("penalty_to", &escrow.fulfiller),
+        ];
+        Self::new("cancel", id, escrow, details)
+    }
+
+    pub fn cancel_requested(id: &str, escrow: &Escrow, until: u64) -> Self {
+        let details = vec![attr("pending_until", until.to_string())];
+        Self::new("request_cancel", id, escrow, details)
+    }
+
+    pub fn cancel_undone(id: &str, escrow: &Escrow) -> Self {
+        Self::new("undo_cancel", id, escrow, vec![])
+    }
+
+    pub fn fulfilled(id: &str, escrow: &Escrow, dispute_deadline: u64) -> Self {
+        let details = vec![attr("dispute_deadline", dispute_deadline.to_string())];
+        Self::new("fulfill", id, escrow, details)
+    }
+
+    /// Fulfilled by someone the creator trusts enough to pay right away
+    pub fn auto_completed(id: &str, escrow: &Escrow) -> Self {
+        let details = vec![
+            attr("auto_completed", "true"),
+            attr("to", &escrow.fulfiller),
+        ];
+        Self::new("fulfill", id, escrow, details)
+    }
+
+    pub fn completed(id: &str, escrow: &Escrow, release_bps: Option<u64>) -> Self {
+        let mut details = vec![attr("to", &escrow.fulfiller)];
+        details.extend(release_bps.map(|bps| attr("release_bps", bps.to_string())));
+        Self::new("creator_complete", id, escrow, details)
+    }
+
+    pub fn claimed_timed_out(id: &str, escrow: &Escrow) -> Self {
+        let details = vec![attr("to", &escrow.fulfiller)];
+        Self::new("claim_timed_out", id, escrow, details)
+    }
+
+    pub fn arbitration_requested(id: &str, escrow: &Escrow, reason_code: &str) -> Self {
+        let details = vec![attr("reason_code", reason_code)];
+        Self::new("request_arbitration", id, escrow, details)
+    }
+
+    pub fn arbiter_role_acked(id: &str, escrow: &Escrow) -> Self {
+        Self::new("ack_arbiter_role", id, escrow, vec![])
+    }
+
+    /// The parties' clients watch for this one
+    pub fn info_requested(id: &str, escrow: &Escrow, request: &str, deadline: u64) -> Self {
+        let details = vec![
+            attr("creator", &escrow.creator),
+            attr("fulfiller", &escrow.fulfiller),
+            attr("request", request),
+            attr("arbitration_deadline", deadline.to_string()),
+        ];
+        Self::new("request_info", id, escrow, details)
+    }
+
+    pub fn recused(id: &str, escrow: &Escrow, arbiter: &Addr) -> Self {
+        let details = vec![
+            attr("arbiter", arbiter),
+            attr("in_arbitration", escrow.is_in_arbitration.to_string()),
+            attr("needs_reassignment", "true"),
+        ];
+        Self::new("recuse", id, escrow, details)
+    }
+
+    pub fn arbiter_reassigned(id: &str, escrow: &Escrow, old_arbiter: &Addr) -> Self {
+        let new_arbiter = escrow
+            .arbiter
+            .as_ref()
+            .map(Addr::as_str)
+            .unwrap_or_default();
+        let details = vec![
+            attr("old_arbiter", old_arbiter),
+            attr("new_arbiter", new_arbiter),
+        ];
+        Self::new("reassign_arbiter", id, escrow, details)
+    }
+
+    pub fn arbitrated(id: &str, escrow: &Escrow, to: &str) -> Self {
+        Self::new("arbitrate", id, escrow, vec![attr("to", to)])
+    }
+
+    /// The other party has until mediation ends to accept it
+    pub fn settlement_proposed(id: &str, escrow: &Escrow, creator_bps: u64) -> Self {
+        let details = vec![attr("creator_bps", creator_bps.to_string())];
+        Self::new("propose_settlement", id, escrow, details)
+    }
+
+    pub fn settled(id: &str, escrow: &Escrow, creator_bps: u64) -> Self {
+        let details = vec![attr("creator_bps", creator_bps.to_string())];
+        Self::new("accept_settlement", id, escrow, details)
+    }
+
+    /// The arbiter missed the deadline and the creator was refunded
+    pub fn expired_arbitration_resolved(id: &str, escrow: &Escrow) -> Self {
+        let details = vec![attr("to", &escrow.creator)];
+        Self::new("resolve_expired_arbitration", id, escrow, details)
+    }
+
+    /// The escrow expired before the fulfiller reported and the creator was refunded
+    pub fn refunded_expired(id: &str, escrow: &Escrow) -> Self {
+        let details = vec![attr("to", &escrow.creator)];
+        Self::new("refund_expired", id, escrow, details)
+    }
+
+    pub fn creator_feedback(id: &str, escrow: &Escrow) -> Self {
+        Self::new("creator_feedback", id, escrow, vec![])
+    }
+
+    pub fn fulfiller_feedback(id: &str, escrow: &Escrow) -> Self {
+        Self::new("fulfiller_feedback", id, escrow, vec![])
+    }
+
+    pub fn attributes(self) -> Vec<Attribute> {
+        self.attributes
+    }
+
+    pub fn response(self) -> Response {
+        Response::new().add_attributes(self.attributes)
+    }
+}
+
+/// What the handlers not about a single escrow respond with: the admin's, and those
+/// covering several escrows, which list an escrow_id and fingerprint for each.
+/// Always the action first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractEvent {
+    attributes: Vec<Attribute>,
+}
+
+impl ContractEvent {
+    fn new(action: &str, details: Vec<Attribute>) -> Self {
+        let mut attributes = vec![attr("action", action)];
+        attributes.extend(details);
+        ContractEvent { attributes }
+    }
+
+    /// Escrow ids with their fingerprints
+    fn escrows(action: &str, escrows: &[(String, String)]) -> Self {
+        let details = escrows
+            .iter()
+            .flat_map(|(id, fingerprint)| {
+                vec![attr("escrow_id", id), attr("fingerprint", fingerprint)]
+            })
+            .collect();
+        Self::new(action, details)
+    }
+
+    pub fn batch_created(escrows: &[(String, String)]) -> Self {
+        Self::escrows("create_batch", escrows)
+    }
+
+    /// Those waiting out the cancel cooloff come after the canceled ones, and where to
+    /// go on from when the page was full
+    pub fn canceled_all(
+        canceled: &[(String, String)],
+        pending: &[(String, String)],
+        next_start_after: Option<String>,
+    ) -> Self {
+        let mut event = Self::escrows("cancel_all", canceled);
+        for (id, fingerprint) in pending {
+            event.attributes.push(attr("pending_escrow_id", id));
+            event
+                .attributes
+                .push(attr("pending_fingerprint", fingerprint));
+        }
+        if let Some(id) = next_start_after {
+            event.attributes.push(attr("next_start_after", id));
+        }
+        event
+    }
+
+    pub fn blacklisted(address: &Addr) -> Self {
+        Self::new("add_to_blacklist", vec![attr("address", address)])
+    }
+
+    pub fn unblacklisted(address: &Addr) -> Self {
+        Self::new("remove_from_blacklist", vec![attr("address", address)])
+    }
+
+    pub fn trust_metrics_seeded(count: usize) -> Self {
+        Self::new("seed_trust_metrics", vec![attr("count", count.to_string())])
+    }
+
+    pub fn metrics_imported(wallet: &Addr, peer: &Addr) -> Self {
+        let details = vec![attr("wallet", wallet.as_str()), attr("peer", peer.as_str())];
+        Self::new("import_metrics", details)
+    }
+
+    pub fn seeding_closed() -> Self {
+        Self::new("close_seeding", vec![])
+    }
+
+    pub fn sunset_begun() -> Self {
+        Self::new("begin_sunset", vec![])
+    }
+
+    pub fn config_updated() -> Self {
+        Self::new("update_config", vec![])
+    }
+
+    pub fn locked_reconciled(escrows: usize, drifted: bool) -> Self {
+        let details = vec![
+            attr("escrows", escrows.to_string()),
+            attr("drifted", drifted.to_string()),
+        ];
+        Self::new("reconcile_locked", details)
+    }
+
+    /// denom is the cw20 token address for tokens
+    pub fn stray_recovered(denom: &str, to: &Addr, amount: Uint128) -> Self {
+        let details = vec![attr("to", to), attr(amount_key(denom), amount)];
+        Self::new("recover_stray", details)
+    }
+
+    pub fn arbiter_added(address: &Addr) -> Self {
+        Self::new("add_arbiter", vec![attr("address", address)])
+    }
+
+    pub fn arbiter_removed(address: &Addr) -> Self {
+        Self::new("remove_arbiter", vec![attr("address", address)])
+    }
+
+    pub fn trust_preset_set(name: &str) -> Self {
+        Self::new("set_trust_preset", vec![attr("name", name)])
+    }
+
+    pub fn trust_preset_removed(name: &str) -> Self {
+        Self::new("remove_trust_preset", vec![attr("name", name)])
+    }
+
+    pub fn attributes(self) -> Vec<Attribute> {
+        self.attributes
+    }
+
+    pub fn response(self) -> Response {
+        Response::new().add_attributes(self.attributes)
+    }
+}
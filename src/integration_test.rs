@@ -1,15 +1,84 @@
 #![cfg(test)]
 
 use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
-use cosmwasm_std::{coins, to_binary, Addr, Empty, Uint128};
-use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg};
+use cosmwasm_std::{
+    coins, to_binary, Addr, Api, Binary, CanonicalAddr, Decimal, Deps, DepsMut, Empty, Env,
+    MessageInfo, RecoverPubkeyError, Reply, Response, StdError, StdResult, SubMsg, Uint128,
+    VerificationError, WasmMsg,
+};
+use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw_multi_test::{App, BankKeeper, Contract, ContractWrapper, Executor};
+use cw_storage_plus::Item;
 
+use crate::kyc::{IsVerifiedResponse, KycQueryMsg};
 use crate::msg::{CreateMsg, DetailsResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+use crate::state::{FundingSource, TrustMetrics};
+use crate::ContractError;
+
+/// cw-multi-test names contracts "Contract #0", which MockApi rejects as not normalized,
+/// so address validation only checks the format here and delegates everything else.
+struct AppApi(MockApi);
+
+impl Api for AppApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.0.addr_canonicalize(human)?;
+        Ok(Addr::unchecked(human))
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        self.0.addr_canonicalize(human)
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        self.0.addr_humanize(canonical)
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.0
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
 
 fn mock_app() -> App {
     let env = mock_env();
-    let api = MockApi::default();
+    let api = AppApi(MockApi::default());
     let bank = BankKeeper::new();
 
     App::new(api, env.block, bank, MockStorage::new())
@@ -33,8 +102,168 @@ pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
     Box::new(contract)
 }
 
+/// What the escrow answered when the hostile token called back
+const CALLBACK_ERROR: Item<String> = Item::new("callback_error");
+
+/// A cw20 token that keeps no balances and, when paid out to, tries to accept the escrow
+/// "demo" from within the transfer
+pub fn contract_hostile_cw20() -> Box<dyn Contract<Empty>> {
+    fn instantiate(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn execute(_: DepsMut, _: Env, info: MessageInfo, msg: Cw20ExecuteMsg) -> StdResult<Response> {
+        match msg {
+            Cw20ExecuteMsg::Send {
+                contract,
+                amount,
+                msg,
+            } => {
+                let receive = Cw20ReceiveMsg {
+                    sender: info.sender.into(),
+                    amount,
+                    msg,
+                };
+                Ok(Response::new().add_message(receive.into_cosmos_msg(contract)?))
+            }
+            Cw20ExecuteMsg::Transfer { .. } => {
+                let callback = WasmMsg::Execute {
+                    contract_addr: info.sender.into(),
+                    msg: to_binary(&ExecuteMsg::FAccept {
+                        id: "demo".to_string(),
+                        expected_exchange_rate: None,
+                        required_creator_metrics: None,
+                        payout_memo: false,
+                    })?,
+                    funds: vec![],
+                };
+                Ok(Response::new().add_submessage(SubMsg::reply_on_error(callback, 0)))
+            }
+            _ => Err(StdError::generic_err("unsupported")),
+        }
+    }
+
+    fn reply(deps: DepsMut, _: Env, reply: Reply) -> StdResult<Response> {
+        let error = reply.result.unwrap_err();
+        CALLBACK_ERROR.save(deps.storage, &error)?;
+        Ok(Response::new())
+    }
+
+    fn query(deps: Deps, _: Env, _: Empty) -> StdResult<Binary> {
+        to_binary(&CALLBACK_ERROR.may_load(deps.storage)?)
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply))
+}
+
+/// Who the mock KYC contract has verified
+const VERIFIED: Item<Vec<String>> = Item::new("verified");
+
+/// A KYC contract verifying the addresses it was instantiated with
+pub fn contract_kyc() -> Box<dyn Contract<Empty>> {
+    fn instantiate(
+        deps: DepsMut,
+        _: Env,
+        _: MessageInfo,
+        verified: Vec<String>,
+    ) -> StdResult<Response> {
+        VERIFIED.save(deps.storage, &verified)?;
+        Ok(Response::new())
+    }
+
+    fn execute(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Err(StdError::generic_err("unsupported"))
+    }
+
+    fn query(deps: Deps, _: Env, msg: KycQueryMsg) -> StdResult<Binary> {
+        match msg {
+            KycQueryMsg::IsVerified { address } => to_binary(&IsVerifiedResponse {
+                verified: VERIFIED.load(deps.storage)?.contains(&address),
+            }),
+        }
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
+/// What the mock peer answers to any query
+const PEER_ANSWER: Item<Binary> = Item::new("peer_answer");
+
+/// A peer deployment answering every query with what it was instantiated with
+pub fn contract_fake_peer() -> Box<dyn Contract<Empty>> {
+    fn instantiate(deps: DepsMut, _: Env, _: MessageInfo, answer: Binary) -> StdResult<Response> {
+        PEER_ANSWER.save(deps.storage, &answer)?;
+        Ok(Response::new())
+    }
+
+    fn execute(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Err(StdError::generic_err("unsupported"))
+    }
+
+    fn query(deps: Deps, _: Env, _: QueryMsg) -> StdResult<Binary> {
+        PEER_ANSWER.load(deps.storage)
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
+/// Instantiating with every setting left to its default
+fn default_instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        admin: None,
+        seconds_per_block: None,
+        max_whitelist_len: None,
+        bump_cooldown_seconds: None,
+        allow_mixed_funding: None,
+        post_accept_cancel_window_seconds: None,
+        cancel_penalty_bps: None,
+        split_native_payouts: None,
+        listing_deposit: None,
+        fee_collector: None,
+        require_arbiter_ack: None,
+        arbiter_ack_window_seconds: None,
+        display_denoms: None,
+        listing_grace_period_seconds: None,
+        arbitration_extension_seconds: None,
+        max_arbitration_extensions: None,
+        kyc_contract: None,
+        allow_third_party_topup: None,
+        max_active_escrows: None,
+        daily_volume_limit_ust: None,
+        daily_accept_limit_ust: None,
+        dispute_window_seconds: None,
+        creator_ack_timeout_seconds: None,
+        max_open_to_all_seconds: None,
+        cancel_cooloff_seconds: None,
+        max_escrow_bytes: None,
+        mid_lifecycle_expiry: None,
+        risk_weights: None,
+        mediation_window_seconds: None,
+        min_remaining_on_accept_seconds: None,
+        peer_contracts: None,
+        max_actions_per_address: None,
+        initial_arbiters: vec![],
+        seed_metrics: vec![],
+    }
+}
+
+fn instantiate_escrow(router: &mut App, owner: &Addr, kyc_contract: Option<&Addr>) -> Addr {
+    let msg = InstantiateMsg {
+        kyc_contract: kyc_contract.map(Addr::to_string),
+        ..default_instantiate_msg()
+    };
+    instantiate_escrow_with(router, owner, &msg)
+}
+
+fn instantiate_escrow_with(router: &mut App, owner: &Addr, msg: &InstantiateMsg) -> Addr {
+    let escrow_id = router.store_code(contract_escrow());
+    router
+        .instantiate_contract(escrow_id, owner.clone(), msg, &[], "Escrow", None)
+        .unwrap()
+}
+
 #[test]
-// receive cw20 tokens and release upon approval
+// receive cw20 tokens and release upon creator completion
 fn escrow_happy_path_cw20_tokens() {
     let mut router = mock_app();
 
@@ -61,17 +290,7 @@ fn escrow_happy_path_cw20_tokens() {
         .unwrap();
 
     // set up reflect contract
-    let escrow_id = router.store_code(contract_escrow());
-    let escrow_addr = router
-        .instantiate_contract(
-            escrow_id,
-            owner.clone(),
-            &InstantiateMsg {},
-            &[],
-            "Escrow",
-            None,
-        )
-        .unwrap();
+    let escrow_addr = instantiate_escrow(&mut router, &owner, None);
 
     // they are different
     assert_ne!(cash_addr, escrow_addr);
@@ -89,13 +308,27 @@ fn escrow_happy_path_cw20_tokens() {
     let arb = Addr::unchecked("arbiter");
     let ben = String::from("beneficiary");
     let id = "demo".to_string();
-    let create_msg = ReceiveMsg::Create(CreateMsg {
+    let create_msg = ReceiveMsg::CCreate(CreateMsg {
         id: id.clone(),
-        arbiter: arb.to_string(),
-        recipient: ben.clone(),
+        arbiter: Some(arb.to_string()),
+        arbitration_enabled: true,
+        fiat_currency: "VES".to_string(),
         end_height: None,
         end_time: None,
+        exchange_rate: Decimal::percent(3650),
         cw20_whitelist: None,
+        required_trust_metrics: TrustMetrics::default().into(),
+        auto_complete_trust: None,
+        allowed_fulfillers: None,
+        description: None,
+        dispute_window_seconds: None,
+        require_creator_ack: false,
+        auto_extend_on_relist: None,
+        payout_memo: false,
+        tags: None,
+        start_time: None,
+        creator_locale: None,
+        creator_utc_offset_minutes: None,
     });
     let send_msg = Cw20ExecuteMsg::Send {
         contract: escrow_addr.to_string(),
@@ -116,7 +349,7 @@ fn escrow_happy_path_cw20_tokens() {
     assert_eq!(res.events[2].ty.as_str(), "execute");
     let escrow_attr = res.custom_attrs(3);
     println!("{:?}", escrow_attr);
-    assert_eq!(2, escrow_attr.len());
+    assert_eq!(3, escrow_attr.len());
 
     // ensure balances updated
     let owner_balance = cash.balance(&router, owner.clone()).unwrap();
@@ -130,8 +363,14 @@ fn escrow_happy_path_cw20_tokens() {
         .query_wasm_smart(&escrow_addr, &QueryMsg::Details { id: id.clone() })
         .unwrap();
     assert_eq!(id, details.id);
-    assert_eq!(arb, details.arbiter);
-    assert_eq!(ben, details.recipient);
+    assert_eq!(Some(arb.to_string()), details.arbiter);
+    assert_eq!(owner, details.creator);
+    assert_eq!(
+        FundingSource::Cw20Hook {
+            token: cash_addr.clone()
+        },
+        details.funding_source
+    );
     assert_eq!(
         vec![Cw20Coin {
             address: cash_addr.to_string(),
@@ -140,10 +379,33 @@ fn escrow_happy_path_cw20_tokens() {
         details.cw20_balance
     );
 
+    // ben accepts and fulfills the escrow
+    let ben_addr = Addr::unchecked(&ben);
+    let accept_msg = ExecuteMsg::FAccept {
+        id: id.clone(),
+        expected_exchange_rate: None,
+        required_creator_metrics: None,
+        payout_memo: false,
+    };
+    router
+        .execute_contract(ben_addr.clone(), escrow_addr.clone(), &accept_msg, &[])
+        .unwrap();
+    let fulfill_msg = ExecuteMsg::FComplete {
+        id: id.clone(),
+        delivered_fiat_amount: None,
+    };
+    router
+        .execute_contract(ben_addr, escrow_addr.clone(), &fulfill_msg, &[])
+        .unwrap();
+
     // release escrow
-    let approve_msg = ExecuteMsg::Approve { id };
+    let complete_msg = ExecuteMsg::CComplete {
+        id,
+        accept_rate_deviation: false,
+        release_bps: None,
+    };
     let _ = router
-        .execute_contract(arb, escrow_addr.clone(), &approve_msg, &[])
+        .execute_contract(owner.clone(), escrow_addr.clone(), &complete_msg, &[])
         .unwrap();
 
     // ensure balances updated - release to ben
@@ -154,3 +416,473 @@ fn escrow_happy_path_cw20_tokens() {
     let ben_balance = cash.balance(&router, ben).unwrap();
     assert_eq!(ben_balance, Uint128::new(1200));
 }
+
+#[test]
+// a token paid out to can call back, but only after the escrow settled
+fn cw20_callback_during_payout_finds_escrow_settled() {
+    let mut router = mock_app();
+    let owner = Addr::unchecked("owner");
+    let token_id = router.store_code(contract_hostile_cw20());
+    let token_addr = router
+        .instantiate_contract(token_id, owner.clone(), &Empty {}, &[], "HOSTILE", None)
+        .unwrap();
+    let escrow_addr = instantiate_escrow(&mut router, &owner, None);
+
+    let create_msg = ReceiveMsg::CCreate(CreateMsg {
+        id: "demo".to_string(),
+        arbiter: Some("arbiter".to_string()),
+        arbitration_enabled: true,
+        fiat_currency: "VES".to_string(),
+        end_height: None,
+        end_time: None,
+        exchange_rate: Decimal::percent(3650),
+        cw20_whitelist: None,
+        required_trust_metrics: TrustMetrics::default().into(),
+        auto_complete_trust: None,
+        allowed_fulfillers: None,
+        description: None,
+        dispute_window_seconds: None,
+        require_creator_ack: false,
+        auto_extend_on_relist: None,
+        payout_memo: false,
+        tags: None,
+        start_time: None,
+        creator_locale: None,
+        creator_utc_offset_minutes: None,
+    });
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: escrow_addr.to_string(),
+        amount: Uint128::new(1200),
+        msg: to_binary(&create_msg).unwrap(),
+    };
+    router
+        .execute_contract(owner.clone(), token_addr.clone(), &send_msg, &[])
+        .unwrap();
+
+    let fulfiller = Addr::unchecked("fulfiller");
+    let msgs = [
+        ExecuteMsg::FAccept {
+            id: "demo".to_string(),
+            expected_exchange_rate: None,
+            required_creator_metrics: None,
+            payout_memo: false,
+        },
+        ExecuteMsg::FComplete {
+            id: "demo".to_string(),
+            delivered_fiat_amount: None,
+        },
+    ];
+    for msg in msgs {
+        router
+            .execute_contract(fulfiller.clone(), escrow_addr.clone(), &msg, &[])
+            .unwrap();
+    }
+    let complete_msg = ExecuteMsg::CComplete {
+        id: "demo".to_string(),
+        accept_rate_deviation: false,
+        release_bps: None,
+    };
+    router
+        .execute_contract(owner, escrow_addr.clone(), &complete_msg, &[])
+        .unwrap();
+
+    // the token's attempt to accept mid-release found no escrow left to accept
+    let callback_error: Option<String> = router
+        .wrap()
+        .query_wasm_smart(&token_addr, &Empty {})
+        .unwrap();
+    assert!(callback_error.unwrap().contains("is finished"));
+    let details: StdResult<DetailsResponse> = router.wrap().query_wasm_smart(
+        &escrow_addr,
+        &QueryMsg::Details {
+            id: "demo".to_string(),
+        },
+    );
+    assert!(details.is_err());
+}
+
+#[test]
+// with a KYC contract configured, only attested creators and fulfillers get through
+fn kyc_attestation_is_required_to_create_and_accept() {
+    let mut router = mock_app();
+    let owner = Addr::unchecked("owner");
+    let stranger = Addr::unchecked("stranger");
+    for address in [&owner, &stranger] {
+        router
+            .init_bank_balance(address, coins(2000, "uusd"))
+            .unwrap();
+    }
+    let kyc_id = router.store_code(contract_kyc());
+    let kyc_addr = router
+        .instantiate_contract(
+            kyc_id,
+            owner.clone(),
+            &vec![owner.to_string(), "fulfiller".to_string()],
+            &[],
+            "KYC",
+            None,
+        )
+        .unwrap();
+    let escrow_addr = instantiate_escrow(&mut router, &owner, Some(&kyc_addr));
+
+    let create = |id: &str| {
+        ExecuteMsg::CCreate(CreateMsg {
+            id: id.to_string(),
+            arbiter: Some("arbiter".to_string()),
+            arbitration_enabled: true,
+            fiat_currency: "VES".to_string(),
+            end_height: None,
+            end_time: None,
+            exchange_rate: Decimal::percent(3650),
+            cw20_whitelist: None,
+            required_trust_metrics: TrustMetrics::default().into(),
+            auto_complete_trust: None,
+            allowed_fulfillers: None,
+            description: None,
+            dispute_window_seconds: None,
+            require_creator_ack: false,
+            auto_extend_on_relist: None,
+            payout_memo: false,
+            tags: None,
+            start_time: None,
+            creator_locale: None,
+            creator_utc_offset_minutes: None,
+        })
+    };
+    let funds = coins(100, "uusd");
+    let err = router
+        .execute_contract(
+            stranger.clone(),
+            escrow_addr.clone(),
+            &create("demo"),
+            &funds,
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), ContractError::KycRequired {}.to_string());
+    router
+        .execute_contract(owner.clone(), escrow_addr.clone(), &create("demo"), &funds)
+        .unwrap();
+
+    let accept = ExecuteMsg::FAccept {
+        id: "demo".to_string(),
+        expected_exchange_rate: None,
+        required_creator_metrics: None,
+        payout_memo: false,
+    };
+    let err = router
+        .execute_contract(stranger, escrow_addr.clone(), &accept, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), ContractError::KycRequired {}.to_string());
+    router
+        .execute_contract(Addr::unchecked("fulfiller"), escrow_addr, &accept, &[])
+        .unwrap();
+
+    // a KYC contract that isn't there lets nobody through
+    let nowhere = Addr::unchecked("nowhere");
+    let escrow_addr = instantiate_escrow(&mut router, &owner, Some(&nowhere));
+    let err = router
+        .execute_contract(owner, escrow_addr, &create("demo"), &funds)
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("The KYC contract can't be queried"));
+}
+
+#[test]
+// the admin can send out what was transferred to the contract outside any escrow, no more
+fn stray_funds_are_recovered_but_escrowed_ones_stay() {
+    let mut router = mock_app();
+    let owner = Addr::unchecked("owner");
+    router
+        .init_bank_balance(&owner, coins(2000, "uusd"))
+        .unwrap();
+    let cw20_id = router.store_code(contract_cw20());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name: "Cash Money".to_string(),
+        symbol: "CASH".to_string(),
+        decimals: 2,
+        initial_balances: vec![Cw20Coin {
+            address: owner.to_string(),
+            amount: Uint128::new(5000),
+        }],
+        mint: None,
+        marketing: None,
+    };
+    let cash_addr = router
+        .instantiate_contract(cw20_id, owner.clone(), &msg, &[], "CASH", None)
+        .unwrap();
+    let cash = Cw20Contract(cash_addr.clone());
+    let escrow_addr = instantiate_escrow(&mut router, &owner, None);
+
+    // one escrow in cash, one in uusd, and cash transferred instead of sent
+    let create = |id: &str| CreateMsg {
+        id: id.to_string(),
+        arbiter: Some("arbiter".to_string()),
+        arbitration_enabled: true,
+        fiat_currency: "VES".to_string(),
+        end_height: None,
+        end_time: None,
+        exchange_rate: Decimal::percent(3650),
+        cw20_whitelist: None,
+        required_trust_metrics: TrustMetrics::default().into(),
+        auto_complete_trust: None,
+        allowed_fulfillers: None,
+        description: None,
+        dispute_window_seconds: None,
+        require_creator_ack: false,
+        auto_extend_on_relist: None,
+        payout_memo: false,
+        tags: None,
+        start_time: None,
+        creator_locale: None,
+        creator_utc_offset_minutes: None,
+    };
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: escrow_addr.to_string(),
+        amount: Uint128::new(1200),
+        msg: to_binary(&ReceiveMsg::CCreate(create("cash"))).unwrap(),
+    };
+    router
+        .execute_contract(owner.clone(), cash_addr.clone(), &send_msg, &[])
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            escrow_addr.clone(),
+            &ExecuteMsg::CCreate(create("native")),
+            &coins(100, "uusd"),
+        )
+        .unwrap();
+    let transfer = Cw20ExecuteMsg::Transfer {
+        recipient: escrow_addr.to_string(),
+        amount: Uint128::new(300),
+    };
+    router
+        .execute_contract(owner.clone(), cash_addr.clone(), &transfer, &[])
+        .unwrap();
+
+    let recover =
+        |token: Option<&Addr>, denom: Option<&str>, amount: u128| ExecuteMsg::RecoverStray {
+            token: token.map(Addr::to_string),
+            denom: denom.map(String::from),
+            to: owner.to_string(),
+            amount: Uint128::new(amount),
+        };
+    let err = router
+        .execute_contract(
+            Addr::unchecked("stranger"),
+            escrow_addr.clone(),
+            &recover(Some(&cash_addr), None, 300),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), ContractError::Unauthorized {}.to_string());
+    for msg in [
+        recover(Some(&cash_addr), Some("uusd"), 300),
+        recover(None, None, 300),
+    ] {
+        let err = router
+            .execute_contract(owner.clone(), escrow_addr.clone(), &msg, &[])
+            .unwrap_err();
+        let expected = ContractError::InvalidStrayAsset {};
+        assert_eq!(err.to_string(), expected.to_string());
+    }
+    // a token that can't tell its balance has nothing to recover
+    let nowhere = Addr::unchecked("nowhere");
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            escrow_addr.clone(),
+            &recover(Some(&nowhere), None, 1),
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("The contract's balance of nowhere can't be queried"));
+
+    // the cash escrow's tokens are off limits
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            escrow_addr.clone(),
+            &recover(Some(&cash_addr), None, 301),
+            &[],
+        )
+        .unwrap_err();
+    let expected = ContractError::ExceedsStraySurplus {
+        asset: cash_addr.to_string(),
+        surplus: Uint128::new(300),
+    };
+    assert_eq!(err.to_string(), expected.to_string());
+    router
+        .execute_contract(
+            owner.clone(),
+            escrow_addr.clone(),
+            &recover(Some(&cash_addr), None, 300),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        cash.balance(&router, owner.clone()).unwrap(),
+        Uint128::new(3800)
+    );
+    assert_eq!(
+        cash.balance(&router, escrow_addr.clone()).unwrap(),
+        Uint128::new(1200)
+    );
+
+    // native coins the same way, with no surplus at all until some turns up
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            escrow_addr.clone(),
+            &recover(None, Some("uusd"), 1),
+            &[],
+        )
+        .unwrap_err();
+    let expected = ContractError::ExceedsStraySurplus {
+        asset: "uusd".to_string(),
+        surplus: Uint128::zero(),
+    };
+    assert_eq!(err.to_string(), expected.to_string());
+    router
+        .init_bank_balance(&escrow_addr, coins(150, "uusd"))
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            escrow_addr.clone(),
+            &recover(None, Some("uusd"), 50),
+            &[],
+        )
+        .unwrap();
+    let balance = router.wrap().query_balance(&escrow_addr, "uusd").unwrap();
+    assert_eq!(balance.amount, Uint128::new(100));
+    let balance = router.wrap().query_balance(&owner, "uusd").unwrap();
+    assert_eq!(balance.amount, Uint128::new(1950));
+}
+
+#[test]
+// a wallet's record at another corridor's deployment carries over, once per deployment
+fn trust_metrics_are_imported_from_peer_deployments_once() {
+    let mut router = mock_app();
+    let owner = Addr::unchecked("owner");
+    let fulfiller = Addr::unchecked("fulfiller");
+    let at_peer = TrustMetrics {
+        percent_completed: 90,
+        percent_satisfied: 80,
+        avg_volume: 100,
+        avg_completion_speed: 600_000,
+        total_volume: 1_000,
+        total_completed: 10,
+        disputes_won: 1,
+        clean_completions: 10,
+        ..TrustMetrics::default()
+    };
+    let inflated = TrustMetrics {
+        percent_completed: 250,
+        ..at_peer.clone()
+    };
+    let msg = InstantiateMsg {
+        seed_metrics: vec![
+            (fulfiller.to_string(), at_peer),
+            ("inflated".to_string(), inflated),
+        ],
+        ..default_instantiate_msg()
+    };
+    let ves = instantiate_escrow_with(&mut router, &owner, &msg);
+    let peer_id = router.store_code(contract_fake_peer());
+    let garbled = router
+        .instantiate_contract(
+            peer_id,
+            owner.clone(),
+            &Binary::from(br#"{"percent_completed":"most"}"#),
+            &[],
+            "Garbled",
+            None,
+        )
+        .unwrap();
+    let here = TrustMetrics {
+        percent_completed: 100,
+        percent_satisfied: 100,
+        avg_volume: 300,
+        avg_completion_speed: 300_000,
+        total_volume: 3_000,
+        total_completed: 10,
+        clean_completions: 5,
+        ..TrustMetrics::default()
+    };
+    let msg = InstantiateMsg {
+        peer_contracts: Some(vec![ves.to_string(), garbled.to_string()]),
+        seed_metrics: vec![(fulfiller.to_string(), here)],
+        ..default_instantiate_msg()
+    };
+    let cop = instantiate_escrow_with(&mut router, &owner, &msg);
+
+    let import = |from: &Addr| ExecuteMsg::ImportMetrics {
+        from_contract: from.to_string(),
+    };
+    router
+        .execute_contract(fulfiller.clone(), cop.clone(), &import(&ves), &[])
+        .unwrap();
+    let query = QueryMsg::TrustMetrics {
+        address: fulfiller.to_string(),
+    };
+    let merged: TrustMetrics = router.wrap().query_wasm_smart(&cop, &query).unwrap();
+    assert_eq!(
+        merged,
+        TrustMetrics {
+            percent_completed: 95,
+            percent_satisfied: 90,
+            avg_volume: 200,
+            avg_completion_speed: 500_000,
+            total_volume: 4_000,
+            total_completed: 20,
+            disputes_won: 1,
+            clean_completions: 15,
+            ..TrustMetrics::default()
+        }
+    );
+
+    let peer = |peer: &Addr| peer.to_string();
+    let rejected = vec![
+        (
+            &fulfiller,
+            import(&ves),
+            ContractError::MetricsAlreadyImported { peer: peer(&ves) },
+        ),
+        (
+            &fulfiller,
+            import(&cop),
+            ContractError::UnknownPeer { peer: peer(&cop) },
+        ),
+        (
+            &owner,
+            import(&ves),
+            ContractError::NothingToImport { peer: peer(&ves) },
+        ),
+    ];
+    for (sender, msg, expected) in rejected {
+        let err = router
+            .execute_contract(sender.clone(), cop.clone(), &msg, &[])
+            .unwrap_err();
+        assert_eq!(err.to_string(), expected.to_string());
+    }
+    let err = router
+        .execute_contract(Addr::unchecked("inflated"), cop.clone(), &import(&ves), &[])
+        .unwrap_err();
+    let expected = ContractError::ImplausiblePeerMetrics {
+        peer: peer(&ves),
+        reason: "a percentage above 100".to_string(),
+    };
+    assert_eq!(err.to_string(), expected.to_string());
+    // an answer that isn't metrics at all is no import either, and can be tried again
+    let err = router
+        .execute_contract(fulfiller.clone(), cop.clone(), &import(&garbled), &[])
+        .unwrap_err();
+    let prefix = format!("The peer contract {} can't be queried", garbled);
+    assert!(err.to_string().starts_with(&prefix));
+    let unchanged: TrustMetrics = router.wrap().query_wasm_smart(&cop, &query).unwrap();
+    assert_eq!(unchanged, merged);
+}
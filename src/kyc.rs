@@ -0,0 +1,45 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, QuerierWrapper};
+
+use crate::error::ContractError;
+
+/// What the KYC contract answers, see Config::kyc_contract
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum KycQueryMsg {
+    /// Return type: IsVerifiedResponse.
+    IsVerified { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct IsVerifiedResponse {
+    pub verified: bool,
+}
+
+/// Checks the address holds an attestation from the KYC contract, if one is configured.
+/// Fails closed: when the KYC contract can't answer, nobody passes.
+pub fn assert_verified(
+    querier: &QuerierWrapper,
+    kyc_contract: Option<&Addr>,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    let kyc_contract = match kyc_contract {
+        Some(kyc_contract) => kyc_contract,
+        None => return Ok(()),
+    };
+    let query = KycQueryMsg::IsVerified {
+        address: address.to_string(),
+    };
+    let response: IsVerifiedResponse =
+        querier
+            .query_wasm_smart(kyc_contract, &query)
+            .map_err(|err| ContractError::KycUnavailable {
+                reason: err.to_string(),
+            })?;
+    if !response.verified {
+        return Err(ContractError::KycRequired {});
+    }
+    Ok(())
+}
@@ -1,7 +1,15 @@
+pub mod capabilities;
+#[cfg(feature = "client")]
+pub mod client;
 pub mod contract;
 mod error;
+pub mod escrow_logic;
+pub mod events;
 mod integration_test;
+pub mod kyc;
 pub mod msg;
+pub mod pagination;
+pub mod payments;
 pub mod state;
 
 pub use crate::error::ContractError;
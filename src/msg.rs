@@ -1,36 +1,443 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Api, Coin, StdResult};
+use cosmwasm_std::{Addr, Api, Binary, Coin, Decimal, StdResult, Uint128};
 
-use cw20::{Cw20Coin, Cw20ReceiveMsg};
+use cw20::{Cw20Coin, Cw20ReceiveMsg, Denom};
 
-use crate::state::{TrustMetrics};
+use crate::state::{
+    AdminLogEntry, ArbiterStats, CreatorRequirement, DisplayDenom, Dispute, DisputeReason,
+    EscrowStatus, ExpirySource, FundingSource, HistoryEntry, MidLifecycleExpiry, RiskWeights,
+    SettlementProposal, TrustMetrics, DEFAULT_FIAT_CURRENCY,
+};
 
 #[derive(Serialize, Deserialize, JsonSchema)]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    /// The admin manages the blacklist, defaults to the instantiator
+    pub admin: Option<String>,
+    /// Average block time used to estimate when end_height is reached, defaults to 6
+    pub seconds_per_block: Option<u64>,
+    /// Most cw20 tokens an escrow may whitelist, defaults to 10
+    pub max_whitelist_len: Option<u32>,
+    /// How often a creator may bump a listing, defaults to 6 hours
+    pub bump_cooldown_seconds: Option<u64>,
+    /// Whether escrows funded with a cw20 token take native top-ups, defaults to false
+    pub allow_mixed_funding: Option<bool>,
+    /// How long after acceptance the creator may still cancel, paying the fulfiller
+    /// cancel_penalty_bps of the balance. Defaults to 0, not at all.
+    pub post_accept_cancel_window_seconds: Option<u64>,
+    /// At most 10000, defaults to 0
+    pub cancel_penalty_bps: Option<u64>,
+    /// Whether payouts send each native denom in its own BankMsg, defaults to false
+    pub split_native_payouts: Option<bool>,
+    /// Charged on top of the funds of escrows created with native coins. Refunded when
+    /// the escrow ends, unless it's left to expire unaccepted. Defaults to none.
+    pub listing_deposit: Option<Vec<Coin>>,
+    /// Receives the deposits of listings left to expire, defaults to the admin
+    pub fee_collector: Option<String>,
+    /// Whether escrows with an arbiter wait for them to take the role, see
+    /// ExecuteMsg::AckArbiterRole. Defaults to false.
+    pub require_arbiter_ack: Option<bool>,
+    /// How long the arbiter has to take the role, defaults to a day
+    pub arbiter_ack_window_seconds: Option<u64>,
+    /// How responses show native amounts, by denom. Defaults to uusd as UST, 6 decimals.
+    pub display_denoms: Option<Vec<DisplayDenom>>,
+    /// How long a new escrow waits before it may be accepted, defaults to 0
+    pub listing_grace_period_seconds: Option<u64>,
+    /// How much each ArbiterRequestInfo extends the arbitration deadline, defaults to a day
+    pub arbitration_extension_seconds: Option<u64>,
+    /// How often the arbiter may extend one dispute's deadline, defaults to 2
+    pub max_arbitration_extensions: Option<u32>,
+    /// Contract answering kyc::KycQueryMsg, creators and fulfillers need its attestation.
+    /// Defaults to none, no attestation needed.
+    pub kyc_contract: Option<String>,
+    /// Whether anyone may top up a listed escrow as a gift, defaults to false
+    pub allow_third_party_topup: Option<bool>,
+    /// Most escrows stored at once, defaults to no cap. 0 means no cap too.
+    pub max_active_escrows: Option<u64>,
+    /// Most uusd one wallet may escrow, creating and topping up, in any 24 hours.
+    /// Defaults to no limit, 0 means no limit too.
+    pub daily_volume_limit_ust: Option<Uint128>,
+    /// Most uusd one wallet may accept in any 24 hours, as daily_volume_limit_ust
+    pub daily_accept_limit_ust: Option<Uint128>,
+    /// How long the creator has to confirm or dispute once the fulfiller reports,
+    /// defaults to an hour. Escrows may set their own.
+    pub dispute_window_seconds: Option<u64>,
+    /// How long creators requiring it have to CAckFulfiller before the acceptance lapses,
+    /// defaults to an hour
+    pub creator_ack_timeout_seconds: Option<u64>,
+    /// Longest OpenToAll may waive the trust requirements of a listing for, defaults to a day
+    pub max_open_to_all_seconds: Option<u64>,
+    /// How long a canceled listing can still be restored with UndoCancel before it's
+    /// refunded. Defaults to 0, refunding right away.
+    pub cancel_cooloff_seconds: Option<u64>,
+    /// Most bytes top ups, edits, rejections and info requests may grow an escrow to,
+    /// so none gets too costly to pay out. Defaults to 16 KiB.
+    pub max_escrow_bytes: Option<u64>,
+    /// Whether accepted escrows that expire before they're fulfilled wait for the accept
+    /// timeout or may be refunded right away. Defaults to GraceUntilAcceptTimeout.
+    pub mid_lifecycle_expiry: Option<MidLifecycleExpiry>,
+    /// What listing risk scores are made of, defaults to 50 points for the creator's
+    /// disputes, 30 for the escrow's value and 20 for disabled arbitration
+    pub risk_weights: Option<RiskWeights>,
+    /// How long a dispute stays with the parties to settle by ProposeSettlement before it
+    /// goes to the arbiter. Defaults to 0, straight to the arbiter.
+    pub mediation_window_seconds: Option<u64>,
+    /// Least time a listing must have left before it expires for FAccept, so nobody takes
+    /// on a remittance they can't deliver in time. Defaults to 30 minutes, 0 turns it off.
+    pub min_remaining_on_accept_seconds: Option<u64>,
+    /// Deployments for other corridors whose trust metrics wallets may bring over with
+    /// ImportMetrics. At most 10, defaults to none.
+    pub peer_contracts: Option<Vec<String>>,
+    /// How many actions an address may take on one escrow besides settling, refunding and
+    /// disputing it, e.g. FAccept or CChange. Defaults to 50.
+    pub max_actions_per_address: Option<u32>,
+    /// Added to the arbiter registry. At most 100, one storage write each.
+    pub initial_arbiters: Vec<String>,
+    /// Trust metrics carried over from the pilot, by address. At most 100,
+    /// one storage write each, and no address twice.
+    pub seed_metrics: Vec<(String, TrustMetrics)>,
+}
 
 // List of all possible execution methods
+//
+// Migration notes
+//
+// el_arbitrate, c_feedback and f_feedback used to take the escrow id and the message as a
+// positional pair and now take named fields like every other message:
+//
+//   {"el_arbitrate": ["some-id", {...}]}  becomes  {"el_arbitrate": {"id": "some-id", "msg": {...}}}
+//
+// and the same for c_feedback and f_feedback. The old shape is rejected. Accepting both for a
+// release isn't feasible: serde-json-wasm can't look at the payload before picking how to
+// decode it, so clients have to switch along with the contract.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    ElArbitrate(String, ArbitrateMsg),
+    ElArbitrate {
+        id: String,
+        msg: ArbitrateMsg,
+    },
+    /// Responds with the created escrow as a JSON-encoded DetailsResponse in the data field
     CCreate(CreateMsg),
-    FAccept { id: String, },
-    CCancel { id: String },
-    FUnaccept { id: String },
+    /// Creates up to 20 escrows at once, each funded by its amount out of the coins sent.
+    /// The amounts must add up to exactly what was sent. Fails as a whole if any item does.
+    /// Responds with a JSON-encoded BatchCreateResponse in the data field.
+    CCreateBatch {
+        items: Vec<BatchCreateItem>,
+    },
+    /// Adds funds to an existing, still listed escrow
+    TopUp {
+        id: String,
+    },
+    /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
+    Receive(Cw20ReceiveMsg),
+    /// Responds with the accepted escrow as a JSON-encoded DetailsResponse in the data field
+    FAccept {
+        id: String,
+        /// The rate the fulfiller was shown, the accept fails if the creator changed it since
+        expected_exchange_rate: Option<Decimal>,
+        /// The accept fails unless the escrow's creator has this record, see CreatorMetrics
+        #[serde(default)]
+        required_creator_metrics: Option<CreatorRequirement>,
+        /// For fulfillers that are contracts, see CreateMsg::payout_memo
+        #[serde(default)]
+        payout_memo: bool,
+    },
+    /// Refunds a listed escrow, or an accepted one the fulfiller ran out of time on.
+    /// Within the post accept cancel window the fulfiller is paid the cancel penalty.
+    /// With a cancel cooloff configured, a listing nobody accepted is only set aside
+    /// until then, see UndoCancel and FinalizeCancel.
+    CCancel {
+        id: String,
+    },
+    /// The fulfiller gives back an escrow they accepted, before fulfilling, and it's
+    /// listed again
+    FUnaccept {
+        id: String,
+    },
+    /// The creator turns away who accepted, before they fulfill, and lists the escrow
+    /// again. That fulfiller may not accept it again, their trust metrics are untouched.
+    /// At most 3 times per escrow.
+    CReject {
+        id: String,
+        /// Shown in the event, at most 1024 bytes once trimmed
+        reason: Option<String>,
+    },
+    /// The creator vouches for who accepted an escrow requiring it, letting them fulfill
+    /// and read the payout details. Their time to fulfill starts over. Without it the
+    /// acceptance lapses after the configured creator_ack_timeout_seconds, and the next
+    /// FAccept takes the escrow.
+    CAckFulfiller {
+        id: String,
+    },
+    /// The creator restores a listing they canceled, before the cancel cooloff runs out.
+    /// It's listed again where it was.
+    UndoCancel {
+        id: String,
+    },
+    /// Refunds a canceled listing once its cancel cooloff ran out, anyone may send it
+    FinalizeCancel {
+        id: String,
+    },
+    /// Cancels and refunds the sender's listings nobody accepted, among the next limit
+    /// (default 10, at most 30) of their escrows after start_after. Only expired ones
+    /// unless only_expired is false, and those not expired wait out the cancel cooloff
+    /// first, as with CCancel. Escrows further along are skipped. A full page answers
+    /// with next_start_after.
+    CancelAll {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        #[serde(default = "default_true")]
+        only_expired: bool,
+    },
+    /// Edits the exchange rate, trust requirements and description of a listing
+    /// until it is accepted. A later end_time or end_height extends the listing, an
+    /// earlier or missing one leaves it. Everything else is fixed at creation.
     CChange(CreateMsg),
-    FComplete { id: String },
-    CReqArbitration { id: String },
-    CComplete { id: String },
-    CFeedback(String, FeedbackMsg),
-    FFeedback(String, FeedbackMsg),
+    /// Creates a new escrow with the terms of one the sender created, funded by the coins sent.
+    /// The source may have settled already. The expiry and start are as far from now as the source's were from its creation.
+    /// Responds with the new escrow as a JSON-encoded DetailsResponse in the data field.
+    CloneEscrow {
+        source_id: String,
+    },
+    /// Moves a listing back to the top of Listings, at most once per bump cooldown
+    Bump {
+        id: String,
+    },
+    /// The creator lets anyone accept the listing for the next duration_seconds, whatever
+    /// their trust metrics, at most the configured max_open_to_all_seconds. The stored
+    /// requirements apply again afterward. Opening it again replaces the previous window.
+    OpenToAll {
+        id: String,
+        duration_seconds: u64,
+    },
+    /// The fulfiller marks the escrow fulfilled, optionally reporting the fiat delivered.
+    /// Responds with the dispute deadline, after which they may ClaimTimedOut, as JSON-encoded
+    /// seconds in the data field.
+    FComplete {
+        id: String,
+        /// In cents, e.g. 150000 for 1500.00 Bolivares
+        delivered_fiat_amount: Option<Uint128>,
+    },
+    /// The fulfiller claims the funds once the creator has let the confirmation window lapse
+    ClaimTimedOut {
+        id: String,
+    },
+    /// Anyone may refund the creator once the arbiter let the arbitration deadline pass
+    ResolveExpiredArbitration {
+        id: String,
+    },
+    /// Anyone may refund the creator of an accepted escrow that expired before it was
+    /// fulfilled, if the config's mid_lifecycle_expiry is HardExpire
+    RefundExpired {
+        id: String,
+    },
+    /// Disputes a fulfillment, up to its dispute_deadline. With a mediation window configured
+    /// the parties get that long to settle by ProposeSettlement before the arbiter's
+    /// deadline starts.
+    CReqArbitration {
+        id: String,
+        reason_code: DisputeReason,
+        /// Free text for the arbiter, at most 1024 bytes once trimmed
+        details: Option<String>,
+    },
+    /// Either party offers to settle a dispute in mediation with this split, replacing any
+    /// earlier offer. Recorded in the escrow's history.
+    ProposeSettlement {
+        id: String,
+        /// The creator's share of the balance in basis points, the fulfiller gets the rest
+        creator_bps: u64,
+    },
+    /// The party who didn't propose the settlement agrees to it, paying out the split
+    /// without the arbiter. Responds with the receipt in the data field.
+    AcceptSettlement {
+        id: String,
+        /// Fails if the proposal changed from this share since it was read
+        #[serde(default)]
+        expected_creator_bps: Option<u64>,
+    },
+    CComplete {
+        id: String,
+        /// Required when the delivered amount strays too far from the listed exchange rate
+        #[serde(default)]
+        accept_rate_deviation: bool,
+        /// Settles an under-delivery proportionally: this share of the balance, in basis points,
+        /// goes to the fulfiller and the rest is refunded. Implies accepting the rate deviation.
+        release_bps: Option<u64>,
+    },
+    CFeedback {
+        id: String,
+        msg: FeedbackMsg,
+    },
+    FFeedback {
+        id: String,
+        msg: FeedbackMsg,
+    },
+    /// Admin only. Stops the address from creating, accepting or topping up escrows.
+    /// Escrows already in flight are unaffected.
+    AddToBlacklist {
+        address: String,
+        reason: String,
+    },
+    /// Admin only
+    RemoveFromBlacklist {
+        address: String,
+    },
+    /// Admin only. Imports more trust metrics, like InstantiateMsg::seed_metrics,
+    /// until seeding is closed.
+    SeedTrustMetrics {
+        metrics: Vec<(String, TrustMetrics)>,
+    },
+    /// Admin only. Stops any further seeding, for good.
+    CloseSeeding {},
+    /// Merges the sender's trust metrics at a peer contract, see Config::peer_contracts,
+    /// into theirs here. Counts and volumes add up, averages are weighted by what they
+    /// were averaged over. Once per wallet and peer.
+    ImportMetrics {
+        from_contract: String,
+    },
+    /// Admin only. Winds the contract down for good: creating, accepting, topping up and
+    /// bumping escrows fail from then on, while refunds, completions, disputes and
+    /// CancelAll keep working. Creators may cancel listings nobody accepted right away,
+    /// expired or not, skipping the cancel cooloff, and get their listing deposit back.
+    BeginSunset {},
+    /// Admin only. Changes the fields given, leaving the others as they are.
+    UpdateConfig(UpdateConfigMsg),
+    /// Admin only. Rebuilds the total TotalLocked reports and the count Stats reports from
+    /// the escrows themselves, should they ever drift. Fails with more than 1000 escrows stored.
+    ReconcileLocked {},
+    /// Admin only. Sends out funds the contract holds beyond what TotalLocked accounts for,
+    /// such as cw20 tokens transferred to it instead of sent. Give either the cw20 token or
+    /// the native denom. Fails for more than the surplus, so escrowed funds stay put.
+    RecoverStray {
+        token: Option<String>,
+        denom: Option<String>,
+        to: String,
+        amount: Uint128,
+    },
+    /// Admin only. Adds an arbiter to the registry.
+    AddArbiter {
+        address: String,
+    },
+    /// Admin only. Removes an arbiter from the registry, escrows naming them are unaffected.
+    RemoveArbiter {
+        address: String,
+    },
+    /// Admin only. Adds or replaces a preset CreateMsg::required_trust_metrics can name.
+    SetTrustPreset {
+        name: String,
+        metrics: TrustMetrics,
+    },
+    /// Admin only. Removes a preset, escrows created with it keep its metrics.
+    RemoveTrustPreset {
+        name: String,
+    },
+    /// The named arbiter takes the role, listing the escrow. Only while the escrow waits
+    /// for them, see InstantiateMsg::require_arbiter_ack. Until then the creator may cancel.
+    AckArbiterRole {
+        id: String,
+    },
+    /// The arbiter asks the parties for something they need to rule, e.g. a bank statement.
+    /// Recorded with the dispute, and extends the arbitration deadline by the configured
+    /// increment, a limited number of times.
+    ArbiterRequestInfo {
+        id: String,
+        request: String,
+    },
+    /// The arbiter steps aside, e.g. knowing one of the parties. Until the admin reassigns
+    /// the escrow nobody may accept it, and a dispute's deadline is paused.
+    ArbiterRecuse {
+        id: String,
+        /// Kept in the escrow's history, at most 1024 bytes once trimmed
+        reason: String,
+    },
+    /// Creator only. Stores the payout details (e.g. bank account) the fulfiller delivers
+    /// the fiat to, encrypted by the client for the fulfiller. Replaces what was stored before,
+    /// empty ciphertext removes it. Dropped once the escrow settles.
+    SetPayoutDetails {
+        id: String,
+        ciphertext: Binary,
+    },
+    /// Admin only. Replaces the arbiter of an escrow in arbitration, e.g. when the current
+    /// one lost their keys, and restarts the arbitration deadline. Also replaces an arbiter
+    /// who recused themselves before any dispute.
+    ReassignArbiter {
+        id: String,
+        new_arbiter: String,
+    },
 }
 
+// Messages are parsed once per call, boxing CreateMsg would only complicate callers
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReceiveMsg {
+    /// Responds like ExecuteMsg::CCreate
     CCreate(CreateMsg),
+    /// Adds all sent native tokens to the contract
+    TopUp { id: String },
+}
+
+/// What cw20 payouts to a party that set payout_memo carry, for its Receive hook to tell
+/// which escrow paid it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutHookMsg {
+    EscrowPayout {
+        escrow_id: String,
+        fingerprint: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpdateConfigMsg {
+    pub admin: Option<String>,
+    pub seconds_per_block: Option<u64>,
+    pub max_whitelist_len: Option<u32>,
+    pub bump_cooldown_seconds: Option<u64>,
+    pub allow_mixed_funding: Option<bool>,
+    pub post_accept_cancel_window_seconds: Option<u64>,
+    pub cancel_penalty_bps: Option<u64>,
+    pub split_native_payouts: Option<bool>,
+    pub listing_deposit: Option<Vec<Coin>>,
+    pub fee_collector: Option<String>,
+    pub require_arbiter_ack: Option<bool>,
+    pub arbiter_ack_window_seconds: Option<u64>,
+    /// Replaces the whole list
+    pub display_denoms: Option<Vec<DisplayDenom>>,
+    pub listing_grace_period_seconds: Option<u64>,
+    pub arbitration_extension_seconds: Option<u64>,
+    pub max_arbitration_extensions: Option<u32>,
+    /// An empty address stops requiring attestations
+    pub kyc_contract: Option<String>,
+    pub allow_third_party_topup: Option<bool>,
+    /// 0 removes the cap
+    pub max_active_escrows: Option<u64>,
+    /// 0 removes the limit, as for daily_accept_limit_ust
+    pub daily_volume_limit_ust: Option<Uint128>,
+    pub daily_accept_limit_ust: Option<Uint128>,
+    /// Escrows already fulfilled keep the deadline they got
+    pub dispute_window_seconds: Option<u64>,
+    pub creator_ack_timeout_seconds: Option<u64>,
+    /// Listings already opened to everyone stay open as long as they were
+    pub max_open_to_all_seconds: Option<u64>,
+    /// Listings already canceled keep the cooloff they got, 0 refunds the next ones right away
+    pub cancel_cooloff_seconds: Option<u64>,
+    /// Escrows already past it can still be paid out, just not grown further
+    pub max_escrow_bytes: Option<u64>,
+    /// Applies to escrows already accepted, too
+    pub mid_lifecycle_expiry: Option<MidLifecycleExpiry>,
+    /// Scores are computed when read, so this rescores every listing
+    pub risk_weights: Option<RiskWeights>,
+    /// Disputes already opened keep the mediation window they got
+    pub mediation_window_seconds: Option<u64>,
+    /// Escrows already accepted keep their fulfiller
+    pub min_remaining_on_accept_seconds: Option<u64>,
+    /// Replaces the list. Wallets keep what they imported from peers dropped from it.
+    pub peer_contracts: Option<Vec<String>>,
+    /// Actions already counted stay counted, against the new ceiling
+    pub max_actions_per_address: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -38,28 +445,115 @@ pub struct CreateMsg {
     /// id is a human-readable name for the escrow to use later
     /// 3-20 bytes of utf-8 text
     pub id: String,
-    /// arbiter can decide to approve or refund the escrow
-    pub arbiter: String,
+    /// arbiter can decide to approve or refund the escrow.
+    /// Required unless arbitration is disabled, ignored if it is.
+    pub arbiter: Option<String>,
+    /// When false the escrow has no arbiter and can't be disputed, it only settles
+    /// by the creator confirming or the confirmation window lapsing
+    #[serde(default = "default_true")]
+    pub arbitration_enabled: bool,
     /// When end height set and block height exceeds this value, the escrow is expired.
     /// Once an escrow is expired, it can be returned to the original funder (via "refund").
     pub end_height: Option<u64>,
     /// When end time (in seconds since epoch 00:00:00 UTC on 1 January 1970) is set and
     /// block time exceeds this value, the escrow is expired.
     /// Once an escrow is expired, it can be returned to the original funder (via "refund").
+    /// This and end_height may be at most 7 days after creation.
     pub end_time: Option<u64>,
-    /// Exchange rate desired, in Bolivares per UST
-    pub exchange_rate: u128,
+    /// Exchange rate desired, in fiat_currency per UST
+    pub exchange_rate: Decimal,
+    /// ISO 4217 code of the fiat wanted, defaults to VES
+    #[serde(default = "default_fiat_currency")]
+    pub fiat_currency: String,
     /// Besides any possible tokens sent with the CreateMsg, this is a list of all cw20 token addresses
     /// that are accepted by the escrow during a top-up. This is required to avoid a DoS attack by topping-up
     /// with an invalid cw20 contract. See https://github.com/CosmWasm/cosmwasm-plus/issues/19
+    /// Stored sorted and without duplicates.
     pub cw20_whitelist: Option<Vec<String>>,
     /// The required trust metrics for a fulfiller accept function to succeed
-    pub required_trust_metrics: TrustMetrics,
+    pub required_trust_metrics: TrustRequirement,
+    /// A fulfiller whose metrics meet these gets paid as soon as they report fulfilling,
+    /// without waiting for the creator. Unless the delivered amount strays from the rate.
+    pub auto_complete_trust: Option<TrustMetrics>,
+    /// When set, only these addresses may accept, whatever their trust metrics.
+    /// At most 20 and not empty.
+    pub allowed_fulfillers: Option<Vec<String>>,
+    /// Free-form terms shown to fulfillers, at most 1024 bytes once trimmed
+    pub description: Option<String>,
+    /// How long the creator has to confirm or dispute once the fulfiller reports,
+    /// instead of the configured dispute_window_seconds. Not zero.
+    pub dispute_window_seconds: Option<u64>,
+    /// When set, whoever accepts may not fulfill, and PayoutDetails stays hidden, until the
+    /// creator looked them up and sends CAckFulfiller. The creator may CReject instead.
+    #[serde(default)]
+    pub require_creator_ack: bool,
+    /// Seconds to push end_time back by whenever the escrow goes back on the market after
+    /// an acceptance fell through, up to 7 days after its creation. Recorded in its history.
+    #[serde(default)]
+    pub auto_extend_on_relist: Option<u64>,
+    /// For creators that are contracts: cw20 tokens paid back to the creator are sent with
+    /// a PayoutHookMsg naming the escrow rather than transferred. The creator must handle
+    /// it, or the payout fails. Native coins are sent as usual, the escrow id is in the
+    /// event attributes.
+    #[serde(default)]
+    pub payout_memo: bool,
+    /// Up to 5 categories to find the listing by, e.g. "caracas" or "zelle-ok", each 2 to
+    /// 16 lowercase letters, digits or dashes. See ListByTag.
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Seconds since epoch the escrow goes live at. Until then nobody may accept it and
+    /// listings leave it out, while the creator may still change or cancel it. Not after
+    /// end_time.
+    #[serde(default)]
+    pub start_time: Option<u64>,
+    /// The creator's locale as a BCP-47 tag, e.g. "es-VE", at most 16 characters. Only
+    /// shown to fulfillers, like creator_utc_offset_minutes.
+    #[serde(default)]
+    pub creator_locale: Option<String>,
+    /// How far the creator's clock is from UTC, e.g. -240 in Caracas, so fulfillers know
+    /// their bank hours. At most 840 either way. Deadlines stay in UTC regardless.
+    #[serde(default)]
+    pub creator_utc_offset_minutes: Option<i16>,
+}
+
+/// Trust metrics given inline, or by the name of a preset the admin set up
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustRequirement {
+    Metrics(TrustMetrics),
+    /// Looked up when the escrow is created or changed, the escrow keeps the metrics
+    /// it found even if the preset is edited later
+    Preset(String),
+}
+
+impl From<TrustMetrics> for TrustRequirement {
+    fn from(metrics: TrustMetrics) -> Self {
+        TrustRequirement::Metrics(metrics)
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_fiat_currency() -> String {
+    DEFAULT_FIAT_CURRENCY.to_string()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchCreateItem {
+    pub msg: CreateMsg,
+    /// The part of the coins sent that funds this escrow
+    pub amount: Vec<Coin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ArbitrateMsg {
-    pub reciever: Addr,
+    /// The party the arbiter rules in favor of, either the creator or the fulfiller
+    pub receiver: String,
+    /// When set, the balance is split instead: this share (in basis points) is refunded
+    /// to the creator and the remainder goes to the fulfiller
+    pub creator_bps: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -85,14 +579,211 @@ pub fn is_valid_name(name: &str) -> bool {
     true
 }
 
+/// Orders List and Listings by when escrows were created rather than their default order
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ListOrder {
+    CreatedAsc,
+    CreatedDesc,
+}
+
+/// Ranks Arbiters by their load or track record rather than by address
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ArbiterSort {
+    /// Fewest open escrows first, then fewest disputes waiting on them
+    LeastLoaded,
+    /// Most rulings first
+    MostExperienced,
+}
+
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    /// Show all open escrows. Return type is ListResponse.
-    List {},
-    /// Returns the details of the named escrow, error if not created
+    /// Show all open escrows, by id or in the order given. Listings that expired
+    /// unaccepted are left out unless include_expired, those not live yet unless
+    /// include_upcoming, canceled ones waiting out the cooloff always are.
+    /// Return type is ListResponse.
+    List {
+        #[serde(default)]
+        include_expired: bool,
+        #[serde(default)]
+        include_upcoming: bool,
+        order: Option<ListOrder>,
+    },
+    /// Lists escrows still open for acceptance, most recently created or bumped first,
+    /// or by creation time alone with an order.
+    /// start_after takes the id of the last listing of the previous page.
+    /// With a viewer, each listing tells whether they may accept it and what's in the way.
+    /// Expired listings are left out, or included and marked expired with include_expired.
+    /// Listings not live yet are left out, or included with their starts_at with
+    /// include_upcoming. Return type: ListingsResponse.
+    Listings {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        viewer: Option<String>,
+        #[serde(default)]
+        include_expired: bool,
+        #[serde(default)]
+        include_upcoming: bool,
+        order: Option<ListOrder>,
+    },
+    /// Walks a page of Listings, as with the same start_after, limit and include flags,
+    /// and splits it into the listings the address may accept and those it may not, with
+    /// the reason. A page may hold few or no eligible listings, keep paging with
+    /// next_start_after. Return type: EligibleListingsResponse.
+    EligibleListings {
+        address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        #[serde(default)]
+        include_expired: bool,
+        #[serde(default)]
+        include_upcoming: bool,
+    },
+    /// Lists escrows open for acceptance carrying the tag, by id. start_after takes the id
+    /// of the last listing of the previous page. Expired listings and those not live yet
+    /// are left out.
+    /// Return type: ListingsResponse.
+    ListByTag {
+        tag: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the details of the named escrow, error if not created. Its shape is frozen,
+    /// new fields only go to DetailsV2.
     /// Return type: DetailsResponse.
     Details { id: String },
+    /// The named escrow grouped by concern, with its status and deadlines, error if not
+    /// created
+    /// Return type: DetailsV2Response.
+    DetailsV2 { id: String },
+    /// The CreateMsg that would create an escrow on the same terms, for replacing one with
+    /// tweaks. Works for settled escrows too, under the next free id as for live ones.
+    /// Return type: AsCreateMsgResponse.
+    AsCreateMsg { id: String },
+    /// What an escrow paid out when it settled, the same as the data of the response that
+    /// settled it. Only for escrows completed, claimed or arbitrated, not canceled ones.
+    /// Return type: Receipt.
+    Receipt { id: String },
+    /// The encrypted payout details of the escrow, see ExecuteMsg::SetPayoutDetails.
+    /// Details only tells whether there are any. Hidden until the creator acks a fulfiller
+    /// on escrows requiring it, see CreateMsg::require_creator_ack.
+    /// Return type: PayoutDetailsResponse.
+    PayoutDetails { id: String },
+    /// Lists the transfers that releasing the escrow with the given outcome would produce,
+    /// without changing any state. Return type: SimulateReleaseResponse.
+    SimulateRelease { id: String, outcome: ReleaseOutcome },
+    /// Returns the details of the escrow with the given fingerprint, case insensitive.
+    /// Return type: DetailsResponse.
+    ByFingerprint { fingerprint: String },
+    /// The escrows the fulfiller accepted from the creator, newest first. Settled ones too
+    /// if include_archived. Pass the last revision seen as start_after for the next page.
+    /// Return type: FindByPartiesResponse.
+    FindByParties {
+        creator: String,
+        fulfiller: String,
+        #[serde(default)]
+        include_archived: bool,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns every deadline of the named escrow. Return type: DeadlinesResponse.
+    Deadlines { id: String },
+    /// Lists the escrows changed after the given revision, oldest change first.
+    /// Pass the last revision seen to poll incrementally. Return type: ChangedSinceResponse.
+    ChangedSince { revision: u64, limit: Option<u32> },
+    /// Returns the address's trust metrics, zero for an unknown address.
+    /// Return type: TrustMetrics.
+    TrustMetrics { address: String },
+    /// Returns the address's record as a creator, zero for an unknown address.
+    /// Return type: CreatorMetrics.
+    CreatorMetrics { address: String },
+    /// Returns the arbiter's track record, registered or not. Return type: ArbiterStatsResponse.
+    ArbiterStats { address: String },
+    /// Lists the track record of every arbiter that ever ruled or missed a deadline,
+    /// in ascending order. Return type: AllArbiterStatsResponse.
+    AllArbiterStats {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lists trust presets in ascending order of name. Return type: TrustPresetsResponse.
+    TrustPresets {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Lists registered arbiters with their load and track record, in ascending order
+    /// or ranked by sort_by. Ties keep address order, and start_after takes the address
+    /// of the last arbiter of the previous page either way. Ranking reads the whole
+    /// registry, and a page may shift as loads change. Return type: ArbitersResponse.
+    Arbiters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        sort_by: Option<ArbiterSort>,
+    },
+    /// Lists blacklisted addresses in ascending order. Return type: BlacklistResponse.
+    Blacklist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Runs the checks CCreate would on the message and funds, without creating anything.
+    /// The blacklist is only checked when the creator is given.
+    /// Return type: ValidateCreateResponse.
+    ValidateCreate {
+        msg: CreateMsg,
+        funds: Vec<Coin>,
+        creator: Option<String>,
+    },
+    /// Lists the features this deployment supports. Return type: CapabilitiesResponse.
+    Capabilities {},
+    /// Lists the last 1000 admin actions, newest first. start_after takes a sequence number.
+    /// Return type: AdminLogResponse.
+    AdminLog {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The uusd listed and not yet accepted, per fiat currency sought. Only the currency
+    /// given, or every currency with listings. Listings that expired unaccepted count until
+    /// they're canceled, unlike in Listings. Return type: OpenInterestResponse.
+    OpenInterest { currency: Option<String> },
+    /// What happened to the escrow so far, oldest first, also after it settled.
+    /// Return type: HistoryResponse.
+    History { id: String },
+    /// What all escrows hold together, to check against the contract's own balances.
+    /// Return type: TotalLockedResponse.
+    TotalLocked {},
+    /// How many escrows are active, against the configured cap. Return type: StatsResponse.
+    Stats {},
+    /// Scans a page of escrows, at most 30, in ascending order of id for stored state
+    /// contradicting itself. The counters are checked against the page: exactly when it
+    /// holds every escrow, otherwise only for not falling short of it.
+    /// Return type: InvariantsResponse.
+    CheckInvariants {
+        start_after: Option<String>,
+        limit: u32,
+    },
+}
+
+/// The ways in which an escrow's balance can be paid out
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseOutcome {
+    /// The creator confirms fulfillment via CComplete
+    CreatorComplete,
+    /// The creator confirms part of the fulfillment via CComplete with release_bps
+    PartialComplete { release_bps: u64 },
+    /// The fulfiller claims after the creator's confirmation window lapsed
+    ClaimTimedOut,
+    /// The creator is refunded after the arbiter missed the deadline
+    ArbitrationTimedOut,
+    /// The parties settle a dispute in mediation via AcceptSettlement
+    Settlement { creator_bps: u64 },
+    /// The arbiter rules via ElArbitrate
+    ArbitrateFor {
+        receiver: String,
+        creator_bps: Option<u64>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -101,12 +792,13 @@ pub struct ListResponse {
     pub escrows: Vec<String>,
 }
 
+/// Frozen for the clients that parse it, see DetailsV2Response for anything new
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct DetailsResponse {
     /// id of this escrow
     pub id: String,
-    /// arbiter can decide to approve or refund the escrow
-    pub arbiter: String,
+    /// arbiter can decide to approve or refund the escrow, none if arbitration is disabled
+    pub arbiter: Option<String>,
     /// if approved, funds go to the recipient
     pub fulfiller: String,
     /// if refunded, funds go to the source
@@ -118,10 +810,462 @@ pub struct DetailsResponse {
     /// block time exceeds this value, the escrow is expired.
     /// Once an escrow is expired, it can be returned to the original funder (via "refund").
     pub end_time: Option<u64>,
+    /// Nobody may accept before then, see CreateMsg
+    pub start_time: Option<u64>,
+    /// The last second the escrow is open, resolved from whichever of end_height
+    /// (estimated from the block time) and end_time comes first
+    pub expires_at_seconds: Option<u64>,
+    /// Which bound expires_at_seconds was resolved from
+    pub expires_by: Option<ExpirySource>,
     /// Balance in native tokens
     pub native_balance: Vec<Coin>,
+    /// native_balance formatted for display, coin by coin
+    pub native_balance_display: Vec<String>,
     /// Balance in cw20 tokens
     pub cw20_balance: Vec<Cw20Coin>,
+    /// Deposit paid for listing, held apart from the balance
+    pub listing_deposit: Vec<Coin>,
+    /// listing_deposit formatted for display, coin by coin
+    pub listing_deposit_display: Vec<String>,
     /// Whitelisted cw20 tokens
     pub cw20_whitelist: Vec<String>,
+    /// The only addresses that may accept, anyone if none
+    pub allowed_fulfillers: Option<Vec<String>>,
+    /// Who the creator turned away, they may not accept
+    pub rejected_fulfillers: Vec<String>,
+    /// Trust metrics that release the escrow on fulfillment, see CreateMsg
+    pub auto_complete_trust: Option<TrustMetrics>,
+    pub description: Option<String>,
+    /// The creator's locale, see CreateMsg
+    pub creator_locale: Option<String>,
+    /// The creator's offset from UTC in minutes, see CreateMsg
+    pub creator_utc_offset_minutes: Option<i16>,
+    /// Exchange rate listed, in fiat_currency per UST
+    pub exchange_rate: Decimal,
+    pub fiat_currency: String,
+    /// Fiat the fulfiller reports delivering, in cents
+    pub delivered_fiat_amount: Option<Uint128>,
+    /// Exchange rate implied by the delivered amount and the uusd held
+    pub realized_exchange_rate: Option<Decimal>,
+    /// Why the creator requested arbitration, if they did
+    pub dispute: Option<Dispute>,
+    /// While disputed, when the parties' time to settle runs out and the arbiter's starts
+    pub mediation_until: Option<u64>,
+    /// The split offered during mediation, see ProposeSettlement
+    pub settlement_proposal: Option<SettlementProposal>,
+    /// Short code for people to reference the escrow by
+    pub fingerprint: String,
+    /// Whether the escrow was created with native coins or through a cw20 token's hook
+    pub funding_source: FundingSource,
+    /// Waiting for the arbiter to take the role before being listed
+    pub pending_arbiter_ack: bool,
+    /// The arbiter recused themselves, waiting for the admin to ReassignArbiter
+    pub pending_reassignment: bool,
+    /// When the arbiter's time to take the role runs out, while pending
+    pub arbiter_ack_deadline: Option<u64>,
+    /// Whether the creator vets fulfillers before they may fulfill, see CreateMsg
+    pub require_creator_ack: bool,
+    /// Accepted, waiting for the creator to CAckFulfiller or CReject
+    pub awaiting_creator_ack: bool,
+    /// When the acceptance lapses unless the creator acks, while awaiting it
+    pub creator_ack_deadline: Option<u64>,
+    /// Until when anyone may accept regardless of the trust requirements, see OpenToAll
+    pub open_to_all_until: Option<u64>,
+    /// Canceled, the creator may UndoCancel until then and anyone FinalizeCancel after
+    pub cancel_pending_until: Option<u64>,
+    /// Whether the creator stored payout details, see QueryMsg::PayoutDetails
+    pub has_payout_details: bool,
+    /// Bytes the escrow takes in storage, appends fail past the configured max_escrow_bytes
+    pub approximate_size: u64,
+    /// How risky accepting the escrow is for the fulfiller, from 0 to 100, see risk_score
+    pub risk_score: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DetailsV2Response {
+    pub id: String,
+    /// Short code for people to reference the escrow by
+    pub fingerprint: String,
+    pub status: EscrowStatus,
+    pub creator: String,
+    /// Who accepted, none while listed
+    pub fulfiller: Option<String>,
+    /// None if arbitration is disabled
+    pub arbiter: Option<String>,
+    /// The arbiter recused themselves, waiting for the admin to ReassignArbiter
+    pub pending_reassignment: bool,
+    /// When the arbiter's time to take the role runs out, while pending
+    pub arbiter_ack_deadline: Option<u64>,
+    /// Whether the escrow was created with native coins or through a cw20 token's hook
+    pub funding_source: FundingSource,
+    pub native_balance: Vec<Coin>,
+    /// native_balance formatted for display, coin by coin
+    pub native_balance_display: Vec<String>,
+    pub cw20_balance: Vec<Cw20Coin>,
+    pub cw20_whitelist: Vec<String>,
+    /// Exchange rate listed, in fiat_currency per UST
+    pub exchange_rate: Decimal,
+    pub fiat_currency: String,
+    /// Fiat the fulfiller reports delivering, in cents
+    pub delivered_fiat_amount: Option<Uint128>,
+    /// Exchange rate implied by the delivered amount and the uusd held
+    pub realized_exchange_rate: Option<Decimal>,
+    pub description: Option<String>,
+    /// The creator's locale, see CreateMsg
+    pub creator_locale: Option<String>,
+    /// The creator's offset from UTC in minutes, see CreateMsg
+    pub creator_utc_offset_minutes: Option<i16>,
+    pub timestamps: EscrowTimestamps,
+    pub trust: EscrowTrust,
+    pub fees: EscrowFees,
+    pub deadlines: DeadlinesResponse,
+    /// Why the creator requested arbitration, if they did
+    pub dispute: Option<Dispute>,
+    /// The split offered during mediation, see ProposeSettlement
+    pub settlement_proposal: Option<SettlementProposal>,
+    /// Whether the creator stored payout details, see QueryMsg::PayoutDetails
+    pub has_payout_details: bool,
+    /// Bytes the escrow takes in storage, appends fail past the configured max_escrow_bytes
+    pub approximate_size: u64,
+    /// Non-terminal actions each address took on the escrow, see
+    /// InstantiateMsg::max_actions_per_address
+    pub action_counts: Vec<ActionCount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ActionCount {
+    pub address: String,
+    pub actions: u32,
+}
+
+/// When an escrow reached each step, in seconds since epoch
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EscrowTimestamps {
+    pub created: Option<u64>,
+    pub accepted: Option<u64>,
+    pub fulfilled: Option<u64>,
+    pub arbitration_started: Option<u64>,
+    /// When the creator last bumped the listing, see Bump
+    pub last_bumped: Option<u64>,
+}
+
+/// Who may accept an escrow and how far the creator can be trusted
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EscrowTrust {
+    /// What a fulfiller's metrics must meet to accept, see CreateMsg
+    pub required_trust_metrics: TrustMetrics,
+    /// Trust metrics that release the escrow on fulfillment, see CreateMsg
+    pub auto_complete_trust: Option<TrustMetrics>,
+    /// The only addresses that may accept, anyone if none
+    pub allowed_fulfillers: Option<Vec<String>>,
+    /// Who the creator turned away, they may not accept
+    pub rejected_fulfillers: Vec<String>,
+    /// Until when anyone may accept regardless of the trust requirements, see OpenToAll
+    pub open_to_all_until: Option<u64>,
+    /// Whether the creator vets fulfillers before they may fulfill, see CreateMsg
+    pub require_creator_ack: bool,
+    /// How risky accepting the escrow is for the fulfiller, from 0 to 100, see risk_score
+    pub risk_score: u8,
+}
+
+/// What an escrow costs its parties besides the balance it moves
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EscrowFees {
+    /// Deposit paid for listing, held apart from the balance
+    pub listing_deposit: Vec<Coin>,
+    /// listing_deposit formatted for display, coin by coin
+    pub listing_deposit_display: Vec<String>,
+    /// Of the balance, what the creator pays the fulfiller for canceling after acceptance
+    pub cancel_penalty_bps: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AsCreateMsgResponse {
+    /// Under the first free one of the id, id-2, id-3, ... The expiry is as stored, one
+    /// already past must be moved before creating. The funds and listing deposit aren't
+    /// part of it, they're sent along as before. Neither are the payout details.
+    pub create_msg: CreateMsg,
+    /// Whether the escrow settled, and this is how it was when it did
+    pub archived: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PayoutDetailsResponse {
+    /// As the creator's client encrypted it, None if nothing is stored or it's hidden
+    pub ciphertext: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PartyEscrow {
+    pub id: String,
+    /// The revision the fulfiller accepted at
+    pub revision: u64,
+    /// For a settled escrow its last step, e.g. "creator_complete", otherwise see
+    /// Escrow::status
+    pub status: String,
+    pub archived: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct FindByPartiesResponse {
+    pub escrows: Vec<PartyEscrow>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HistoryResponse {
+    pub entries: Vec<HistoryEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TotalLockedResponse {
+    /// Ordered by denom
+    pub native: Vec<Coin>,
+    /// Ordered by token address
+    pub cw20: Vec<Cw20Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StatsResponse {
+    /// Escrows created and not yet settled or canceled
+    pub active_escrows: u64,
+    /// Creating escrows fails once active_escrows reaches it
+    pub max_active_escrows: Option<u64>,
+    /// The contract is winding down, see BeginSunset
+    pub sunset: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct InvariantsResponse {
+    pub violations: Vec<InvariantViolation>,
+    /// How many escrows the page held
+    pub scanned: u32,
+    /// Where the next page starts, None once the escrows are exhausted
+    pub next_start_after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct InvariantViolation {
+    /// The escrow at fault, None for the contract-wide counters
+    pub id: Option<String>,
+    /// contradictory_status, inconsistent_balance, zero_native_amount,
+    /// timestamps_out_of_order, fulfiller_is_creator, active_escrows_mismatch or
+    /// open_interest_mismatch
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OpenInterestResponse {
+    /// Ordered by currency code
+    pub currencies: Vec<CurrencyOpenInterest>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CurrencyOpenInterest {
+    pub currency: String,
+    /// uusd held by the listings
+    pub value_ust: Uint128,
+    pub listings: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ListingsResponse {
+    pub listings: Vec<ListingSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EligibleListingsResponse {
+    /// The listings of the page the address may accept, in listing order
+    pub listings: Vec<ListingSummary>,
+    /// The other listings of the page, with the first requirement the address fails
+    pub near_misses: Vec<NearMiss>,
+    /// Where the next page starts, None once the listings are exhausted
+    pub next_start_after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NearMiss {
+    pub id: String,
+    /// Why accepting would fail, as FAccept would report it
+    pub reason: String,
+}
+
+/// What a fulfiller needs to pick a listing
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ListingSummary {
+    pub id: String,
+    pub fingerprint: String,
+    pub creator: String,
+    /// Exchange rate desired, in fiat_currency per UST
+    pub exchange_rate: Decimal,
+    pub fiat_currency: String,
+    pub native_balance: Vec<Coin>,
+    /// native_balance formatted for display, coin by coin
+    pub native_balance_display: Vec<String>,
+    pub cw20_balance: Vec<Cw20Coin>,
+    pub description: Option<String>,
+    /// The creator's locale, see CreateMsg
+    pub creator_locale: Option<String>,
+    /// The creator's offset from UTC in minutes, see CreateMsg
+    pub creator_utc_offset_minutes: Option<i16>,
+    /// Whether the listing ran out unaccepted, only ever true with include_expired
+    pub expired: bool,
+    /// Until when anyone may accept regardless of the trust requirements, see OpenToAll
+    pub open_to_all_until: Option<u64>,
+    /// When the listing goes live, only ever set with include_upcoming
+    pub starts_at: Option<u64>,
+    /// How risky accepting the listing is for the fulfiller, from 0 to 100, see risk_score
+    pub risk_score: u8,
+    /// Whether the viewer may accept the listing, only when a viewer was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acceptable: Option<bool>,
+    /// Codes of the requirements the viewer fails, in the order FAccept checks them:
+    /// sunset, blacklisted, creator, arbiter, pending_arbiter_ack, pending_reassignment, accepted, not_listed, expired,
+    /// insufficient_time, grace_period, not_yet_active, not_on_allowlist, rejected, trust_metrics. Only when a viewer was given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockers: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SimulateReleaseResponse {
+    /// Every transfer the release would send, in the order the messages are emitted
+    pub transfers: Vec<Transfer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Transfer {
+    pub recipient: String,
+    /// Native denom or cw20 token address
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AdminLogResponse {
+    pub entries: Vec<AdminLogEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BlacklistResponse {
+    pub entries: Vec<BlacklistInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BlacklistInfo {
+    pub address: String,
+    pub reason: String,
+    /// When the address was blacklisted, in seconds since epoch
+    pub since: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DeadlinesResponse {
+    /// The last second the escrow is open, see DetailsResponse
+    pub expires_at_seconds: Option<u64>,
+    pub expires_by: Option<ExpirySource>,
+    /// Once accepted, when the fulfiller's time to fulfill runs out
+    pub accept_deadline: Option<u64>,
+    /// Once accepted and until fulfilled, when the fulfiller's time to fulfill runs out
+    /// under the config's mid_lifecycle_expiry: the accept_deadline, or the expiry if it
+    /// comes first under HardExpire
+    pub effective_accept_deadline: Option<u64>,
+    /// Once fulfilled, when the creator's time to confirm or dispute runs out. After it the
+    /// fulfiller may ClaimTimedOut.
+    pub fulfill_deadline: Option<u64>,
+    /// Once disputed, when mediation ends and the arbiter's time to rule starts
+    pub mediation_until: Option<u64>,
+    /// Once disputed, when the arbiter's time to rule runs out, extensions included
+    pub arbitration_deadline: Option<u64>,
+    /// How often the arbiter extended the arbitration deadline, see ArbiterRequestInfo
+    pub arbitration_extensions: u32,
+    /// When the escrow may first be accepted, if the grace period is configured
+    pub grace_period_end: Option<u64>,
+    /// When the escrow goes live, if the creator scheduled it
+    pub start_time: Option<u64>,
+    /// While the creator is to ack the fulfiller, when the acceptance lapses
+    pub creator_ack_deadline: Option<u64>,
+    /// Once canceled during a cooloff, when it may no longer be undone
+    pub cancel_pending_until: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ArbitersResponse {
+    pub arbiters: Vec<ArbiterEntry>,
+}
+
+/// What a creator weighs when picking an arbiter, out of their ArbiterStats
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ArbiterEntry {
+    pub address: String,
+    /// Open escrows naming them
+    pub active_escrows: u32,
+    /// Of those, the ones disputed and not yet resolved
+    pub in_arbitration: u32,
+    pub rulings: u32,
+    /// uusd held by the escrows disputed with them, over all time
+    pub disputed_volume_ust: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TrustPresetsResponse {
+    pub presets: Vec<TrustPreset>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TrustPreset {
+    pub name: String,
+    pub metrics: TrustMetrics,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ChangedSinceResponse {
+    pub changes: Vec<EscrowChange>,
+    /// The revision of the latest change overall
+    pub latest_revision: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EscrowChange {
+    pub id: String,
+    pub revision: u64,
+    /// The escrow as of this change, none if it was paid out or canceled since
+    pub escrow: Option<DetailsResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ArbiterStatsResponse {
+    pub address: String,
+    /// Whether the arbiter is still in the registry
+    pub registered: bool,
+    pub stats: ArbiterStats,
+    /// Average seconds from dispute to ruling, none before the first ruling
+    pub average_ruling_seconds: Option<u64>,
+    /// Share of disputes resolved by default because the arbiter missed the deadline
+    pub missed_deadline_ratio: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllArbiterStatsResponse {
+    pub arbiters: Vec<ArbiterStatsResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CapabilitiesResponse {
+    /// The cw2 contract name and version
+    pub contract: String,
+    pub version: String,
+    /// Feature names, e.g. "topup" or "arbitration_split"
+    pub capabilities: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BatchCreateResponse {
+    /// The escrows created, in the order of the items
+    pub ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ValidateCreateResponse {
+    pub ok: bool,
+    /// Why CCreate would fail, in the order it checks
+    pub violations: Vec<String>,
 }
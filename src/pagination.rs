@@ -0,0 +1,151 @@
+use cosmwasm_std::{Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Map, PrimaryKey};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Entries in a page when the caller doesn't say
+pub const DEFAULT_LIMIT: u32 = 10;
+/// Most entries in a page, whatever the caller asks for
+pub const MAX_LIMIT: u32 = 30;
+
+/// How many entries a page holds for the limit asked for
+pub fn page_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize
+}
+
+/// Range bounds walking a map in the order given, starting right after the raw key
+/// start_after. Descending walks start from the top, so there the cursor is the upper bound.
+pub fn page_bounds(start_after: Option<Vec<u8>>, order: Order) -> (Option<Bound>, Option<Bound>) {
+    let cursor = start_after.map(Bound::exclusive);
+    match order {
+        Order::Ascending => (cursor, None),
+        Order::Descending => (None, cursor),
+    }
+}
+
+/// One page of a map's entries with their raw keys, for integer and pair keys.
+/// start_after is the raw key of the last entry of the previous page.
+pub fn page_raw<'a, K, T>(
+    map: &Map<'a, K, T>,
+    storage: &dyn Storage,
+    start_after: Option<Vec<u8>>,
+    order: Order,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Vec<u8>, T)>>
+where
+    K: PrimaryKey<'a, SubPrefix = ()>,
+    T: Serialize + DeserializeOwned,
+{
+    let (min, max) = page_bounds(start_after, order);
+    map.sub_prefix(())
+        .range(storage, min, max, order)
+        .take(page_limit(limit))
+        .collect()
+}
+
+/// One page of a map keyed by strings or addresses, in ascending key order
+pub fn page_map<'a, K, T>(
+    map: &Map<'a, K, T>,
+    storage: &dyn Storage,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, T)>>
+where
+    K: PrimaryKey<'a, SubPrefix = ()>,
+    T: Serialize + DeserializeOwned,
+{
+    let start_after = start_after.map(String::into_bytes);
+    page_raw(map, storage, start_after, Order::Ascending, limit)?
+        .into_iter()
+        .map(|(key, value)| Ok((String::from_utf8(key)?, value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Addr;
+    use cw_storage_plus::U64Key;
+
+    const NAMES: Map<&str, u32> = Map::new("names");
+    const ADDRESSES: Map<&Addr, u32> = Map::new("addresses");
+    const COMPOSITE: Map<(U64Key, &str), u32> = Map::new("composite");
+
+    fn names(page: Vec<(String, u32)>) -> Vec<String> {
+        page.into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn page_limit_defaults_and_clamps() {
+        assert_eq!(page_limit(None), DEFAULT_LIMIT as usize);
+        assert_eq!(page_limit(Some(0)), 0);
+        assert_eq!(page_limit(Some(5)), 5);
+        assert_eq!(page_limit(Some(MAX_LIMIT + 1)), MAX_LIMIT as usize);
+    }
+
+    #[test]
+    fn page_map_boundaries() {
+        let mut storage = MockStorage::new();
+        assert_eq!(page_map(&NAMES, &storage, None, None).unwrap(), vec![]);
+        let cursor = Some("a".to_string());
+        assert_eq!(page_map(&NAMES, &storage, cursor, None).unwrap(), vec![]);
+
+        for (i, name) in ["a", "b", "c"].iter().enumerate() {
+            NAMES.save(&mut storage, name, &(i as u32)).unwrap();
+        }
+        let page = page_map(&NAMES, &storage, None, Some(2)).unwrap();
+        assert_eq!(page, vec![("a".to_string(), 0), ("b".to_string(), 1)]);
+        // the cursor itself is never repeated
+        let page = page_map(&NAMES, &storage, Some("b".to_string()), None).unwrap();
+        assert_eq!(names(page), vec!["c"]);
+        let page = page_map(&NAMES, &storage, Some("c".to_string()), None).unwrap();
+        assert_eq!(page, vec![]);
+        // a cursor that isn't a key still pages on from where it would be
+        let page = page_map(&NAMES, &storage, Some("aa".to_string()), None).unwrap();
+        assert_eq!(names(page), vec!["b", "c"]);
+        assert_eq!(page_map(&NAMES, &storage, None, Some(0)).unwrap(), vec![]);
+
+        // addresses page by their string too
+        for name in ["x", "y"] {
+            ADDRESSES
+                .save(&mut storage, &Addr::unchecked(name), &0)
+                .unwrap();
+        }
+        let page = page_map(&ADDRESSES, &storage, Some("x".to_string()), None).unwrap();
+        assert_eq!(names(page), vec!["y"]);
+    }
+
+    #[test]
+    fn page_raw_boundaries() {
+        let mut storage = MockStorage::new();
+        let page = page_raw(&COMPOSITE, &storage, None, Order::Descending, None).unwrap();
+        assert_eq!(page, vec![]);
+
+        let keys = [(1u64, "b"), (2, "a"), (2, "b"), (3, "a")];
+        for (i, (n, s)) in keys.iter().enumerate() {
+            COMPOSITE
+                .save(&mut storage, ((*n).into(), s), &(i as u32))
+                .unwrap();
+        }
+        let values = |page: Vec<(Vec<u8>, u32)>| -> Vec<u32> {
+            page.into_iter().map(|(_, value)| value).collect()
+        };
+        let raw = |n: u64, s: &str| Some((U64Key::from(n), s).joined_key());
+
+        let page = page_raw(&COMPOSITE, &storage, None, Order::Descending, Some(3)).unwrap();
+        assert_eq!(values(page), vec![3, 2, 1]);
+        let page = page_raw(&COMPOSITE, &storage, raw(2, "a"), Order::Descending, None);
+        assert_eq!(values(page.unwrap()), vec![0]);
+        let page = page_raw(&COMPOSITE, &storage, raw(2, "a"), Order::Ascending, None);
+        assert_eq!(values(page.unwrap()), vec![2, 3]);
+        // past the last key either way
+        let page = page_raw(&COMPOSITE, &storage, raw(3, "a"), Order::Ascending, None);
+        assert_eq!(page.unwrap(), vec![]);
+        let page = page_raw(&COMPOSITE, &storage, raw(1, "b"), Order::Descending, None);
+        assert_eq!(page.unwrap(), vec![]);
+        let page = page_raw(&COMPOSITE, &storage, None, Order::Ascending, Some(0));
+        assert_eq!(page.unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,637 @@
+use cosmwasm_std::{to_binary, Addr, BankMsg, Binary, Coin, StdResult, SubMsg, WasmMsg};
+use cw20::{Balance, Cw20CoinVerified, Cw20ExecuteMsg};
+
+use crate::state::{DisplayDenom, GenericBalance};
+
+/// Basis points in a whole
+pub const MAX_BPS: u64 = 10_000;
+
+/// Builds the messages paying out the whole balance to one recipient: a single
+/// BankMsg for all native coins, or one per denom with `split_native`, followed by
+/// one cw20 transfer per token. Denoms and tokens go out in GenericBalance::normalize
+/// order, however the balance was stored.
+pub fn build_transfers(
+    to: &Addr,
+    balance: &GenericBalance,
+    split_native: bool,
+) -> StdResult<Vec<SubMsg>> {
+    build_transfers_with_hook(to, balance, split_native, None)
+}
+
+/// As build_transfers, but with a hook the cw20 tokens are sent to the recipient with
+/// Cw20ExecuteMsg::Send carrying it, for contracts that want to know what they're paid
+/// for. The recipient must accept the hook or the payout fails.
+pub fn build_transfers_with_hook(
+    to: &Addr,
+    balance: &GenericBalance,
+    split_native: bool,
+    hook: Option<&Binary>,
+) -> StdResult<Vec<SubMsg>> {
+    let mut balance = balance.clone();
+    balance.normalize();
+    // Some chains reject zero-amount sends, which would make the escrow unpayable
+    let native_balance: Vec<_> = balance
+        .native
+        .iter()
+        .filter(|c| !c.amount.is_zero())
+        .cloned()
+        .collect();
+    let mut msgs: Vec<SubMsg> = if native_balance.is_empty() {
+        vec![]
+    } else if split_native {
+        native_balance
+            .into_iter()
+            .map(|coin| {
+                SubMsg::new(BankMsg::Send {
+                    to_address: to.into(),
+                    amount: vec![coin],
+                })
+            })
+            .collect()
+    } else {
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: to.into(),
+            amount: native_balance,
+        })]
+    };
+
+    let cw20_balance = &balance.cw20;
+    let cw20_msgs: StdResult<Vec<_>> = cw20_balance
+        .iter()
+        .filter(|c| !c.amount.is_zero())
+        .map(|c| {
+            let msg = match hook {
+                Some(hook) => Cw20ExecuteMsg::Send {
+                    contract: to.into(),
+                    amount: c.amount,
+                    msg: hook.clone(),
+                },
+                None => Cw20ExecuteMsg::Transfer {
+                    recipient: to.into(),
+                    amount: c.amount,
+                },
+            };
+            let exec = SubMsg::new(WasmMsg::Execute {
+                contract_addr: c.address.to_string(),
+                msg: to_binary(&msg)?,
+                funds: vec![],
+            });
+            Ok(exec)
+        })
+        .collect();
+    msgs.append(&mut cw20_msgs?);
+    Ok(msgs)
+}
+
+/// Splits a balance in two, the first part getting `bps` basis points of every token
+/// and the second part the rest, so rounding favors the second part.
+/// Tokens a part gets none of are left out of it. `bps` above MAX_BPS counts as MAX_BPS.
+pub fn split(balance: &GenericBalance, bps: u64) -> (GenericBalance, GenericBalance) {
+    let bps = bps.min(MAX_BPS);
+    let mut first = GenericBalance::default();
+    let mut second = GenericBalance::default();
+    for coin in &balance.native {
+        let part = coin.amount.multiply_ratio(bps, MAX_BPS);
+        let mut first_coin = coin.clone();
+        first_coin.amount = part;
+        let mut second_coin = coin.clone();
+        second_coin.amount = coin.amount - part;
+        first.native.push(first_coin);
+        second.native.push(second_coin);
+    }
+    for token in &balance.cw20 {
+        let part = token.amount.multiply_ratio(bps, MAX_BPS);
+        first.cw20.push(Cw20CoinVerified {
+            address: token.address.clone(),
+            amount: part,
+        });
+        second.cw20.push(Cw20CoinVerified {
+            address: token.address.clone(),
+            amount: token.amount - part,
+        });
+    }
+    for part in [&mut first, &mut second] {
+        part.native.retain(|c| !c.amount.is_zero());
+        part.cw20.retain(|c| !c.amount.is_zero());
+    }
+    (first, second)
+}
+
+/// Adds two balances together, in GenericBalance::normalize order
+pub fn merge(a: &GenericBalance, b: &GenericBalance) -> GenericBalance {
+    let mut merged = a.clone();
+    merged.add_tokens(Balance::from(b.native.clone()));
+    for token in &b.cw20 {
+        merged.add_tokens(Balance::Cw20(token.clone()));
+    }
+    merged
+}
+
+/// Merges native coins of the same denom and orders them by denom, so funds are checked
+/// the way they'll be stored. Zero amounts merge in too, a denom adding up to zero is kept
+/// for assert_no_zero_amounts to reject. cw20 balances are a single token already.
+pub fn normalize(balance: Balance) -> Balance {
+    match balance {
+        Balance::Native(coins) => {
+            let mut merged: Vec<Coin> = Vec::with_capacity(coins.0.len());
+            for coin in coins.0 {
+                match merged.iter_mut().find(|c| c.denom == coin.denom) {
+                    Some(existing) => existing.amount += coin.amount,
+                    None => merged.push(coin),
+                }
+            }
+            merged.sort_by(|a, b| a.denom.cmp(&b.denom));
+            Balance::from(merged)
+        }
+        token => token,
+    }
+}
+
+/// The coin's amount in whole units of its display denom, keeping every decimal,
+/// e.g. "100.000000 UST". Denoms without one show the raw amount.
+pub fn display_amount(coin: &Coin, denoms: &[DisplayDenom]) -> String {
+    let raw = coin.amount.to_string();
+    let display = match denoms.iter().find(|d| d.denom == coin.denom) {
+        Some(display) => display,
+        None => return raw,
+    };
+    let decimals = display.decimals as usize;
+    if decimals == 0 {
+        return format!("{} {}", raw, display.symbol);
+    }
+    // at least one digit before the point
+    let padded = format!("{:0>width$}", raw, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    format!("{}.{} {}", whole, fraction, display.symbol)
+}
+
+/// Formats every coin, see display_amount
+pub fn display_amounts(coins: &[Coin], denoms: &[DisplayDenom]) -> Vec<String> {
+    coins.iter().map(|c| display_amount(c, denoms)).collect()
+}
+
+/// Takes the deposit out of native funds, None if they fall short of it.
+/// Funds sent through a cw20 hook carry no native coins and pay no deposit.
+pub fn take_deposit(balance: Balance, deposit: &[Coin]) -> Option<(Balance, Vec<Coin>)> {
+    let mut coins = match balance {
+        Balance::Native(coins) if !deposit.is_empty() => coins.0,
+        balance => return Some((balance, vec![])),
+    };
+    for owed in deposit {
+        let coin = coins.iter_mut().find(|c| c.denom == owed.denom)?;
+        coin.amount = coin.amount.checked_sub(owed.amount).ok()?;
+    }
+    // what only paid the deposit isn't escrowed, zero amounts sent are still rejected
+    coins.retain(|c| !c.amount.is_zero() || !deposit.iter().any(|d| d.denom == c.denom));
+    Some((Balance::from(coins), deposit.to_vec()))
+}
+
+/// Takes b out of a. Tokens a runs out of are left out, a never goes below zero.
+pub fn subtract(a: &GenericBalance, b: &GenericBalance) -> GenericBalance {
+    let mut rest = a.clone();
+    for coin in &b.native {
+        if let Some(have) = rest.native.iter_mut().find(|c| c.denom == coin.denom) {
+            have.amount = have.amount.saturating_sub(coin.amount);
+        }
+    }
+    for token in &b.cw20 {
+        if let Some(have) = rest.cw20.iter_mut().find(|t| t.address == token.address) {
+            have.amount = have.amount.saturating_sub(token.amount);
+        }
+    }
+    rest.native.retain(|c| !c.amount.is_zero());
+    rest.cw20.retain(|t| !t.amount.is_zero());
+    rest
+}
+
+/// Shares a refund out among those who paid into `total`: each share gets its own
+/// fraction of every token in the refund, rounded down, and `rest_to` gets what's left,
+/// listed first. Shares only ever draw on what the refund has left of a token.
+/// Recipients left with nothing are dropped.
+pub fn share_out(
+    refund: &GenericBalance,
+    total: &GenericBalance,
+    shares: &[(Addr, GenericBalance)],
+    rest_to: &Addr,
+) -> Vec<(Addr, GenericBalance)> {
+    let mut rest = refund.clone();
+    let mut payouts = vec![];
+    for (to, share) in shares {
+        let mut part = GenericBalance::default();
+        for coin in &share.native {
+            let paid = total.native.iter().find(|c| c.denom == coin.denom);
+            let paid = paid.filter(|c| !c.amount.is_zero());
+            let left = rest.native.iter().find(|c| c.denom == coin.denom);
+            if let (Some(paid), Some(left)) = (paid, left) {
+                let refunded = refund.native.iter().find(|c| c.denom == coin.denom);
+                let refunded = refunded.map(|c| c.amount).unwrap_or_default();
+                let amount = coin.amount.multiply_ratio(refunded, paid.amount);
+                part.native.push(Coin {
+                    denom: coin.denom.clone(),
+                    amount: amount.min(left.amount),
+                });
+            }
+        }
+        for token in &share.cw20 {
+            let paid = total.cw20.iter().find(|t| t.address == token.address);
+            let paid = paid.filter(|t| !t.amount.is_zero());
+            let left = rest.cw20.iter().find(|t| t.address == token.address);
+            if let (Some(paid), Some(left)) = (paid, left) {
+                let refunded = refund.cw20.iter().find(|t| t.address == token.address);
+                let refunded = refunded.map(|t| t.amount).unwrap_or_default();
+                let amount = token.amount.multiply_ratio(refunded, paid.amount);
+                part.cw20.push(Cw20CoinVerified {
+                    address: token.address.clone(),
+                    amount: amount.min(left.amount),
+                });
+            }
+        }
+        part.native.retain(|c| !c.amount.is_zero());
+        part.cw20.retain(|t| !t.amount.is_zero());
+        rest = subtract(&rest, &part);
+        payouts.push((to.clone(), part));
+    }
+    payouts.insert(0, (rest_to.clone(), rest));
+    payouts.retain(|(_, balance)| !balance.native.is_empty() || !balance.cw20.is_empty());
+    payouts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::{coin, coins, Uint128};
+
+    fn cw20(address: &str, amount: u128) -> Cw20CoinVerified {
+        Cw20CoinVerified {
+            address: Addr::unchecked(address),
+            amount: Uint128::new(amount),
+        }
+    }
+
+    fn transfer(recipient: &str, token: &str, amount: u128) -> SubMsg {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::new(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    }
+
+    #[test]
+    fn build_transfers_empty_balance() {
+        let to = Addr::unchecked("fulfiller");
+        assert!(build_transfers(&to, &GenericBalance::default(), false)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn build_transfers_many_denoms_and_tokens() {
+        let balance = GenericBalance {
+            native: vec![coin(7, "tokens"), coin(3, "stake")],
+            cw20: vec![cw20("cash", 5), cw20("bolos", 9)],
+        };
+        let msgs = build_transfers(&Addr::unchecked("fulfiller"), &balance, false).unwrap();
+        // paid out in canonical order, whatever order the balance was in
+        assert_eq!(
+            msgs,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "fulfiller".to_string(),
+                    amount: vec![coin(3, "stake"), coin(7, "tokens")],
+                }),
+                transfer("fulfiller", "bolos", 9),
+                transfer("fulfiller", "cash", 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_transfers_drops_empty_coins() {
+        let balance = GenericBalance {
+            native: vec![coin(0, "phantom"), coin(7, "tokens")],
+            cw20: vec![cw20("cash", 0)],
+        };
+        let msgs = build_transfers(&Addr::unchecked("fulfiller"), &balance, false).unwrap();
+        assert_eq!(
+            msgs,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "fulfiller".to_string(),
+                amount: coins(7, "tokens"),
+            })]
+        );
+
+        let empty = GenericBalance {
+            native: vec![coin(0, "phantom")],
+            cw20: vec![],
+        };
+        assert!(
+            build_transfers(&Addr::unchecked("fulfiller"), &empty, false)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn split_rounds_in_favor_of_the_second_part() {
+        let balance = GenericBalance {
+            native: vec![coin(3, "stake"), coin(1001, "tokens")],
+            cw20: vec![cw20("cash", 10)],
+        };
+        let (first, second) = split(&balance, 2500);
+        assert_eq!(
+            first,
+            GenericBalance {
+                native: vec![coin(250, "tokens")],
+                cw20: vec![cw20("cash", 2)],
+            }
+        );
+        assert_eq!(
+            second,
+            GenericBalance {
+                native: vec![coin(3, "stake"), coin(751, "tokens")],
+                cw20: vec![cw20("cash", 8)],
+            }
+        );
+        assert_eq!(merge(&first, &second), balance);
+    }
+
+    #[test]
+    fn split_extremes() {
+        let balance = GenericBalance {
+            native: coins(100, "tokens"),
+            cw20: vec![cw20("cash", 10)],
+        };
+        assert_eq!(
+            split(&balance, 0),
+            (GenericBalance::default(), balance.clone())
+        );
+        assert_eq!(
+            split(&balance, MAX_BPS),
+            (balance.clone(), GenericBalance::default())
+        );
+        assert_eq!(split(&balance, MAX_BPS + 1), split(&balance, MAX_BPS));
+        assert_eq!(
+            split(&GenericBalance::default(), 5000),
+            (GenericBalance::default(), GenericBalance::default())
+        );
+    }
+
+    #[test]
+    fn merge_sums_matching_tokens() {
+        let a = GenericBalance {
+            native: vec![coin(2, "stake"), coin(1, "tokens")],
+            cw20: vec![cw20("cash", 3)],
+        };
+        let b = GenericBalance {
+            native: vec![coin(20, "atom"), coin(10, "stake")],
+            cw20: vec![cw20("bolos", 30), cw20("cash", 40)],
+        };
+        assert_eq!(
+            merge(&a, &b),
+            GenericBalance {
+                native: vec![coin(20, "atom"), coin(12, "stake"), coin(1, "tokens")],
+                cw20: vec![cw20("bolos", 30), cw20("cash", 43)],
+            }
+        );
+        assert_eq!(merge(&GenericBalance::default(), &b), b);
+        assert_eq!(merge(&a, &GenericBalance::default()), a);
+    }
+
+    #[test]
+    fn balances_keep_one_order() {
+        let a = GenericBalance {
+            native: vec![coin(1, "uusd"), coin(2, "uluna")],
+            cw20: vec![cw20("cash", 1), cw20("bolos", 2)],
+        };
+        let b = GenericBalance {
+            native: vec![coin(2, "uluna"), coin(1, "uusd")],
+            cw20: vec![cw20("bolos", 2), cw20("cash", 1)],
+        };
+        let empty = GenericBalance::default();
+        let merged = merge(&empty, &a);
+        assert_eq!(merged, merge(&empty, &b));
+        assert_eq!(merged.native, vec![coin(2, "uluna"), coin(1, "uusd")]);
+        assert_eq!(merged.cw20, vec![cw20("bolos", 2), cw20("cash", 1)]);
+
+        // topping up with a new denom or token slots it into place
+        let mut topped_up = merged.clone();
+        topped_up.add_tokens(Balance::from(vec![coin(3, "ukrw")]));
+        topped_up.add_tokens(Balance::Cw20(cw20("atom", 4)));
+        let denoms: Vec<_> = topped_up.native.iter().map(|c| c.denom.as_str()).collect();
+        assert_eq!(denoms, vec!["ukrw", "uluna", "uusd"]);
+        assert_eq!(topped_up.cw20[0], cw20("atom", 4));
+
+        // so identical escrows pay out in identical messages
+        let to = Addr::unchecked("fulfiller");
+        for split_native in [false, true] {
+            assert_eq!(
+                build_transfers(&to, &a, split_native).unwrap(),
+                build_transfers(&to, &b, split_native).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn subtract_stops_at_zero() {
+        let a = GenericBalance {
+            native: vec![coin(3, "stake"), coin(100, "tokens")],
+            cw20: vec![cw20("bolos", 9), cw20("cash", 10)],
+        };
+        let b = GenericBalance {
+            native: vec![coin(1, "other"), coin(5, "stake"), coin(40, "tokens")],
+            cw20: vec![cw20("cash", 10)],
+        };
+        assert_eq!(
+            subtract(&a, &b),
+            GenericBalance {
+                native: coins(60, "tokens"),
+                cw20: vec![cw20("bolos", 9)],
+            }
+        );
+        assert_eq!(subtract(&merge(&a, &b), &b), a);
+    }
+
+    #[test]
+    fn normalize_merges_and_sorts_denoms() {
+        let funds = vec![
+            coin(5, "uusd"),
+            coin(0, "phantom"),
+            coin(3, "stake"),
+            coin(0, "uusd"),
+            coin(7, "uusd"),
+        ];
+        assert_eq!(
+            normalize(Balance::from(funds)),
+            Balance::from(vec![coin(0, "phantom"), coin(3, "stake"), coin(12, "uusd")])
+        );
+        let token = Balance::Cw20(cw20("cash", 5));
+        assert_eq!(normalize(token.clone()), token);
+    }
+
+    #[test]
+    fn build_transfers_split_native() {
+        let balance = GenericBalance {
+            native: vec![coin(7, "tokens"), coin(0, "dust"), coin(3, "stake")],
+            cw20: vec![cw20("cash", 5)],
+        };
+        let msgs = build_transfers(&Addr::unchecked("fulfiller"), &balance, true).unwrap();
+        assert_eq!(
+            msgs,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "fulfiller".to_string(),
+                    amount: coins(3, "stake"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "fulfiller".to_string(),
+                    amount: coins(7, "tokens"),
+                }),
+                transfer("fulfiller", "cash", 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn take_deposit_from_native_funds() {
+        let deposit = coins(10, "uusd");
+        let funds = Balance::from(vec![coin(5, "stake"), coin(110, "uusd")]);
+        let (rest, taken) = take_deposit(funds, &deposit).unwrap();
+        assert_eq!(
+            rest,
+            Balance::from(vec![coin(5, "stake"), coin(100, "uusd")])
+        );
+        assert_eq!(taken, deposit);
+
+        // only paying the deposit leaves nothing, zero amounts sent stay to be rejected
+        let funds = Balance::from(vec![coin(0, "stake"), coin(10, "uusd")]);
+        let (rest, _) = take_deposit(funds, &deposit).unwrap();
+        assert_eq!(rest, Balance::from(vec![coin(0, "stake")]));
+
+        assert_eq!(
+            take_deposit(Balance::from(coins(9, "uusd")), &deposit),
+            None
+        );
+        assert_eq!(
+            take_deposit(Balance::from(coins(9, "stake")), &deposit),
+            None
+        );
+        let token = Balance::Cw20(cw20("cash", 5));
+        assert_eq!(take_deposit(token.clone(), &deposit), Some((token, vec![])));
+    }
+
+    #[test]
+    fn display_amount_keeps_every_decimal() {
+        let denoms = vec![
+            DisplayDenom::ust(),
+            DisplayDenom {
+                denom: "ucount".to_string(),
+                symbol: "COUNT".to_string(),
+                decimals: 0,
+            },
+        ];
+        let show = |amount: u128, denom: &str| display_amount(&coin(amount, denom), &denoms);
+        assert_eq!(show(100_000_000, "uusd"), "100.000000 UST");
+        assert_eq!(show(1_500_000, "uusd"), "1.500000 UST");
+        assert_eq!(show(1, "uusd"), "0.000001 UST");
+        assert_eq!(show(0, "uusd"), "0.000000 UST");
+        assert_eq!(show(42, "ucount"), "42 COUNT");
+        assert_eq!(
+            show(u128::MAX, "uusd"),
+            "340282366920938463463374607431768.211455 UST"
+        );
+        // unknown denoms show the raw amount
+        assert_eq!(show(1_500_000, "stake"), "1500000");
+        assert_eq!(
+            display_amounts(&[coin(5, "stake"), coin(5, "uusd")], &denoms),
+            vec!["5", "0.000005 UST"]
+        );
+    }
+
+    #[test]
+    fn share_out_refunds_each_share_pro_rata() {
+        let total = GenericBalance {
+            native: vec![coin(100, "stake"), coin(10, "tokens")],
+            cw20: vec![cw20("cash", 30)],
+        };
+        let shares = vec![
+            (
+                Addr::unchecked("cousin"),
+                GenericBalance {
+                    native: vec![coin(50, "stake"), coin(10, "tokens")],
+                    cw20: vec![],
+                },
+            ),
+            (
+                Addr::unchecked("aunt"),
+                GenericBalance {
+                    native: coins(25, "stake"),
+                    cw20: vec![cw20("cash", 30)],
+                },
+            ),
+        ];
+        let creator = Addr::unchecked("creator");
+
+        // a full refund gives everyone back what they paid
+        assert_eq!(
+            share_out(&total, &total, &shares, &creator),
+            vec![
+                (
+                    creator.clone(),
+                    GenericBalance {
+                        native: coins(25, "stake"),
+                        cw20: vec![],
+                    }
+                ),
+                shares[0].clone(),
+                shares[1].clone(),
+            ]
+        );
+
+        // a partial one shares the loss, rounding in favor of the creator
+        let refund = GenericBalance {
+            native: vec![coin(75, "stake"), coin(3, "tokens")],
+            cw20: vec![cw20("cash", 7)],
+        };
+        assert_eq!(
+            share_out(&refund, &total, &shares, &creator),
+            vec![
+                (
+                    creator.clone(),
+                    GenericBalance {
+                        native: vec![coin(20, "stake")],
+                        cw20: vec![],
+                    }
+                ),
+                (
+                    Addr::unchecked("cousin"),
+                    GenericBalance {
+                        native: vec![coin(37, "stake"), coin(3, "tokens")],
+                        cw20: vec![],
+                    }
+                ),
+                (
+                    Addr::unchecked("aunt"),
+                    GenericBalance {
+                        native: coins(18, "stake"),
+                        cw20: vec![cw20("cash", 7)],
+                    }
+                ),
+            ]
+        );
+
+        // without shares the creator gets it all, and nobody gets nothing
+        assert_eq!(
+            share_out(&refund, &total, &[], &creator),
+            vec![(creator.clone(), refund)]
+        );
+        assert_eq!(
+            share_out(&GenericBalance::default(), &total, &shares, &creator),
+            vec![]
+        );
+    }
+}
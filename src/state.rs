@@ -1,10 +1,354 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Coin, Env, Order, StdError, StdResult, Storage, Timestamp};
-use cw_storage_plus::Map;
+use std::convert::{TryFrom, TryInto};
 
-use cw20::{Balance, Cw20CoinVerified};
+use cosmwasm_std::{
+    to_vec, Addr, Binary, Coin, Decimal, Empty, Env, Order, StdError, StdResult, Storage,
+    Timestamp, Uint128,
+};
+use cw_storage_plus::{Item, Map, U64Key};
+
+use cw20::{Balance, Cw20CoinVerified, Denom};
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::payments::{self, MAX_BPS};
+
+/// How long a fulfiller has to fulfill an escrow they accepted
+pub const ACCEPT_TIMEOUT_SECONDS: u64 = 60 * 60;
+/// How long the creator has to confirm or dispute a fulfillment, unless configured otherwise
+pub const FULFILL_TIMEOUT_SECONDS: u64 = 60 * 60;
+/// How long the arbiter has to rule on a dispute
+pub const ARBITRATION_TIMEOUT_SECONDS: u64 = 2 * 24 * 60 * 60;
+/// Block time assumed when converting heights to times, unless configured otherwise
+pub const DEFAULT_SECONDS_PER_BLOCK: u64 = 6;
+/// How far apart end_height and end_time may resolve when both are given
+pub const MAX_EXPIRY_DRIFT_SECONDS: u64 = 60 * 60;
+/// Longest an escrow may stay open counting from its creation, bounds auto extensions
+pub const MAX_ESCROW_LIFETIME_SECONDS: u64 = 7 * 24 * 60 * 60;
+/// Longest listing description accepted, in bytes
+pub const MAX_DESCRIPTION_BYTES: usize = 1024;
+/// The highest risk score, see risk_score
+pub const MAX_RISK_SCORE: u8 = 100;
+/// Most tags an escrow may carry
+pub const MAX_TAGS: usize = 5;
+/// Shortest and longest tag accepted, in characters
+pub const MIN_TAG_LEN: usize = 2;
+pub const MAX_TAG_LEN: usize = 16;
+/// Longest creator locale accepted, in characters
+pub const MAX_LOCALE_LEN: usize = 16;
+/// Furthest a creator's UTC offset may be from UTC, the range of real timezones
+pub const MAX_UTC_OFFSET_MINUTES: i16 = 14 * 60;
+/// Longest encrypted payout details accepted, in bytes
+pub const MAX_PAYOUT_DETAILS_BYTES: usize = 2048;
+/// The native denom the exchange rate is quoted against
+pub const UST_DENOM: &str = "uusd";
+/// The fiat exchange rates are quoted in, unless the creator names another
+pub const DEFAULT_FIAT_CURRENCY: &str = "VES";
+/// uusd in one UST, the unit trust metric volumes are kept in
+pub const UUSD_PER_UST: u128 = 1_000_000;
+/// Converts fiat cents per uusd into Bolivares per UST
+pub const RATE_SCALE: u128 = 10_000;
+/// How far the delivered amount may stray from the listed exchange rate, in basis points,
+/// before the creator has to accept the deviation explicitly
+pub const MAX_RATE_DEVIATION_BPS: u128 = 500;
+/// Most arbiters or trust metrics seeded in one message, each is a storage write
+pub const MAX_SEED_ENTRIES: usize = 100;
+/// Most addresses an escrow may allow to accept it
+pub const MAX_ALLOWED_FULFILLERS: usize = 20;
+/// Most cw20 tokens an escrow may whitelist, unless configured otherwise
+pub const DEFAULT_MAX_WHITELIST_LEN: u32 = 10;
+/// How many admin actions the audit log keeps, older ones are pruned
+pub const MAX_ADMIN_LOG_ENTRIES: u64 = 1_000;
+/// How often a creator may bump a listing, unless configured otherwise
+pub const DEFAULT_BUMP_COOLDOWN_SECONDS: u64 = 6 * 60 * 60;
+/// How long an arbiter has to take the role, unless configured otherwise
+pub const DEFAULT_ARBITER_ACK_WINDOW_SECONDS: u64 = 24 * 60 * 60;
+/// How much an arbiter asking for information extends the arbitration deadline, unless
+/// configured otherwise
+pub const DEFAULT_ARBITRATION_EXTENSION_SECONDS: u64 = 24 * 60 * 60;
+/// Longest a creator may open a listing to everyone at once, unless configured otherwise
+pub const DEFAULT_MAX_OPEN_TO_ALL_SECONDS: u64 = 24 * 60 * 60;
+/// Most bytes an escrow may grow to through appends, unless configured otherwise
+pub const DEFAULT_MAX_ESCROW_BYTES: u64 = 16 * 1024;
+/// How often an arbiter may extend the deadline, unless configured otherwise
+pub const DEFAULT_MAX_ARBITRATION_EXTENSIONS: u32 = 2;
+/// Least time a listing must have left to be accepted, unless configured otherwise
+pub const DEFAULT_MIN_REMAINING_ON_ACCEPT_SECONDS: u64 = 30 * 60;
+/// How many non-terminal actions an address may take on one escrow, unless configured
+/// otherwise
+pub const DEFAULT_MAX_ACTIONS_PER_ADDRESS: u32 = 50;
+/// Most escrows CCreateBatch creates at once
+pub const MAX_BATCH_ITEMS: usize = 20;
+/// Most escrows ReconcileLocked adds up, so the scan fits in a block
+pub const MAX_RECONCILE_ESCROWS: usize = 1_000;
+/// Most cw20 tokens one escrow holds, each is a transfer on payout
+pub const MAX_CW20_BALANCES: usize = 10;
+/// Most third parties topping up one escrow, each is a payout on refund
+pub const MAX_CONTRIBUTORS: usize = 10;
+/// How often a creator may turn away who accepted one escrow, so a listing can't keep
+/// fulfillers waiting forever
+pub const MAX_REJECTIONS: usize = 3;
+/// How many entries an escrow's history keeps, see record_history
+pub const MAX_HISTORY_ENTRIES: usize = 20;
+/// Most peer contracts wallets may import trust metrics from
+pub const MAX_PEER_CONTRACTS: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Config {
+    /// The admin manages the blacklist
+    pub admin: Addr,
+    /// Average block time, used to estimate when a height bound will be reached
+    pub seconds_per_block: u64,
+    /// Most cw20 tokens an escrow may whitelist
+    pub max_whitelist_len: u32,
+    /// Set once the admin is done importing trust metrics
+    pub seeding_closed: bool,
+    /// Set for good once the admin began winding the contract down. Nothing new may be
+    /// created, accepted, topped up or bumped, while whatever is open may still settle.
+    pub sunset: bool,
+    /// How often a creator may bump a listing
+    pub bump_cooldown_seconds: u64,
+    /// Whether escrows funded through a cw20 hook take native top-ups too
+    pub allow_mixed_funding: bool,
+    /// How long after acceptance the creator may still cancel, 0 for not at all
+    pub post_accept_cancel_window_seconds: u64,
+    /// Share of the balance a cancel within that window pays the fulfiller, in basis points
+    pub cancel_penalty_bps: u64,
+    /// Whether payouts send each native denom in its own BankMsg
+    pub split_native_payouts: bool,
+    /// Charged on top of the escrowed funds when creating with native coins
+    pub listing_deposit: Vec<Coin>,
+    /// Receives the deposits of listings left to expire
+    pub fee_collector: Addr,
+    /// Whether escrows with an arbiter wait for them to take the role before being listed
+    pub require_arbiter_ack: bool,
+    /// How long the arbiter has to take the role
+    pub arbiter_ack_window_seconds: u64,
+    /// How to show native amounts in responses, by denom
+    pub display_denoms: Vec<DisplayDenom>,
+    /// How long a new escrow waits before it may be accepted, so its creator can still
+    /// cancel or change it
+    pub listing_grace_period_seconds: u64,
+    /// How much each ArbiterRequestInfo extends the arbitration deadline
+    pub arbitration_extension_seconds: u64,
+    /// How often the arbiter may extend the deadline of one dispute, 0 for never
+    pub max_arbitration_extensions: u32,
+    /// Creators and fulfillers need its attestation when set, see kyc::assert_verified
+    pub kyc_contract: Option<Addr>,
+    /// Whether anyone, not just the creator, may top up a listed escrow
+    pub allow_third_party_topup: bool,
+    /// Most escrows stored at once, creating more fails until some settle. None for no cap.
+    pub max_active_escrows: Option<u64>,
+    /// Most uusd one wallet may escrow, creating and topping up, in any 24 hours
+    pub daily_volume_limit_ust: Option<Uint128>,
+    /// Most uusd one wallet may accept in any 24 hours
+    pub daily_accept_limit_ust: Option<Uint128>,
+    /// How long the creator has to confirm or dispute once the fulfiller reports, unless
+    /// the escrow sets its own. Fixed for each escrow when it's fulfilled.
+    pub dispute_window_seconds: u64,
+    /// How long the creator of an escrow requiring it has to CAckFulfiller, after which
+    /// the acceptance lapses and anyone may accept again
+    pub creator_ack_timeout_seconds: u64,
+    /// Longest a creator may waive the trust requirements of a listing for, see OpenToAll
+    pub max_open_to_all_seconds: u64,
+    /// How long a canceled listing waits before it's refunded, 0 refunds right away
+    pub cancel_cooloff_seconds: u64,
+    /// Most bytes top ups, edits, rejections and info requests may grow an escrow to,
+    /// see Escrow::approximate_size
+    pub max_escrow_bytes: u64,
+    /// What happens to accepted escrows that expire before they're fulfilled
+    pub mid_lifecycle_expiry: MidLifecycleExpiry,
+    /// How much each part of a listing's risk score may add, see risk_score
+    pub risk_weights: RiskWeights,
+    /// How long a dispute stays with the parties to settle before it goes to the arbiter,
+    /// 0 for straight to them. Fixed for each dispute when it's opened.
+    pub mediation_window_seconds: u64,
+    /// Least time a listing must have left before it expires for anyone to accept it
+    pub min_remaining_on_accept_seconds: u64,
+    /// Deployments for other corridors whose trust metrics wallets may bring over with
+    /// ImportMetrics, once per peer
+    pub peer_contracts: Vec<Addr>,
+    /// How many non-terminal actions an address may take on one escrow, so a hostile
+    /// counterparty can't keep the other side busy forever, see Escrow::count_action
+    pub max_actions_per_address: u32,
+}
+
+impl Config {
+    /// Fails once the contract is sunsetting, for anything that would start something new
+    pub fn assert_not_sunsetting(&self) -> Result<(), ContractError> {
+        match self.sunset {
+            true => Err(ContractError::ContractSunsetting {}),
+            false => Ok(()),
+        }
+    }
+
+    /// Checks the config as a whole, so a partial update can't leave it broken.
+    /// The admin and fee collector are validated where they're parsed.
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let deposit = &self.listing_deposit;
+        let display = &self.display_denoms;
+        let peers = &self.peer_contracts;
+        let invalid = [
+            ("seconds_per_block", self.seconds_per_block == 0),
+            ("max_whitelist_len", self.max_whitelist_len == 0),
+            ("bump_cooldown_seconds", self.bump_cooldown_seconds == 0),
+            (
+                "arbiter_ack_window_seconds",
+                self.arbiter_ack_window_seconds == 0,
+            ),
+            (
+                "arbitration_extension_seconds",
+                self.arbitration_extension_seconds == 0,
+            ),
+            ("dispute_window_seconds", self.dispute_window_seconds == 0),
+            (
+                "creator_ack_timeout_seconds",
+                self.creator_ack_timeout_seconds == 0,
+            ),
+            ("max_open_to_all_seconds", self.max_open_to_all_seconds == 0),
+            ("max_escrow_bytes", self.max_escrow_bytes == 0),
+            ("max_actions_per_address", self.max_actions_per_address == 0),
+            ("cancel_penalty_bps", self.cancel_penalty_bps > MAX_BPS),
+            (
+                "risk_weights",
+                self.risk_weights.total() > MAX_RISK_SCORE as u32,
+            ),
+            (
+                "listing_deposit",
+                deposit.iter().enumerate().any(|(i, coin)| {
+                    coin.amount.is_zero() || deposit[..i].iter().any(|c| c.denom == coin.denom)
+                }),
+            ),
+            (
+                "display_denoms",
+                display.iter().enumerate().any(|(i, d)| {
+                    d.symbol.is_empty() || display[..i].iter().any(|other| other.denom == d.denom)
+                }),
+            ),
+            (
+                "peer_contracts",
+                peers.len() > MAX_PEER_CONTRACTS
+                    || peers
+                        .iter()
+                        .enumerate()
+                        .any(|(i, peer)| peers[..i].contains(peer)),
+            ),
+        ];
+        match invalid.iter().find(|(_, is_invalid)| *is_invalid) {
+            Some((field, _)) => Err(ContractError::InvalidConfig {
+                field: field.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// What an escrow's end_height and end_time mean once it's accepted
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MidLifecycleExpiry {
+    /// Only the accept and fulfill timeouts count, the expiry of the listing doesn't
+    #[default]
+    GraceUntilAcceptTimeout,
+    /// Once it expires unfulfilled the fulfiller may no longer report, and anyone may
+    /// RefundExpired it to the creator
+    HardExpire,
+}
+
+/// The points each part of a listing's risk score adds at most, together at most
+/// MAX_RISK_SCORE. Tuning them rescores every listing, scores aren't stored.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RiskWeights {
+    /// For a creator who disputed as many escrows as they created, less for fewer.
+    /// Disputes they lost count twice.
+    pub dispute_history: u8,
+    /// For an escrow worth twice the creator's average or more, less down to nothing at
+    /// the average
+    pub value_above_average: u8,
+    /// For a listing with arbitration disabled
+    pub no_arbitration: u8,
+}
+
+impl RiskWeights {
+    pub fn total(&self) -> u32 {
+        self.dispute_history as u32 + self.value_above_average as u32 + self.no_arbitration as u32
+    }
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        RiskWeights {
+            dispute_history: 50,
+            value_above_average: 30,
+            no_arbitration: 20,
+        }
+    }
+}
+
+/// How amounts of a native denom are shown, e.g. uusd as UST with 6 decimals
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DisplayDenom {
+    pub denom: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl DisplayDenom {
+    /// The chain-native denom, shown unless configured otherwise
+    pub fn ust() -> Self {
+        DisplayDenom {
+            denom: UST_DENOM.to_string(),
+            symbol: "UST".to_string(),
+            decimals: 6,
+        }
+    }
+}
+
+/// What is listed and not yet accepted in one fiat currency. Nothing stores an escrow when
+/// its listing expires, so expired listings stay in until canceled.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct OpenInterest {
+    /// uusd held by the listings, see Escrow::value_ust
+    pub value_ust: Uint128,
+    pub listings: u64,
+}
+
+/// An arbiter's track record
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct ArbiterStats {
+    pub rulings: u32,
+    /// Seconds from dispute to ruling, summed over all rulings
+    pub total_ruling_seconds: u64,
+    /// Disputes resolved by default because the arbiter didn't rule in time
+    pub missed_deadlines: u32,
+    /// Rulings paying everything to the creator
+    pub for_creator: u32,
+    /// Rulings paying everything to the fulfiller
+    pub for_fulfiller: u32,
+    /// Rulings splitting the balance
+    pub splits: u32,
+    /// Escrows they stepped aside from, see ArbiterRecuse
+    pub recusals: u32,
+    /// uusd held by the escrows disputed with them, see Escrow::value_ust
+    #[serde(default)]
+    pub disputed_volume_ust: Uint128,
+    /// Open escrows naming them, kept by save_escrow and remove_escrow
+    #[serde(default)]
+    pub active_escrows: u32,
+    /// Of those, the ones disputed and not yet resolved
+    #[serde(default)]
+    pub in_arbitration: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BlacklistEntry {
+    /// Why the address was blacklisted
+    pub reason: String,
+    /// When the address was blacklisted, in seconds since epoch
+    pub since: u64,
+}
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct GenericBalance {
@@ -13,6 +357,21 @@ pub struct GenericBalance {
 }
 
 impl GenericBalance {
+    /// The uusd in the balance
+    pub fn value_ust(&self) -> Uint128 {
+        self.native
+            .iter()
+            .filter(|c| c.denom == UST_DENOM)
+            .fold(Uint128::zero(), |sum, c| sum + c.amount)
+    }
+
+    /// Orders native coins by denom and cw20 tokens by address, the one order balances are
+    /// kept and paid out in so identical escrows produce identical messages
+    pub fn normalize(&mut self) {
+        self.native.sort_by(|a, b| a.denom.cmp(&b.denom));
+        self.cw20.sort_by(|a, b| a.address.cmp(&b.address));
+    }
+
     pub fn add_tokens(&mut self, add: Balance) {
         match add {
             Balance::Native(balance) => {
@@ -44,49 +403,282 @@ impl GenericBalance {
                 }
             }
         };
+        self.normalize();
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct TrustMetrics {
-    pub percent_completed: u8, // Contracts
-    pub percent_satisfied: u8, // Creator Feedback
-    pub avg_volume: u32, // UST
+    pub percent_completed: u8,     // Contracts
+    pub percent_satisfied: u8,     // Creator Feedback
+    pub avg_volume: u32,           // UST
     pub avg_completion_speed: u32, // Milliseconds
-    pub total_volume: u32, // UST
-    pub total_completed: u32, // Contracts
+    pub total_volume: u32,         // UST
+    pub total_completed: u32,      // Contracts
+    /// Disputes this wallet opened as a creator. Like the other dispute counters,
+    /// lower is better, so as a requirement it is the most allowed, 0 for no limit.
+    /// Defaults to 0 for metrics stored before disputes were tracked.
+    #[serde(default)]
+    pub disputes_opened: u32,
+    /// Disputes the arbiter ruled against this wallet. As a requirement 0 allows none.
+    #[serde(default)]
+    pub disputes_lost: u32,
+    /// Disputes the arbiter ruled for this wallet
+    #[serde(default)]
+    pub disputes_won: u32,
+    /// Completions averaged into avg_completion_speed: escrows the creator confirmed or that
+    /// were released automatically, never ones that went through arbitration
+    #[serde(default)]
+    pub clean_completions: u32,
+    /// Average time from acceptance to ruling of the escrows this wallet fulfilled that went
+    /// through arbitration, milliseconds. Not a requirement, is_higher ignores it.
+    #[serde(default)]
+    pub avg_disputed_resolution_time: u32,
+    /// Arbitrated escrows averaged into avg_disputed_resolution_time
+    #[serde(default)]
+    pub disputed_resolutions: u32,
 }
 
 impl TrustMetrics {
+    /// Whether the fulfiller's metrics meet these required ones. A required
+    /// avg_completion_speed of 0 places no limit on speed.
     pub fn is_higher(&self, fulfiller_trust_metrics: TrustMetrics) -> bool {
         let other = fulfiller_trust_metrics;
         if self.percent_completed > other.percent_completed {
-            return false
+            return false;
         }
         if self.percent_satisfied > other.percent_satisfied {
-            return false
+            return false;
         }
         if self.avg_volume > other.avg_volume {
-            return false
+            return false;
         }
-        if self.avg_completion_speed < other.avg_completion_speed {
-            return false
+        if self.avg_completion_speed != 0 && self.avg_completion_speed < other.avg_completion_speed
+        {
+            return false;
         }
         if self.total_volume > other.total_volume {
-            return false
+            return false;
         }
         if self.total_completed > other.total_completed {
-            return false
+            return false;
+        }
+        if self.disputes_lost < other.disputes_lost {
+            return false;
+        }
+        if self.disputes_opened != 0 && self.disputes_opened < other.disputes_opened {
+            return false;
+        }
+        if self.disputes_won != 0 && self.disputes_won < other.disputes_won {
+            return false;
         }
-        return true
+        true
     }
+
+    /// Averages a clean completion, taking the given milliseconds since acceptance, into
+    /// avg_completion_speed
+    pub fn record_clean_completion(&mut self, millis: u64) {
+        self.avg_completion_speed =
+            running_average(self.avg_completion_speed, self.clean_completions, millis);
+        self.clean_completions = self.clean_completions.saturating_add(1);
+    }
+
+    /// Averages an arbitrated escrow into avg_disputed_resolution_time, keeping it out of
+    /// avg_completion_speed
+    pub fn record_disputed_resolution(&mut self, millis: u64) {
+        self.avg_disputed_resolution_time = running_average(
+            self.avg_disputed_resolution_time,
+            self.disputed_resolutions,
+            millis,
+        );
+        self.disputed_resolutions = self.disputed_resolutions.saturating_add(1);
+    }
+
+    /// Why metrics another contract reported can't be true, if they can't
+    pub fn implausibility(&self) -> Option<&'static str> {
+        if self.percent_completed > 100 || self.percent_satisfied > 100 {
+            Some("a percentage above 100")
+        } else if self.avg_volume > self.total_volume {
+            Some("an average volume above the total")
+        } else {
+            None
+        }
+    }
+
+    /// Adds another deployment's track record to this one: counts and volumes are summed,
+    /// averages weighted by what they were averaged over
+    pub fn merge(&mut self, other: &TrustMetrics) {
+        let (completed, other_completed) = (self.total_completed, other.total_completed);
+        self.percent_completed = weighted_average(
+            self.percent_completed.into(),
+            completed,
+            other.percent_completed.into(),
+            other_completed,
+        ) as u8;
+        self.percent_satisfied = weighted_average(
+            self.percent_satisfied.into(),
+            completed,
+            other.percent_satisfied.into(),
+            other_completed,
+        ) as u8;
+        self.avg_volume = weighted_average(
+            self.avg_volume,
+            completed,
+            other.avg_volume,
+            other_completed,
+        );
+        self.avg_completion_speed = weighted_average(
+            self.avg_completion_speed,
+            self.clean_completions,
+            other.avg_completion_speed,
+            other.clean_completions,
+        );
+        self.avg_disputed_resolution_time = weighted_average(
+            self.avg_disputed_resolution_time,
+            self.disputed_resolutions,
+            other.avg_disputed_resolution_time,
+            other.disputed_resolutions,
+        );
+        self.total_volume = self.total_volume.saturating_add(other.total_volume);
+        self.total_completed = completed.saturating_add(other_completed);
+        self.disputes_opened = self.disputes_opened.saturating_add(other.disputes_opened);
+        self.disputes_lost = self.disputes_lost.saturating_add(other.disputes_lost);
+        self.disputes_won = self.disputes_won.saturating_add(other.disputes_won);
+        self.clean_completions = self
+            .clean_completions
+            .saturating_add(other.clean_completions);
+        self.disputed_resolutions = self
+            .disputed_resolutions
+            .saturating_add(other.disputed_resolutions);
+    }
+}
+
+/// A creator's track record, the fulfiller's side of trust: creators who dispute or go
+/// silent after the fiat was sent tie up the fulfiller's capital
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct CreatorMetrics {
+    pub escrows_created: u32,
+    /// Fulfillments the creator confirmed with CComplete
+    pub confirmations: u32,
+    /// Average time from the fulfiller reporting to the creator confirming, seconds
+    pub avg_confirmation_seconds: u32,
+    /// Fulfillments the creator never answered, so the fulfiller claimed them
+    pub confirmations_missed: u32,
+    pub disputes_opened: u32,
+    /// Disputes the arbiter ruled against the creator
+    pub disputes_lost: u32,
+    /// Escrows canceled within the post accept cancel window, after someone accepted
+    pub cancels_after_accept: u32,
+    /// uusd the creator escrowed, as created, for their average. Escrows created before it
+    /// was kept count as none.
+    #[serde(default)]
+    pub value_created_ust: Uint128,
 }
 
+/// What a fulfiller asks of an escrow's creator on FAccept, unset fields place no limit
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct CreatorRequirement {
+    pub min_escrows_created: Option<u32>,
+    pub max_avg_confirmation_seconds: Option<u32>,
+    pub max_confirmations_missed: Option<u32>,
+    pub max_disputes_opened: Option<u32>,
+    pub max_disputes_lost: Option<u32>,
+    pub max_cancels_after_accept: Option<u32>,
+}
+
+impl CreatorRequirement {
+    pub fn is_met_by(&self, metrics: &CreatorMetrics) -> bool {
+        let at_least = |min: Option<u32>, value: u32| min.is_none_or(|min| value >= min);
+        let at_most = |max: Option<u32>, value: u32| max.is_none_or(|max| value <= max);
+        at_least(self.min_escrows_created, metrics.escrows_created)
+            && at_most(
+                self.max_avg_confirmation_seconds,
+                metrics.avg_confirmation_seconds,
+            )
+            && at_most(self.max_confirmations_missed, metrics.confirmations_missed)
+            && at_most(self.max_disputes_opened, metrics.disputes_opened)
+            && at_most(self.max_disputes_lost, metrics.disputes_lost)
+            && at_most(self.max_cancels_after_accept, metrics.cancels_after_accept)
+    }
+}
+
+impl CreatorMetrics {
+    /// Averages a confirmation, taking the given seconds since the fulfiller reported, into
+    /// avg_confirmation_seconds
+    pub fn record_confirmation(&mut self, seconds: u64) {
+        self.avg_confirmation_seconds =
+            running_average(self.avg_confirmation_seconds, self.confirmations, seconds);
+        self.confirmations += 1;
+    }
+}
+
+/// Estimates from 0 to MAX_RISK_SCORE how likely accepting the escrow ends in trouble for
+/// the fulfiller, from the creator's disputes, the escrow's value against their average
+/// and whether an arbiter can step in. Each part adds up to its weight.
+pub fn risk_score(metrics: &CreatorMetrics, escrow: &Escrow, weights: &RiskWeights) -> u8 {
+    let created = u128::from(metrics.escrows_created);
+    let disputes = u128::from(metrics.disputes_opened) + u128::from(metrics.disputes_lost);
+    let dispute_history = match created {
+        0 => 0,
+        _ => u128::from(weights.dispute_history) * disputes.min(created) / created,
+    };
+    let average = metrics
+        .value_created_ust
+        .u128()
+        .checked_div(created)
+        .unwrap_or_default();
+    let above_average = escrow.value_ust().u128().saturating_sub(average);
+    let value_above_average = match average {
+        0 => 0,
+        _ => u128::from(weights.value_above_average) * above_average.min(average) / average,
+    };
+    let no_arbitration = match escrow.arbiter {
+        Some(_) => 0,
+        None => u128::from(weights.no_arbitration),
+    };
+    (dispute_history + value_above_average + no_arbitration).min(u128::from(MAX_RISK_SCORE)) as u8
+}
+
+fn running_average(average: u32, count: u32, sample: u64) -> u32 {
+    let total = average as u128 * count as u128 + sample as u128;
+    u32::try_from(total / (count as u128 + 1)).unwrap_or(u32::MAX)
+}
+
+/// The average of two averages over count and other_count samples, the first over none
+fn weighted_average(average: u32, count: u32, other: u32, other_count: u32) -> u32 {
+    let samples = count as u128 + other_count as u128;
+    if samples == 0 {
+        return average;
+    }
+    let total = average as u128 * count as u128 + other as u128 * other_count as u128;
+    (total / samples) as u32
+}
+
+/// Which message created an escrow. Native coins come with c_create itself, cw20 tokens
+/// through the token contract's Receive hook, where the creator is the one who sent them.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum FundingSource {
+    NativeDirect,
+    Cw20Hook { token: Addr },
+}
+
+impl FundingSource {
+    pub fn of(balance: &Balance) -> Self {
+        match balance {
+            Balance::Native(_) => FundingSource::NativeDirect,
+            Balance::Cw20(token) => FundingSource::Cw20Hook {
+                token: token.address.clone(),
+            },
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Escrow {
-    /// arbiter can decide to approve or refund the escrow
-    pub arbiter: Addr,
+    /// arbiter can decide to approve or refund the escrow, none if arbitration is disabled.
+    /// Escrows stored before arbitration could be disabled load with theirs.
+    pub arbiter: Option<Addr>,
     /// if is_completed, funds go to the fulfiller
     pub fulfiller: Addr,
     /// if canceled or arbitrated in favor of them, funds go to the creator
@@ -100,13 +692,39 @@ pub struct Escrow {
     pub end_time: Option<u64>,
     /// Balance in Native and Cw20 tokens
     pub balance: GenericBalance,
-    /// Exchange rate desired in Bolivares per UST
-    pub exchange_rate: u128,
+    /// How the creator funded the escrow, see FundingSource
+    pub funding_source: FundingSource,
+    /// Exchange rate desired in fiat_currency per UST
+    pub exchange_rate: Decimal,
+    /// ISO 4217 code of the fiat the creator wants, e.g. VES or COP
+    pub fiat_currency: String,
     /// All possible contracts that we accept tokens from
     pub cw20_whitelist: Vec<Addr>,
     /// Required Trust Metrics
     pub required_trust_metrics: TrustMetrics,
+    /// The creator pays out on fulfillment to fulfillers meeting these
+    pub auto_complete_trust: Option<TrustMetrics>,
+    /// Taken from the creator's funds when listing, see Config::listing_deposit
+    pub listing_deposit: Vec<Coin>,
+    /// The only addresses that may accept, e.g. the creator's cash-out agents.
+    /// Sorted and without duplicates.
+    pub allowed_fulfillers: Option<Vec<Addr>>,
+    /// Free-form terms shown to fulfillers, e.g. the city or the banks accepted
+    pub description: Option<String>,
+    /// Fiat the fulfiller reports delivering, in cents
+    pub delivered_fiat_amount: Option<Uint128>,
+    /// Set once the creator requests arbitration
+    pub dispute: Option<Dispute>,
+    /// Short code for people to reference the escrow by, see reserve_fingerprint
+    pub fingerprint: String,
+    /// Bumped on every change, see save_escrow
+    pub revision: u64,
+    /// Listings are shown highest first. The creation time, until the creator bumps it.
+    pub listing_sort_key: u64,
+    pub last_bumped: Option<u64>,
     /// States
+    /// Waiting for the arbiter to take the role, listed once they do
+    pub is_pending_arbiter_ack: bool,
     pub is_listed: bool,
     pub is_canceled: bool,
     pub is_accepted: bool,
@@ -118,9 +736,260 @@ pub struct Escrow {
     pub time_accepted: Option<u64>,
     pub time_fulfilled: Option<u64>,
     pub time_arbitration_started: Option<u64>,
+    /// How often the arbiter asked for information, each extending the arbitration deadline
+    pub arbitration_extensions: u32,
+    /// Added to the arbitration deadline by those requests
+    pub arbitration_extended_seconds: u64,
+    /// What others than the creator topped up, by contributor. Refunds go back to them
+    /// pro rata, see Escrow::refund_payouts.
+    pub contributors: Vec<(Addr, GenericBalance)>,
+    /// The creator's window to confirm or dispute, when the creator chose their own
+    pub dispute_window_seconds: Option<u64>,
+    /// When that window closes, fixed when the fulfiller reports so later config changes
+    /// don't move it
+    pub dispute_deadline: Option<u64>,
+    /// Fulfillers the creator turned away with CReject, they may not accept again
+    pub rejected_fulfillers: Vec<Addr>,
+    /// The arbiter recused themselves and may not act until the admin reassigns the
+    /// escrow. Meanwhile nobody may accept, and the arbitration deadline is paused.
+    pub is_pending_reassignment: bool,
+    /// Whether the creator vets each fulfiller before they may fulfill, see CreateMsg
+    pub require_creator_ack: bool,
+    /// Accepted, waiting for the creator to CAckFulfiller or CReject
+    pub is_awaiting_creator_ack: bool,
+    /// Anyone may accept regardless of the trust requirements until then, see OpenToAll
+    pub open_to_all_until: Option<u64>,
+    /// Canceled during the cancel cooloff, which ends then. Fixed at cancel time so
+    /// later config changes don't move it. Delisted meanwhile, still holding the funds.
+    pub cancel_pending_until: Option<u64>,
+    /// Seconds to push end_time back by whenever an acceptance falls through, see CreateMsg
+    pub auto_extend_on_relist: Option<u64>,
+    /// Whether the creator takes cw20 payouts with a PayoutHookMsg, see CreateMsg::payout_memo
+    pub creator_payout_memo: bool,
+    /// Whether the fulfiller takes cw20 payouts with a PayoutHookMsg, set on FAccept
+    pub fulfiller_payout_memo: bool,
+    /// Categories to find the listing by, see ListByTag. Sorted and without duplicates.
+    pub tags: Vec<String>,
+    /// Nobody may accept before then, and listings leave it out, see CreateMsg
+    pub start_time: Option<u64>,
+    /// Disputed, when the parties' time to settle runs out and the arbiter's starts.
+    /// time_arbitration_started is set to it.
+    pub mediation_until: Option<u64>,
+    /// The split one party offered the other during mediation, see ProposeSettlement
+    pub settlement_proposal: Option<SettlementProposal>,
+    /// Shown to fulfillers, see CreateMsg::creator_locale
+    pub creator_locale: Option<String>,
+    /// Shown to fulfillers, see CreateMsg::creator_utc_offset_minutes
+    pub creator_utc_offset_minutes: Option<i16>,
+    /// Non-terminal actions each address took on the escrow, see count_action
+    pub action_counts: Vec<(Addr, u32)>,
 }
 
 impl Escrow {
+    pub fn is_creator(&self, address: &Addr) -> bool {
+        *address == self.creator
+    }
+
+    /// Unauthorized unless the sender created the escrow
+    pub fn assert_creator(&self, sender: &Addr) -> Result<(), ContractError> {
+        if !self.is_creator(sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// Unauthorized unless the sender is the fulfiller, which is the creator until accepted
+    pub fn assert_fulfiller(&self, sender: &Addr) -> Result<(), ContractError> {
+        if *sender != self.fulfiller {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    pub fn is_party(&self, address: &Addr) -> bool {
+        self.is_creator(address) || *address == self.fulfiller
+    }
+
+    /// NotAParty unless the address is the creator or the fulfiller
+    pub fn assert_party(&self, address: &Addr) -> Result<(), ContractError> {
+        if !self.is_party(address) {
+            return Err(ContractError::NotAParty {});
+        }
+        Ok(())
+    }
+
+    /// The arbiter, if the sender is them. ArbitrationDisabled when there is none.
+    pub fn assert_arbiter(&self, sender: &Addr) -> Result<&Addr, ContractError> {
+        let arbiter = self
+            .arbiter
+            .as_ref()
+            .ok_or(ContractError::ArbitrationDisabled {})?;
+        if sender != arbiter {
+            return Err(ContractError::Unauthorized {});
+        }
+        if self.is_pending_reassignment {
+            return Err(ContractError::ArbiterRecused {});
+        }
+        Ok(arbiter)
+    }
+
+    /// The uusd the escrow holds, which the exchange rate applies to
+    pub fn value_ust(&self) -> Uint128 {
+        self.balance.value_ust()
+    }
+
+    pub fn deposit(&self) -> GenericBalance {
+        GenericBalance {
+            native: self.listing_deposit.clone(),
+            cw20: vec![],
+        }
+    }
+
+    /// Everything the contract holds for the escrow, its balance and listing deposit
+    pub fn locked(&self) -> GenericBalance {
+        payments::merge(&self.balance, &self.deposit())
+    }
+
+    /// Counts a non-terminal action of the sender, refusing it once they took max of them
+    pub fn count_action(&mut self, sender: &Addr, max: u32) -> Result<(), ContractError> {
+        let index = match self.action_counts.iter().position(|(a, _)| a == sender) {
+            Some(index) => index,
+            None => {
+                self.action_counts.push((sender.clone(), 0));
+                self.action_counts.len() - 1
+            }
+        };
+        let count = &mut self.action_counts[index].1;
+        if *count >= max {
+            return Err(ContractError::ActionLimitReached {});
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    /// Bytes the escrow takes in storage, which every handler loading it pays gas for.
+    /// Its history and payout details are stored apart and not counted.
+    pub fn approximate_size(&self) -> u64 {
+        to_vec(self).map_or(u64::MAX, |bytes| bytes.len() as u64)
+    }
+
+    /// Who gets a refund of the escrowed funds: each contributor their share, the creator
+    /// the rest, first
+    pub fn refund_payouts(&self, refund: &GenericBalance) -> Vec<(Addr, GenericBalance)> {
+        payments::share_out(refund, &self.balance, &self.contributors, &self.creator)
+    }
+
+    /// Who gets the listing deposit back: the creator, unless the listing was left
+    /// to expire without anyone accepting it. Only unexpired listings go through the
+    /// cancel cooloff, so expiring during it doesn't count.
+    pub fn deposit_payout(&self, env: &Env, config: &Config) -> Option<(Addr, GenericBalance)> {
+        if self.listing_deposit.is_empty() {
+            return None;
+        }
+        let forfeited = self.is_expired_listing(env) && self.cancel_pending_until.is_none();
+        // a sunset isn't the creator's doing
+        let to = if forfeited && !config.sunset {
+            config.fee_collector.clone()
+        } else {
+            self.creator.clone()
+        };
+        Some((to, self.deposit()))
+    }
+
+    /// Bolivares per UST implied by the delivered amount, if reported and the escrow holds uusd
+    pub fn realized_exchange_rate(&self) -> Option<Decimal> {
+        let delivered = self.delivered_fiat_amount?.u128().checked_mul(RATE_SCALE)?;
+        let value = self.value_ust();
+        if value.is_zero() {
+            return None;
+        }
+        Some(Decimal::from_ratio(delivered, value))
+    }
+
+    /// Whether the delivered amount implies a rate more than MAX_RATE_DEVIATION_BPS
+    /// away from the listed one. Escrows without uusd have no rate to compare against.
+    pub fn is_rate_deviation_exceeded(&self) -> bool {
+        let delivered = match self.delivered_fiat_amount {
+            Some(delivered) if !self.value_ust().is_zero() => {
+                delivered.u128().saturating_mul(RATE_SCALE)
+            }
+            _ => return false,
+        };
+        // both in millionths of a Bolivar
+        let expected = (self.exchange_rate * self.value_ust()).u128();
+        let deviation = delivered.abs_diff(expected);
+        deviation.saturating_mul(10_000) > expected.saturating_mul(MAX_RATE_DEVIATION_BPS)
+    }
+
+    /// Where the escrow stands, for support and clients
+    pub fn lifecycle(&self) -> EscrowStatus {
+        if self.is_in_arbitration {
+            EscrowStatus::InArbitration
+        } else if self.is_fulfilled {
+            EscrowStatus::Fulfilled
+        } else if self.is_awaiting_creator_ack {
+            EscrowStatus::AwaitingCreatorAck
+        } else if self.is_accepted {
+            EscrowStatus::Accepted
+        } else if self.cancel_pending_until.is_some() {
+            EscrowStatus::PendingCancel
+        } else if self.is_pending_arbiter_ack {
+            EscrowStatus::PendingArbiterAck
+        } else {
+            EscrowStatus::Listed
+        }
+    }
+
+    /// lifecycle by its JSON name: pending_arbiter_ack, listed, pending_cancel,
+    /// awaiting_creator_ack, accepted, fulfilled or in_arbitration
+    pub fn status(&self) -> &'static str {
+        self.lifecycle().as_str()
+    }
+
+    /// Puts an accepted escrow back on the market, returning who had accepted it
+    pub fn clear_acceptance(&mut self) -> Addr {
+        self.is_accepted = false;
+        self.is_awaiting_creator_ack = false;
+        self.is_listed = true;
+        self.time_accepted = None;
+        self.fulfiller_payout_memo = false;
+        std::mem::replace(&mut self.fulfiller, self.creator.clone())
+    }
+
+    /// Pushes end_time back by auto_extend_on_relist once the escrow is listed again, but
+    /// never past MAX_ESCROW_LIFETIME_SECONDS after its creation. The new end_time, if it
+    /// moved. Escrows expiring by height alone keep their expiry.
+    pub fn extend_on_relist(&mut self) -> Option<u64> {
+        let extension = self.auto_extend_on_relist?;
+        let end_time = self.end_time?;
+        let cap = self
+            .time_created
+            .map_or(u64::MAX, |t| t.saturating_add(MAX_ESCROW_LIFETIME_SECONDS));
+        let extended = end_time.saturating_add(extension).min(cap);
+        if extended <= end_time {
+            return None;
+        }
+        self.end_time = Some(extended);
+        Some(extended)
+    }
+
+    /// Whether cw20 payouts to the address go with a PayoutHookMsg, as its party asked
+    pub fn wants_payout_memo(&self, to: &Addr) -> bool {
+        (self.creator_payout_memo && *to == self.creator)
+            || (self.fulfiller_payout_memo && self.is_accepted && *to == self.fulfiller)
+    }
+
+    /// Where the escrow sits in CREATED_ESCROWS. Escrows from before time_created was kept
+    /// sort first.
+    pub fn created_sort_key(&self) -> u64 {
+        self.time_created.unwrap_or_default()
+    }
+
+    /// Whether the listing ran out before anyone accepted it, all that's left is canceling it
+    pub fn is_expired_listing(&self, env: &Env) -> bool {
+        !self.is_accepted && self.is_expired(env)
+    }
+
     pub fn is_expired(&self, env: &Env) -> bool {
         if let Some(end_height) = self.end_height {
             if env.block.height > end_height {
@@ -139,27 +1008,882 @@ impl Escrow {
         false
     }
 
+    /// Resolves end_height and end_time into the last second the escrow is still open,
+    /// along with the bound that produced it. Heights are converted using the configured
+    /// block time, and whichever bound comes first wins, just like in is_expired.
+    pub fn expires_at(&self, env: &Env, seconds_per_block: u64) -> Option<(u64, ExpirySource)> {
+        let by_height = self.end_height.map(|height| {
+            (
+                height_to_time(env, height, seconds_per_block),
+                ExpirySource::EndHeight,
+            )
+        });
+        let by_time = self.end_time.map(|time| (time, ExpirySource::EndTime));
+        match (by_height, by_time) {
+            (Some(by_height), Some(by_time)) if by_height.0 < by_time.0 => Some(by_height),
+            (by_height, by_time) => by_time.or(by_height),
+        }
+    }
+
+    pub fn accept_deadline(&self) -> Option<u64> {
+        self.time_accepted.map(|t| t + ACCEPT_TIMEOUT_SECONDS)
+    }
+
+    pub fn fulfill_deadline(&self) -> Option<u64> {
+        // escrows fulfilled before the deadline was stored had the fixed window
+        self.dispute_deadline
+            .or_else(|| self.time_fulfilled.map(|t| t + FULFILL_TIMEOUT_SECONDS))
+    }
+
+    /// Whose load in ARBITER_STATS the escrow counts toward, and whether as disputed
+    pub fn arbiter_load(&self) -> Option<(Addr, bool)> {
+        let arbiter = self.arbiter.clone()?;
+        Some((arbiter, self.is_in_arbitration))
+    }
+
+    /// When mediation ends, while the parties may still settle
+    pub fn ongoing_mediation(&self, env: &Env) -> Option<u64> {
+        self.mediation_until
+            .filter(|until| self.is_in_arbitration && env.block.time.seconds() < *until)
+    }
+
+    pub fn arbitration_deadline(&self) -> Option<u64> {
+        // paused while the arbiter who recused awaits a replacement
+        self.time_arbitration_started
+            .filter(|_| !self.is_pending_reassignment)
+            .map(|t| t + ARBITRATION_TIMEOUT_SECONDS + self.arbitration_extended_seconds)
+    }
+
+    /// When the arbiter's time to take the role runs out, while they haven't
+    pub fn arbiter_ack_deadline(&self, window: u64) -> Option<u64> {
+        match self.is_pending_arbiter_ack {
+            true => self.time_created.map(|t| t + window),
+            false => None,
+        }
+    }
+
+    /// When the creator's time to ack the fulfiller runs out, while they haven't
+    pub fn creator_ack_deadline(&self, timeout: u64) -> Option<u64> {
+        match self.is_awaiting_creator_ack {
+            true => self.time_accepted.map(|t| t + timeout),
+            false => None,
+        }
+    }
+
+    /// Whether the creator let their time to ack the fulfiller run out, the acceptance
+    /// is void from then on
+    pub fn is_creator_ack_lapsed(&self, env: &Env, timeout: u64) -> bool {
+        self.creator_ack_deadline(timeout)
+            .is_some_and(|deadline| env.block.time.seconds() >= deadline)
+    }
+
+    /// Whether PayoutDetails stays hidden, until the creator acks a fulfiller
+    pub fn withholds_payout_details(&self) -> bool {
+        self.require_creator_ack && (!self.is_accepted || self.is_awaiting_creator_ack)
+    }
+
+    /// Until when the trust requirements are waived, while they are
+    pub fn open_to_all_until(&self, env: &Env) -> Option<u64> {
+        self.open_to_all_until
+            .filter(|until| env.block.time.seconds() < *until)
+    }
+
+    /// When the creator scheduled the escrow to go live, while that's still ahead
+    pub fn upcoming_start(&self, env: &Env) -> Option<u64> {
+        self.start_time
+            .filter(|start| env.block.time.seconds() < *start)
+    }
+
+    /// Until when nobody may accept, see Config::listing_grace_period_seconds
+    pub fn grace_period_end(&self, grace_period: u64) -> Option<u64> {
+        match grace_period {
+            0 => None,
+            _ => self.time_created.map(|t| t + grace_period),
+        }
+    }
+
+    /// Whether the escrow expired accepted but unfulfilled under HardExpire
+    pub fn is_hard_expired(&self, env: &Env, policy: MidLifecycleExpiry) -> bool {
+        policy == MidLifecycleExpiry::HardExpire
+            && self.is_accepted
+            && !self.is_fulfilled
+            && self.is_expired(env)
+    }
+
+    /// Once accepted and until fulfilled, when the fulfiller's time runs out: the accept
+    /// deadline, or the expiry if it comes first under HardExpire
+    pub fn effective_accept_deadline(
+        &self,
+        env: &Env,
+        seconds_per_block: u64,
+        policy: MidLifecycleExpiry,
+    ) -> Option<u64> {
+        if self.is_fulfilled {
+            return None;
+        }
+        let deadline = self.accept_deadline()?;
+        match (policy, self.expires_at(env, seconds_per_block)) {
+            (MidLifecycleExpiry::HardExpire, Some((expiry, _))) => Some(deadline.min(expiry)),
+            _ => Some(deadline),
+        }
+    }
+
     pub fn is_accept_expired(&self, env: &Env) -> bool {
         // Check if the time since the fulfiller accepted has exceeded an hour
-        return true;
+        is_timer_expired(self.time_accepted, ACCEPT_TIMEOUT_SECONDS, env)
+    }
+
+    /// Whether the creator may still back out of an accepted escrow, see
+    /// Config::post_accept_cancel_window_seconds
+    pub fn in_post_accept_cancel_window(&self, env: &Env, window: u64) -> bool {
+        self.time_accepted
+            .is_some_and(|t| env.block.time.seconds() < t + window)
     }
 
     pub fn is_fulfill_expired(&self, env: &Env) -> bool {
-        // Check if the time since the fulfiller completed has exceeded an hour
-        return true;
+        self.fulfill_deadline()
+            .is_some_and(|deadline| env.block.time.seconds() > deadline)
     }
 
     pub fn is_arbitration_expired(&self, env: &Env) -> bool {
-        // Check if the time since the arbitration started has exceeded two days
-        return true;
+        // Check if the time since the arbitration started has exceeded two days, plus
+        // whatever the arbiter's requests for information added
+        !self.is_pending_reassignment
+            && is_timer_expired(
+                self.time_arbitration_started,
+                ARBITRATION_TIMEOUT_SECONDS + self.arbitration_extended_seconds,
+                env,
+            )
     }
 
     pub fn human_whitelist(&self) -> Vec<String> {
         self.cw20_whitelist.iter().map(|a| a.to_string()).collect()
     }
+
+    /// Every cw20 token held is whitelisted, held once and in a non-zero amount, so payouts
+    /// only send real transfers
+    pub fn assert_balance_consistent(&self) -> Result<(), ContractError> {
+        let inconsistent = |reason: &str, token: &Addr| ContractError::InconsistentBalance {
+            reason: format!("{} {}", reason, token),
+        };
+        for (i, token) in self.balance.cw20.iter().enumerate() {
+            if !self.cw20_whitelist.contains(&token.address) {
+                return Err(inconsistent("holds unlisted token", &token.address));
+            }
+            if token.amount.is_zero() {
+                return Err(inconsistent("holds none of", &token.address));
+            }
+            if self.balance.cw20[..i]
+                .iter()
+                .any(|other| other.address == token.address)
+            {
+                return Err(inconsistent("holds twice", &token.address));
+            }
+        }
+        Ok(())
+    }
+
+    /// Codes of the ways the stored escrow contradicts itself, see QueryMsg::CheckInvariants
+    pub fn invariant_violations(&self) -> Vec<&'static str> {
+        let mut violations = vec![];
+        // settled escrows are removed, and each stage implies the ones before it
+        let contradictory = self.is_canceled
+            || self.is_completed
+            || (self.is_listed && (self.is_accepted || self.is_pending_arbiter_ack))
+            || (!self.is_listed
+                && !self.is_accepted
+                && !self.is_pending_arbiter_ack
+                && self.cancel_pending_until.is_none())
+            || (self.cancel_pending_until.is_some() && (self.is_listed || self.is_accepted))
+            || (self.is_fulfilled && !self.is_accepted)
+            || (self.is_in_arbitration && !self.is_fulfilled)
+            || (self.is_awaiting_creator_ack && (!self.is_accepted || self.is_fulfilled));
+        if contradictory {
+            violations.push("contradictory_status");
+        }
+        if self.assert_balance_consistent().is_err() {
+            violations.push("inconsistent_balance");
+        }
+        if self.balance.native.iter().any(|coin| coin.amount.is_zero()) {
+            violations.push("zero_native_amount");
+        }
+        let timers = [
+            (self.time_created, true),
+            (self.time_accepted, self.is_accepted),
+            (self.time_fulfilled, self.is_fulfilled),
+            (self.time_arbitration_started, self.is_in_arbitration),
+        ];
+        let times: Vec<u64> = timers.iter().filter_map(|(time, _)| *time).collect();
+        if timers.iter().any(|(time, set)| *set && time.is_none())
+            || times.windows(2).any(|pair| pair[0] > pair[1])
+        {
+            violations.push("timestamps_out_of_order");
+        }
+        if self.is_accepted && self.fulfiller == self.creator {
+            violations.push("fulfiller_is_creator");
+        }
+        violations
+    }
+}
+
+/// Why the creator disputes a fulfillment, for arbiters to triage by
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeReason {
+    /// The beneficiary got nothing
+    NotReceived,
+    WrongAmount,
+    /// The fiat went to someone else
+    WrongBeneficiary,
+    Late,
+    Other,
+}
+
+impl DisputeReason {
+    /// The same name serde uses, for event attributes
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisputeReason::NotReceived => "not_received",
+            DisputeReason::WrongAmount => "wrong_amount",
+            DisputeReason::WrongBeneficiary => "wrong_beneficiary",
+            DisputeReason::Late => "late",
+            DisputeReason::Other => "other",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Dispute {
+    pub reason_code: DisputeReason,
+    pub details: Option<String>,
+    /// What the arbiter asked the parties for, oldest first
+    pub info_requests: Vec<String>,
+}
+
+/// A split of a disputed escrow one party offered, for the other to AcceptSettlement
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SettlementProposal {
+    pub proposer: Addr,
+    /// The creator's share of the balance in basis points, the fulfiller gets the rest
+    pub creator_bps: u64,
+}
+
+/// Which bound an escrow's expiry was resolved from
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpirySource {
+    EndHeight,
+    EndTime,
+}
+
+/// Where an escrow stands, see Escrow::lifecycle
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowStatus {
+    /// Waiting for the arbiter to take the role before being listed
+    PendingArbiterAck,
+    Listed,
+    /// Canceled, the creator may UndoCancel until cancel_pending_until
+    PendingCancel,
+    /// Accepted, waiting for the creator to CAckFulfiller or CReject
+    AwaitingCreatorAck,
+    /// Accepted, waiting for the fulfiller to send the fiat
+    Accepted,
+    /// The fulfiller reported sending the fiat, waiting for the creator to confirm
+    Fulfilled,
+    /// Disputed, see the dispute and its deadlines
+    InArbitration,
 }
 
+impl EscrowStatus {
+    /// The same name serde uses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EscrowStatus::PendingArbiterAck => "pending_arbiter_ack",
+            EscrowStatus::Listed => "listed",
+            EscrowStatus::PendingCancel => "pending_cancel",
+            EscrowStatus::AwaitingCreatorAck => "awaiting_creator_ack",
+            EscrowStatus::Accepted => "accepted",
+            EscrowStatus::Fulfilled => "fulfilled",
+            EscrowStatus::InArbitration => "in_arbitration",
+        }
+    }
+}
+
+/// Estimates the time of the given block from the current one
+pub fn height_to_time(env: &Env, height: u64, seconds_per_block: u64) -> u64 {
+    let now = env.block.time.seconds();
+    if height >= env.block.height {
+        now + (height - env.block.height) * seconds_per_block
+    } else {
+        now.saturating_sub((env.block.height - height) * seconds_per_block)
+    }
+}
+
+/// A timer that was never started can't expire
+fn is_timer_expired(started: Option<u64>, timeout: u64, env: &Env) -> bool {
+    match started {
+        Some(start) => env.block.time.seconds() > start + timeout,
+        None => false,
+    }
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
 pub const ESCROWS: Map<&str, Escrow> = Map::new("escrow");
+/// Addresses that may not create, accept or top up escrows
+pub const BLACKLIST: Map<&Addr, BlacklistEntry> = Map::new("blacklist");
+/// Arbiters the admin has vetted
+pub const ARBITERS: Map<&Addr, Empty> = Map::new("arbiters");
+/// Named trust requirements the admin maintains, see msg::TrustRequirement::Preset
+pub const TRUST_PRESETS: Map<&str, TrustMetrics> = Map::new("trust_presets");
+/// Every arbiter's track record, kept when they leave the registry
+pub const ARBITER_STATS: Map<&Addr, ArbiterStats> = Map::new("arbiter_stats");
+/// The escrow id of every fingerprint ever handed out
+pub const FINGERPRINTS: Map<&str, String> = Map::new("fingerprints");
+/// Each wallet's track record, absent for wallets with none
+pub const TRUST_METRICS: Map<&Addr, TrustMetrics> = Map::new("trust_metrics");
+/// The metrics each wallet imported from each peer contract, by wallet then peer.
+/// A wallet imports from a peer at most once.
+pub const IMPORTED_METRICS: Map<(&Addr, &Addr), TrustMetrics> = Map::new("imported_metrics");
+/// Each creator's track record, absent for wallets that never created an escrow
+pub const CREATOR_METRICS: Map<&Addr, CreatorMetrics> = Map::new("creator_metrics");
+/// The revision of the latest change to any escrow
+pub const LATEST_REVISION: Item<u64> = Item::new("latest_revision");
+/// Each escrow's latest change by revision, and a tombstone for every removed escrow
+pub const REVISIONS: Map<U64Key, RevisionEntry> = Map::new("revisions");
+/// The latest admin log sequence number
+pub const ADMIN_LOG_SEQUENCE: Item<u64> = Item::new("admin_log_sequence");
+/// The last MAX_ADMIN_LOG_ENTRIES admin actions by sequence number
+pub const ADMIN_LOG: Map<U64Key, AdminLogEntry> = Map::new("admin_log");
+/// Every escrow id by its creator, kept in sync by save_escrow and remove_escrow.
+/// Escrows saved before the index existed are only added on their next save.
+pub const CREATOR_ESCROWS: Map<(&Addr, &str), Empty> = Map::new("creator_escrows");
+/// Listed escrow ids by listing_sort_key, kept in sync by save_escrow and remove_escrow
+pub const LISTINGS: Map<(U64Key, &str), String> = Map::new("listings");
+/// Every escrow id by time_created, kept in sync by save_escrow and remove_escrow.
+/// Escrows saved before the index existed are only added on their next save.
+pub const CREATED_ESCROWS: Map<(U64Key, &str), String> = Map::new("created_escrows");
+/// Listed escrows by each of their tags, then id
+pub const TAGGED_LISTINGS: Map<(&str, &str), Empty> = Map::new("tagged_listings");
+/// Listed escrows totalled by fiat currency, kept in sync by save_escrow and remove_escrow
+pub const OPEN_INTEREST: Map<&str, OpenInterest> = Map::new("open_interest");
+/// Escrows as they were when they settled, see QueryMsg::AsCreateMsg. Their ids stay
+/// taken for good.
+pub const ARCHIVED_ESCROWS: Map<&str, Escrow> = Map::new("archived_escrows");
+/// The receipts of escrows paid out to the fulfiller or by arbitration, kept as long as
+/// the archived escrow
+pub const RECEIPTS: Map<&str, Receipt> = Map::new("receipts");
+/// What happened to each escrow, oldest first. Kept after the escrow settles.
+pub const HISTORY: Map<&str, Vec<HistoryEntry>> = Map::new("history");
+/// Escrow ids by creator and fulfiller, then the revision of the acceptance so pairs
+/// list newest first. Kept after the escrow settles, dropped when it's unaccepted.
+pub const PARTY_ESCROWS: Map<(&Addr, &Addr, U64Key), String> = Map::new("party_escrows");
+/// What all escrows hold, balances and listing deposits, added up and sorted. Kept in sync by
+/// save_escrow and remove_escrow, ReconcileLocked rebuilds it.
+pub const TOTAL_LOCKED: Item<GenericBalance> = Item::new("total_locked");
+/// How many escrows are stored, settled ones are removed. Kept in sync by save_escrow and
+/// remove_escrow, ReconcileLocked recounts it.
+pub const ACTIVE_ESCROWS: Item<u64> = Item::new("active_escrows");
+/// uusd each wallet escrowed lately, see daily_volume_used
+pub const CREATED_VOLUME: Map<&Addr, Vec<VolumeBucket>> = Map::new("created_volume");
+/// uusd each wallet accepted lately, see daily_volume_used
+pub const ACCEPTED_VOLUME: Map<&Addr, Vec<VolumeBucket>> = Map::new("accepted_volume");
+/// The creator's encrypted payout details by escrow id. Kept apart from ESCROWS so Details
+/// can't return them, remove_escrow drops them when the escrow settles.
+pub const PAYOUT_DETAILS: Map<&str, Binary> = Map::new("payout_details");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RevisionEntry {
+    pub id: String,
+    /// A tombstone, the escrow was paid out or canceled
+    pub removed: bool,
+}
+
+/// An admin-only execution, with the parameters it ran with
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    AddToBlacklist {
+        address: Addr,
+        reason: String,
+    },
+    RemoveFromBlacklist {
+        address: Addr,
+    },
+    /// The metrics themselves are in TRUST_METRICS
+    SeedTrustMetrics {
+        addresses: Vec<Addr>,
+    },
+    CloseSeeding {},
+    BeginSunset {},
+    /// The config after the update
+    UpdateConfig {
+        config: Box<Config>,
+    },
+    AddArbiter {
+        address: Addr,
+    },
+    RemoveArbiter {
+        address: Addr,
+    },
+    ReassignArbiter {
+        id: String,
+        old_arbiter: Addr,
+        new_arbiter: Addr,
+    },
+    /// Whether TOTAL_LOCKED had drifted from the escrows' balances
+    ReconcileLocked {
+        drifted: bool,
+    },
+    RecoverStray {
+        denom: Denom,
+        to: Addr,
+        amount: Uint128,
+    },
+    SetTrustPreset {
+        name: String,
+        metrics: TrustMetrics,
+    },
+    RemoveTrustPreset {
+        name: String,
+    },
+}
+
+/// What a settled escrow paid out, for off-chain accounting. The data of the response
+/// settling it, and kept like the archived escrow. Clients depend on the field names.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Receipt {
+    pub escrow_id: String,
+    pub fingerprint: String,
+    pub creator: Addr,
+    pub fulfiller: Addr,
+    /// Every payout in the order the messages were emitted, the listing deposit included
+    pub amounts_released: Vec<ReleasedAmount>,
+    /// Kept by the contract, nothing as long as releases are free
+    pub fee_taken: GenericBalance,
+    pub exchange_rate: Decimal,
+    pub fiat_currency: String,
+    /// The step that settled the escrow, as in its history, e.g. "creator_complete"
+    pub terminal_action: String,
+    /// In seconds since epoch
+    pub block_time: u64,
+    pub block_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReleasedAmount {
+    pub recipient: Addr,
+    pub amount: GenericBalance,
+}
+
+/// One step in an escrow's life, see HISTORY
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HistoryEntry {
+    /// The action attribute of the step's response, e.g. "accept"
+    pub action: String,
+    /// Who sent the message
+    pub actor: Addr,
+    /// In seconds since epoch
+    pub time: u64,
+    /// Why, for steps that take a reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AdminLogEntry {
+    pub sequence: u64,
+    pub admin: Addr,
+    /// In seconds since epoch
+    pub time: u64,
+    pub action: AdminAction,
+}
+
+/// Appends to an escrow's history. Past MAX_HISTORY_ENTRIES the second entry is dropped:
+/// the first is the creation and a settling one always comes last, so both are kept.
+pub fn record_history(
+    storage: &mut dyn Storage,
+    id: &str,
+    action: &str,
+    actor: &Addr,
+    env: &Env,
+) -> StdResult<()> {
+    record_history_note(storage, id, action, actor, env, None)
+}
+
+/// As record_history, with the reason given for the step
+pub fn record_history_note(
+    storage: &mut dyn Storage,
+    id: &str,
+    action: &str,
+    actor: &Addr,
+    env: &Env,
+    note: Option<String>,
+) -> StdResult<()> {
+    let mut history = HISTORY.may_load(storage, id)?.unwrap_or_default();
+    history.push(HistoryEntry {
+        action: action.to_string(),
+        actor: actor.clone(),
+        time: env.block.time.seconds(),
+        note,
+    });
+    if history.len() > MAX_HISTORY_ENTRIES {
+        history.remove(1);
+    }
+    HISTORY.save(storage, id, &history)
+}
+
+/// Volume moved in the hour starting at `start`, in seconds since epoch
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VolumeBucket {
+    pub start: u64,
+    pub amount: Uint128,
+}
+
+/// Hourly buckets make the 24 hours a wallet's volume is limited over roll forward
+/// an hour at a time, while keeping at most 24 per wallet
+pub const VOLUME_BUCKET_SECONDS: u64 = 3_600;
+pub const VOLUME_WINDOW_SECONDS: u64 = 86_400;
+
+/// The buckets still inside the window ending now
+fn live_volume_buckets(
+    storage: &dyn Storage,
+    volume: &Map<&Addr, Vec<VolumeBucket>>,
+    address: &Addr,
+    env: &Env,
+) -> StdResult<Vec<VolumeBucket>> {
+    let now = env.block.time.seconds();
+    let mut buckets = volume.may_load(storage, address)?.unwrap_or_default();
+    buckets.retain(|b| b.start + VOLUME_WINDOW_SECONDS > now);
+    Ok(buckets)
+}
+
+/// What the address moved in the last 24 hours
+pub fn daily_volume_used(
+    storage: &dyn Storage,
+    volume: &Map<&Addr, Vec<VolumeBucket>>,
+    address: &Addr,
+    env: &Env,
+) -> StdResult<Uint128> {
+    let buckets = live_volume_buckets(storage, volume, address, env)?;
+    Ok(buckets.iter().map(|b| b.amount).sum())
+}
+
+/// Fails with DailyLimitExceeded if `amount` more would take the address past `limit`
+pub fn assert_daily_volume(
+    storage: &dyn Storage,
+    volume: &Map<&Addr, Vec<VolumeBucket>>,
+    address: &Addr,
+    env: &Env,
+    amount: Uint128,
+    limit: Option<Uint128>,
+) -> Result<(), ContractError> {
+    let limit = match limit {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+    let used = daily_volume_used(storage, volume, address, env)?;
+    if used + amount > limit {
+        return Err(ContractError::DailyLimitExceeded { used, limit });
+    }
+    Ok(())
+}
+
+/// Adds to the address' volume, dropping the buckets that fell out of the window
+pub fn record_daily_volume(
+    storage: &mut dyn Storage,
+    volume: &Map<&Addr, Vec<VolumeBucket>>,
+    address: &Addr,
+    env: &Env,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut buckets = live_volume_buckets(storage, volume, address, env)?;
+    let now = env.block.time.seconds();
+    let start = now - now % VOLUME_BUCKET_SECONDS;
+    match buckets.last_mut() {
+        Some(bucket) if bucket.start == start => bucket.amount += amount,
+        _ => buckets.push(VolumeBucket { start, amount }),
+    }
+    volume.save(storage, address, &buckets)
+}
+
+/// Appends to the admin log, pruning the entry that falls out of the last MAX_ADMIN_LOG_ENTRIES
+pub fn log_admin_action(
+    storage: &mut dyn Storage,
+    env: &Env,
+    admin: &Addr,
+    action: AdminAction,
+) -> StdResult<()> {
+    let sequence = ADMIN_LOG_SEQUENCE.may_load(storage)?.unwrap_or_default() + 1;
+    ADMIN_LOG_SEQUENCE.save(storage, &sequence)?;
+    if sequence > MAX_ADMIN_LOG_ENTRIES {
+        ADMIN_LOG.remove(storage, (sequence - MAX_ADMIN_LOG_ENTRIES).into());
+    }
+    let entry = AdminLogEntry {
+        sequence,
+        admin: admin.clone(),
+        time: env.block.time.seconds(),
+        action,
+    };
+    ADMIN_LOG.save(storage, sequence.into(), &entry)
+}
+
+/// Saves the escrow under a new revision. All escrow writes go through here
+/// so that ChangedSince sees them.
+pub fn save_escrow(storage: &mut dyn Storage, id: &str, escrow: &mut Escrow) -> StdResult<()> {
+    debug_assert_eq!(escrow.assert_balance_consistent(), Ok(()));
+    // escrows stored before balances were kept in order get it on their next change
+    escrow.balance.normalize();
+    if escrow.revision > 0 {
+        REVISIONS.remove(storage, escrow.revision.into());
+    }
+    escrow.revision = next_revision(storage)?;
+    let entry = RevisionEntry {
+        id: id.to_string(),
+        removed: false,
+    };
+    REVISIONS.save(storage, escrow.revision.into(), &entry)?;
+
+    let (previous_locked, previous_load) = match ESCROWS.may_load(storage, id)? {
+        Some(previous) => {
+            LISTINGS.remove(storage, (previous.listing_sort_key.into(), id));
+            CREATED_ESCROWS.remove(storage, (previous.created_sort_key().into(), id));
+            for tag in &previous.tags {
+                TAGGED_LISTINGS.remove(storage, (tag.as_str(), id));
+            }
+            if previous.is_listed {
+                count_open_interest(storage, &previous, false)?;
+            }
+            (previous.locked(), previous.arbiter_load())
+        }
+        None => {
+            let active = ACTIVE_ESCROWS.may_load(storage)?.unwrap_or_default();
+            ACTIVE_ESCROWS.save(storage, &(active + 1))?;
+            (GenericBalance::default(), None)
+        }
+    };
+    let locked = escrow.locked();
+    if previous_locked != locked {
+        update_locked(storage, &previous_locked, &locked)?;
+    }
+    let load = escrow.arbiter_load();
+    if previous_load != load {
+        count_arbiter_load(storage, previous_load, false)?;
+        count_arbiter_load(storage, load, true)?;
+    }
+    CREATOR_ESCROWS.save(storage, (&escrow.creator, id), &Empty {})?;
+    CREATED_ESCROWS.save(
+        storage,
+        (escrow.created_sort_key().into(), id),
+        &id.to_string(),
+    )?;
+    if escrow.is_listed {
+        LISTINGS.save(
+            storage,
+            (escrow.listing_sort_key.into(), id),
+            &id.to_string(),
+        )?;
+        for tag in &escrow.tags {
+            TAGGED_LISTINGS.save(storage, (tag.as_str(), id), &Empty {})?;
+        }
+        count_open_interest(storage, escrow, true)?;
+    }
+    ESCROWS.save(storage, id, escrow)
+}
+
+/// Moves an escrow in TOTAL_LOCKED from what it held to what it holds now
+fn update_locked(
+    storage: &mut dyn Storage,
+    released: &GenericBalance,
+    locked: &GenericBalance,
+) -> StdResult<()> {
+    let total = TOTAL_LOCKED.may_load(storage)?.unwrap_or_default();
+    let total = payments::subtract(&payments::merge(&total, locked), released);
+    TOTAL_LOCKED.save(storage, &total)
+}
+
+/// Adds a listed escrow to the open interest in its currency, or takes it back out
+fn count_open_interest(storage: &mut dyn Storage, escrow: &Escrow, add: bool) -> StdResult<()> {
+    let currency = escrow.fiat_currency.as_str();
+    let mut interest = OPEN_INTEREST
+        .may_load(storage, currency)?
+        .unwrap_or_default();
+    if add {
+        interest.value_ust += escrow.value_ust();
+        interest.listings += 1;
+    } else {
+        interest.value_ust = interest.value_ust.checked_sub(escrow.value_ust())?;
+        interest.listings = interest.listings.saturating_sub(1);
+    }
+    if interest.listings == 0 {
+        OPEN_INTEREST.remove(storage, currency);
+        Ok(())
+    } else {
+        OPEN_INTEREST.save(storage, currency, &interest)
+    }
+}
+
+/// Adds an escrow to its arbiter's load, or takes it back out. Escrows stored before the
+/// load was kept were never added, so it doesn't go below 0.
+fn count_arbiter_load(
+    storage: &mut dyn Storage,
+    load: Option<(Addr, bool)>,
+    add: bool,
+) -> StdResult<()> {
+    let (arbiter, in_arbitration) = match load {
+        Some(load) => load,
+        None => return Ok(()),
+    };
+    let mut stats = ARBITER_STATS
+        .may_load(storage, &arbiter)?
+        .unwrap_or_default();
+    let step = |count: u32| match add {
+        true => count + 1,
+        false => count.saturating_sub(1),
+    };
+    stats.active_escrows = step(stats.active_escrows);
+    if in_arbitration {
+        stats.in_arbitration = step(stats.in_arbitration);
+    }
+    ARBITER_STATS.save(storage, &arbiter, &stats)
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// 8 base32 characters of the sha256 of the id, creator and creation time.
+/// A nonce other than 0 is hashed in too, to get past a collision.
+pub fn fingerprint(id: &str, creator: &Addr, time_created: u64, nonce: u32) -> String {
+    let mut hasher = Sha256::new();
+    for part in [id.as_bytes(), creator.as_bytes()] {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.update(time_created.to_be_bytes());
+    if nonce > 0 {
+        hasher.update(nonce.to_be_bytes());
+    }
+    let hash = hasher.finalize();
+
+    // 8 characters of 5 bits each take the first 40 bits
+    let bits = hash[..5]
+        .iter()
+        .fold(0u64, |bits, byte| bits << 8 | *byte as u64);
+    (0..8)
+        .rev()
+        .map(|i| BASE32_ALPHABET[(bits >> (i * 5)) as usize & 31] as char)
+        .collect()
+}
+
+/// Finds a fingerprint no escrow has ever had and reserves it for this one, so
+/// old receipts never point at a different escrow
+pub fn reserve_fingerprint(
+    storage: &mut dyn Storage,
+    id: &str,
+    creator: &Addr,
+    time_created: u64,
+) -> StdResult<String> {
+    let mut nonce = 0;
+    loop {
+        let candidate = fingerprint(id, creator, time_created, nonce);
+        if !FINGERPRINTS.has(storage, &candidate) {
+            FINGERPRINTS.save(storage, &candidate, &id.to_string())?;
+            return Ok(candidate);
+        }
+        nonce += 1;
+    }
+}
+
+/// Removes the escrow, leaving a tombstone so pollers learn it is gone
+pub fn remove_escrow(storage: &mut dyn Storage, id: &str, escrow: &Escrow) -> StdResult<()> {
+    REVISIONS.remove(storage, escrow.revision.into());
+    let entry = RevisionEntry {
+        id: id.to_string(),
+        removed: true,
+    };
+    let revision = next_revision(storage)?;
+    REVISIONS.save(storage, revision.into(), &entry)?;
+    // handlers may have unlisted their copy already, what counts is what was stored
+    if let Some(stored) = ESCROWS.may_load(storage, id)? {
+        if stored.is_listed {
+            count_open_interest(storage, &stored, false)?;
+        }
+        update_locked(storage, &stored.locked(), &GenericBalance::default())?;
+        count_arbiter_load(storage, stored.arbiter_load(), false)?;
+        // escrows stored before the count was kept aren't in it until ReconcileLocked
+        let active = ACTIVE_ESCROWS.may_load(storage)?.unwrap_or_default();
+        ACTIVE_ESCROWS.save(storage, &active.saturating_sub(1))?;
+    }
+    LISTINGS.remove(storage, (escrow.listing_sort_key.into(), id));
+    CREATOR_ESCROWS.remove(storage, (&escrow.creator, id));
+    CREATED_ESCROWS.remove(storage, (escrow.created_sort_key().into(), id));
+    for tag in &escrow.tags {
+        TAGGED_LISTINGS.remove(storage, (tag.as_str(), id));
+    }
+    ESCROWS.remove(storage, id);
+    ARCHIVED_ESCROWS.save(storage, id, escrow)?;
+    PAYOUT_DETAILS.remove(storage, id);
+    Ok(())
+}
+
+/// The escrow, or why there is none: EscrowFinished with the last thing that happened to it
+/// if its history outlived it, EscrowNotFound if it never existed
+pub fn load_active_or_explain(storage: &dyn Storage, id: &str) -> Result<Escrow, ContractError> {
+    if let Some(escrow) = ESCROWS.may_load(storage, id)? {
+        return Ok(escrow);
+    }
+    let last = HISTORY
+        .may_load(storage, id)?
+        .and_then(|mut history| history.pop());
+    match last {
+        Some(entry) => Err(ContractError::EscrowFinished {
+            id: id.to_string(),
+            status: entry.action,
+        }),
+        None => Err(ContractError::EscrowNotFound { id: id.to_string() }),
+    }
+}
+
+/// Indexes an escrow just saved as accepted under its creator and fulfiller
+pub fn index_parties(storage: &mut dyn Storage, id: &str, escrow: &Escrow) -> StdResult<()> {
+    let key = (&escrow.creator, &escrow.fulfiller, escrow.revision.into());
+    PARTY_ESCROWS.save(storage, key, &id.to_string())
+}
+
+/// Drops the pairing of an escrow with a fulfiller who no longer holds it
+pub fn unindex_parties(
+    storage: &mut dyn Storage,
+    id: &str,
+    creator: &Addr,
+    fulfiller: &Addr,
+) -> StdResult<()> {
+    let stale: Vec<Vec<u8>> = PARTY_ESCROWS
+        .prefix((creator, fulfiller))
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((revision, indexed)) if indexed == id => Some(Ok(revision)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<StdResult<_>>()?;
+    for revision in stale {
+        let revision = revision_from_key(&revision)?;
+        PARTY_ESCROWS.remove(storage, (creator, fulfiller, revision.into()));
+    }
+    Ok(())
+}
+
+/// Reads a revision back from the raw key of an index ordered by revision
+pub fn revision_from_key(key: &[u8]) -> StdResult<u64> {
+    key.try_into()
+        .map(u64::from_be_bytes)
+        .map_err(|_| StdError::generic_err("Corrupted revision key"))
+}
+
+fn next_revision(storage: &mut dyn Storage) -> StdResult<u64> {
+    let revision = LATEST_REVISION.may_load(storage)?.unwrap_or_default() + 1;
+    LATEST_REVISION.save(storage, &revision)?;
+    Ok(revision)
+}
 
 /// This returns the list of ids for all registered escrows
 pub fn all_escrow_ids(storage: &dyn Storage) -> StdResult<Vec<String>> {
@@ -173,7 +1897,8 @@ pub fn all_escrow_ids(storage: &dyn Storage) -> StdResult<Vec<String>> {
 mod tests {
     use super::*;
 
-    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+    use cosmwasm_std::{coin, coins, from_slice, to_vec};
 
     #[test]
     fn no_escrow_ids() {
@@ -184,26 +1909,70 @@ mod tests {
 
     fn dummy_escrow() -> Escrow {
         Escrow {
-            arbiter: Addr::unchecked("arb"),
-            recipient: Addr::unchecked("recip"),
-            source: Addr::unchecked("source"),
+            arbiter: Some(Addr::unchecked("arb")),
+            fulfiller: Addr::unchecked("creator"),
+            creator: Addr::unchecked("creator"),
             end_height: None,
             end_time: None,
             balance: Default::default(),
+            funding_source: FundingSource::NativeDirect,
+            fiat_currency: DEFAULT_FIAT_CURRENCY.to_string(),
+            exchange_rate: Decimal::one(),
             cw20_whitelist: vec![],
+            required_trust_metrics: Default::default(),
+            auto_complete_trust: None,
+            listing_deposit: vec![],
+            allowed_fulfillers: None,
+            description: None,
+            delivered_fiat_amount: None,
+            dispute: None,
+            fingerprint: String::new(),
+            revision: 0,
+            is_pending_arbiter_ack: false,
+            is_listed: true,
+            is_canceled: false,
+            is_accepted: false,
+            is_fulfilled: false,
+            is_in_arbitration: false,
+            is_completed: false,
+            time_created: Some(0),
+            time_accepted: None,
+            time_fulfilled: None,
+            time_arbitration_started: None,
+            arbitration_extensions: 0,
+            arbitration_extended_seconds: 0,
+            contributors: vec![],
+            dispute_window_seconds: None,
+            dispute_deadline: None,
+            rejected_fulfillers: vec![],
+            is_pending_reassignment: false,
+            require_creator_ack: false,
+            is_awaiting_creator_ack: false,
+            open_to_all_until: None,
+            cancel_pending_until: None,
+            auto_extend_on_relist: None,
+            creator_payout_memo: false,
+            fulfiller_payout_memo: false,
+            tags: vec![],
+            start_time: None,
+            mediation_until: None,
+            settlement_proposal: None,
+            creator_locale: None,
+            creator_utc_offset_minutes: None,
+            action_counts: vec![],
+            listing_sort_key: 0,
+            last_bumped: None,
         }
     }
 
     #[test]
     fn all_escrow_ids_in_order() {
         let mut storage = MockStorage::new();
+        ESCROWS.save(&mut storage, "lazy", &dummy_escrow()).unwrap();
         ESCROWS
-            .save(&mut storage, &"lazy", &dummy_escrow())
+            .save(&mut storage, "assign", &dummy_escrow())
             .unwrap();
-        ESCROWS
-            .save(&mut storage, &"assign", &dummy_escrow())
-            .unwrap();
-        ESCROWS.save(&mut storage, &"zen", &dummy_escrow()).unwrap();
+        ESCROWS.save(&mut storage, "zen", &dummy_escrow()).unwrap();
 
         let ids = all_escrow_ids(&storage).unwrap();
         assert_eq!(3, ids.len());
@@ -212,4 +1981,352 @@ mod tests {
             ids
         )
     }
+
+    #[test]
+    fn dispute_reason_serializes_to_stable_strings() {
+        let all = [
+            (DisputeReason::NotReceived, "not_received"),
+            (DisputeReason::WrongAmount, "wrong_amount"),
+            (DisputeReason::WrongBeneficiary, "wrong_beneficiary"),
+            (DisputeReason::Late, "late"),
+            (DisputeReason::Other, "other"),
+        ];
+        for (reason, name) in all {
+            let json = to_vec(&reason).unwrap();
+            assert_eq!(json, format!("\"{}\"", name).into_bytes());
+            assert_eq!(from_slice::<DisputeReason>(&json).unwrap(), reason);
+            assert_eq!(reason.as_str(), name);
+        }
+        assert!(from_slice::<DisputeReason>(b"\"NotReceived\"").is_err());
+    }
+
+    #[test]
+    fn fingerprints_are_short_stable_and_unique() {
+        let creator = Addr::unchecked("creator");
+        let fp = fingerprint("foobar", &creator, 1_571_797_419, 0);
+        assert_eq!(fp.len(), 8);
+        assert!(fp.bytes().all(|b| BASE32_ALPHABET.contains(&b)));
+        assert_eq!(fp, fingerprint("foobar", &creator, 1_571_797_419, 0));
+        assert_ne!(fp, fingerprint("foobar", &creator, 1_571_797_420, 0));
+        assert_ne!(fp, fingerprint("foobar", &creator, 1_571_797_419, 1));
+
+        // a taken fingerprint is re-hashed with a nonce
+        let mut storage = MockStorage::new();
+        FINGERPRINTS
+            .save(&mut storage, &fp, &"other".to_string())
+            .unwrap();
+        let reserved =
+            reserve_fingerprint(&mut storage, "foobar", &creator, 1_571_797_419).unwrap();
+        assert_eq!(reserved, fingerprint("foobar", &creator, 1_571_797_419, 1));
+        assert_eq!(FINGERPRINTS.load(&storage, &reserved).unwrap(), "foobar");
+    }
+
+    #[test]
+    fn trust_metrics_stored_before_disputes_load_with_zero_disputes() {
+        let old = br#"{"percent_completed":95,"percent_satisfied":90,"avg_volume":100,"avg_completion_speed":600000,"total_volume":2000,"total_completed":20}"#;
+        let metrics: TrustMetrics = from_slice(old).unwrap();
+        assert_eq!(metrics.total_completed, 20);
+        assert_eq!(
+            (
+                metrics.disputes_opened,
+                metrics.disputes_lost,
+                metrics.disputes_won
+            ),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn dispute_counters_are_lower_is_better() {
+        let required = TrustMetrics {
+            disputes_lost: 1,
+            ..TrustMetrics::default()
+        };
+        let clean = TrustMetrics::default();
+        let one_lost = TrustMetrics {
+            disputes_lost: 1,
+            ..TrustMetrics::default()
+        };
+        let two_lost = TrustMetrics {
+            disputes_lost: 2,
+            ..TrustMetrics::default()
+        };
+        assert!(required.is_higher(clean.clone()));
+        assert!(required.is_higher(one_lost));
+        assert!(!required.is_higher(two_lost));
+        // 0 places no limit on disputes opened or won
+        let busy = TrustMetrics {
+            disputes_opened: 5,
+            disputes_won: 5,
+            ..clean
+        };
+        assert!(TrustMetrics::default().is_higher(busy.clone()));
+        let required = TrustMetrics {
+            disputes_won: 4,
+            ..TrustMetrics::default()
+        };
+        assert!(!required.is_higher(busy));
+    }
+
+    #[test]
+    fn config_validation_names_the_first_bad_field() {
+        for seconds_per_block in [0, 6] {
+            for max_whitelist_len in [0, 10] {
+                for bump_cooldown_seconds in [0, 60] {
+                    let config = Config {
+                        admin: Addr::unchecked("admin"),
+                        seconds_per_block,
+                        max_whitelist_len,
+                        seeding_closed: false,
+                        sunset: false,
+                        bump_cooldown_seconds,
+                        allow_mixed_funding: false,
+                        post_accept_cancel_window_seconds: 0,
+                        cancel_penalty_bps: 0,
+                        split_native_payouts: false,
+                        listing_deposit: vec![],
+                        fee_collector: Addr::unchecked("admin"),
+                        require_arbiter_ack: false,
+                        arbiter_ack_window_seconds: 60,
+                        display_denoms: vec![],
+                        listing_grace_period_seconds: 0,
+                        arbitration_extension_seconds: DEFAULT_ARBITRATION_EXTENSION_SECONDS,
+                        max_arbitration_extensions: DEFAULT_MAX_ARBITRATION_EXTENSIONS,
+                        kyc_contract: None,
+                        allow_third_party_topup: false,
+                        max_active_escrows: None,
+                        daily_volume_limit_ust: None,
+                        daily_accept_limit_ust: None,
+                        dispute_window_seconds: FULFILL_TIMEOUT_SECONDS,
+                        creator_ack_timeout_seconds: ACCEPT_TIMEOUT_SECONDS,
+                        max_open_to_all_seconds: DEFAULT_MAX_OPEN_TO_ALL_SECONDS,
+                        cancel_cooloff_seconds: 0,
+                        max_escrow_bytes: DEFAULT_MAX_ESCROW_BYTES,
+                        mid_lifecycle_expiry: MidLifecycleExpiry::default(),
+                        risk_weights: RiskWeights::default(),
+                        mediation_window_seconds: 0,
+                        min_remaining_on_accept_seconds: DEFAULT_MIN_REMAINING_ON_ACCEPT_SECONDS,
+                        peer_contracts: vec![],
+                        max_actions_per_address: DEFAULT_MAX_ACTIONS_PER_ADDRESS,
+                    };
+                    let expected = if seconds_per_block == 0 {
+                        Some("seconds_per_block")
+                    } else if max_whitelist_len == 0 {
+                        Some("max_whitelist_len")
+                    } else if bump_cooldown_seconds == 0 {
+                        Some("bump_cooldown_seconds")
+                    } else {
+                        None
+                    };
+                    let result = config.validate();
+                    match expected {
+                        Some(field) => assert_eq!(
+                            result,
+                            Err(ContractError::InvalidConfig {
+                                field: field.to_string()
+                            })
+                        ),
+                        None => assert_eq!(result, Ok(())),
+                    }
+                }
+            }
+        }
+
+        let config = Config {
+            admin: Addr::unchecked("admin"),
+            seconds_per_block: 6,
+            max_whitelist_len: 10,
+            seeding_closed: false,
+            sunset: false,
+            bump_cooldown_seconds: 60,
+            allow_mixed_funding: false,
+            post_accept_cancel_window_seconds: 600,
+            cancel_penalty_bps: MAX_BPS + 1,
+            split_native_payouts: false,
+            listing_deposit: vec![],
+            fee_collector: Addr::unchecked("admin"),
+            require_arbiter_ack: false,
+            arbiter_ack_window_seconds: 60,
+            display_denoms: vec![],
+            listing_grace_period_seconds: 0,
+            arbitration_extension_seconds: DEFAULT_ARBITRATION_EXTENSION_SECONDS,
+            max_arbitration_extensions: DEFAULT_MAX_ARBITRATION_EXTENSIONS,
+            kyc_contract: None,
+            allow_third_party_topup: false,
+            max_active_escrows: None,
+            daily_volume_limit_ust: None,
+            daily_accept_limit_ust: None,
+            dispute_window_seconds: FULFILL_TIMEOUT_SECONDS,
+            creator_ack_timeout_seconds: ACCEPT_TIMEOUT_SECONDS,
+            max_open_to_all_seconds: DEFAULT_MAX_OPEN_TO_ALL_SECONDS,
+            cancel_cooloff_seconds: 0,
+            max_escrow_bytes: DEFAULT_MAX_ESCROW_BYTES,
+            mid_lifecycle_expiry: MidLifecycleExpiry::default(),
+            risk_weights: RiskWeights::default(),
+            mediation_window_seconds: 0,
+            min_remaining_on_accept_seconds: DEFAULT_MIN_REMAINING_ON_ACCEPT_SECONDS,
+            peer_contracts: vec![],
+            max_actions_per_address: DEFAULT_MAX_ACTIONS_PER_ADDRESS,
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ContractError::InvalidConfig {
+                field: "cancel_penalty_bps".to_string()
+            })
+        );
+        for listing_deposit in [
+            coins(0, UST_DENOM),
+            vec![coin(1, UST_DENOM), coin(2, UST_DENOM)],
+        ] {
+            let config = Config {
+                cancel_penalty_bps: 0,
+                listing_deposit,
+                ..config.clone()
+            };
+            assert_eq!(
+                config.validate(),
+                Err(ContractError::InvalidConfig {
+                    field: "listing_deposit".to_string()
+                })
+            );
+        }
+        let nameless = DisplayDenom {
+            symbol: String::new(),
+            ..DisplayDenom::ust()
+        };
+        for display_denoms in [
+            vec![nameless],
+            vec![DisplayDenom::ust(), DisplayDenom::ust()],
+        ] {
+            let config = Config {
+                cancel_penalty_bps: 0,
+                display_denoms,
+                ..config.clone()
+            };
+            assert_eq!(
+                config.validate(),
+                Err(ContractError::InvalidConfig {
+                    field: "display_denoms".to_string()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn history_keeps_the_creation_and_the_last_entries() {
+        let mut storage = MockStorage::new();
+        let mut env = mock_env();
+        let creator = Addr::unchecked("creator");
+        record_history(&mut storage, "foobar", "create", &creator, &env).unwrap();
+        for _ in 0..MAX_HISTORY_ENTRIES + 5 {
+            env.block.time = env.block.time.plus_seconds(1);
+            record_history(&mut storage, "foobar", "bump", &creator, &env).unwrap();
+        }
+        record_history(&mut storage, "foobar", "cancel", &creator, &env).unwrap();
+
+        let history = HISTORY.load(&storage, "foobar").unwrap();
+        assert_eq!(history.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history[0].action, "create");
+        assert_eq!(history[MAX_HISTORY_ENTRIES - 1].action, "cancel");
+        // the oldest bumps went first
+        assert_eq!(history[1].time, mock_env().block.time.seconds() + 8);
+    }
+
+    #[test]
+    fn corrupted_cw20_balances_are_caught() {
+        let held = |address: &str, amount: u128| Cw20CoinVerified {
+            address: Addr::unchecked(address),
+            amount: Uint128::new(amount),
+        };
+        let mut escrow = Escrow {
+            cw20_whitelist: vec![Addr::unchecked("cash"), Addr::unchecked("bolos")],
+            ..dummy_escrow()
+        };
+        escrow.balance.cw20 = vec![held("cash", 100), held("bolos", 5)];
+        assert_eq!(escrow.assert_balance_consistent(), Ok(()));
+
+        let corruptions = [
+            (held("stolen", 100), "holds unlisted token stolen"),
+            (held("bolos", 0), "holds none of bolos"),
+            (held("cash", 1), "holds twice cash"),
+        ];
+        for (extra, reason) in corruptions {
+            let mut corrupted = escrow.clone();
+            corrupted.balance.cw20 = vec![held("cash", 100), extra];
+            assert_eq!(
+                corrupted.assert_balance_consistent(),
+                Err(ContractError::InconsistentBalance {
+                    reason: reason.to_string()
+                })
+            );
+        }
+
+        // whitelisted tokens that never arrived are fine
+        escrow.balance.cw20.clear();
+        assert_eq!(escrow.assert_balance_consistent(), Ok(()));
+    }
+
+    #[test]
+    fn risk_scores_are_pinned_for_representative_listings() {
+        let weights = RiskWeights::default();
+        let escrow = |uusd: u128, arbiter: bool| Escrow {
+            balance: GenericBalance {
+                native: coins(uusd, UST_DENOM),
+                cw20: vec![],
+            },
+            arbiter: Some(Addr::unchecked("arb")).filter(|_| arbiter),
+            ..dummy_escrow()
+        };
+        let metrics = |created: u32, opened: u32, lost: u32, value: u128| CreatorMetrics {
+            escrows_created: created,
+            disputes_opened: opened,
+            disputes_lost: lost,
+            value_created_ust: Uint128::new(value),
+            ..CreatorMetrics::default()
+        };
+
+        // (creator, escrow, score), retuning the defaults means repinning these
+        let cases = [
+            // a clean creator listing their usual amount with an arbiter
+            (metrics(10, 0, 0, 1_000), escrow(100, true), 0),
+            // no history at all, only the missing arbiter counts
+            (metrics(0, 0, 0, 0), escrow(100, false), 20),
+            // a history from before values were kept can't be compared against
+            (metrics(4, 0, 0, 0), escrow(100, true), 0),
+            // one dispute in ten escrows
+            (metrics(10, 1, 0, 1_000), escrow(100, true), 5),
+            // lost, it counts twice
+            (metrics(10, 1, 1, 1_000), escrow(100, true), 10),
+            // disputing everything maxes the history part out
+            (metrics(2, 2, 2, 200), escrow(100, true), 50),
+            // half again their average
+            (metrics(10, 0, 0, 1_000), escrow(150, true), 15),
+            // twice their average and more weigh the same
+            (metrics(10, 0, 0, 1_000), escrow(200, true), 30),
+            (metrics(10, 0, 0, 1_000), escrow(10_000, true), 30),
+            // less than usual is no risk
+            (metrics(10, 0, 0, 1_000), escrow(10, true), 0),
+            // everything at once
+            (metrics(2, 2, 2, 200), escrow(1_000, false), 100),
+        ];
+        for (i, (metrics, escrow, expected)) in cases.iter().enumerate() {
+            assert_eq!(
+                risk_score(metrics, escrow, &weights),
+                *expected,
+                "case {}",
+                i
+            );
+        }
+
+        // the admin's weights apply
+        let tuned = RiskWeights {
+            dispute_history: 0,
+            value_above_average: 0,
+            no_arbitration: 100,
+        };
+        assert_eq!(
+            risk_score(&metrics(0, 0, 0, 0), &escrow(100, false), &tuned),
+            100
+        );
+    }
 }
@@ -0,0 +1,899 @@
+//! Pins the JSON of what leaves the contract: query responses, receipts, hook messages
+//! and event attributes. Off-chain accounting and indexers parse these, and a renamed field
+//! or enum variant breaks them without a compile error anywhere. Each value is serialized
+//! as the contract serializes it and compared with its fixture in tests/golden, byte for
+//! byte, then read back from the fixture.
+//!
+//! When a change to the JSON is intended, rewrite the fixtures with
+//!
+//! ```text
+//! GOLDEN_BLESS=1 cargo test --test golden
+//! ```
+//!
+//! and commit them along with it, so the new format is reviewed like the code.
+
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::path::PathBuf;
+
+use cosmwasm_std::{coin, from_slice, to_vec, Addr, Attribute, Binary, Decimal, Uint128};
+use cw20::{Cw20Coin, Cw20CoinVerified, Denom};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use ellib_escrow::events::{ContractEvent, EscrowEvent};
+use ellib_escrow::kyc::IsVerifiedResponse;
+use ellib_escrow::msg::{
+    ActionCount, AdminLogResponse, AllArbiterStatsResponse, ArbiterEntry, ArbiterStatsResponse,
+    ArbitersResponse, AsCreateMsgResponse, BatchCreateResponse, BlacklistInfo, BlacklistResponse,
+    CapabilitiesResponse, ChangedSinceResponse, CreateMsg, CurrencyOpenInterest, DeadlinesResponse,
+    DetailsResponse, DetailsV2Response, EligibleListingsResponse, EscrowChange, EscrowFees,
+    EscrowTimestamps, EscrowTrust, FindByPartiesResponse, HistoryResponse, InvariantViolation,
+    InvariantsResponse, ListResponse, ListingSummary, ListingsResponse, NearMiss,
+    OpenInterestResponse, PartyEscrow, PayoutDetailsResponse, PayoutHookMsg,
+    SimulateReleaseResponse, StatsResponse, TotalLockedResponse, Transfer, TrustPreset,
+    TrustPresetsResponse, TrustRequirement, ValidateCreateResponse,
+};
+use ellib_escrow::state::{
+    AdminAction, AdminLogEntry, ArbiterStats, CreatorMetrics, Dispute, DisputeReason, Escrow,
+    EscrowStatus, ExpirySource, FundingSource, GenericBalance, HistoryEntry, Receipt,
+    ReleasedAmount, TrustMetrics,
+};
+
+/// Set to rewrite the fixtures from the values instead of checking them
+const BLESS: &str = "GOLDEN_BLESS";
+
+/// The block time of mock_env, for timestamps that look like real ones
+const NOW: u64 = 1_571_797_419;
+const ID: &str = "remesa-caracas";
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.json", name))
+}
+
+fn assert_golden<T>(name: &str, value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let path = fixture(name);
+    let json = String::from_utf8(to_vec(value).unwrap()).unwrap();
+    if env::var_os(BLESS).is_some() {
+        fs::write(&path, json + "\n").unwrap();
+        return;
+    }
+    let golden = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("{} is missing, run with {}=1", path.display(), BLESS));
+    assert_eq!(
+        json,
+        golden.trim_end(),
+        "{} changed, if on purpose run with {}=1 and commit the fixture",
+        name,
+        BLESS
+    );
+    let read_back: T = from_slice(golden.as_bytes()).unwrap();
+    assert_eq!(&read_back, value, "{} doesn't read back", name);
+}
+
+fn details() -> DetailsResponse {
+    DetailsResponse {
+        id: ID.to_string(),
+        arbiter: Some("arbiter".to_string()),
+        fulfiller: "fulfiller".to_string(),
+        creator: "creator".to_string(),
+        end_height: None,
+        end_time: Some(NOW + 86400),
+        start_time: None,
+        expires_at_seconds: Some(NOW + 86400),
+        expires_by: Some(ExpirySource::EndTime),
+        native_balance: vec![coin(250000000, "uusd")],
+        native_balance_display: vec!["250 UST".to_string()],
+        cw20_balance: vec![],
+        listing_deposit: vec![coin(1000000, "uusd")],
+        listing_deposit_display: vec!["1 UST".to_string()],
+        cw20_whitelist: vec![],
+        allowed_fulfillers: None,
+        rejected_fulfillers: vec!["slowpoke".to_string()],
+        auto_complete_trust: None,
+        description: Some("Efectivo en Chacao".to_string()),
+        creator_locale: Some("es-VE".to_string()),
+        creator_utc_offset_minutes: Some(-240),
+        exchange_rate: Decimal::percent(3700),
+        fiat_currency: "VES".to_string(),
+        delivered_fiat_amount: Some(Uint128::new(925000)),
+        realized_exchange_rate: Some(Decimal::percent(3700)),
+        dispute: Some(Dispute {
+            reason_code: DisputeReason::WrongAmount,
+            details: Some("Came up short".to_string()),
+            info_requests: vec!["Bank statement".to_string()],
+        }),
+        mediation_until: None,
+        settlement_proposal: None,
+        fingerprint: "K7QZ2M4D".to_string(),
+        funding_source: FundingSource::NativeDirect,
+        pending_arbiter_ack: false,
+        pending_reassignment: false,
+        arbiter_ack_deadline: None,
+        require_creator_ack: true,
+        awaiting_creator_ack: false,
+        creator_ack_deadline: None,
+        open_to_all_until: None,
+        cancel_pending_until: None,
+        has_payout_details: true,
+        approximate_size: 1412,
+        risk_score: 37,
+    }
+}
+
+/// The same escrow as details, in arbitration
+fn details_v2() -> DetailsV2Response {
+    let v1 = details();
+    DetailsV2Response {
+        id: v1.id,
+        fingerprint: v1.fingerprint,
+        status: EscrowStatus::InArbitration,
+        creator: v1.creator,
+        fulfiller: Some(v1.fulfiller),
+        arbiter: v1.arbiter,
+        pending_reassignment: v1.pending_reassignment,
+        arbiter_ack_deadline: v1.arbiter_ack_deadline,
+        funding_source: v1.funding_source,
+        native_balance: v1.native_balance,
+        native_balance_display: v1.native_balance_display,
+        cw20_balance: v1.cw20_balance,
+        cw20_whitelist: v1.cw20_whitelist,
+        exchange_rate: v1.exchange_rate,
+        fiat_currency: v1.fiat_currency,
+        delivered_fiat_amount: v1.delivered_fiat_amount,
+        realized_exchange_rate: v1.realized_exchange_rate,
+        description: v1.description,
+        creator_locale: v1.creator_locale,
+        creator_utc_offset_minutes: v1.creator_utc_offset_minutes,
+        timestamps: EscrowTimestamps {
+            created: Some(NOW - 7200),
+            accepted: Some(NOW - 3600),
+            fulfilled: Some(NOW - 1800),
+            arbitration_started: Some(NOW - 600),
+            last_bumped: None,
+        },
+        trust: EscrowTrust {
+            required_trust_metrics: trust_metrics(),
+            auto_complete_trust: v1.auto_complete_trust,
+            allowed_fulfillers: v1.allowed_fulfillers,
+            rejected_fulfillers: v1.rejected_fulfillers,
+            open_to_all_until: v1.open_to_all_until,
+            require_creator_ack: v1.require_creator_ack,
+            risk_score: v1.risk_score,
+        },
+        fees: EscrowFees {
+            listing_deposit: v1.listing_deposit,
+            listing_deposit_display: v1.listing_deposit_display,
+            cancel_penalty_bps: 500,
+        },
+        deadlines: DeadlinesResponse {
+            expires_at_seconds: v1.expires_at_seconds,
+            expires_by: v1.expires_by,
+            accept_deadline: Some(NOW),
+            effective_accept_deadline: None,
+            fulfill_deadline: Some(NOW + 84600),
+            mediation_until: v1.mediation_until,
+            arbitration_deadline: Some(NOW + 258600),
+            arbitration_extensions: 0,
+            grace_period_end: None,
+            start_time: v1.start_time,
+            creator_ack_deadline: v1.creator_ack_deadline,
+            cancel_pending_until: v1.cancel_pending_until,
+        },
+        dispute: v1.dispute,
+        settlement_proposal: v1.settlement_proposal,
+        has_payout_details: v1.has_payout_details,
+        approximate_size: v1.approximate_size,
+        action_counts: vec![
+            ActionCount {
+                address: "creator".to_string(),
+                actions: 2,
+            },
+            ActionCount {
+                address: "fulfiller".to_string(),
+                actions: 1,
+            },
+        ],
+    }
+}
+
+/// As a viewer sees it, with what keeps them from accepting
+fn listing() -> ListingSummary {
+    ListingSummary {
+        id: ID.to_string(),
+        fingerprint: "K7QZ2M4D".to_string(),
+        creator: "creator".to_string(),
+        exchange_rate: Decimal::percent(3700),
+        fiat_currency: "VES".to_string(),
+        native_balance: vec![coin(250000000, "uusd")],
+        native_balance_display: vec!["250 UST".to_string()],
+        cw20_balance: vec![],
+        description: Some("Efectivo en Chacao".to_string()),
+        creator_locale: Some("es-VE".to_string()),
+        creator_utc_offset_minutes: Some(-240),
+        expired: false,
+        open_to_all_until: None,
+        starts_at: None,
+        risk_score: 37,
+        acceptable: Some(false),
+        blockers: Some(vec!["rejected".to_string(), "trust_metrics".to_string()]),
+    }
+}
+
+fn trust_metrics() -> TrustMetrics {
+    TrustMetrics {
+        percent_completed: 98,
+        percent_satisfied: 95,
+        avg_volume: 120,
+        avg_completion_speed: 5400000,
+        total_volume: 14400,
+        total_completed: 120,
+        disputes_opened: 1,
+        disputes_lost: 0,
+        disputes_won: 2,
+        clean_completions: 117,
+        avg_disputed_resolution_time: 172800000,
+        disputed_resolutions: 3,
+    }
+}
+
+fn arbiter_stats() -> ArbiterStatsResponse {
+    ArbiterStatsResponse {
+        address: "arbiter".to_string(),
+        registered: true,
+        stats: ArbiterStats {
+            rulings: 4,
+            total_ruling_seconds: 345600,
+            missed_deadlines: 1,
+            for_creator: 2,
+            for_fulfiller: 1,
+            splits: 1,
+            recusals: 0,
+            disputed_volume_ust: Uint128::new(1200000000),
+            active_escrows: 3,
+            in_arbitration: 1,
+        },
+        average_ruling_seconds: Some(86400),
+        missed_deadline_ratio: Some(Decimal::percent(20)),
+    }
+}
+
+/// Only the parties and the fingerprint make it into the attributes
+fn escrow() -> Escrow {
+    Escrow {
+        arbiter: Some(Addr::unchecked("arbiter")),
+        fulfiller: Addr::unchecked("fulfiller"),
+        creator: Addr::unchecked("creator"),
+        end_height: None,
+        end_time: Some(NOW + 86400),
+        balance: GenericBalance {
+            native: vec![coin(250000000, "uusd")],
+            cw20: vec![],
+        },
+        funding_source: FundingSource::NativeDirect,
+        exchange_rate: Decimal::percent(3700),
+        fiat_currency: "VES".to_string(),
+        cw20_whitelist: vec![],
+        required_trust_metrics: TrustMetrics::default(),
+        auto_complete_trust: None,
+        listing_deposit: vec![],
+        allowed_fulfillers: None,
+        description: None,
+        delivered_fiat_amount: None,
+        dispute: None,
+        fingerprint: "K7QZ2M4D".to_string(),
+        revision: 5,
+        listing_sort_key: NOW,
+        last_bumped: None,
+        is_pending_arbiter_ack: false,
+        is_listed: false,
+        is_canceled: false,
+        is_accepted: true,
+        is_fulfilled: true,
+        is_in_arbitration: true,
+        is_completed: false,
+        time_created: Some(NOW),
+        time_accepted: Some(NOW + 600),
+        time_fulfilled: Some(NOW + 3600),
+        time_arbitration_started: Some(NOW + 4000),
+        arbitration_extensions: 0,
+        arbitration_extended_seconds: 0,
+        contributors: vec![],
+        dispute_window_seconds: None,
+        dispute_deadline: Some(NOW + 90000),
+        rejected_fulfillers: vec![],
+        is_pending_reassignment: false,
+        require_creator_ack: false,
+        is_awaiting_creator_ack: false,
+        open_to_all_until: None,
+        cancel_pending_until: None,
+        auto_extend_on_relist: None,
+        creator_payout_memo: false,
+        fulfiller_payout_memo: false,
+        tags: vec![],
+        start_time: None,
+        mediation_until: None,
+        settlement_proposal: None,
+        creator_locale: None,
+        creator_utc_offset_minutes: None,
+        action_counts: vec![],
+    }
+}
+
+/// Frozen, anything new goes to DetailsV2Response
+#[test]
+fn details_response() {
+    assert_golden("details_response", &details());
+}
+
+#[test]
+fn details_v2_response() {
+    assert_golden("details_v2_response", &details_v2());
+}
+
+#[test]
+fn as_create_msg_response() {
+    assert_golden(
+        "as_create_msg_response",
+        &AsCreateMsgResponse {
+            create_msg: CreateMsg {
+                id: ID.to_string(),
+                arbiter: Some("arbiter".to_string()),
+                arbitration_enabled: true,
+                end_height: None,
+                end_time: Some(NOW + 86400),
+                exchange_rate: Decimal::percent(3700),
+                fiat_currency: "VES".to_string(),
+                cw20_whitelist: None,
+                required_trust_metrics: TrustRequirement::Preset("verified".to_string()),
+                auto_complete_trust: None,
+                allowed_fulfillers: Some(vec!["agent".to_string()]),
+                description: Some("Efectivo en Chacao".to_string()),
+                dispute_window_seconds: Some(7200),
+                require_creator_ack: true,
+                auto_extend_on_relist: None,
+                payout_memo: false,
+                tags: Some(vec!["caracas".to_string(), "zelle-ok".to_string()]),
+                start_time: None,
+                creator_locale: None,
+                creator_utc_offset_minutes: None,
+            },
+            archived: true,
+        },
+    );
+}
+
+#[test]
+fn receipt() {
+    assert_golden(
+        "receipt",
+        &Receipt {
+            escrow_id: ID.to_string(),
+            fingerprint: "K7QZ2M4D".to_string(),
+            creator: Addr::unchecked("creator"),
+            fulfiller: Addr::unchecked("fulfiller"),
+            amounts_released: vec![
+                ReleasedAmount {
+                    recipient: Addr::unchecked("fulfiller"),
+                    amount: GenericBalance {
+                        native: vec![coin(250000000, "uusd")],
+                        cw20: vec![Cw20CoinVerified {
+                            address: Addr::unchecked("token"),
+                            amount: Uint128::new(5000000),
+                        }],
+                    },
+                },
+                ReleasedAmount {
+                    recipient: Addr::unchecked("creator"),
+                    amount: GenericBalance {
+                        native: vec![coin(1000000, "uusd")],
+                        cw20: vec![],
+                    },
+                },
+            ],
+            fee_taken: GenericBalance {
+                native: vec![],
+                cw20: vec![],
+            },
+            exchange_rate: Decimal::percent(3700),
+            fiat_currency: "VES".to_string(),
+            terminal_action: "creator_complete".to_string(),
+            block_time: NOW + 7200,
+            block_height: 12346,
+        },
+    );
+}
+
+#[test]
+fn list_response() {
+    assert_golden(
+        "list_response",
+        &ListResponse {
+            escrows: vec!["pago-bogota".to_string(), ID.to_string()],
+        },
+    );
+}
+
+#[test]
+fn listings_response() {
+    assert_golden(
+        "listings_response",
+        &ListingsResponse {
+            listings: vec![
+                listing(),
+                ListingSummary {
+                    id: "pago-bogota".to_string(),
+                    fingerprint: "9XW3TQ5A".to_string(),
+                    creator: "creator".to_string(),
+                    exchange_rate: Decimal::percent(400000),
+                    fiat_currency: "COP".to_string(),
+                    native_balance: vec![],
+                    native_balance_display: vec![],
+                    cw20_balance: vec![Cw20Coin {
+                        address: "token".to_string(),
+                        amount: Uint128::new(5000000),
+                    }],
+                    description: None,
+                    creator_locale: None,
+                    creator_utc_offset_minutes: None,
+                    expired: true,
+                    open_to_all_until: Some(NOW + 3600),
+                    starts_at: Some(NOW + 600),
+                    risk_score: 0,
+                    acceptable: None,
+                    blockers: None,
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn eligible_listings_response() {
+    assert_golden(
+        "eligible_listings_response",
+        &EligibleListingsResponse {
+            listings: vec![ListingSummary {
+                id: ID.to_string(),
+                fingerprint: "K7QZ2M4D".to_string(),
+                creator: "creator".to_string(),
+                exchange_rate: Decimal::percent(3700),
+                fiat_currency: "VES".to_string(),
+                native_balance: vec![coin(250000000, "uusd")],
+                native_balance_display: vec!["250 UST".to_string()],
+                cw20_balance: vec![],
+                description: Some("Efectivo en Chacao".to_string()),
+                creator_locale: None,
+                creator_utc_offset_minutes: None,
+                expired: false,
+                open_to_all_until: None,
+                starts_at: None,
+                risk_score: 37,
+                acceptable: None,
+                blockers: None,
+            }],
+            near_misses: vec![NearMiss {
+                id: "pago-bogota".to_string(),
+                reason: "Escrow expired".to_string(),
+            }],
+            next_start_after: Some("pago-bogota".to_string()),
+        },
+    );
+}
+
+#[test]
+fn deadlines_response() {
+    assert_golden(
+        "deadlines_response",
+        &DeadlinesResponse {
+            expires_at_seconds: Some(NOW + 86400),
+            expires_by: Some(ExpirySource::EndHeight),
+            accept_deadline: Some(NOW + 3600),
+            effective_accept_deadline: Some(NOW + 3600),
+            fulfill_deadline: None,
+            mediation_until: None,
+            arbitration_deadline: None,
+            arbitration_extensions: 0,
+            grace_period_end: Some(NOW + 60),
+            start_time: Some(NOW + 600),
+            creator_ack_deadline: None,
+            cancel_pending_until: None,
+        },
+    );
+}
+
+#[test]
+fn changed_since_response() {
+    assert_golden(
+        "changed_since_response",
+        &ChangedSinceResponse {
+            changes: vec![
+                EscrowChange {
+                    id: ID.to_string(),
+                    revision: 7,
+                    escrow: Some(details()),
+                },
+                EscrowChange {
+                    id: "pago-bogota".to_string(),
+                    revision: 8,
+                    escrow: None,
+                },
+            ],
+            latest_revision: 8,
+        },
+    );
+}
+
+#[test]
+fn history_response() {
+    assert_golden(
+        "history_response",
+        &HistoryResponse {
+            entries: vec![
+                HistoryEntry {
+                    action: "create".to_string(),
+                    actor: Addr::unchecked("creator"),
+                    time: NOW,
+                    note: None,
+                },
+                HistoryEntry {
+                    action: "reject".to_string(),
+                    actor: Addr::unchecked("creator"),
+                    time: NOW + 600,
+                    note: Some("No reviews yet".to_string()),
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn admin_log_response() {
+    assert_golden(
+        "admin_log_response",
+        &AdminLogResponse {
+            entries: vec![
+                AdminLogEntry {
+                    sequence: 1,
+                    admin: Addr::unchecked("admin"),
+                    time: NOW,
+                    action: AdminAction::AddToBlacklist {
+                        address: Addr::unchecked("scammer"),
+                        reason: "Chargebacks".to_string(),
+                    },
+                },
+                AdminLogEntry {
+                    sequence: 2,
+                    admin: Addr::unchecked("admin"),
+                    time: NOW + 60,
+                    action: AdminAction::RecoverStray {
+                        denom: Denom::Cw20(Addr::unchecked("token")),
+                        to: Addr::unchecked("admin"),
+                        amount: Uint128::new(500),
+                    },
+                },
+                AdminLogEntry {
+                    sequence: 3,
+                    admin: Addr::unchecked("admin"),
+                    time: NOW + 120,
+                    action: AdminAction::CloseSeeding {},
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn trust_metrics_golden() {
+    assert_golden("trust_metrics", &trust_metrics());
+}
+
+#[test]
+fn creator_metrics() {
+    assert_golden(
+        "creator_metrics",
+        &CreatorMetrics {
+            escrows_created: 42,
+            confirmations: 38,
+            avg_confirmation_seconds: 1800,
+            confirmations_missed: 1,
+            disputes_opened: 2,
+            disputes_lost: 1,
+            cancels_after_accept: 0,
+            value_created_ust: Uint128::new(8400000000),
+        },
+    );
+}
+
+#[test]
+fn arbiter_stats_response() {
+    assert_golden("arbiter_stats_response", &arbiter_stats());
+}
+
+#[test]
+fn all_arbiter_stats_response() {
+    assert_golden(
+        "all_arbiter_stats_response",
+        &AllArbiterStatsResponse {
+            arbiters: vec![
+                arbiter_stats(),
+                ArbiterStatsResponse {
+                    address: "retired".to_string(),
+                    registered: false,
+                    stats: ArbiterStats {
+                        rulings: 0,
+                        total_ruling_seconds: 0,
+                        missed_deadlines: 0,
+                        for_creator: 0,
+                        for_fulfiller: 0,
+                        splits: 0,
+                        recusals: 1,
+                        disputed_volume_ust: Uint128::zero(),
+                        active_escrows: 0,
+                        in_arbitration: 0,
+                    },
+                    average_ruling_seconds: None,
+                    missed_deadline_ratio: None,
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn simulate_release_response() {
+    assert_golden(
+        "simulate_release_response",
+        &SimulateReleaseResponse {
+            transfers: vec![
+                Transfer {
+                    recipient: "fulfiller".to_string(),
+                    denom: Denom::Native("uusd".to_string()),
+                    amount: Uint128::new(250000000),
+                },
+                Transfer {
+                    recipient: "fulfiller".to_string(),
+                    denom: Denom::Cw20(Addr::unchecked("token")),
+                    amount: Uint128::new(5000000),
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn blacklist_response() {
+    assert_golden(
+        "blacklist_response",
+        &BlacklistResponse {
+            entries: vec![BlacklistInfo {
+                address: "scammer".to_string(),
+                reason: "Chargebacks".to_string(),
+                since: NOW,
+            }],
+        },
+    );
+}
+
+#[test]
+fn arbiters_response() {
+    assert_golden(
+        "arbiters_response",
+        &ArbitersResponse {
+            arbiters: vec![
+                ArbiterEntry {
+                    address: "arbiter".to_string(),
+                    active_escrows: 3,
+                    in_arbitration: 1,
+                    rulings: 4,
+                    disputed_volume_ust: Uint128::new(1200000000),
+                },
+                ArbiterEntry {
+                    address: "backup".to_string(),
+                    active_escrows: 0,
+                    in_arbitration: 0,
+                    rulings: 0,
+                    disputed_volume_ust: Uint128::zero(),
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn trust_presets_response() {
+    assert_golden(
+        "trust_presets_response",
+        &TrustPresetsResponse {
+            presets: vec![TrustPreset {
+                name: "verified".to_string(),
+                metrics: trust_metrics(),
+            }],
+        },
+    );
+}
+
+#[test]
+fn capabilities_response() {
+    assert_golden(
+        "capabilities_response",
+        &CapabilitiesResponse {
+            contract: "crates.io:cw20-escrow".to_string(),
+            version: "0.9.0".to_string(),
+            capabilities: vec!["topup".to_string(), "arbitration_split".to_string()],
+        },
+    );
+}
+
+#[test]
+fn validate_create_response() {
+    assert_golden(
+        "validate_create_response",
+        &ValidateCreateResponse {
+            ok: false,
+            violations: vec![
+                "Invalid exchange rate".to_string(),
+                "start_time is after end_time".to_string(),
+            ],
+        },
+    );
+}
+
+#[test]
+fn open_interest_response() {
+    assert_golden(
+        "open_interest_response",
+        &OpenInterestResponse {
+            currencies: vec![
+                CurrencyOpenInterest {
+                    currency: "COP".to_string(),
+                    value_ust: Uint128::new(0),
+                    listings: 1,
+                },
+                CurrencyOpenInterest {
+                    currency: "VES".to_string(),
+                    value_ust: Uint128::new(250000000),
+                    listings: 3,
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn total_locked_response() {
+    assert_golden(
+        "total_locked_response",
+        &TotalLockedResponse {
+            native: vec![coin(251000000, "uusd")],
+            cw20: vec![Cw20Coin {
+                address: "token".to_string(),
+                amount: Uint128::new(5000000),
+            }],
+        },
+    );
+}
+
+#[test]
+fn stats_response() {
+    assert_golden(
+        "stats_response",
+        &StatsResponse {
+            active_escrows: 12,
+            max_active_escrows: None,
+            sunset: false,
+        },
+    );
+}
+
+#[test]
+fn invariants_response() {
+    assert_golden(
+        "invariants_response",
+        &InvariantsResponse {
+            violations: vec![
+                InvariantViolation {
+                    id: Some(ID.to_string()),
+                    code: "timestamps_out_of_order".to_string(),
+                },
+                InvariantViolation {
+                    id: None,
+                    code: "active_escrows_mismatch".to_string(),
+                },
+            ],
+            scanned: 30,
+            next_start_after: Some("zona-sur".to_string()),
+        },
+    );
+}
+
+#[test]
+fn find_by_parties_response() {
+    assert_golden(
+        "find_by_parties_response",
+        &FindByPartiesResponse {
+            escrows: vec![PartyEscrow {
+                id: ID.to_string(),
+                revision: 3,
+                status: "creator_complete".to_string(),
+                archived: true,
+            }],
+        },
+    );
+}
+
+#[test]
+fn batch_create_response() {
+    assert_golden(
+        "batch_create_response",
+        &BatchCreateResponse {
+            ids: vec!["lote-1".to_string(), "lote-2".to_string()],
+        },
+    );
+}
+
+#[test]
+fn payout_details_response() {
+    assert_golden(
+        "payout_details_response",
+        &PayoutDetailsResponse {
+            ciphertext: Some(Binary::from(b"sealed".to_vec())),
+        },
+    );
+}
+
+#[test]
+fn is_verified_response() {
+    assert_golden(
+        "is_verified_response",
+        &IsVerifiedResponse { verified: true },
+    );
+}
+
+#[test]
+fn payout_hook_msg() {
+    assert_golden(
+        "payout_hook_msg",
+        &PayoutHookMsg::EscrowPayout {
+            escrow_id: ID.to_string(),
+            fingerprint: "K7QZ2M4D".to_string(),
+        },
+    );
+}
+
+#[test]
+fn escrow_events() {
+    let escrow = escrow();
+    let events: Vec<Vec<Attribute>> = vec![
+        EscrowEvent::created(ID, &escrow).attributes(),
+        EscrowEvent::accepted(ID, &escrow, Some(&Addr::unchecked("slowpoke"))).attributes(),
+        EscrowEvent::fulfilled(ID, &escrow, NOW + 90000).attributes(),
+        EscrowEvent::completed(ID, &escrow, Some(6000)).attributes(),
+        EscrowEvent::arbitration_requested(ID, &escrow, DisputeReason::WrongAmount.as_str())
+            .attributes(),
+        EscrowEvent::info_requested(ID, &escrow, "Bank statement", NOW + 176400).attributes(),
+        EscrowEvent::arbitrated(ID, &escrow, "fulfiller").attributes(),
+        EscrowEvent::settlement_proposed(ID, &escrow, 7000).attributes(),
+        EscrowEvent::canceled(ID, &escrow, Some(&Addr::unchecked("creator"))).attributes(),
+    ];
+    assert_golden("escrow_events", &events);
+}
+
+#[test]
+fn contract_events() {
+    let events: Vec<Vec<Attribute>> = vec![
+        ContractEvent::batch_created(&[("lote-1".to_string(), "9XW3TQ5A".to_string())])
+            .attributes(),
+        ContractEvent::blacklisted(&Addr::unchecked("scammer")).attributes(),
+        ContractEvent::locked_reconciled(12, true).attributes(),
+        ContractEvent::stray_recovered("uusd", &Addr::unchecked("admin"), Uint128::new(500))
+            .attributes(),
+    ];
+    assert_golden("contract_events", &events);
+}